@@ -19,7 +19,8 @@ use wax_bench_reducer::{
 use wax_bench_runner::BenchmarkRunner;
 use wax_bench_runner::RunRequest;
 use wax_bench_text_engine::{
-    profile_first_vector_query, query_batch_ranked_results, query_text_preview, PackedTextEngine,
+    profile_doc_hydrate, profile_first_vector_query, query_batch_ranked_results,
+    query_text_preview, DocCompression, PackedTextEngine,
 };
 
 #[derive(Debug, Parser)]
@@ -90,6 +91,16 @@ enum Command {
         #[arg(long)]
         output: Option<PathBuf>,
     },
+    ProfileDocHydrate {
+        #[arg(long)]
+        dataset: PathBuf,
+        #[arg(long, default_value = "none")]
+        compression: String,
+        #[arg(long, default_value_t = 1)]
+        sample_count: u32,
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
     QualityReport {
         #[arg(long)]
         query_set: PathBuf,
@@ -257,6 +268,25 @@ fn main() -> Result<(), String> {
             println!("{rendered}");
             Ok(())
         }
+        Some(Command::ProfileDocHydrate {
+            dataset,
+            compression,
+            sample_count,
+            output,
+        }) => {
+            let compression = parse_doc_compression(&compression)?;
+            let mut profiles = Vec::with_capacity(sample_count as usize);
+            for _ in 0..sample_count {
+                profiles.push(profile_doc_hydrate(&dataset, compression)?);
+            }
+            let rendered =
+                serde_json::to_string_pretty(&profiles).map_err(|error| error.to_string())?;
+            if let Some(output) = output {
+                std::fs::write(output, &rendered).map_err(|error| error.to_string())?;
+            }
+            println!("{rendered}");
+            Ok(())
+        }
         Some(Command::QualityReport {
             query_set,
             qrels,
@@ -324,6 +354,15 @@ fn parse_vector_mode(value: &str) -> Result<VectorQueryMode, String> {
     }
 }
 
+fn parse_doc_compression(value: &str) -> Result<DocCompression, String> {
+    match value {
+        "none" => Ok(DocCompression::None),
+        "lz4" => Ok(DocCompression::Lz4),
+        "zstd" => Ok(DocCompression::Zstd),
+        _ => Err("unsupported compression".to_owned()),
+    }
+}
+
 struct SystemClock {
     start: Instant,
 }
@@ -98,7 +98,7 @@ pub(crate) fn checksum_label(bytes: &[u8]) -> String {
 fn embedding_dtype_from_spec_id(spec_id: &str) -> &str {
     spec_id
         .split('-')
-        .find(|part| matches!(*part, "f32" | "i8" | "u8"))
+        .find(|part| matches!(*part, "f32" | "f16" | "bf16" | "i8" | "u8"))
         .unwrap_or("f32")
 }
 
@@ -121,7 +121,7 @@ pub(crate) fn manifest_query_fingerprint(
 pub(crate) fn embedding_dimensions_from_spec_id(spec_id: &str) -> Option<u32> {
     let parts: Vec<&str> = spec_id.split('-').collect();
     for window in parts.windows(2) {
-        if matches!(window[1], "f32" | "i8" | "u8") {
+        if matches!(window[1], "f32" | "f16" | "bf16" | "i8" | "u8") {
             if let Ok(dimensions) = window[0].parse::<u32>() {
                 return Some(dimensions);
             }
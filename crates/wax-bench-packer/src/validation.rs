@@ -272,7 +272,7 @@ fn validate_constrained_values(manifest: &DatasetPackManifest) -> Result<(), Val
 
     if !matches!(
         manifest.vector_profile.embedding_dtype.as_str(),
-        "f32" | "i8" | "u8"
+        "f32" | "f16" | "bf16" | "i8" | "u8"
     ) {
         return Err(ValidationError::new("invalid embedding_dtype"));
     }
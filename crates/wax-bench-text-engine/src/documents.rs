@@ -3,7 +3,7 @@ use std::path::Path;
 
 use serde_json::Value;
 use wax_bench_model::DatasetPackManifest;
-use wax_v2_docstore::{validate_store_segment_against_dataset_pack, Docstore};
+use wax_v2_docstore::{validate_store_segment_against_dataset_pack, DocMetadata, Docstore};
 use wax_v2_text::validate_store_segment_against_dataset_pack as validate_text_segment_against_dataset_pack;
 use wax_v2_vector::validate_store_segment_against_dataset_pack as validate_vector_segment_against_dataset_pack;
 
@@ -50,6 +50,15 @@ pub(crate) fn load_documents_by_id(
         .map_err(docstore_error)
 }
 
+pub(crate) fn load_metadata_by_id(
+    docstore: &Docstore,
+    target_doc_ids: &[String],
+) -> Result<HashMap<String, DocMetadata>, String> {
+    docstore
+        .load_metadata_by_id(target_doc_ids)
+        .map_err(docstore_error)
+}
+
 pub(crate) fn docstore_error(error: wax_v2_docstore::DocstoreError) -> String {
     match error {
         wax_v2_docstore::DocstoreError::Io(message)
@@ -13,7 +13,8 @@ use wax_bench_model::{
     MountRequest, OpenRequest, OpenResult, RankedDocumentHit, RankedQueryResult, SearchRequest,
     SearchResult, VectorQueryMode, WaxEngine,
 };
-use wax_v2_docstore::Docstore;
+pub use wax_v2_docstore::DocCompression;
+use wax_v2_docstore::{prepare_raw_documents_segment_with_compression, DocMetadata, Docstore};
 use wax_v2_search::{
     filter_hits_by_metadata, hybrid_search_with_diagnostics, search_first_hybrid_query,
     MetadataFilter, MetadataSource,
@@ -22,14 +23,15 @@ use wax_v2_text::{TextBatchQuery, TextLane};
 use wax_v2_vector::{elapsed_ms, VectorLane};
 
 use crate::documents::{
-    load_documents_by_id, open_docstore, validate_store_segments_against_dataset_pack,
-    SegmentValidationOptions,
+    docstore_error, load_documents_by_id, load_metadata_by_id, open_docstore,
+    validate_store_segments_against_dataset_pack, SegmentValidationOptions,
 };
 use crate::query_support::load_query_vector_records;
 
 const METADATA_FILTER_MIN_CANDIDATES: usize = 64;
 const METADATA_FILTER_CANDIDATE_MULTIPLIER: usize = 64;
 const METADATA_FILTER_MAX_CANDIDATES: usize = 4096;
+const METADATA_FIELD_PREFIX: &str = "metadata.";
 
 pub struct PackedTextEngine {
     mounted_path: Option<PathBuf>,
@@ -59,6 +61,19 @@ impl MetadataSource for JsonDocumentMetadata<'_> {
     }
 }
 
+/// Filter fields under `metadata.` read from the doc segment's binary metadata, so the
+/// payloads of filtered candidates are never parsed.
+struct BinaryDocumentMetadata<'a> {
+    metadata: &'a HashMap<String, DocMetadata>,
+}
+
+impl MetadataSource for BinaryDocumentMetadata<'_> {
+    fn field_value(&self, doc_id: &str, field: &str) -> Option<&str> {
+        let path = field.strip_prefix(METADATA_FIELD_PREFIX)?;
+        self.metadata.get(doc_id)?.get(path)?.as_str()
+    }
+}
+
 impl PackedTextEngine {
     pub fn with_vector_mode(vector_mode: VectorQueryMode) -> Self {
         Self {
@@ -217,6 +232,19 @@ pub struct FirstVectorQueryProfile {
     pub hits: Vec<String>,
 }
 
+/// Cost of encoding the pack's documents as one store doc segment with a block codec, opening
+/// it, and hydrating one document and then every document from it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DocHydrateProfile {
+    pub compression: String,
+    pub doc_count: usize,
+    pub segment_bytes: usize,
+    pub encode_ms: f64,
+    pub open_ms: f64,
+    pub hydrate_one_ms: f64,
+    pub hydrate_all_ms: f64,
+}
+
 pub fn query_text_preview(
     dataset_path: &Path,
     query_text: &str,
@@ -402,15 +430,32 @@ pub fn query_batch_ranked_results(
                 let (docstore, _) = docstore
                     .as_ref()
                     .ok_or_else(|| "docstore not available for metadata filtering".to_owned())?;
-                let documents = load_documents_by_id(docstore, &hits)?;
                 let filter = MetadataFilter::from_pairs(query.filter_spec.equals.iter().cloned());
-                let metadata = JsonDocumentMetadata {
-                    documents: &documents,
+                let filtered = if query
+                    .filter_spec
+                    .equals
+                    .iter()
+                    .all(|(field, _)| field.starts_with(METADATA_FIELD_PREFIX))
+                {
+                    let metadata = load_metadata_by_id(docstore, &hits)?;
+                    filter_hits_by_metadata(
+                        &hits,
+                        &BinaryDocumentMetadata {
+                            metadata: &metadata,
+                        },
+                        &filter,
+                    )
+                } else {
+                    let documents = load_documents_by_id(docstore, &hits)?;
+                    filter_hits_by_metadata(
+                        &hits,
+                        &JsonDocumentMetadata {
+                            documents: &documents,
+                        },
+                        &filter,
+                    )
                 };
-                filter_hits_by_metadata(&hits, &metadata, &filter)
-                    .into_iter()
-                    .take(limit)
-                    .collect()
+                filtered.into_iter().take(limit).collect()
             };
             if uses_vector_lane {
                 auto_vector_query_pending = false;
@@ -433,8 +478,10 @@ fn metadata_filter_candidate_limit(top_k: usize, active_doc_count: usize) -> usi
     }
     top_k
         .saturating_mul(METADATA_FILTER_CANDIDATE_MULTIPLIER)
-        .max(METADATA_FILTER_MIN_CANDIDATES)
-        .min(METADATA_FILTER_MAX_CANDIDATES)
+        .clamp(
+            METADATA_FILTER_MIN_CANDIDATES,
+            METADATA_FILTER_MAX_CANDIDATES,
+        )
         .min(active_doc_count)
 }
 
@@ -482,6 +529,74 @@ pub fn profile_first_vector_query(
     })
 }
 
+pub fn profile_doc_hydrate(
+    dataset_path: &Path,
+    compression: DocCompression,
+) -> Result<DocHydrateProfile, String> {
+    let manifest_text = fs::read_to_string(dataset_path.join("manifest.json"))
+        .map_err(|error| error.to_string())?;
+    let manifest: DatasetPackManifest =
+        serde_json::from_str(&manifest_text).map_err(|error| error.to_string())?;
+    let pack_docstore =
+        Docstore::open_dataset_pack(dataset_path, &manifest).map_err(docstore_error)?;
+    let doc_ids = pack_docstore.load_document_ids().map_err(docstore_error)?;
+    let mut documents = load_documents_by_id(&pack_docstore, &doc_ids)?;
+    let ordered_documents = doc_ids
+        .iter()
+        .map(|doc_id| {
+            documents
+                .remove(doc_id)
+                .map(|document| (doc_id.clone(), document))
+                .ok_or_else(|| format!("dataset pack document missing for doc_id {doc_id}"))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    // The profiled store lives in a scratch mount so the dataset pack is left untouched.
+    let scratch = tempfile::tempdir().map_err(|error| error.to_string())?;
+    let store_path = scratch.path().join("store.wax");
+    wax_v2_core::create_empty_store(&store_path).map_err(|error| error.to_string())?;
+    let encode_start = Instant::now();
+    let prepared =
+        prepare_raw_documents_segment_with_compression(&store_path, ordered_documents, compression)
+            .map_err(docstore_error)?;
+    let encode_ms = elapsed_ms(encode_start.elapsed());
+    let segment_bytes = prepared.object_bytes.len();
+    wax_v2_core::publish_segment(&store_path, prepared.descriptor, &prepared.object_bytes)
+        .map_err(|error| error.to_string())?;
+
+    let open_start = Instant::now();
+    let docstore = open_docstore(scratch.path(), &manifest)?;
+    let open_ms = elapsed_ms(open_start.elapsed());
+    let hydrate_one_start = Instant::now();
+    let middle = doc_ids
+        .get(doc_ids.len() / 2)
+        .cloned()
+        .into_iter()
+        .collect::<Vec<_>>();
+    load_documents_by_id(&docstore, &middle)?;
+    let hydrate_one_ms = elapsed_ms(hydrate_one_start.elapsed());
+    let hydrate_all_start = Instant::now();
+    let hydrated = load_documents_by_id(&docstore, &doc_ids)?;
+    let hydrate_all_ms = elapsed_ms(hydrate_all_start.elapsed());
+    if hydrated.len() != doc_ids.len() {
+        return Err(format!(
+            "hydrated {} of {} documents from the profiled doc segment",
+            hydrated.len(),
+            doc_ids.len()
+        ));
+    }
+
+    Ok(DocHydrateProfile {
+        compression: compression.as_str().to_owned(),
+        doc_count: doc_ids.len(),
+        segment_bytes,
+        encode_ms,
+        open_ms,
+        hydrate_one_ms,
+        hydrate_all_ms,
+    })
+}
+
 impl WaxEngine for PackedTextEngine {
     type Error = String;
 
@@ -629,13 +744,13 @@ mod tests {
     use std::collections::{HashMap, VecDeque};
 
     use wax_bench_model::VectorQueryMode;
-    use wax_v2_docstore::parse_document_id;
+    use wax_v2_docstore::{parse_document_id, DocMetadata};
     use wax_v2_search::{filter_hits_by_metadata, MetadataFilter};
     use wax_v2_vector::resolve_auto_vector_mode;
 
     use crate::{
-        metadata_filter_candidate_limit, pop_text_hits, JsonDocumentMetadata,
-        METADATA_FILTER_MAX_CANDIDATES, METADATA_FILTER_MIN_CANDIDATES,
+        metadata_filter_candidate_limit, pop_text_hits, BinaryDocumentMetadata,
+        JsonDocumentMetadata, METADATA_FILTER_MAX_CANDIDATES, METADATA_FILTER_MIN_CANDIDATES,
     };
 
     #[test]
@@ -772,6 +887,32 @@ mod tests {
         assert_eq!(filtered, vec!["doc-001"]);
     }
 
+    #[test]
+    fn binary_metadata_filter_matches_nested_paths_under_metadata_prefix() {
+        let metadata = HashMap::from([
+            (
+                "doc-001".to_owned(),
+                DocMetadata::encode(&serde_json::json!({"workspace": {"name": "prod"}})),
+            ),
+            (
+                "doc-002".to_owned(),
+                DocMetadata::encode(&serde_json::json!({"workspace": {"name": "dev"}})),
+            ),
+        ]);
+        let source = BinaryDocumentMetadata {
+            metadata: &metadata,
+        };
+        let filter = MetadataFilter::from_pairs([("metadata.workspace.name", "prod")]);
+
+        let filtered = filter_hits_by_metadata(
+            &["doc-002".to_owned(), "doc-001".to_owned()],
+            &source,
+            &filter,
+        );
+
+        assert_eq!(filtered, vec!["doc-001"]);
+    }
+
     #[test]
     fn parse_document_id_rejects_missing_doc_id() {
         let error = parse_document_id("{\"text\":\"missing\"}", "document line").unwrap_err();
@@ -2,10 +2,13 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use serde::Deserialize;
 use wax_v2_runtime::{
-    NewDocument, NewDocumentVector, RuntimeSearchMode, RuntimeSearchRequest, RuntimeStore,
+    Checkpoint, DocCompression, NewDocument, NewDocumentMultiVector, NewDocumentSparseVector,
+    NewDocumentVector, RuntimeEmbeddingIdentity, RuntimeError, RuntimeSearchMode,
+    RuntimeSearchRequest, RuntimeStore, RuntimeVectorMetric, RuntimeVectorSpace, StoreVerifyReport,
+    DEFAULT_WRITER_LOCK_TIMEOUT,
 };
 
 #[derive(Debug, Parser)]
@@ -40,6 +43,56 @@ enum Command {
         #[arg(long, default_value_t = false)]
         preview: bool,
     },
+    /// Checks the whole store and prints a JSON report; exits non-zero when it finds issues.
+    Verify {
+        #[arg(long)]
+        root: PathBuf,
+        /// Truncates a torn tail left by an interrupted publish before checking.
+        #[arg(long, default_value_t = false)]
+        repair: bool,
+    },
+    /// Republishes an older generation's segments as a new generation.
+    Rollback {
+        #[arg(long)]
+        root: PathBuf,
+        #[arg(long)]
+        generation: u64,
+    },
+    /// Named checkpoints of store generations.
+    Snapshot {
+        #[command(subcommand)]
+        command: SnapshotCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum SnapshotCommand {
+    Create {
+        #[arg(long)]
+        root: PathBuf,
+        #[arg(long)]
+        name: String,
+        /// Generation to name; defaults to the active one.
+        #[arg(long)]
+        generation: Option<u64>,
+    },
+    List {
+        #[arg(long)]
+        root: PathBuf,
+    },
+    Delete {
+        #[arg(long)]
+        root: PathBuf,
+        #[arg(long)]
+        name: String,
+    },
+    /// Publishes a new generation with the snapshot's segments.
+    Restore {
+        #[arg(long)]
+        root: PathBuf,
+        #[arg(long)]
+        name: String,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -49,15 +102,97 @@ enum IngestCommand {
         root: PathBuf,
         #[arg(long)]
         input: PathBuf,
+        /// Block codec for the published doc segment.
+        #[arg(long, value_enum, default_value_t = CliDocCompression::None)]
+        compression: CliDocCompression,
     },
     Vectors {
         #[arg(long)]
         root: PathBuf,
         #[arg(long)]
         input: PathBuf,
+        #[arg(long)]
+        space: Option<String>,
+        #[arg(long, requires = "space")]
+        dimensions: Option<usize>,
+        #[arg(long, value_enum, default_value_t = CliVectorMetric::Cosine, requires = "space")]
+        metric: CliVectorMetric,
+        #[arg(long, default_value_t = false, requires = "space")]
+        multi_vector: bool,
+        #[command(flatten)]
+        embedding: CliEmbeddingIdentity,
+    },
+    Sparse {
+        #[arg(long)]
+        root: PathBuf,
+        #[arg(long)]
+        input: PathBuf,
     },
 }
 
+/// Embedding model behind ingested vectors; every space, the default one included, requires it.
+#[derive(Debug, Args)]
+struct CliEmbeddingIdentity {
+    #[arg(long, requires_all = ["embedding_model_version", "embedding_model_hash"])]
+    embedding_spec_id: Option<String>,
+    #[arg(long, requires = "embedding_spec_id")]
+    embedding_model_version: Option<String>,
+    #[arg(long, requires = "embedding_spec_id")]
+    embedding_model_hash: Option<String>,
+}
+
+impl CliEmbeddingIdentity {
+    fn resolve(self, space: Option<&str>) -> Result<RuntimeEmbeddingIdentity, String> {
+        match (
+            self.embedding_spec_id,
+            self.embedding_model_version,
+            self.embedding_model_hash,
+        ) {
+            (Some(spec_id), Some(model_version), Some(model_hash)) => Ok(
+                RuntimeEmbeddingIdentity::new(spec_id, model_version, model_hash),
+            ),
+            _ => Err(format!(
+                "vector space {} requires --embedding-spec-id, --embedding-model-version and --embedding-model-hash",
+                space.unwrap_or("default")
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum CliVectorMetric {
+    Cosine,
+    Dot,
+    L2,
+}
+
+impl From<CliVectorMetric> for RuntimeVectorMetric {
+    fn from(metric: CliVectorMetric) -> Self {
+        match metric {
+            CliVectorMetric::Cosine => Self::Cosine,
+            CliVectorMetric::Dot => Self::Dot,
+            CliVectorMetric::L2 => Self::L2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum CliDocCompression {
+    None,
+    Lz4,
+    Zstd,
+}
+
+impl From<CliDocCompression> for DocCompression {
+    fn from(compression: CliDocCompression) -> Self {
+        match compression {
+            CliDocCompression::None => Self::None,
+            CliDocCompression::Lz4 => Self::Lz4,
+            CliDocCompression::Zstd => Self::Zstd,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct CliNewDocument {
     doc_id: String,
@@ -76,6 +211,18 @@ struct CliNewDocumentVector {
     values: Vec<f32>,
 }
 
+#[derive(Debug, Deserialize)]
+struct CliNewDocumentSparseVector {
+    doc_id: String,
+    terms: Vec<(u32, f32)>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CliNewDocumentMultiVector {
+    doc_id: String,
+    vectors: Vec<Vec<f32>>,
+}
+
 fn main() -> Result<(), String> {
     let cli = Cli::parse();
 
@@ -86,41 +233,120 @@ fn main() -> Result<(), String> {
             Ok(())
         }
         Command::Ingest { command } => match command {
-            IngestCommand::Docs { root, input } => {
+            IngestCommand::Docs {
+                root,
+                input,
+                compression,
+            } => {
+                let mut runtime = RuntimeStore::open(&root)
+                    .map_err(|error| error.to_string())?
+                    .with_doc_compression(compression.into());
+                let documents = stream_jsonl::<CliNewDocument>(&input)?.map(|document| {
+                    document
+                        .map_err(RuntimeError::InvalidRequest)
+                        .map(|document| {
+                            let mut runtime_document =
+                                NewDocument::new(document.doc_id, document.text)
+                                    .with_metadata(document.metadata);
+                            if let Some(timestamp_ms) = document.timestamp_ms {
+                                runtime_document = runtime_document.with_timestamp_ms(timestamp_ms);
+                            }
+                            for (key, value) in document.extra_fields {
+                                runtime_document = runtime_document.with_extra_field(key, value);
+                            }
+                            runtime_document
+                        })
+                });
+                let report = runtime
+                    .writer()
+                    .map_err(|error| error.to_string())?
+                    .publish_document_stream(documents)
+                    .map_err(|error| error.to_string())?;
+                println!("{}", render_publish_report(&report)?);
+                runtime.close().map_err(|error| error.to_string())?;
+                Ok(())
+            }
+            IngestCommand::Vectors {
+                root,
+                input,
+                space,
+                dimensions,
+                metric,
+                multi_vector: true,
+                embedding,
+            } => {
+                let space = space.expect("clap requires --space with --multi-vector");
+                let embedding = embedding.resolve(Some(&space))?;
                 let mut runtime = RuntimeStore::open(&root).map_err(|error| error.to_string())?;
-                let documents = read_jsonl::<CliNewDocument>(&input)?
+                let vectors = read_jsonl::<CliNewDocumentMultiVector>(&input)?
                     .into_iter()
-                    .map(|document| {
-                        let mut runtime_document = NewDocument::new(document.doc_id, document.text)
-                            .with_metadata(document.metadata);
-                        if let Some(timestamp_ms) = document.timestamp_ms {
-                            runtime_document = runtime_document.with_timestamp_ms(timestamp_ms);
-                        }
-                        for (key, value) in document.extra_fields {
-                            runtime_document = runtime_document.with_extra_field(key, value);
-                        }
-                        runtime_document
-                    })
+                    .map(|vector| NewDocumentMultiVector::new(vector.doc_id, vector.vectors))
                     .collect::<Vec<_>>();
+                let dimensions = dimensions
+                    .or_else(|| {
+                        vectors
+                            .first()
+                            .and_then(|vector| vector.vectors.first())
+                            .map(Vec::len)
+                    })
+                    .unwrap_or(0);
                 let report = runtime
                     .writer()
                     .map_err(|error| error.to_string())?
-                    .publish_raw_documents(documents)
+                    .publish_raw_multi_vectors_to_space(
+                        RuntimeVectorSpace::multi_vector(space, dimensions, metric.into())
+                            .with_embedding(embedding),
+                        vectors,
+                    )
                     .map_err(|error| error.to_string())?;
                 println!("{}", render_publish_report(&report)?);
                 runtime.close().map_err(|error| error.to_string())?;
                 Ok(())
             }
-            IngestCommand::Vectors { root, input } => {
+            IngestCommand::Vectors {
+                root,
+                input,
+                space,
+                dimensions,
+                metric,
+                multi_vector: false,
+                embedding,
+            } => {
+                let embedding = embedding.resolve(space.as_deref())?;
                 let mut runtime = RuntimeStore::open(&root).map_err(|error| error.to_string())?;
                 let vectors = read_jsonl::<CliNewDocumentVector>(&input)?
                     .into_iter()
                     .map(|vector| NewDocumentVector::new(vector.doc_id, vector.values))
                     .collect::<Vec<_>>();
+                let writer = runtime.writer().map_err(|error| error.to_string())?;
+                let report = match space {
+                    Some(space) => {
+                        let dimensions = dimensions
+                            .or_else(|| vectors.first().map(|vector| vector.values.len()))
+                            .unwrap_or(0);
+                        writer.publish_raw_vectors_to_space(
+                            RuntimeVectorSpace::new(space, dimensions, metric.into())
+                                .with_embedding(embedding),
+                            vectors,
+                        )
+                    }
+                    None => writer.publish_raw_vectors(embedding, vectors),
+                }
+                .map_err(|error| error.to_string())?;
+                println!("{}", render_publish_report(&report)?);
+                runtime.close().map_err(|error| error.to_string())?;
+                Ok(())
+            }
+            IngestCommand::Sparse { root, input } => {
+                let mut runtime = RuntimeStore::open(&root).map_err(|error| error.to_string())?;
+                let vectors = read_jsonl::<CliNewDocumentSparseVector>(&input)?
+                    .into_iter()
+                    .map(|vector| NewDocumentSparseVector::new(vector.doc_id, vector.terms))
+                    .collect::<Vec<_>>();
                 let report = runtime
                     .writer()
                     .map_err(|error| error.to_string())?
-                    .publish_raw_vectors(vectors)
+                    .publish_raw_sparse_vectors(vectors)
                     .map_err(|error| error.to_string())?;
                 println!("{}", render_publish_report(&report)?);
                 runtime.close().map_err(|error| error.to_string())?;
@@ -149,9 +375,9 @@ fn main() -> Result<(), String> {
                 .search(RuntimeSearchRequest {
                     mode: RuntimeSearchMode::Text,
                     text_query: Some(text),
-                    vector_query: None,
                     top_k,
                     include_preview: preview,
+                    ..Default::default()
                 })
                 .map_err(|error| error.to_string())?;
             let rendered_hits = response
@@ -171,6 +397,82 @@ fn main() -> Result<(), String> {
             runtime.close().map_err(|error| error.to_string())?;
             Ok(())
         }
+        Command::Verify { root, repair } => {
+            let repaired_tail_bytes = if repair {
+                Some(
+                    RuntimeStore::repair_torn_tail(&root, DEFAULT_WRITER_LOCK_TIMEOUT)
+                        .map_err(|error| error.to_string())?,
+                )
+            } else {
+                None
+            };
+            let report = RuntimeStore::verify(&root).map_err(|error| error.to_string())?;
+            println!("{}", render_verify_report(&report, repaired_tail_bytes)?);
+            if report.is_ok() {
+                Ok(())
+            } else {
+                Err(format!(
+                    "store verification found {} issue(s)",
+                    report.issues.len()
+                ))
+            }
+        }
+        Command::Rollback { root, generation } => {
+            let mut runtime = RuntimeStore::open(&root).map_err(|error| error.to_string())?;
+            let report = runtime
+                .writer()
+                .map_err(|error| error.to_string())?
+                .rollback_to(generation)
+                .map_err(|error| error.to_string())?;
+            println!("{}", render_publish_report(&report)?);
+            runtime.close().map_err(|error| error.to_string())?;
+            Ok(())
+        }
+        Command::Snapshot { command } => match command {
+            SnapshotCommand::Create {
+                root,
+                name,
+                generation,
+            } => {
+                let mut runtime = RuntimeStore::open(&root).map_err(|error| error.to_string())?;
+                let checkpoint = runtime
+                    .writer()
+                    .map_err(|error| error.to_string())?
+                    .create_checkpoint(&name, generation)
+                    .map_err(|error| error.to_string())?;
+                println!("{}", render_checkpoints(&[checkpoint])?);
+                runtime.close().map_err(|error| error.to_string())?;
+                Ok(())
+            }
+            SnapshotCommand::List { root } => {
+                let runtime = RuntimeStore::open(&root).map_err(|error| error.to_string())?;
+                let checkpoints = runtime.checkpoints().map_err(|error| error.to_string())?;
+                println!("{}", render_checkpoints(&checkpoints)?);
+                Ok(())
+            }
+            SnapshotCommand::Delete { root, name } => {
+                let mut runtime = RuntimeStore::open(&root).map_err(|error| error.to_string())?;
+                let checkpoint = runtime
+                    .writer()
+                    .map_err(|error| error.to_string())?
+                    .delete_checkpoint(&name)
+                    .map_err(|error| error.to_string())?;
+                println!("{}", render_checkpoints(&[checkpoint])?);
+                runtime.close().map_err(|error| error.to_string())?;
+                Ok(())
+            }
+            SnapshotCommand::Restore { root, name } => {
+                let mut runtime = RuntimeStore::open(&root).map_err(|error| error.to_string())?;
+                let report = runtime
+                    .writer()
+                    .map_err(|error| error.to_string())?
+                    .restore_checkpoint(&name)
+                    .map_err(|error| error.to_string())?;
+                println!("{}", render_publish_report(&report)?);
+                runtime.close().map_err(|error| error.to_string())?;
+                Ok(())
+            }
+        },
     }
 }
 
@@ -179,17 +481,91 @@ fn default_metadata() -> serde_json::Value {
 }
 
 fn read_jsonl<T: for<'de> Deserialize<'de>>(path: &std::path::Path) -> Result<Vec<T>, String> {
-    BufReader::new(File::open(path).map_err(|error| error.to_string())?)
-        .lines()
-        .filter_map(|line| match line {
-            Ok(line) if line.trim().is_empty() => None,
-            other => Some(other),
-        })
-        .map(|line| {
-            let line = line.map_err(|error| error.to_string())?;
-            serde_json::from_str(&line).map_err(|error| error.to_string())
-        })
-        .collect()
+    stream_jsonl(path)?.collect()
+}
+
+/// Parses `path` one line at a time as the returned iterator is consumed.
+fn stream_jsonl<T: for<'de> Deserialize<'de>>(
+    path: &std::path::Path,
+) -> Result<impl Iterator<Item = Result<T, String>>, String> {
+    Ok(
+        BufReader::new(File::open(path).map_err(|error| error.to_string())?)
+            .lines()
+            .filter_map(|line| match line {
+                Ok(line) if line.trim().is_empty() => None,
+                other => Some(other),
+            })
+            .map(|line| {
+                let line = line.map_err(|error| error.to_string())?;
+                serde_json::from_str(&line).map_err(|error| error.to_string())
+            }),
+    )
+}
+
+fn render_verify_report(
+    report: &StoreVerifyReport,
+    repaired_tail_bytes: Option<u64>,
+) -> Result<String, String> {
+    serde_json::to_string_pretty(&serde_json::json!({
+        "ok": report.is_ok(),
+        "active_generation": report.active_generation,
+        "torn_tail_bytes": report.torn_tail_bytes,
+        "repaired_tail_bytes": repaired_tail_bytes,
+        "superblocks": report
+            .superblocks
+            .iter()
+            .map(|superblock| {
+                serde_json::json!({
+                    "slot": superblock.slot,
+                    "generation": superblock.generation,
+                    "active": superblock.active,
+                    "error": superblock.error,
+                })
+            })
+            .collect::<Vec<_>>(),
+        "segments": report
+            .segments
+            .iter()
+            .map(|segment| {
+                serde_json::json!({
+                    "family": segment.family.as_str(),
+                    "segment_generation": segment.segment_generation,
+                    "object_offset": segment.object_offset,
+                    "object_length": segment.object_length,
+                    "checksum_valid": segment.checksum_valid,
+                    "decoded_items": segment.decoded_items,
+                    "stale": segment.stale,
+                })
+            })
+            .collect::<Vec<_>>(),
+        "issues": report
+            .issues
+            .iter()
+            .map(|issue| {
+                serde_json::json!({
+                    "kind": issue.kind.as_str(),
+                    "message": issue.message,
+                })
+            })
+            .collect::<Vec<_>>(),
+    }))
+    .map_err(|error| error.to_string())
+}
+
+fn render_checkpoints(checkpoints: &[Checkpoint]) -> Result<String, String> {
+    serde_json::to_string_pretty(
+        &checkpoints
+            .iter()
+            .map(|checkpoint| {
+                serde_json::json!({
+                    "name": checkpoint.name,
+                    "generation": checkpoint.generation,
+                    "created_at_ms": checkpoint.created_at_ms,
+                })
+            })
+            .collect::<Vec<_>>(),
+    )
+    .map_err(|error| error.to_string())
 }
 
 fn render_publish_report(report: &wax_v2_runtime::RuntimePublishReport) -> Result<String, String> {
@@ -209,5 +585,6 @@ fn runtime_publish_family_name(family: &wax_v2_runtime::RuntimePublishFamily) ->
         wax_v2_runtime::RuntimePublishFamily::Doc => "doc",
         wax_v2_runtime::RuntimePublishFamily::Text => "text",
         wax_v2_runtime::RuntimePublishFamily::Vector => "vector",
+        wax_v2_runtime::RuntimePublishFamily::Sparse => "sparse",
     }
 }
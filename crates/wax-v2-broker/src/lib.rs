@@ -3,8 +3,9 @@ use std::fmt;
 use std::path::Path;
 
 use wax_v2_runtime::{
-    NewDocument, NewDocumentVector, RuntimePublishFamily, RuntimePublishReport, RuntimeSearchMode,
-    RuntimeSearchRequest, RuntimeSearchResponse, RuntimeStore,
+    NewDocument, NewDocumentVector, RuntimeEmbeddingIdentity, RuntimePublishFamily,
+    RuntimePublishReport, RuntimeSearchMode, RuntimeSearchRequest, RuntimeSearchResponse,
+    RuntimeStore,
 };
 
 const DEFAULT_MAX_SESSIONS: usize = 64;
@@ -27,6 +28,7 @@ pub enum BrokerPublishFamily {
     Doc,
     Text,
     Vector,
+    Sparse,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -50,6 +52,14 @@ pub struct SessionNewDocumentVector {
     pub values: Vec<f32>,
 }
 
+/// Embedding model that produced ingested vectors; it must match the model stored with the space.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionEmbeddingIdentity {
+    pub spec_id: String,
+    pub model_version: String,
+    pub model_hash: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SessionSearchRequest {
     text_query: String,
@@ -158,9 +168,9 @@ impl WaxBroker {
             .search(RuntimeSearchRequest {
                 mode: RuntimeSearchMode::Text,
                 text_query: Some(request.text_query),
-                vector_query: None,
                 top_k: request.top_k,
                 include_preview: request.include_preview,
+                ..Default::default()
             })
             .map_err(runtime_error)
     }
@@ -213,9 +223,11 @@ impl WaxBroker {
         Ok(map_publish_report(report))
     }
 
+    /// Ingests default-space vectors produced by the `embedding` model.
     pub fn ingest_vectors(
         &mut self,
         session_id: SessionId,
+        embedding: SessionEmbeddingIdentity,
         vectors: Vec<SessionNewDocumentVector>,
     ) -> Result<SessionImportReport, BrokerError> {
         let runtime = self
@@ -226,6 +238,11 @@ impl WaxBroker {
             .writer()
             .map_err(runtime_error)?
             .publish_raw_vectors(
+                RuntimeEmbeddingIdentity::new(
+                    embedding.spec_id,
+                    embedding.model_version,
+                    embedding.model_hash,
+                ),
                 vectors
                     .into_iter()
                     .map(|vector| NewDocumentVector::new(vector.doc_id, vector.values))
@@ -250,6 +267,13 @@ fn runtime_error(error: wax_v2_runtime::RuntimeError) -> BrokerError {
             BrokerError::InvalidRequest(message)
         }
         wax_v2_runtime::RuntimeError::Storage(message) => BrokerError::Storage(message),
+        error @ wax_v2_runtime::RuntimeError::EmbeddingMismatch(_) => {
+            BrokerError::InvalidRequest(error.to_string())
+        }
+        error @ (wax_v2_runtime::RuntimeError::Embedding(_)
+        | wax_v2_runtime::RuntimeError::WriterBusy { .. }) => {
+            BrokerError::Storage(error.to_string())
+        }
     }
 }
 
@@ -263,6 +287,7 @@ fn map_publish_report(report: RuntimePublishReport) -> SessionImportReport {
                 RuntimePublishFamily::Doc => BrokerPublishFamily::Doc,
                 RuntimePublishFamily::Text => BrokerPublishFamily::Text,
                 RuntimePublishFamily::Vector => BrokerPublishFamily::Vector,
+                RuntimePublishFamily::Sparse => BrokerPublishFamily::Sparse,
             })
             .collect(),
     }
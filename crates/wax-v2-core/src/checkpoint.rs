@@ -0,0 +1,366 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::history::segments_reverted_to;
+use crate::{
+    list_manifest_generations, map_segment_object, open_store, publish_existing_segments,
+    publish_segments_with_precondition, read_u16, read_u32, read_u64, ActiveManifest, CoreError,
+    OpenedStore, PendingSegmentDescriptor, PendingSegmentWrite, SegmentKind,
+};
+
+const CHECKPOINT_SEGMENT_MAGIC: &[u8; 4] = b"WXCK";
+const CHECKPOINT_SEGMENT_MAJOR: u16 = 1;
+const CHECKPOINT_SEGMENT_MINOR: u16 = 0;
+const CHECKPOINT_SEGMENT_HEADER_LENGTH: usize = 16;
+
+/// A named pointer at a manifest generation that space reclamation must keep readable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub name: String,
+    pub generation: u64,
+    pub created_at_ms: u64,
+}
+
+/// Lists the checkpoints recorded in the active generation, ordered by name.
+pub fn list_checkpoints(path: &Path) -> Result<Vec<Checkpoint>, CoreError> {
+    let opened = open_store(path)?;
+    checkpoints_in_manifest(path, &opened.manifest)
+}
+
+/// Records `name` for `generation`, or for the active generation when `None`.
+///
+/// The checkpoint list is itself a segment, so recording one publishes a new generation that
+/// otherwise matches the active one.
+pub fn create_checkpoint(
+    path: &Path,
+    name: &str,
+    generation: Option<u64>,
+    lock_timeout: Duration,
+) -> Result<Checkpoint, CoreError> {
+    if name.is_empty() || name.trim() != name {
+        return Err(CoreError::PublishPreconditionFailed(
+            "checkpoint name must be non-empty without surrounding whitespace".to_owned(),
+        ));
+    }
+    let opened = open_store(path)?;
+    let generation = generation.unwrap_or(opened.manifest.generation);
+    // Only older generations need the history walk; the active one is already open.
+    if generation != opened.manifest.generation
+        && !list_manifest_generations(path)?
+            .iter()
+            .any(|entry| entry.generation == generation)
+    {
+        return Err(CoreError::PublishPreconditionFailed(format!(
+            "manifest generation {generation} is not present in the store"
+        )));
+    }
+    let mut checkpoints = checkpoints_in_manifest(path, &opened.manifest)?;
+    if checkpoints.iter().any(|checkpoint| checkpoint.name == name) {
+        return Err(CoreError::PublishPreconditionFailed(format!(
+            "checkpoint {name} already exists"
+        )));
+    }
+    let checkpoint = Checkpoint {
+        name: name.to_owned(),
+        generation,
+        created_at_ms: now_ms(),
+    };
+    checkpoints.push(checkpoint.clone());
+    publish_checkpoints(path, opened.manifest.generation, checkpoints, lock_timeout)?;
+    Ok(checkpoint)
+}
+
+/// Removes the checkpoint called `name` and returns it.
+pub fn delete_checkpoint(
+    path: &Path,
+    name: &str,
+    lock_timeout: Duration,
+) -> Result<Checkpoint, CoreError> {
+    let opened = open_store(path)?;
+    let mut checkpoints = checkpoints_in_manifest(path, &opened.manifest)?;
+    let index = checkpoints
+        .iter()
+        .position(|checkpoint| checkpoint.name == name)
+        .ok_or_else(|| missing_checkpoint(name))?;
+    let removed = checkpoints.remove(index);
+    publish_checkpoints(path, opened.manifest.generation, checkpoints, lock_timeout)?;
+    Ok(removed)
+}
+
+/// Publishes a new generation whose segments are those of the checkpointed generation, like
+/// [`rollback_to`](crate::rollback_to).
+pub fn restore_checkpoint(
+    path: &Path,
+    name: &str,
+    lock_timeout: Duration,
+) -> Result<OpenedStore, CoreError> {
+    publish_existing_segments(
+        path,
+        |active| {
+            let checkpoint = checkpoints_in_manifest(path, active)?
+                .into_iter()
+                .find(|checkpoint| checkpoint.name == name)
+                .ok_or_else(|| missing_checkpoint(name))?;
+            segments_reverted_to(path, active, checkpoint.generation)
+        },
+        lock_timeout,
+    )
+}
+
+/// Generations a vacuum or compaction must keep readable: the active one and every
+/// checkpointed one.
+pub fn protected_generations(path: &Path) -> Result<BTreeSet<u64>, CoreError> {
+    let opened = open_store(path)?;
+    let mut generations = checkpoints_in_manifest(path, &opened.manifest)?
+        .into_iter()
+        .map(|checkpoint| checkpoint.generation)
+        .collect::<BTreeSet<_>>();
+    generations.insert(opened.manifest.generation);
+    Ok(generations)
+}
+
+pub(crate) fn checkpoints_in_manifest(
+    path: &Path,
+    manifest: &ActiveManifest,
+) -> Result<Vec<Checkpoint>, CoreError> {
+    let Some(descriptor) = manifest
+        .segments
+        .iter()
+        .find(|segment| segment.family == SegmentKind::Ckp)
+    else {
+        return Ok(Vec::new());
+    };
+    decode_checkpoint_segment(&map_segment_object(path, descriptor)?)
+}
+
+fn publish_checkpoints(
+    path: &Path,
+    expected_generation: u64,
+    mut checkpoints: Vec<Checkpoint>,
+    lock_timeout: Duration,
+) -> Result<OpenedStore, CoreError> {
+    checkpoints.sort_by(|left, right| left.name.cmp(&right.name));
+    let created_at = checkpoints
+        .iter()
+        .map(|checkpoint| checkpoint.created_at_ms);
+    let pending = PendingSegmentWrite {
+        descriptor: PendingSegmentDescriptor {
+            family: SegmentKind::Ckp,
+            family_version: CHECKPOINT_SEGMENT_MAJOR,
+            flags: 0,
+            doc_id_start: 0,
+            doc_id_end_exclusive: 0,
+            min_timestamp_ms: created_at.clone().min().unwrap_or(0),
+            max_timestamp_ms: created_at.max().unwrap_or(0),
+            live_items: checkpoints.len() as u64,
+            tombstoned_items: 0,
+            backend_id: 0,
+            backend_aux: 0,
+        },
+        object_bytes: encode_checkpoint_segment(&checkpoints)?,
+    };
+    publish_segments_with_precondition(
+        path,
+        vec![pending],
+        |manifest| {
+            if manifest.generation == expected_generation {
+                Ok(())
+            } else {
+                Err(CoreError::PublishPreconditionFailed(format!(
+                    "store moved from generation {expected_generation} to {} while updating checkpoints",
+                    manifest.generation
+                )))
+            }
+        },
+        lock_timeout,
+    )
+}
+
+fn missing_checkpoint(name: &str) -> CoreError {
+    CoreError::PublishPreconditionFailed(format!("no checkpoint named {name}"))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+fn encode_checkpoint_segment(checkpoints: &[Checkpoint]) -> Result<Vec<u8>, CoreError> {
+    let mut bytes = vec![0; CHECKPOINT_SEGMENT_HEADER_LENGTH];
+    bytes[..4].copy_from_slice(CHECKPOINT_SEGMENT_MAGIC);
+    bytes[4..6].copy_from_slice(&CHECKPOINT_SEGMENT_MAJOR.to_le_bytes());
+    bytes[6..8].copy_from_slice(&CHECKPOINT_SEGMENT_MINOR.to_le_bytes());
+    bytes[8..16].copy_from_slice(&(checkpoints.len() as u64).to_le_bytes());
+    for checkpoint in checkpoints {
+        let name_length = u32::try_from(checkpoint.name.len()).map_err(|_| {
+            CoreError::PublishPreconditionFailed(format!(
+                "checkpoint name too long: {}",
+                checkpoint.name
+            ))
+        })?;
+        bytes.extend_from_slice(&name_length.to_le_bytes());
+        bytes.extend_from_slice(checkpoint.name.as_bytes());
+        bytes.extend_from_slice(&checkpoint.generation.to_le_bytes());
+        bytes.extend_from_slice(&checkpoint.created_at_ms.to_le_bytes());
+    }
+    Ok(bytes)
+}
+
+pub(crate) fn decode_checkpoint_segment(bytes: &[u8]) -> Result<Vec<Checkpoint>, CoreError> {
+    let truncated = || CoreError::UnexpectedLength {
+        context: "checkpoint segment",
+        expected_at_least: CHECKPOINT_SEGMENT_HEADER_LENGTH,
+        actual: bytes.len(),
+    };
+    if bytes.len() < CHECKPOINT_SEGMENT_HEADER_LENGTH {
+        return Err(truncated());
+    }
+    if &bytes[..4] != CHECKPOINT_SEGMENT_MAGIC {
+        return Err(CoreError::InvalidMagic {
+            context: "checkpoint segment",
+        });
+    }
+    let major = read_u16(bytes, 4);
+    if major != CHECKPOINT_SEGMENT_MAJOR {
+        return Err(CoreError::InvalidVersion(u32::from(major)));
+    }
+    let count = read_u64(bytes, 8);
+    let mut checkpoints = Vec::new();
+    let mut cursor = CHECKPOINT_SEGMENT_HEADER_LENGTH;
+    for _ in 0..count {
+        if bytes.len() < cursor + 4 {
+            return Err(truncated());
+        }
+        let name_end = cursor + 4 + read_u32(bytes, cursor) as usize;
+        if bytes.len() < name_end + 16 {
+            return Err(truncated());
+        }
+        let name = std::str::from_utf8(&bytes[cursor + 4..name_end])
+            .map_err(|_| CoreError::InvalidManifest("checkpoint name is not utf-8".to_owned()))?;
+        checkpoints.push(Checkpoint {
+            name: name.to_owned(),
+            generation: read_u64(bytes, name_end),
+            created_at_ms: read_u64(bytes, name_end + 8),
+        });
+        cursor = name_end + 16;
+    }
+    if cursor != bytes.len() {
+        return Err(CoreError::InvalidManifest(
+            "checkpoint segment has trailing bytes".to_owned(),
+        ));
+    }
+    Ok(checkpoints)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use crate::tests::{append_torn_object, doc_descriptor};
+    use crate::{
+        create_empty_store, open_store, publish_segment, read_segment_object, verify_store,
+        CoreError, SegmentKind, DEFAULT_WRITER_LOCK_TIMEOUT,
+    };
+
+    use super::{
+        create_checkpoint, delete_checkpoint, list_checkpoints, protected_generations,
+        restore_checkpoint,
+    };
+
+    fn doc_payload(path: &std::path::Path) -> Vec<u8> {
+        let opened = open_store(path).expect("open");
+        let descriptor = opened
+            .manifest
+            .segments
+            .iter()
+            .find(|segment| segment.family == SegmentKind::Doc)
+            .expect("doc segment");
+        read_segment_object(path, descriptor).expect("read")
+    }
+
+    #[test]
+    fn checkpoints_survive_publishes_and_restore_their_generation() {
+        let temp_dir = tempdir().expect("tempdir");
+        let path = temp_dir.path().join("store.wax");
+        create_empty_store(&path).expect("create");
+        publish_segment(&path, doc_descriptor(), b"first").expect("publish first");
+
+        let before = create_checkpoint(&path, "before-second", None, DEFAULT_WRITER_LOCK_TIMEOUT)
+            .expect("checkpoint");
+        assert_eq!(before.generation, 1);
+        publish_segment(&path, doc_descriptor(), b"second").expect("publish second");
+        create_checkpoint(&path, "initial", Some(0), DEFAULT_WRITER_LOCK_TIMEOUT)
+            .expect("checkpoint initial");
+        assert_eq!(
+            create_checkpoint(&path, "initial", None, DEFAULT_WRITER_LOCK_TIMEOUT),
+            Err(CoreError::PublishPreconditionFailed(
+                "checkpoint initial already exists".to_owned()
+            ))
+        );
+        assert!(create_checkpoint(&path, "future", Some(99), DEFAULT_WRITER_LOCK_TIMEOUT).is_err());
+
+        let listed = list_checkpoints(&path).expect("list");
+        assert_eq!(
+            listed
+                .iter()
+                .map(|checkpoint| (checkpoint.name.as_str(), checkpoint.generation))
+                .collect::<Vec<_>>(),
+            vec![("before-second", 1), ("initial", 0)]
+        );
+        let active = open_store(&path).expect("open").manifest.generation;
+        assert_eq!(
+            protected_generations(&path)
+                .expect("protected")
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![0, 1, active]
+        );
+
+        let restored = restore_checkpoint(&path, "before-second", DEFAULT_WRITER_LOCK_TIMEOUT)
+            .expect("restore");
+        assert_eq!(restored.manifest.generation, active + 1);
+        assert_eq!(doc_payload(&path), b"first");
+        assert_eq!(list_checkpoints(&path).expect("list"), listed);
+        assert!(verify_store(&path).expect("verify").is_ok());
+
+        assert_eq!(
+            delete_checkpoint(&path, "initial", DEFAULT_WRITER_LOCK_TIMEOUT)
+                .expect("delete")
+                .generation,
+            0
+        );
+        assert_eq!(list_checkpoints(&path).expect("list").len(), 1);
+        assert!(restore_checkpoint(&path, "initial", DEFAULT_WRITER_LOCK_TIMEOUT).is_err());
+        assert_eq!(doc_payload(&path), b"first");
+    }
+
+    #[test]
+    fn checkpoints_create_and_restore_after_a_torn_publish() {
+        let temp_dir = tempdir().expect("tempdir");
+        let path = temp_dir.path().join("store.wax");
+        create_empty_store(&path).expect("create");
+        publish_segment(&path, doc_descriptor(), b"first").expect("publish first");
+        create_checkpoint(&path, "first", None, DEFAULT_WRITER_LOCK_TIMEOUT).expect("checkpoint");
+        append_torn_object(&path, 3);
+        publish_segment(&path, doc_descriptor(), b"second").expect("publish second");
+
+        let older = create_checkpoint(&path, "initial", Some(0), DEFAULT_WRITER_LOCK_TIMEOUT)
+            .expect("checkpoint older generation");
+        assert_eq!(older.generation, 0);
+        append_torn_object(&path, 5);
+        let restored =
+            restore_checkpoint(&path, "first", DEFAULT_WRITER_LOCK_TIMEOUT).expect("restore");
+        assert_eq!(restored.manifest.generation, 5);
+        assert_eq!(doc_payload(&path), b"first");
+        assert_eq!(
+            list_checkpoints(&path)
+                .expect("list")
+                .iter()
+                .map(|checkpoint| checkpoint.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["first", "initial"]
+        );
+    }
+}
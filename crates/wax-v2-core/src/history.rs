@@ -0,0 +1,268 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    align_up, decode_object_payload, open_store_from_file, publish_existing_segments, read_u16,
+    read_u64, ActiveManifest, CoreError, ObjectType, OpenedStore, SegmentDescriptor, SegmentKind,
+    Superblock, DEFAULT_OBJECT_ALIGNMENT, OBJECT_HEADER_LENGTH, OBJECT_MAGIC, OBJECT_VERSION,
+    SUPERBLOCK_SIZE,
+};
+
+/// A manifest generation still physically present in the store file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestGeneration {
+    pub generation: u64,
+    pub manifest_offset: u64,
+    pub manifest_length: u32,
+    pub segment_count: usize,
+    /// Whether the superblock currently points at this generation.
+    pub active: bool,
+}
+
+/// Lists the manifest generations reachable from the active one, oldest first.
+///
+/// Walks the append region up to the end of the active manifest. A publish interrupted before its
+/// superblock switch can leave a manifest that a later publish rewrites with the same generation;
+/// the later object wins, so the list holds one entry per generation. A torn object such a
+/// publish left behind is skipped by resyncing at the next aligned object whose checksum verifies.
+pub fn list_manifest_generations(path: &Path) -> Result<Vec<ManifestGeneration>, CoreError> {
+    Ok(read_manifest_history(path)?
+        .into_iter()
+        .map(|entry| entry.summary)
+        .collect())
+}
+
+/// Opens the store as of `generation`, or the active generation when `None`.
+///
+/// The returned superblock is synthesized from the historical manifest object; it is never
+/// written back, so opening an older generation does not move the store.
+pub fn open_store_at_generation(
+    path: &Path,
+    generation: Option<u64>,
+) -> Result<OpenedStore, CoreError> {
+    let active = crate::open_store(path)?;
+    let Some(generation) =
+        generation.filter(|generation| *generation != active.manifest.generation)
+    else {
+        return Ok(active);
+    };
+    read_manifest_history(path)?
+        .into_iter()
+        .find(|entry| entry.summary.generation == generation)
+        .map(|entry| entry.opened)
+        .ok_or_else(|| {
+            CoreError::InvalidManifest(format!(
+                "manifest generation {generation} is not present in the store"
+            ))
+        })
+}
+
+/// Reverts the store to `generation` by publishing its segment set as a new generation.
+///
+/// History stays append-only: no objects are copied or removed, and the generations after
+/// `generation` remain listable and openable. Checkpoints recorded in the active generation are
+/// carried over.
+pub fn rollback_to(
+    path: &Path,
+    generation: u64,
+    lock_timeout: Duration,
+) -> Result<OpenedStore, CoreError> {
+    publish_existing_segments(
+        path,
+        |active| {
+            if generation >= active.generation {
+                return Err(CoreError::PublishPreconditionFailed(format!(
+                    "rollback target {generation} must be older than the active generation {}",
+                    active.generation
+                )));
+            }
+            segments_reverted_to(path, active, generation)
+        },
+        lock_timeout,
+    )
+}
+
+/// Segment set of `generation` with the checkpoint segment of `active`.
+pub(crate) fn segments_reverted_to(
+    path: &Path,
+    active: &ActiveManifest,
+    generation: u64,
+) -> Result<Vec<SegmentDescriptor>, CoreError> {
+    let target = read_manifest_history(path)?
+        .into_iter()
+        .find(|entry| entry.summary.generation == generation)
+        .ok_or_else(|| {
+            CoreError::PublishPreconditionFailed(format!(
+                "manifest generation {generation} is not present in the store"
+            ))
+        })?
+        .opened;
+    Ok(target
+        .manifest
+        .segments
+        .into_iter()
+        .filter(|segment| segment.family != SegmentKind::Ckp)
+        .chain(
+            active
+                .segments
+                .iter()
+                .filter(|segment| segment.family == SegmentKind::Ckp)
+                .cloned(),
+        )
+        .collect())
+}
+
+struct HistoricalManifest {
+    summary: ManifestGeneration,
+    opened: OpenedStore,
+}
+
+fn read_manifest_history(path: &Path) -> Result<Vec<HistoricalManifest>, CoreError> {
+    let mut file = OpenOptions::new().read(true).open(path)?;
+    let active = open_store_from_file(&mut file)?;
+    let active_end = active.superblock.active_manifest_offset
+        + u64::from(active.superblock.active_manifest_length);
+
+    let mut history: Vec<HistoricalManifest> = Vec::new();
+    let mut offset = align_up((SUPERBLOCK_SIZE * 2) as u64, DEFAULT_OBJECT_ALIGNMENT)?;
+    // Set after a torn object: only objects whose payload checksum verifies are trusted again.
+    let mut resyncing = false;
+    while offset < active_end {
+        let Some(header) = read_object_header(&mut file, offset, active_end)? else {
+            // Zeros pad before an object written with a coarser alignment; anything else is
+            // the remains of a torn object.
+            resyncing |= read_magic(&mut file, offset, active_end)? != Some([0; 4]);
+            offset += DEFAULT_OBJECT_ALIGNMENT;
+            continue;
+        };
+        let next_offset = align_up(offset + header.object_length, DEFAULT_OBJECT_ALIGNMENT)?;
+        let trusted = if resyncing {
+            payload_checksum_matches(&mut file, offset, &header)?
+        } else {
+            // A length the next object boundary does not confirm belongs to a torn header.
+            offset + header.object_length == active_end
+                || matches!(
+                    read_magic(&mut file, next_offset, active_end)?,
+                    Some(magic) if magic == *OBJECT_MAGIC || magic == [0; 4]
+                )
+        };
+        if !trusted {
+            // An interrupted publish left a torn object that a later publish appended past;
+            // its claimed length cannot be followed, so resync at the next aligned object.
+            resyncing = true;
+            offset += DEFAULT_OBJECT_ALIGNMENT;
+            continue;
+        }
+        if header.object_type == ObjectType::Manifest {
+            let mut object = vec![0u8; header.object_length as usize];
+            file.seek(SeekFrom::Start(offset))?;
+            file.read_exact(&mut object)?;
+            let Ok(decoded) =
+                decode_object_payload(&object, ObjectType::Manifest, header.generation)
+            else {
+                resyncing = true;
+                offset += DEFAULT_OBJECT_ALIGNMENT;
+                continue;
+            };
+            let manifest = ActiveManifest::decode(&object[decoded.payload_range.clone()])?;
+            if manifest.generation <= active.manifest.generation {
+                history.retain(|entry| entry.summary.generation != manifest.generation);
+                history.push(HistoricalManifest {
+                    summary: ManifestGeneration {
+                        generation: manifest.generation,
+                        manifest_offset: offset,
+                        manifest_length: header.object_length as u32,
+                        segment_count: manifest.segments.len(),
+                        active: manifest.generation == active.manifest.generation,
+                    },
+                    opened: OpenedStore {
+                        superblock: Superblock::new(
+                            manifest.generation,
+                            offset,
+                            header.object_length as u32,
+                            decoded.payload_checksum,
+                        ),
+                        manifest,
+                    },
+                });
+            }
+        }
+        resyncing = false;
+        offset = next_offset;
+    }
+    history.sort_by_key(|entry| entry.summary.generation);
+    Ok(history)
+}
+
+/// Header fields of a well-formed object that lies entirely before `active_end`.
+struct ObjectHeader {
+    object_type: ObjectType,
+    generation: u64,
+    object_length: u64,
+    payload_checksum: [u8; 32],
+}
+
+fn read_object_header(
+    file: &mut File,
+    offset: u64,
+    active_end: u64,
+) -> Result<Option<ObjectHeader>, CoreError> {
+    if offset + OBJECT_HEADER_LENGTH as u64 > active_end {
+        return Ok(None);
+    }
+    let mut header = [0u8; OBJECT_HEADER_LENGTH];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut header)?;
+    if header[..4] != OBJECT_MAGIC[..] || read_u16(&header, 6) != OBJECT_VERSION {
+        return Ok(None);
+    }
+    let Ok(object_type) = ObjectType::from_code(read_u16(&header, 4)) else {
+        return Ok(None);
+    };
+    let Some(object_length) = (OBJECT_HEADER_LENGTH as u64)
+        .checked_add(read_u64(&header, 8))
+        .filter(|length| {
+            offset
+                .checked_add(*length)
+                .is_some_and(|end| end <= active_end)
+        })
+    else {
+        return Ok(None);
+    };
+    let mut payload_checksum = [0u8; 32];
+    payload_checksum.copy_from_slice(&header[32..64]);
+    Ok(Some(ObjectHeader {
+        object_type,
+        generation: read_u64(&header, 16),
+        object_length,
+        payload_checksum,
+    }))
+}
+
+fn payload_checksum_matches(
+    file: &mut File,
+    offset: u64,
+    header: &ObjectHeader,
+) -> Result<bool, CoreError> {
+    file.seek(SeekFrom::Start(offset + OBJECT_HEADER_LENGTH as u64))?;
+    let mut payload = file
+        .try_clone()?
+        .take(header.object_length - OBJECT_HEADER_LENGTH as u64);
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut payload, &mut hasher)?;
+    Ok(hasher.finalize()[..] == header.payload_checksum[..])
+}
+
+fn read_magic(file: &mut File, offset: u64, active_end: u64) -> Result<Option<[u8; 4]>, CoreError> {
+    if offset + 4 > active_end {
+        return Ok(None);
+    }
+    let mut magic = [0u8; 4];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut magic)?;
+    Ok(Some(magic))
+}
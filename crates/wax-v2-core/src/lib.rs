@@ -3,14 +3,32 @@ use std::fs::OpenOptions;
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::ops::Range;
 use std::path::Path;
+use std::time::{Duration, Instant};
 
 use fs2::FileExt;
 use memmap2::{Mmap, MmapOptions};
 use sha2::{Digest, Sha256};
 
+mod checkpoint;
+mod history;
+mod verify;
+
+pub use checkpoint::{
+    create_checkpoint, delete_checkpoint, list_checkpoints, protected_generations,
+    restore_checkpoint, Checkpoint,
+};
+pub use history::{
+    list_manifest_generations, open_store_at_generation, rollback_to, ManifestGeneration,
+};
+pub use verify::{
+    verify_store, verify_store_with_decoder, DecodedSegmentRows, SegmentDecoder,
+    SegmentVerification, StoreVerifyReport, SuperblockVerification, VerifyIssue, VerifyIssueKind,
+};
+
 const FILE_MAGIC: &[u8; 8] = b"RAXWAXV2";
 const MANIFEST_MAGIC: &[u8; 8] = b"RAXMANI1";
 const OBJECT_MAGIC: &[u8; 4] = b"WXOB";
+const SPILL_COPY_CHUNK_LENGTH: usize = 1024 * 1024;
 const FORMAT_VERSION: u32 = 1;
 const SUPERBLOCK_CHECKSUM_OFFSET: usize = 64;
 const SUPERBLOCK_CHECKSUM_LENGTH: usize = 32;
@@ -25,6 +43,8 @@ const OBJECT_VERSION: u16 = 1;
 const DEFAULT_OBJECT_ALIGNMENT: u64 = 4096;
 
 pub const SUPERBLOCK_SIZE: usize = 128;
+/// How long publishes wait for another writer to release the store lock unless told otherwise.
+pub const DEFAULT_WRITER_LOCK_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CoreError {
@@ -45,6 +65,10 @@ pub enum CoreError {
     PublishPreconditionFailed(String),
     UnknownSegmentKind(u16),
     NoValidSuperblock,
+    /// Another writer held the store lock for the whole lock timeout.
+    WriterBusy {
+        waited_ms: u64,
+    },
 }
 
 impl From<std::io::Error> for CoreError {
@@ -64,14 +88,29 @@ pub enum SegmentKind {
     Doc,
     Txt,
     Vec,
+    Spr,
+    /// Named checkpoints; see [`create_checkpoint`].
+    Ckp,
 }
 
 impl SegmentKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Doc => "doc",
+            Self::Txt => "txt",
+            Self::Vec => "vec",
+            Self::Spr => "spr",
+            Self::Ckp => "ckp",
+        }
+    }
+
     fn as_code(self) -> u16 {
         match self {
             Self::Doc => 1,
             Self::Txt => 2,
             Self::Vec => 3,
+            Self::Spr => 4,
+            Self::Ckp => 5,
         }
     }
 
@@ -80,6 +119,8 @@ impl SegmentKind {
             1 => Ok(Self::Doc),
             2 => Ok(Self::Txt),
             3 => Ok(Self::Vec),
+            4 => Ok(Self::Spr),
+            5 => Ok(Self::Ckp),
             _ => Err(CoreError::UnknownSegmentKind(code)),
         }
     }
@@ -125,6 +166,18 @@ pub struct PendingSegmentWrite {
     pub object_bytes: Vec<u8>,
 }
 
+/// A segment object a streaming builder staged in a spill file instead of memory.
+#[derive(Debug)]
+pub struct SpilledSegmentWrite {
+    pub descriptor: PendingSegmentDescriptor,
+    pub object: std::fs::File,
+}
+
+enum SegmentObjectSource {
+    Bytes(Vec<u8>),
+    Spilled(std::fs::File),
+}
+
 impl PendingSegmentDescriptor {
     fn publish(
         &self,
@@ -159,6 +212,8 @@ enum ObjectType {
     DocSegment = 2,
     TxtSegment = 3,
     VecSegment = 4,
+    SprSegment = 7,
+    CkpSegment = 8,
 }
 
 impl ObjectType {
@@ -172,6 +227,8 @@ impl ObjectType {
             2 => Ok(Self::DocSegment),
             3 => Ok(Self::TxtSegment),
             4 => Ok(Self::VecSegment),
+            7 => Ok(Self::SprSegment),
+            8 => Ok(Self::CkpSegment),
             _ => Err(CoreError::InvalidManifest(format!(
                 "unknown object type: {code}"
             ))),
@@ -533,7 +590,7 @@ pub fn open_store(path: &Path) -> Result<OpenedStore, CoreError> {
     open_store_from_file(&mut file)
 }
 
-fn open_store_from_file(mut file: &mut OpenOptionsFile) -> Result<OpenedStore, CoreError> {
+fn open_store_from_file(file: &mut OpenOptionsFile) -> Result<OpenedStore, CoreError> {
     let file_length = file.metadata()?.len();
     if file_length < (SUPERBLOCK_SIZE * 2) as u64 {
         return Err(CoreError::UnexpectedLength {
@@ -555,7 +612,7 @@ fn open_store_from_file(mut file: &mut OpenOptionsFile) -> Result<OpenedStore, C
 
     let mut last_error = None;
     for candidate in candidates {
-        match open_store_from_superblock(&mut file, file_length, candidate) {
+        match open_store_from_superblock(file, file_length, candidate) {
             Ok(opened) => return Ok(opened),
             Err(error) => last_error = Some(error),
         }
@@ -564,6 +621,56 @@ fn open_store_from_file(mut file: &mut OpenOptionsFile) -> Result<OpenedStore, C
     Err(last_error.unwrap_or(CoreError::NoValidSuperblock))
 }
 
+/// What [`open_store_with_repair`] does with bytes past the active manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TornTailRepair {
+    Detect,
+    /// Truncates the tail under the exclusive writer lock. Bytes past the active manifest are
+    /// never referenced, so only an interrupted publish's objects are dropped. Publishes never
+    /// do this on their own; they append past the tail and leave it to an explicit repair.
+    Truncate,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveredStore {
+    pub store: OpenedStore,
+    /// End of the active manifest object, the last byte any publish made reachable.
+    pub valid_length: u64,
+    /// Orphaned bytes found past `valid_length` when the store was opened.
+    pub torn_tail_bytes: u64,
+    pub truncated: bool,
+}
+
+/// Opens the store like [`open_store`] and detects the orphaned tail an interrupted publish
+/// leaves behind (objects appended before the superblock switch, or a torn object).
+///
+/// `lock_timeout` bounds the wait for the writer lock when truncating, like a publish.
+pub fn open_store_with_repair(
+    path: &Path,
+    repair: TornTailRepair,
+    lock_timeout: Duration,
+) -> Result<RecoveredStore, CoreError> {
+    let truncate = repair == TornTailRepair::Truncate;
+    let mut file = OpenOptions::new().read(true).write(truncate).open(path)?;
+    if truncate {
+        lock_writer(&file, lock_timeout)?;
+    }
+    let store = open_store_from_file(&mut file)?;
+    let valid_length = active_manifest_end(&store)?;
+    let torn_tail_bytes = file.metadata()?.len().saturating_sub(valid_length);
+    let truncated = truncate && torn_tail_bytes > 0;
+    if truncated {
+        file.set_len(valid_length)?;
+        file.sync_all()?;
+    }
+    Ok(RecoveredStore {
+        store,
+        valid_length,
+        torn_tail_bytes,
+        truncated,
+    })
+}
+
 pub fn publish_segment(
     path: &Path,
     pending: PendingSegmentDescriptor,
@@ -582,18 +689,30 @@ pub fn publish_segments(
     path: &Path,
     pending_segments: Vec<PendingSegmentWrite>,
 ) -> Result<OpenedStore, CoreError> {
-    publish_segments_with_precondition(path, pending_segments, |_| Ok(()))
+    publish_segments_with_precondition(
+        path,
+        pending_segments,
+        |_| Ok(()),
+        DEFAULT_WRITER_LOCK_TIMEOUT,
+    )
 }
 
 pub fn publish_segments_with_precondition<F>(
     path: &Path,
     pending_segments: Vec<PendingSegmentWrite>,
     precondition: F,
+    lock_timeout: Duration,
 ) -> Result<OpenedStore, CoreError>
 where
     F: FnOnce(&ActiveManifest) -> Result<(), CoreError>,
 {
-    publish_segments_replacing_families_with_precondition(path, pending_segments, &[], precondition)
+    publish_segments_replacing_families_with_precondition(
+        path,
+        pending_segments,
+        &[],
+        precondition,
+        lock_timeout,
+    )
 }
 
 pub fn publish_segments_replacing_families_with_precondition<F>(
@@ -601,18 +720,150 @@ pub fn publish_segments_replacing_families_with_precondition<F>(
     pending_segments: Vec<PendingSegmentWrite>,
     removed_families: &[SegmentKind],
     precondition: F,
+    lock_timeout: Duration,
+) -> Result<OpenedStore, CoreError>
+where
+    F: FnOnce(&ActiveManifest) -> Result<(), CoreError>,
+{
+    let published_families = pending_segments
+        .iter()
+        .map(|segment| segment.descriptor.family)
+        .collect::<Vec<_>>();
+    publish_segments_retaining_with_precondition(
+        path,
+        pending_segments,
+        |segment| {
+            !published_families.contains(&segment.family)
+                && !removed_families.contains(&segment.family)
+        },
+        precondition,
+        lock_timeout,
+    )
+}
+
+/// Publishes `pending_segments` as a new generation that keeps only the currently active
+/// segments accepted by `retain`.
+///
+/// Unlike the family-replacing variants, retention is decided per descriptor, so callers can keep
+/// several segments of the same family alive (for example one `Vec` segment per vector space).
+///
+/// Waits up to `lock_timeout` for another writer to finish before failing with
+/// [`CoreError::WriterBusy`].
+pub fn publish_segments_retaining_with_precondition<R, F>(
+    path: &Path,
+    pending_segments: Vec<PendingSegmentWrite>,
+    retain: R,
+    precondition: F,
+    lock_timeout: Duration,
 ) -> Result<OpenedStore, CoreError>
 where
+    R: Fn(&SegmentDescriptor) -> bool,
     F: FnOnce(&ActiveManifest) -> Result<(), CoreError>,
 {
-    if pending_segments.is_empty() {
+    publish_objects_retaining(
+        path,
+        pending_segments
+            .into_iter()
+            .map(|pending| {
+                (
+                    pending.descriptor,
+                    SegmentObjectSource::Bytes(pending.object_bytes),
+                )
+            })
+            .collect(),
+        retain,
+        precondition,
+        lock_timeout,
+    )
+}
+
+/// Publishes segment objects staged in spill files, replacing the active segments of their
+/// families and of `removed_families`. Each spill file is streamed into the store in chunks, so
+/// the objects never need to fit in memory.
+pub fn publish_spilled_segments_with_precondition<F>(
+    path: &Path,
+    spilled_segments: Vec<SpilledSegmentWrite>,
+    removed_families: &[SegmentKind],
+    precondition: F,
+    lock_timeout: Duration,
+) -> Result<OpenedStore, CoreError>
+where
+    F: FnOnce(&ActiveManifest) -> Result<(), CoreError>,
+{
+    let published_families = spilled_segments
+        .iter()
+        .map(|segment| segment.descriptor.family)
+        .collect::<Vec<_>>();
+    publish_spilled_segments_retaining_with_precondition(
+        path,
+        spilled_segments,
+        Vec::new(),
+        |segment| {
+            !published_families.contains(&segment.family)
+                && !removed_families.contains(&segment.family)
+        },
+        precondition,
+        lock_timeout,
+    )
+}
+
+/// Publishes spilled segment objects together with in-memory `pending_segments` as a new
+/// generation that keeps only the currently active segments accepted by `retain`, like
+/// [`publish_segments_retaining_with_precondition`].
+pub fn publish_spilled_segments_retaining_with_precondition<R, F>(
+    path: &Path,
+    spilled_segments: Vec<SpilledSegmentWrite>,
+    pending_segments: Vec<PendingSegmentWrite>,
+    retain: R,
+    precondition: F,
+    lock_timeout: Duration,
+) -> Result<OpenedStore, CoreError>
+where
+    R: Fn(&SegmentDescriptor) -> bool,
+    F: FnOnce(&ActiveManifest) -> Result<(), CoreError>,
+{
+    publish_objects_retaining(
+        path,
+        spilled_segments
+            .into_iter()
+            .map(|spilled| {
+                (
+                    spilled.descriptor,
+                    SegmentObjectSource::Spilled(spilled.object),
+                )
+            })
+            .chain(pending_segments.into_iter().map(|pending| {
+                (
+                    pending.descriptor,
+                    SegmentObjectSource::Bytes(pending.object_bytes),
+                )
+            }))
+            .collect(),
+        retain,
+        precondition,
+        lock_timeout,
+    )
+}
+
+fn publish_objects_retaining<R, F>(
+    path: &Path,
+    pending_objects: Vec<(PendingSegmentDescriptor, SegmentObjectSource)>,
+    retain: R,
+    precondition: F,
+    lock_timeout: Duration,
+) -> Result<OpenedStore, CoreError>
+where
+    R: Fn(&SegmentDescriptor) -> bool,
+    F: FnOnce(&ActiveManifest) -> Result<(), CoreError>,
+{
+    if pending_objects.is_empty() {
         return Err(CoreError::InvalidManifest(
             "publish_segments requires at least one pending segment".to_owned(),
         ));
     }
 
     let mut file = OpenOptions::new().read(true).write(true).open(path)?;
-    file.try_lock_exclusive()?;
+    lock_writer(&file, lock_timeout)?;
 
     let opened = open_store_from_file(&mut file)?;
     precondition(&opened.manifest)?;
@@ -621,29 +872,31 @@ where
         .generation
         .checked_add(1)
         .ok_or_else(|| CoreError::InvalidManifest("manifest generation overflow".to_owned()))?;
-    let published_families = pending_segments
-        .iter()
-        .map(|segment| segment.descriptor.family)
-        .collect::<Vec<_>>();
     let mut segments = opened
         .manifest
         .segments
         .into_iter()
-        .filter(|segment| {
-            !published_families.contains(&segment.family)
-                && !removed_families.contains(&segment.family)
-        })
+        .filter(|segment| retain(segment))
         .collect::<Vec<_>>();
-    for pending_segment in pending_segments {
-        let object_type = object_type_for_family(pending_segment.descriptor.family);
-        let appended_object = append_object(
-            &mut file,
-            object_type,
-            new_generation,
-            DEFAULT_OBJECT_ALIGNMENT,
-            &pending_segment.object_bytes,
-        )?;
-        let published_segment = pending_segment.descriptor.publish(
+    for (descriptor, source) in pending_objects {
+        let object_type = object_type_for_family(descriptor.family);
+        let appended_object = match source {
+            SegmentObjectSource::Bytes(object_bytes) => append_object(
+                &mut file,
+                object_type,
+                new_generation,
+                DEFAULT_OBJECT_ALIGNMENT,
+                &object_bytes,
+            )?,
+            SegmentObjectSource::Spilled(mut object) => append_spilled_object(
+                &mut file,
+                object_type,
+                new_generation,
+                DEFAULT_OBJECT_ALIGNMENT,
+                &mut object,
+            )?,
+        };
+        let published_segment = descriptor.publish(
             appended_object.offset,
             appended_object.length,
             new_generation,
@@ -651,6 +904,67 @@ where
         );
         segments.push(published_segment);
     }
+    commit_manifest(&mut file, new_generation, segments)
+}
+
+/// Publishes a new generation that only references objects already in the store; `segments`
+/// receives the active manifest under the writer lock and returns the next descriptor set.
+pub(crate) fn publish_existing_segments<F>(
+    path: &Path,
+    segments: F,
+    lock_timeout: Duration,
+) -> Result<OpenedStore, CoreError>
+where
+    F: FnOnce(&ActiveManifest) -> Result<Vec<SegmentDescriptor>, CoreError>,
+{
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    lock_writer(&file, lock_timeout)?;
+
+    let opened = open_store_from_file(&mut file)?;
+    let segments = segments(&opened.manifest)?;
+    let new_generation = opened
+        .manifest
+        .generation
+        .checked_add(1)
+        .ok_or_else(|| CoreError::InvalidManifest("manifest generation overflow".to_owned()))?;
+    commit_manifest(&mut file, new_generation, segments)
+}
+
+fn active_manifest_end(store: &OpenedStore) -> Result<u64, CoreError> {
+    store
+        .superblock
+        .active_manifest_offset
+        .checked_add(u64::from(store.superblock.active_manifest_length))
+        .ok_or_else(|| CoreError::InvalidManifest("manifest offset overflow".to_owned()))
+}
+
+/// Takes the exclusive writer lock, polling with backoff until `timeout` runs out.
+fn lock_writer(file: &OpenOptionsFile, timeout: Duration) -> Result<(), CoreError> {
+    let started = Instant::now();
+    let mut backoff = Duration::from_millis(1);
+    loop {
+        match file.try_lock_exclusive() {
+            Ok(()) => return Ok(()),
+            Err(error) if error.raw_os_error() == fs2::lock_contended_error().raw_os_error() => {}
+            Err(error) => return Err(error.into()),
+        }
+        let waited = started.elapsed();
+        if waited >= timeout {
+            return Err(CoreError::WriterBusy {
+                waited_ms: waited.as_millis() as u64,
+            });
+        }
+        std::thread::sleep(backoff.min(timeout - waited));
+        backoff = (backoff * 2).min(Duration::from_millis(50));
+    }
+}
+
+/// Appends the manifest for `new_generation` and switches the superblock to it.
+fn commit_manifest(
+    file: &mut OpenOptionsFile,
+    new_generation: u64,
+    mut segments: Vec<SegmentDescriptor>,
+) -> Result<OpenedStore, CoreError> {
     segments.sort_by_key(|segment| {
         (
             segment.family.as_code(),
@@ -666,14 +980,13 @@ where
     };
     let manifest_bytes = manifest.encode()?;
     let appended_manifest = append_object(
-        &mut file,
+        file,
         ObjectType::Manifest,
         new_generation,
         DEFAULT_OBJECT_ALIGNMENT,
         &manifest_bytes,
     )?;
-    file.flush()?;
-    file.sync_all()?;
+    sync_step(file)?;
 
     let superblock = Superblock::new(
         new_generation,
@@ -681,17 +994,16 @@ where
         appended_manifest.length as u32,
         ActiveManifest::checksum(&manifest_bytes),
     );
-    let superblock_offset = if new_generation % 2 == 0 {
+    let superblock_offset = if new_generation.is_multiple_of(2) {
         0
     } else {
         SUPERBLOCK_SIZE as u64
     };
     file.seek(SeekFrom::Start(superblock_offset))?;
-    file.write_all(&superblock.encode())?;
-    file.flush()?;
-    file.sync_all()?;
+    write_step(file, &superblock.encode())?;
+    sync_step(file)?;
 
-    open_store_from_file(&mut file)
+    open_store_from_file(file)
 }
 
 pub fn read_segment_object(
@@ -722,7 +1034,10 @@ pub fn map_segment_object(
             "segment object range extends past end of file".to_owned(),
         ));
     }
-    if descriptor.object_offset % DEFAULT_OBJECT_ALIGNMENT != 0 {
+    if !descriptor
+        .object_offset
+        .is_multiple_of(DEFAULT_OBJECT_ALIGNMENT)
+    {
         return Err(CoreError::InvalidManifest(
             "segment object offset must use store object alignment".to_owned(),
         ));
@@ -911,7 +1226,7 @@ fn write_zero_padding(file: &mut OpenOptionsFile, target_offset: u64) -> Result<
         let zeroes = [0u8; DEFAULT_OBJECT_ALIGNMENT as usize];
         while padding > 0 {
             let chunk = padding.min(zeroes.len() as u64);
-            file.write_all(&zeroes[..chunk as usize])?;
+            write_step(file, &zeroes[..chunk as usize])?;
             padding -= chunk;
         }
     }
@@ -920,6 +1235,63 @@ fn write_zero_padding(file: &mut OpenOptionsFile, target_offset: u64) -> Result<
 
 type OpenOptionsFile = std::fs::File;
 
+/// Publish-path write; an injected fault writes half of `bytes` and fails, like a crash mid-write.
+fn write_step(file: &mut OpenOptionsFile, bytes: &[u8]) -> Result<(), CoreError> {
+    if fault_injection::should_fail() {
+        file.write_all(&bytes[..bytes.len() / 2])?;
+        return Err(CoreError::Io("injected write fault".to_owned()));
+    }
+    file.write_all(bytes)?;
+    Ok(())
+}
+
+fn sync_step(file: &mut OpenOptionsFile) -> Result<(), CoreError> {
+    if fault_injection::should_fail() {
+        return Err(CoreError::Io("injected sync fault".to_owned()));
+    }
+    file.flush()?;
+    file.sync_all()?;
+    Ok(())
+}
+
+#[cfg(not(test))]
+mod fault_injection {
+    pub(crate) fn should_fail() -> bool {
+        false
+    }
+}
+
+/// Per-thread fault points for crash-recovery tests: every publish write and sync is one step.
+#[cfg(test)]
+mod fault_injection {
+    use std::cell::Cell;
+
+    thread_local! {
+        static STEPS: Cell<usize> = const { Cell::new(0) };
+        static FAIL_AT: Cell<Option<usize>> = const { Cell::new(None) };
+    }
+
+    pub(crate) fn arm(fail_at: Option<usize>) {
+        STEPS.with(|steps| steps.set(0));
+        FAIL_AT.with(|fail| fail.set(fail_at));
+    }
+
+    /// Disarms the fault and returns how many steps ran since `arm`.
+    pub(crate) fn disarm() -> usize {
+        FAIL_AT.with(|fail| fail.set(None));
+        STEPS.with(Cell::get)
+    }
+
+    pub(crate) fn should_fail() -> bool {
+        let step = STEPS.with(|steps| {
+            let step = steps.get();
+            steps.set(step + 1);
+            step
+        });
+        FAIL_AT.with(|fail| fail.get() == Some(step))
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct AppendedObject {
     offset: u64,
@@ -945,8 +1317,8 @@ fn append_object(
         payload.len() as u64,
         payload_checksum,
     );
-    file.write_all(&header)?;
-    file.write_all(payload)?;
+    write_step(file, &header)?;
+    write_step(file, payload)?;
     Ok(AppendedObject {
         offset: object_offset,
         length: (header.len() + payload.len()) as u64,
@@ -954,6 +1326,63 @@ fn append_object(
     })
 }
 
+/// Streaming counterpart of [`append_object`]: hashes the spill file in one pass, then copies it
+/// behind the object header in a second.
+fn append_spilled_object(
+    file: &mut OpenOptionsFile,
+    object_type: ObjectType,
+    logical_generation: u64,
+    alignment: u64,
+    spill: &mut std::fs::File,
+) -> Result<AppendedObject, CoreError> {
+    let mut chunk = vec![0u8; SPILL_COPY_CHUNK_LENGTH];
+    spill.seek(SeekFrom::Start(0))?;
+    let mut hasher = Sha256::new();
+    let mut payload_length = 0u64;
+    loop {
+        let read = spill.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&chunk[..read]);
+        payload_length += read as u64;
+    }
+    let mut payload_checksum = [0u8; 32];
+    payload_checksum.copy_from_slice(&hasher.finalize());
+
+    let current_end = file.seek(SeekFrom::End(0))?;
+    let object_offset = align_up(current_end, alignment.max(DEFAULT_OBJECT_ALIGNMENT))?;
+    write_zero_padding(file, object_offset)?;
+    let header = encode_object_header(
+        object_type,
+        logical_generation,
+        alignment,
+        payload_length,
+        payload_checksum,
+    );
+    write_step(file, &header)?;
+    spill.seek(SeekFrom::Start(0))?;
+    let mut copied = 0u64;
+    loop {
+        let read = spill.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        write_step(file, &chunk[..read])?;
+        copied += read as u64;
+    }
+    if copied != payload_length {
+        return Err(CoreError::Io(
+            "spilled segment object changed while it was published".to_owned(),
+        ));
+    }
+    Ok(AppendedObject {
+        offset: object_offset,
+        length: header.len() as u64 + payload_length,
+        payload_checksum,
+    })
+}
+
 fn encode_object(
     object_type: ObjectType,
     logical_generation: u64,
@@ -1065,6 +1494,8 @@ fn object_type_for_family(family: SegmentKind) -> ObjectType {
         SegmentKind::Doc => ObjectType::DocSegment,
         SegmentKind::Txt => ObjectType::TxtSegment,
         SegmentKind::Vec => ObjectType::VecSegment,
+        SegmentKind::Spr => ObjectType::SprSegment,
+        SegmentKind::Ckp => ObjectType::CkpSegment,
     }
 }
 
@@ -1083,16 +1514,23 @@ fn read_u64(bytes: &[u8], offset: usize) -> u64 {
 #[cfg(test)]
 mod tests {
     use std::fs::OpenOptions;
+    use std::io::{Seek, SeekFrom, Write};
+    use std::path::Path;
+    use std::time::Duration;
 
     use fs2::FileExt;
     use tempfile::tempdir;
 
     use crate::{
-        align_up, create_empty_store, decode_object_payload, default_mmap_allocation_granularity,
-        map_segment_object, open_store, publish_segment, publish_segments_with_precondition,
-        read_segment_object, write_zero_padding, ActiveManifest, CoreError, ObjectType,
-        PendingSegmentDescriptor, PendingSegmentWrite, SegmentDescriptor, SegmentKind,
-        SegmentObjectBacking, Superblock, DEFAULT_OBJECT_ALIGNMENT, FORMAT_VERSION,
+        align_up, create_checkpoint, create_empty_store, decode_object_payload,
+        default_mmap_allocation_granularity, encode_object_header, fault_injection,
+        list_manifest_generations, map_segment_object, open_store, open_store_at_generation,
+        open_store_with_repair, publish_segment, publish_segments_retaining_with_precondition,
+        publish_segments_with_precondition, publish_spilled_segments_with_precondition,
+        read_segment_object, rollback_to, verify_store, write_zero_padding, ActiveManifest,
+        CoreError, ObjectType, PendingSegmentDescriptor, PendingSegmentWrite, SegmentDescriptor,
+        SegmentKind, SegmentObjectBacking, SpilledSegmentWrite, Superblock, TornTailRepair,
+        DEFAULT_OBJECT_ALIGNMENT, DEFAULT_WRITER_LOCK_TIMEOUT, FORMAT_VERSION,
         MANIFEST_HEADER_LENGTH, MANIFEST_MAGIC, MAX_MANIFEST_OBJECT_LENGTH, OBJECT_HEADER_LENGTH,
         OBJECT_MAGIC, SEGMENT_DESCRIPTOR_LENGTH, SUPERBLOCK_SIZE,
     };
@@ -1618,6 +2056,7 @@ mod tests {
                     "document generation changed".to_owned(),
                 ))
             },
+            DEFAULT_WRITER_LOCK_TIMEOUT,
         )
         .unwrap_err();
 
@@ -1713,6 +2152,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn publish_segments_retaining_keeps_selected_segments_of_the_published_family() {
+        let temp_dir = tempdir().expect("tempdir");
+        let path = temp_dir.path().join("family-retain.wax");
+        let vec_descriptor = PendingSegmentDescriptor {
+            family: SegmentKind::Vec,
+            family_version: 1,
+            flags: 0,
+            doc_id_start: 0,
+            doc_id_end_exclusive: 1,
+            min_timestamp_ms: 0,
+            max_timestamp_ms: 0,
+            live_items: 1,
+            tombstoned_items: 0,
+            backend_id: 0,
+            backend_aux: 0,
+        };
+
+        create_empty_store(&path).expect("store should be created");
+        let first =
+            publish_segment(&path, vec_descriptor.clone(), b"space-a").expect("first vec publish");
+        let retained_offset = first.manifest.segments[0].object_offset;
+        let opened = publish_segments_retaining_with_precondition(
+            &path,
+            vec![PendingSegmentWrite {
+                descriptor: vec_descriptor,
+                object_bytes: b"space-b".to_vec(),
+            }],
+            |segment| segment.object_offset == retained_offset,
+            |_| Ok(()),
+            DEFAULT_WRITER_LOCK_TIMEOUT,
+        )
+        .expect("retaining publish");
+
+        assert_eq!(opened.manifest.generation, 2);
+        assert_eq!(opened.manifest.segments.len(), 2);
+        assert_eq!(
+            read_segment_object(&path, &opened.manifest.segments[0]).expect("retained payload"),
+            b"space-a"
+        );
+        assert_eq!(
+            read_segment_object(&path, &opened.manifest.segments[1]).expect("new payload"),
+            b"space-b"
+        );
+    }
+
     #[test]
     fn open_store_uses_latest_valid_generation_after_multiple_publishes() {
         let temp_dir = tempdir().expect("tempdir");
@@ -1911,25 +2396,426 @@ mod tests {
             .expect("open store");
         file.lock_exclusive().expect("take exclusive lock");
 
-        let error = publish_segment(
+        let error = publish_segments_with_precondition(
             &path,
-            PendingSegmentDescriptor {
-                family: SegmentKind::Doc,
-                family_version: 1,
-                flags: 0,
-                doc_id_start: 0,
-                doc_id_end_exclusive: 1,
-                min_timestamp_ms: 0,
-                max_timestamp_ms: 0,
-                live_items: 1,
-                tombstoned_items: 0,
-                backend_id: 0,
-                backend_aux: 0,
-            },
-            b"locked-segment",
+            vec![PendingSegmentWrite {
+                descriptor: doc_descriptor(),
+                object_bytes: b"locked-segment".to_vec(),
+            }],
+            |_| Ok(()),
+            Duration::from_millis(20),
         )
         .expect_err("publish should fail while another writer holds the lock");
 
-        assert!(matches!(error, CoreError::Io(_)));
+        match error {
+            CoreError::WriterBusy { waited_ms } => assert!(waited_ms >= 20),
+            other => panic!("expected WriterBusy, got {other:?}"),
+        }
+        assert_eq!(open_store(&path).expect("open").manifest.generation, 0);
+    }
+
+    #[test]
+    fn spilled_segments_stream_into_the_store_and_replace_their_family() {
+        use std::io::Write;
+
+        let temp_dir = tempdir().expect("tempdir");
+        let path = temp_dir.path().join("spilled.wax");
+        create_empty_store(&path).expect("create store");
+        publish_segment(&path, doc_descriptor(), b"in-memory").expect("first publish");
+
+        let object_bytes = (0..3 * 1024 * 1024 + 17)
+            .map(|index| (index % 251) as u8)
+            .collect::<Vec<_>>();
+        let mut spill = tempfile::tempfile().expect("spill file");
+        spill.write_all(&object_bytes).expect("write spill");
+        let opened = publish_spilled_segments_with_precondition(
+            &path,
+            vec![SpilledSegmentWrite {
+                descriptor: doc_descriptor(),
+                object: spill,
+            }],
+            &[],
+            |manifest| {
+                assert_eq!(manifest.generation, 1);
+                Ok(())
+            },
+            DEFAULT_WRITER_LOCK_TIMEOUT,
+        )
+        .expect("spilled publish");
+
+        assert_eq!(opened.manifest.generation, 2);
+        assert_eq!(opened.manifest.segments.len(), 1);
+        assert_eq!(
+            read_segment_object(&path, &opened.manifest.segments[0]).expect("spilled payload"),
+            object_bytes
+        );
+        assert!(verify_store(&path).expect("verify").is_ok());
+    }
+
+    #[test]
+    fn publish_waits_for_the_writer_lock_until_the_holder_releases_it() {
+        let temp_dir = tempdir().expect("tempdir");
+        let path = temp_dir.path().join("contended.wax");
+        create_empty_store(&path).expect("create store");
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .expect("open store");
+        file.lock_exclusive().expect("take exclusive lock");
+        let holder = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            file.unlock().expect("release lock");
+        });
+
+        let opened = publish_segments_with_precondition(
+            &path,
+            vec![PendingSegmentWrite {
+                descriptor: doc_descriptor(),
+                object_bytes: b"waited-segment".to_vec(),
+            }],
+            |_| Ok(()),
+            Duration::from_secs(10),
+        )
+        .expect("publish after the holder releases the lock");
+        holder.join().expect("holder thread");
+
+        assert_eq!(opened.manifest.generation, 1);
+    }
+
+    pub(crate) fn doc_descriptor() -> PendingSegmentDescriptor {
+        PendingSegmentDescriptor {
+            family: SegmentKind::Doc,
+            family_version: 1,
+            flags: 0,
+            doc_id_start: 0,
+            doc_id_end_exclusive: 1,
+            min_timestamp_ms: 0,
+            max_timestamp_ms: 0,
+            live_items: 1,
+            tombstoned_items: 0,
+            backend_id: 0,
+            backend_aux: 0,
+        }
+    }
+
+    #[test]
+    fn publish_faults_at_every_write_and_sync_step_leave_a_recoverable_store() {
+        fault_injection::arm(None);
+        {
+            let temp_dir = tempdir().expect("tempdir");
+            let path = temp_dir.path().join("count.wax");
+            create_empty_store(&path).expect("create");
+            fault_injection::arm(None);
+            publish_segment(&path, doc_descriptor(), b"second").expect("publish");
+        }
+        let steps = fault_injection::disarm();
+        assert!(
+            steps >= 8,
+            "publish should cover several write and sync steps"
+        );
+
+        for fail_at in 0..steps {
+            let temp_dir = tempdir().expect("tempdir");
+            let path = temp_dir.path().join("store.wax");
+            create_empty_store(&path).expect("create");
+            publish_segment(&path, doc_descriptor(), b"first").expect("publish");
+            let committed_length = std::fs::metadata(&path).expect("metadata").len();
+
+            fault_injection::arm(Some(fail_at));
+            let error = publish_segment(&path, doc_descriptor(), b"second");
+            fault_injection::disarm();
+            assert!(error.is_err(), "step {fail_at} should fail");
+
+            let detected =
+                open_store_with_repair(&path, TornTailRepair::Detect, DEFAULT_WRITER_LOCK_TIMEOUT)
+                    .expect("open after fault");
+            let generation = detected.store.manifest.generation;
+            match generation {
+                // The superblock switch never landed: everything appended is orphaned.
+                1 => assert_eq!(
+                    detected.torn_tail_bytes,
+                    std::fs::metadata(&path).expect("metadata").len() - committed_length,
+                    "step {fail_at}"
+                ),
+                // Only the final sync failed; the new generation is complete.
+                2 => assert_eq!(detected.torn_tail_bytes, 0, "step {fail_at}"),
+                other => panic!("step {fail_at} opened unexpected generation {other}"),
+            }
+            assert_eq!(
+                detected.torn_tail_bytes > 0,
+                !verify_store(&path).unwrap().is_ok()
+            );
+
+            let repaired = open_store_with_repair(
+                &path,
+                TornTailRepair::Truncate,
+                DEFAULT_WRITER_LOCK_TIMEOUT,
+            )
+            .expect("repair");
+            assert_eq!(repaired.truncated, detected.torn_tail_bytes > 0);
+            assert_eq!(
+                std::fs::metadata(&path).expect("metadata").len(),
+                repaired.valid_length
+            );
+
+            let reopened = publish_segment(&path, doc_descriptor(), b"third").expect("republish");
+            assert_eq!(reopened.manifest.generation, generation + 1);
+            let report = verify_store(&path).expect("verify");
+            assert!(report.is_ok(), "step {fail_at}: {:?}", report.issues);
+            let descriptor = open_store(&path).expect("open").manifest.segments[0].clone();
+            assert_eq!(
+                read_segment_object(&path, &descriptor).expect("read"),
+                b"third"
+            );
+        }
+    }
+
+    #[test]
+    fn historical_generations_stay_listable_and_openable_after_later_publishes() {
+        let temp_dir = tempdir().expect("tempdir");
+        let path = temp_dir.path().join("store.wax");
+        create_empty_store(&path).expect("create");
+        publish_segment(&path, doc_descriptor(), b"first").expect("publish first");
+        // An interrupted publish leaves a generation 2 manifest that the next publish supersedes.
+        fault_injection::arm(Some(fault_injection_superblock_step()));
+        publish_segment(&path, doc_descriptor(), b"orphan").expect_err("faulted publish");
+        fault_injection::disarm();
+        publish_segment(&path, doc_descriptor(), b"second").expect("publish second");
+
+        let generations = list_manifest_generations(&path).expect("list");
+        assert_eq!(
+            generations
+                .iter()
+                .map(|entry| (entry.generation, entry.segment_count, entry.active))
+                .collect::<Vec<_>>(),
+            vec![(0, 0, false), (1, 1, false), (2, 1, true)]
+        );
+
+        let historical = open_store_at_generation(&path, Some(1)).expect("open generation 1");
+        assert_eq!(historical.manifest.generation, 1);
+        assert_eq!(
+            read_segment_object(&path, &historical.manifest.segments[0]).expect("read"),
+            b"first"
+        );
+        let latest = open_store_at_generation(&path, Some(2)).expect("open generation 2");
+        assert_eq!(latest, open_store(&path).expect("open active"));
+        assert_eq!(
+            read_segment_object(&path, &latest.manifest.segments[0]).expect("read"),
+            b"second"
+        );
+        assert!(open_store_at_generation(&path, Some(3)).is_err());
+    }
+
+    #[test]
+    fn rollback_republishes_an_older_segment_set_without_dropping_history() {
+        let temp_dir = tempdir().expect("tempdir");
+        let path = temp_dir.path().join("store.wax");
+        create_empty_store(&path).expect("create");
+        for payload in [b"first".as_slice(), b"second", b"third"] {
+            publish_segment(&path, doc_descriptor(), payload).expect("publish");
+        }
+        let doc_payload = || {
+            let opened = open_store(&path).expect("open");
+            read_segment_object(&path, &opened.manifest.segments[0]).expect("read")
+        };
+
+        let rolled_back = rollback_to(&path, 1, DEFAULT_WRITER_LOCK_TIMEOUT).expect("rollback");
+        assert_eq!(rolled_back.manifest.generation, 4);
+        assert_eq!(
+            rolled_back.manifest.segments,
+            open_store_at_generation(&path, Some(1))
+                .expect("open generation 1")
+                .manifest
+                .segments
+        );
+        assert_eq!(doc_payload(), b"first");
+        assert_eq!(
+            list_manifest_generations(&path)
+                .expect("list")
+                .iter()
+                .map(|entry| entry.generation)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+
+        // Rolling forward again is a rollback to the newer generation.
+        rollback_to(&path, 3, DEFAULT_WRITER_LOCK_TIMEOUT).expect("roll forward");
+        assert_eq!(doc_payload(), b"third");
+        assert!(matches!(
+            rollback_to(&path, 5, DEFAULT_WRITER_LOCK_TIMEOUT),
+            Err(CoreError::PublishPreconditionFailed(_))
+        ));
+        assert!(matches!(
+            rollback_to(&path, 9, DEFAULT_WRITER_LOCK_TIMEOUT),
+            Err(CoreError::PublishPreconditionFailed(_))
+        ));
+        assert!(verify_store(&path).expect("verify").is_ok());
+    }
+
+    #[test]
+    fn rollback_recovers_from_a_publish_torn_mid_object() {
+        let temp_dir = tempdir().expect("tempdir");
+        let path = temp_dir.path().join("store.wax");
+        create_empty_store(&path).expect("create");
+        for payload in [b"first".as_slice(), b"second"] {
+            publish_segment(&path, doc_descriptor(), payload).expect("publish");
+        }
+        // The crash leaves the torn object as the tail; the rollback appends past it.
+        append_torn_object(&path, 3);
+
+        let rolled_back = rollback_to(&path, 1, DEFAULT_WRITER_LOCK_TIMEOUT).expect("rollback");
+        assert_eq!(rolled_back.manifest.generation, 3);
+        assert_eq!(
+            read_segment_object(&path, &rolled_back.manifest.segments[0]).expect("read"),
+            b"first"
+        );
+        // History stays walkable, so rolling forward works too.
+        let rolled_forward =
+            rollback_to(&path, 2, DEFAULT_WRITER_LOCK_TIMEOUT).expect("roll forward");
+        assert_eq!(rolled_forward.manifest.generation, 4);
+        assert_eq!(
+            read_segment_object(&path, &rolled_forward.manifest.segments[0]).expect("read"),
+            b"second"
+        );
+        assert_eq!(
+            list_manifest_generations(&path)
+                .expect("list")
+                .iter()
+                .map(|entry| entry.generation)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn publish_appends_past_a_torn_tail_and_leaves_it_to_repair() {
+        let temp_dir = tempdir().expect("tempdir");
+        let path = temp_dir.path().join("store.wax");
+        create_empty_store(&path).expect("create");
+        publish_segment(&path, doc_descriptor(), b"first").expect("publish first");
+        append_torn_object(&path, 2);
+        let torn_length = std::fs::metadata(&path).expect("metadata").len();
+
+        let published = publish_segment(&path, doc_descriptor(), b"second").expect("publish");
+        assert_eq!(published.manifest.generation, 2);
+        // The torn object stays in place; the new doc object lands after it.
+        assert_eq!(
+            published.manifest.segments[0].object_offset,
+            align_up(torn_length, DEFAULT_OBJECT_ALIGNMENT).expect("align")
+        );
+        let detected =
+            open_store_with_repair(&path, TornTailRepair::Detect, DEFAULT_WRITER_LOCK_TIMEOUT)
+                .expect("detect");
+        assert_eq!(detected.torn_tail_bytes, 0);
+        assert!(verify_store(&path).expect("verify").is_ok());
+
+        // A tail left by a later interrupted publish survives publishes until it is repaired.
+        append_torn_object(&path, 3);
+        publish_segment(&path, doc_descriptor(), b"third").expect("publish third");
+        append_torn_object(&path, 4);
+        let detected =
+            open_store_with_repair(&path, TornTailRepair::Detect, DEFAULT_WRITER_LOCK_TIMEOUT)
+                .expect("detect");
+        assert!(detected.torn_tail_bytes > 0);
+        assert!(!detected.truncated);
+        let repaired =
+            open_store_with_repair(&path, TornTailRepair::Truncate, DEFAULT_WRITER_LOCK_TIMEOUT)
+                .expect("repair");
+        assert_eq!(repaired.torn_tail_bytes, detected.torn_tail_bytes);
+        assert!(repaired.truncated);
+        assert_eq!(
+            std::fs::metadata(&path).expect("metadata").len(),
+            repaired.valid_length
+        );
+        assert_eq!(
+            list_manifest_generations(&path)
+                .expect("list")
+                .iter()
+                .map(|entry| entry.generation)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn history_walk_resyncs_past_a_torn_object_left_before_a_later_publish() {
+        let temp_dir = tempdir().expect("tempdir");
+        let path = temp_dir.path().join("store.wax");
+        create_empty_store(&path).expect("create");
+        publish_segment(&path, doc_descriptor(), b"first").expect("publish first");
+        append_torn_object(&path, 2);
+        let published = publish_segment(&path, doc_descriptor(), b"second").expect("publish");
+        assert_eq!(published.manifest.generation, 2);
+        assert_eq!(open_store(&path).expect("open").manifest.generation, 2);
+
+        assert_eq!(
+            list_manifest_generations(&path)
+                .expect("list")
+                .iter()
+                .map(|entry| entry.generation)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        let historical = open_store_at_generation(&path, Some(1)).expect("open generation 1");
+        assert_eq!(
+            read_segment_object(&path, &historical.manifest.segments[0]).expect("read"),
+            b"first"
+        );
+        create_checkpoint(
+            &path,
+            "before-rollback",
+            Some(1),
+            DEFAULT_WRITER_LOCK_TIMEOUT,
+        )
+        .expect("checkpoint");
+        let rolled_back = rollback_to(&path, 1, DEFAULT_WRITER_LOCK_TIMEOUT).expect("rollback");
+        assert_eq!(rolled_back.manifest.generation, 4);
+        let doc_segment = rolled_back
+            .manifest
+            .segments
+            .iter()
+            .find(|segment| segment.family == SegmentKind::Doc)
+            .expect("doc segment");
+        assert_eq!(
+            read_segment_object(&path, doc_segment).expect("read"),
+            b"first"
+        );
+    }
+
+    /// Appends what a publish interrupted mid-object leaves behind: a complete object header
+    /// whose payload was only partly written.
+    pub(crate) fn append_torn_object(path: &Path, generation: u64) {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .expect("open for append");
+        let end = file.seek(SeekFrom::End(0)).expect("seek");
+        let offset = align_up(end, DEFAULT_OBJECT_ALIGNMENT).expect("align");
+        write_zero_padding(&mut file, offset).expect("padding");
+        let header = encode_object_header(
+            ObjectType::DocSegment,
+            generation,
+            DEFAULT_OBJECT_ALIGNMENT,
+            64 * 1024,
+            [7; 32],
+        );
+        file.write_all(&header).expect("write header");
+        file.write_all(&[0xab; 100]).expect("write partial payload");
+        file.sync_all().expect("sync");
+    }
+
+    /// Step index of the superblock write in a single-segment publish: the manifest is fully
+    /// appended and synced, but the store never switches to it.
+    fn fault_injection_superblock_step() -> usize {
+        let temp_dir = tempdir().expect("tempdir");
+        let path = temp_dir.path().join("steps.wax");
+        create_empty_store(&path).expect("create");
+        fault_injection::arm(None);
+        publish_segment(&path, doc_descriptor(), b"steps").expect("publish");
+        // The publish ends with the superblock write and its sync.
+        fault_injection::disarm() - 2
     }
 }
@@ -0,0 +1,532 @@
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::Path;
+
+use crate::checkpoint::decode_checkpoint_segment;
+use crate::{
+    map_segment_object, open_store_from_superblock, sha256, CoreError, SegmentDescriptor,
+    SegmentKind, Superblock, SUPERBLOCK_SIZE,
+};
+
+/// What a family decoder found in one segment payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedSegmentRows {
+    /// External doc ids of the live rows the segment references.
+    pub doc_ids: Vec<String>,
+    /// Live row count the descriptor must record, when the payload determines it. Text segments
+    /// also count documents without tokens, so they leave this unset.
+    pub live_items: Option<u64>,
+    pub tombstoned_items: u64,
+    /// Wax doc id range the rows cover, for families that store wax doc ids.
+    pub doc_id_range: Option<Range<u64>>,
+}
+
+/// Decodes segment payloads of the families a caller knows about; `verify_store_with_decoder`
+/// uses the rows for descriptor and cross-family doc id checks.
+pub trait SegmentDecoder {
+    /// Returns `Ok(None)` for families the decoder does not understand.
+    fn decode_rows(
+        &self,
+        descriptor: &SegmentDescriptor,
+        payload: &[u8],
+    ) -> Result<Option<DecodedSegmentRows>, String>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyIssueKind {
+    Superblock,
+    Manifest,
+    ObjectChecksum,
+    SegmentDecode,
+    DescriptorMismatch,
+    DocIdAlignment,
+    TornTail,
+}
+
+impl VerifyIssueKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Superblock => "superblock",
+            Self::Manifest => "manifest",
+            Self::ObjectChecksum => "object_checksum",
+            Self::SegmentDecode => "segment_decode",
+            Self::DescriptorMismatch => "descriptor_mismatch",
+            Self::DocIdAlignment => "doc_id_alignment",
+            Self::TornTail => "torn_tail",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyIssue {
+    pub kind: VerifyIssueKind,
+    pub message: String,
+}
+
+/// One of the two superblock slots. `error` covers the superblock itself and the manifest it
+/// points at; `active` marks the slot `open_store` serves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuperblockVerification {
+    pub slot: usize,
+    pub generation: Option<u64>,
+    pub active: bool,
+    pub error: Option<String>,
+}
+
+/// One manifest-visible segment. `decoded_items` is the live row count a decoder found; `stale`
+/// marks a segment older than the current doc segment, which is skipped for doc id alignment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentVerification {
+    pub family: SegmentKind,
+    pub segment_generation: u64,
+    pub object_offset: u64,
+    pub object_length: u64,
+    pub checksum_valid: bool,
+    pub decoded_items: Option<u64>,
+    pub stale: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreVerifyReport {
+    pub superblocks: Vec<SuperblockVerification>,
+    pub active_generation: Option<u64>,
+    /// Bytes past the active manifest, left by an interrupted publish.
+    pub torn_tail_bytes: u64,
+    pub segments: Vec<SegmentVerification>,
+    pub issues: Vec<VerifyIssue>,
+}
+
+impl StoreVerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    fn issue(&mut self, kind: VerifyIssueKind, message: String) {
+        self.issues.push(VerifyIssue { kind, message });
+    }
+}
+
+struct NoSegmentDecoder;
+
+impl SegmentDecoder for NoSegmentDecoder {
+    fn decode_rows(
+        &self,
+        _descriptor: &SegmentDescriptor,
+        _payload: &[u8],
+    ) -> Result<Option<DecodedSegmentRows>, String> {
+        Ok(None)
+    }
+}
+
+/// Checks both superblocks, the manifests they point at and every manifest-visible object
+/// checksum. Corruption is reported in the returned report; only I/O failures are errors.
+pub fn verify_store(path: &Path) -> Result<StoreVerifyReport, CoreError> {
+    verify_store_with_decoder(path, &NoSegmentDecoder)
+}
+
+/// [`verify_store`] plus descriptor and cross-family doc id checks over the rows `decoder`
+/// extracts from each segment.
+pub fn verify_store_with_decoder(
+    path: &Path,
+    decoder: &dyn SegmentDecoder,
+) -> Result<StoreVerifyReport, CoreError> {
+    let mut file = OpenOptions::new().read(true).open(path)?;
+    let file_length = file.metadata()?.len();
+    let mut report = StoreVerifyReport {
+        superblocks: Vec::new(),
+        active_generation: None,
+        torn_tail_bytes: 0,
+        segments: Vec::new(),
+        issues: Vec::new(),
+    };
+    if file_length < (SUPERBLOCK_SIZE * 2) as u64 {
+        report.issue(
+            VerifyIssueKind::Superblock,
+            format!("store is {file_length} bytes, shorter than both superblocks"),
+        );
+        return Ok(report);
+    }
+    let mut superblock_bytes = [0u8; SUPERBLOCK_SIZE * 2];
+    file.seek(SeekFrom::Start(0))?;
+    file.read_exact(&mut superblock_bytes)?;
+
+    let mut opened_slots = Vec::new();
+    for slot in 0..2 {
+        let bytes = &superblock_bytes[slot * SUPERBLOCK_SIZE..(slot + 1) * SUPERBLOCK_SIZE];
+        let opened = Superblock::decode(bytes)
+            .and_then(|superblock| open_store_from_superblock(&mut file, file_length, superblock));
+        let verification = match &opened {
+            Ok(opened) => SuperblockVerification {
+                slot,
+                generation: Some(opened.superblock.generation),
+                active: false,
+                error: None,
+            },
+            Err(error) => {
+                report.issue(
+                    VerifyIssueKind::Superblock,
+                    format!("superblock slot {slot}: {error}"),
+                );
+                SuperblockVerification {
+                    slot,
+                    generation: Superblock::decode(bytes)
+                        .ok()
+                        .map(|superblock| superblock.generation),
+                    active: false,
+                    error: Some(error.to_string()),
+                }
+            }
+        };
+        report.superblocks.push(verification);
+        if let Ok(opened) = opened {
+            opened_slots.push((slot, opened));
+        }
+    }
+    // Like `open_store`, prefer the first slot when both hold the same generation.
+    let Some((active_slot, active)) = opened_slots
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, opened)| opened.superblock.generation)
+    else {
+        report.issue(
+            VerifyIssueKind::Manifest,
+            "no superblock points at a readable manifest".to_owned(),
+        );
+        return Ok(report);
+    };
+    report.superblocks[active_slot].active = true;
+    report.active_generation = Some(active.manifest.generation);
+    let manifest_end = active.superblock.active_manifest_offset
+        + u64::from(active.superblock.active_manifest_length);
+    report.torn_tail_bytes = file_length.saturating_sub(manifest_end);
+    if report.torn_tail_bytes > 0 {
+        report.issue(
+            VerifyIssueKind::TornTail,
+            format!(
+                "{} bytes past the active manifest at offset {manifest_end}; run `wax verify --repair` to drop them",
+                report.torn_tail_bytes
+            ),
+        );
+    }
+
+    let latest_doc_generation = active
+        .manifest
+        .segments
+        .iter()
+        .filter(|segment| segment.family == SegmentKind::Doc)
+        .map(|segment| segment.segment_generation)
+        .max();
+    let mut decoded_segments = Vec::new();
+    for descriptor in &active.manifest.segments {
+        let label = segment_label(descriptor);
+        let stale = !matches!(descriptor.family, SegmentKind::Doc | SegmentKind::Ckp)
+            && latest_doc_generation
+                .is_some_and(|generation| descriptor.segment_generation < generation);
+        let mut verification = SegmentVerification {
+            family: descriptor.family,
+            segment_generation: descriptor.segment_generation,
+            object_offset: descriptor.object_offset,
+            object_length: descriptor.object_length,
+            checksum_valid: false,
+            decoded_items: None,
+            stale,
+        };
+        // Mapping validates the object header; the payload hash is only recomputed here.
+        let mapped = map_segment_object(path, descriptor).and_then(|object| {
+            if sha256(&object) == descriptor.object_checksum {
+                Ok(object)
+            } else {
+                Err(CoreError::ChecksumMismatch {
+                    context: "segment payload",
+                })
+            }
+        });
+        match mapped {
+            Ok(object) if descriptor.family == SegmentKind::Ckp => {
+                verification.checksum_valid = true;
+                match decode_checkpoint_segment(&object) {
+                    Ok(checkpoints) => {
+                        verification.decoded_items = Some(checkpoints.len() as u64);
+                        if checkpoints.len() as u64 != descriptor.live_items {
+                            report.issue(
+                                VerifyIssueKind::DescriptorMismatch,
+                                format!(
+                                    "{label}: descriptor live_items {} but segment holds {} checkpoints",
+                                    descriptor.live_items,
+                                    checkpoints.len()
+                                ),
+                            );
+                        }
+                    }
+                    Err(error) => {
+                        report.issue(VerifyIssueKind::SegmentDecode, format!("{label}: {error}"));
+                    }
+                }
+            }
+            Ok(object) => {
+                verification.checksum_valid = true;
+                match decoder.decode_rows(descriptor, &object) {
+                    Ok(Some(rows)) => {
+                        verification.decoded_items = Some(rows.doc_ids.len() as u64);
+                        check_descriptor(&mut report, descriptor, &rows, &label);
+                        decoded_segments.push((descriptor, stale, rows));
+                    }
+                    Ok(None) => {}
+                    Err(error) => {
+                        report.issue(VerifyIssueKind::SegmentDecode, format!("{label}: {error}"));
+                    }
+                }
+            }
+            Err(error) => {
+                report.issue(VerifyIssueKind::ObjectChecksum, format!("{label}: {error}"));
+            }
+        }
+        report.segments.push(verification);
+    }
+
+    let live_doc_ids = decoded_segments
+        .iter()
+        .filter(|(descriptor, _, _)| {
+            descriptor.family == SegmentKind::Doc
+                && Some(descriptor.segment_generation) == latest_doc_generation
+        })
+        .flat_map(|(_, _, rows)| rows.doc_ids.iter().map(String::as_str))
+        .collect::<HashSet<_>>();
+    if latest_doc_generation.is_some() {
+        for (descriptor, stale, rows) in &decoded_segments {
+            if descriptor.family == SegmentKind::Doc || *stale {
+                continue;
+            }
+            let unknown = rows
+                .doc_ids
+                .iter()
+                .filter(|doc_id| !live_doc_ids.contains(doc_id.as_str()))
+                .collect::<Vec<_>>();
+            if let Some(first) = unknown.first() {
+                report.issue(
+                    VerifyIssueKind::DocIdAlignment,
+                    format!(
+                        "{}: {} doc ids missing from the live doc segment, first {first}",
+                        segment_label(descriptor),
+                        unknown.len()
+                    ),
+                );
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+fn check_descriptor(
+    report: &mut StoreVerifyReport,
+    descriptor: &SegmentDescriptor,
+    rows: &DecodedSegmentRows,
+    label: &str,
+) {
+    if let Some(live_items) = rows.live_items {
+        if live_items != descriptor.live_items {
+            report.issue(
+                VerifyIssueKind::DescriptorMismatch,
+                format!(
+                    "{label}: descriptor records {} live items, segment holds {live_items}",
+                    descriptor.live_items
+                ),
+            );
+        }
+    } else if rows.doc_ids.len() as u64 > descriptor.live_items {
+        report.issue(
+            VerifyIssueKind::DescriptorMismatch,
+            format!(
+                "{label}: descriptor records {} live items, segment references {}",
+                descriptor.live_items,
+                rows.doc_ids.len()
+            ),
+        );
+    }
+    if rows.tombstoned_items != descriptor.tombstoned_items {
+        report.issue(
+            VerifyIssueKind::DescriptorMismatch,
+            format!(
+                "{label}: descriptor records {} tombstones, segment holds {}",
+                descriptor.tombstoned_items, rows.tombstoned_items
+            ),
+        );
+    }
+    if let Some(range) = &rows.doc_id_range {
+        let recorded = descriptor.doc_id_start..descriptor.doc_id_end_exclusive;
+        if *range != recorded {
+            report.issue(
+                VerifyIssueKind::DescriptorMismatch,
+                format!(
+                    "{label}: descriptor doc id range {recorded:?}, segment rows cover {range:?}"
+                ),
+            );
+        }
+    }
+}
+
+fn segment_label(descriptor: &SegmentDescriptor) -> String {
+    format!(
+        "{} segment at offset {} (generation {})",
+        descriptor.family.as_str(),
+        descriptor.object_offset,
+        descriptor.segment_generation
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use crate::{
+        create_empty_store, open_store, publish_segments, DecodedSegmentRows,
+        PendingSegmentDescriptor, PendingSegmentWrite, SegmentDecoder, SegmentDescriptor,
+        SegmentKind, SUPERBLOCK_SIZE,
+    };
+
+    use super::{verify_store, verify_store_with_decoder, VerifyIssueKind};
+
+    fn pending(family: SegmentKind, live_items: u64, payload: &[u8]) -> PendingSegmentWrite {
+        PendingSegmentWrite {
+            descriptor: PendingSegmentDescriptor {
+                family,
+                family_version: 1,
+                flags: 0,
+                doc_id_start: 0,
+                doc_id_end_exclusive: live_items,
+                min_timestamp_ms: 0,
+                max_timestamp_ms: 0,
+                live_items,
+                tombstoned_items: 0,
+                backend_id: 0,
+                backend_aux: 0,
+            },
+            object_bytes: payload.to_vec(),
+        }
+    }
+
+    /// Treats each payload as comma-separated doc ids.
+    struct CommaSeparatedDecoder;
+
+    impl SegmentDecoder for CommaSeparatedDecoder {
+        fn decode_rows(
+            &self,
+            _descriptor: &SegmentDescriptor,
+            payload: &[u8],
+        ) -> Result<Option<DecodedSegmentRows>, String> {
+            let doc_ids = std::str::from_utf8(payload)
+                .map_err(|error| error.to_string())?
+                .split(',')
+                .map(ToOwned::to_owned)
+                .collect::<Vec<_>>();
+            Ok(Some(DecodedSegmentRows {
+                live_items: Some(doc_ids.len() as u64),
+                doc_id_range: Some(0..doc_ids.len() as u64),
+                doc_ids,
+                tombstoned_items: 0,
+            }))
+        }
+    }
+
+    #[test]
+    fn verify_store_reports_a_clean_store_and_flags_corrupted_objects_and_superblocks() {
+        let temp_dir = tempdir().expect("tempdir");
+        let path = temp_dir.path().join("verify.wax");
+        create_empty_store(&path).expect("create store");
+        publish_segments(
+            &path,
+            vec![
+                pending(SegmentKind::Doc, 2, b"doc-a,doc-b"),
+                pending(SegmentKind::Vec, 1, b"doc-b"),
+            ],
+        )
+        .expect("publish");
+
+        let report = verify_store_with_decoder(&path, &CommaSeparatedDecoder).expect("verify");
+        assert!(report.is_ok(), "{:?}", report.issues);
+        assert_eq!(report.active_generation, Some(1));
+        assert_eq!(report.superblocks.len(), 2);
+        assert!(report.superblocks[1].active);
+        assert_eq!(report.superblocks[0].generation, Some(0));
+        assert!(report
+            .segments
+            .iter()
+            .all(|segment| segment.checksum_valid && segment.decoded_items.is_some()));
+
+        let vec_offset = open_store(&path)
+            .expect("open")
+            .manifest
+            .segments
+            .iter()
+            .find(|segment| segment.family == SegmentKind::Vec)
+            .expect("vec segment")
+            .object_offset as usize;
+        let mut bytes = std::fs::read(&path).expect("read store");
+        let payload_byte = vec_offset + crate::OBJECT_HEADER_LENGTH;
+        bytes[payload_byte] ^= 0xff;
+        bytes[SUPERBLOCK_SIZE / 2] ^= 0xff;
+        std::fs::write(&path, &bytes).expect("write store");
+
+        let report = verify_store(&path).expect("verify");
+        let kinds = report
+            .issues
+            .iter()
+            .map(|issue| issue.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![VerifyIssueKind::Superblock, VerifyIssueKind::ObjectChecksum]
+        );
+        assert!(report.superblocks[0].error.is_some());
+        assert_eq!(report.active_generation, Some(1));
+        let vec_segment = report
+            .segments
+            .iter()
+            .find(|segment| segment.family == SegmentKind::Vec)
+            .expect("vec segment");
+        assert!(!vec_segment.checksum_valid);
+    }
+
+    #[test]
+    fn verify_store_checks_descriptors_and_doc_id_alignment_against_decoded_rows() {
+        let temp_dir = tempdir().expect("tempdir");
+        let path = temp_dir.path().join("verify.wax");
+        create_empty_store(&path).expect("create store");
+        publish_segments(
+            &path,
+            vec![
+                pending(SegmentKind::Doc, 3, b"doc-a,doc-b"),
+                pending(SegmentKind::Spr, 2, b"doc-a,doc-z"),
+            ],
+        )
+        .expect("publish");
+
+        let report = verify_store_with_decoder(&path, &CommaSeparatedDecoder).expect("verify");
+        let kinds = report
+            .issues
+            .iter()
+            .map(|issue| issue.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                VerifyIssueKind::DescriptorMismatch,
+                VerifyIssueKind::DescriptorMismatch,
+                VerifyIssueKind::DocIdAlignment,
+            ]
+        );
+        assert!(report.issues[2].message.contains("doc-z"));
+
+        // Segments older than the current doc segment are not aligned against it.
+        publish_segments(&path, vec![pending(SegmentKind::Doc, 1, b"doc-a")]).expect("publish");
+        let report = verify_store_with_decoder(&path, &CommaSeparatedDecoder).expect("verify");
+        assert!(report.is_ok(), "{:?}", report.issues);
+        assert!(report
+            .segments
+            .iter()
+            .any(|segment| segment.family == SegmentKind::Spr && segment.stale));
+    }
+}
@@ -0,0 +1,469 @@
+use std::io::Write;
+use std::ops::Range;
+
+use crate::{
+    read_u16, read_u32, read_u64, read_u64_as_usize, DocSection, DocstoreError,
+    DOC_SEGMENT_COMPRESSED_MINOR, MAX_DOCUMENT_OFFSET_ENTRY_LENGTH,
+};
+
+/// Uncompressed bytes per block of a compressed doc segment section.
+pub(crate) const DOC_COMPRESSION_BLOCK_LENGTH: usize = 64 * 1024;
+const ZSTD_LEVEL: i32 = 3;
+
+/// Block codec of the payload, metadata and preview sections of a doc segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DocCompression {
+    #[default]
+    None,
+    Lz4,
+    Zstd,
+}
+
+impl DocCompression {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Lz4 => "lz4",
+            Self::Zstd => "zstd",
+        }
+    }
+
+    pub(crate) fn code(self) -> u16 {
+        match self {
+            Self::None => 0,
+            Self::Lz4 => 1,
+            Self::Zstd => 2,
+        }
+    }
+
+    pub(crate) fn from_code(code: u16) -> Result<Self, DocstoreError> {
+        match code {
+            0 => Ok(Self::None),
+            1 => Ok(Self::Lz4),
+            2 => Ok(Self::Zstd),
+            _ => Err(DocstoreError::InvalidDocument(format!(
+                "unsupported doc segment compression codec {code}"
+            ))),
+        }
+    }
+
+    fn compress_block(self, block: &[u8]) -> Result<Vec<u8>, DocstoreError> {
+        match self {
+            Self::None => Ok(block.to_vec()),
+            Self::Lz4 => Ok(lz4_flex::block::compress(block)),
+            Self::Zstd => Ok(zstd::bulk::compress(block, ZSTD_LEVEL)?),
+        }
+    }
+
+    fn decompress_block(
+        self,
+        block: &[u8],
+        uncompressed_length: usize,
+    ) -> Result<Vec<u8>, DocstoreError> {
+        let bytes = match self {
+            Self::None => block.to_vec(),
+            Self::Lz4 => lz4_flex::block::decompress(block, uncompressed_length)
+                .map_err(|error| DocstoreError::InvalidDocument(error.to_string()))?,
+            Self::Zstd => zstd::bulk::decompress(block, uncompressed_length)?,
+        };
+        if bytes.len() != uncompressed_length {
+            return Err(DocstoreError::InvalidDocument(
+                "doc segment block decompressed to an unexpected length".to_owned(),
+            ));
+        }
+        Ok(bytes)
+    }
+}
+
+/// Block index of one compressed section: block `i` holds the uncompressed bytes
+/// `i * block_length..` and is stored at `block_offsets[i]..block_offsets[i + 1]` of the section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CompressedSection {
+    pub(crate) uncompressed_length: u64,
+    pub(crate) block_offsets: Vec<u64>,
+}
+
+impl CompressedSection {
+    pub(crate) fn encode_index(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.uncompressed_length.to_le_bytes());
+        out.extend_from_slice(&((self.block_offsets.len() - 1) as u64).to_le_bytes());
+        for offset in &self.block_offsets {
+            out.extend_from_slice(&offset.to_le_bytes());
+        }
+    }
+
+    /// Reads one section index at `*cursor` of `index` and checks it against the section length.
+    pub(crate) fn decode_index(
+        index: &[u8],
+        cursor: &mut usize,
+        section_length: usize,
+        block_length: usize,
+    ) -> Result<Self, DocstoreError> {
+        let invalid =
+            || DocstoreError::InvalidDocument("doc segment block index is invalid".to_owned());
+        if index.len() < *cursor + 16 {
+            return Err(invalid());
+        }
+        let uncompressed_length = read_u64(index, *cursor);
+        let block_count = read_u64_as_usize(index, *cursor + 8, "doc segment block count")?;
+        *cursor += 16;
+        let offsets_length = block_count
+            .checked_add(1)
+            .and_then(|count| count.checked_mul(8))
+            .ok_or_else(invalid)?;
+        if index.len() - *cursor < offsets_length {
+            return Err(invalid());
+        }
+        let expected_blocks = usize::try_from(uncompressed_length)
+            .map_err(|_| invalid())?
+            .div_ceil(block_length);
+        if block_count != expected_blocks {
+            return Err(invalid());
+        }
+        let block_offsets = (0..=block_count)
+            .map(|block| read_u64(index, *cursor + block * 8))
+            .collect::<Vec<_>>();
+        *cursor += offsets_length;
+        if block_offsets.first() != Some(&0)
+            || block_offsets.last() != Some(&(section_length as u64))
+            || block_offsets.windows(2).any(|pair| pair[0] > pair[1])
+        {
+            return Err(invalid());
+        }
+        Ok(Self {
+            uncompressed_length,
+            block_offsets,
+        })
+    }
+}
+
+/// Codec, block length and block indexes of a compressed doc segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BlockCompression {
+    pub(crate) codec: DocCompression,
+    pub(crate) block_length: usize,
+}
+
+impl BlockCompression {
+    pub(crate) fn decompress_section(
+        &self,
+        section: &[u8],
+        index: &CompressedSection,
+    ) -> Result<Vec<u8>, DocstoreError> {
+        self.decompress_blocks(section, index, 0..index.block_offsets.len() - 1)
+    }
+
+    /// Uncompressed bytes `offset..offset + length` of a section, decompressing only the blocks
+    /// that overlap the range. `cache` keeps the last decompressed block run for the next call.
+    pub(crate) fn read_range<'c>(
+        &self,
+        section: &[u8],
+        index: &CompressedSection,
+        offset: u64,
+        length: u64,
+        cache: &'c mut BlockCache,
+        label: &str,
+    ) -> Result<&'c [u8], DocstoreError> {
+        let end = offset
+            .checked_add(length)
+            .ok_or_else(|| DocstoreError::InvalidDocument(format!("{label} range overflow")))?;
+        if end > index.uncompressed_length {
+            return Err(DocstoreError::InvalidDocument(format!(
+                "{label} range extends past section bounds"
+            )));
+        }
+        if length == 0 {
+            return Ok(&[]);
+        }
+        let block_length = self.block_length as u64;
+        let first_block = (offset / block_length) as usize;
+        let end_block = ((end - 1) / block_length + 1) as usize;
+        let cached = cache
+            .blocks
+            .as_ref()
+            .is_some_and(|(blocks, _)| blocks.start <= first_block && end_block <= blocks.end);
+        if !cached {
+            let bytes = self.decompress_blocks(section, index, first_block..end_block)?;
+            cache.blocks = Some((first_block..end_block, bytes));
+        }
+        let (blocks, bytes) = cache.blocks.as_ref().expect("block cache was just filled");
+        let start = (offset - blocks.start as u64 * block_length) as usize;
+        Ok(&bytes[start..start + length as usize])
+    }
+
+    fn decompress_blocks(
+        &self,
+        section: &[u8],
+        index: &CompressedSection,
+        blocks: Range<usize>,
+    ) -> Result<Vec<u8>, DocstoreError> {
+        let mut bytes = Vec::with_capacity(blocks.len() * self.block_length);
+        for block in blocks {
+            let start = index.block_offsets[block] as usize;
+            let end = index.block_offsets[block + 1] as usize;
+            let uncompressed_start = block as u64 * self.block_length as u64;
+            let uncompressed_length = (index.uncompressed_length - uncompressed_start)
+                .min(self.block_length as u64) as usize;
+            bytes.extend_from_slice(
+                &self
+                    .codec
+                    .decompress_block(&section[start..end], uncompressed_length)?,
+            );
+        }
+        Ok(bytes)
+    }
+}
+
+/// Codec and per-section block indexes of a minor 2 doc segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SectionCompression {
+    pub(crate) blocks: BlockCompression,
+    pub(crate) payload: CompressedSection,
+    pub(crate) metadata: CompressedSection,
+    pub(crate) preview: CompressedSection,
+}
+
+impl SectionCompression {
+    /// Reads the codec fields of an extended header and the block index at `index_range`.
+    ///
+    /// Minor 2 segments always name a codec; later minors may record `None` with an empty
+    /// block index, in which case the sections are stored plain.
+    pub(crate) fn decode(
+        bytes: &[u8],
+        minor: u16,
+        section_ranges: [&Range<usize>; 3],
+        index_range: Range<usize>,
+    ) -> Result<Option<Self>, DocstoreError> {
+        let codec = DocCompression::from_code(read_u16(bytes, 88))?;
+        if codec == DocCompression::None {
+            if minor == DOC_SEGMENT_COMPRESSED_MINOR {
+                return Err(DocstoreError::InvalidDocument(
+                    "compressed doc segment must name a compression codec".to_owned(),
+                ));
+            }
+            if !index_range.is_empty() || read_u32(bytes, 92) != 0 {
+                return Err(DocstoreError::InvalidDocument(
+                    "uncompressed doc segment must not carry a block index".to_owned(),
+                ));
+            }
+            return Ok(None);
+        }
+        let block_length = read_u32(bytes, 92) as usize;
+        if block_length == 0 || block_length as u64 > MAX_DOCUMENT_OFFSET_ENTRY_LENGTH {
+            return Err(DocstoreError::InvalidDocument(format!(
+                "doc segment block length {block_length} is invalid"
+            )));
+        }
+        let index = &bytes[index_range];
+        let mut cursor = 0;
+        let [payload, metadata, preview] = section_ranges.map(|range| {
+            CompressedSection::decode_index(index, &mut cursor, range.len(), block_length)
+        });
+        let (payload, metadata, preview) = (payload?, metadata?, preview?);
+        if cursor != index.len() {
+            return Err(DocstoreError::InvalidDocument(
+                "doc segment block index is invalid".to_owned(),
+            ));
+        }
+        Ok(Some(Self {
+            blocks: BlockCompression {
+                codec,
+                block_length,
+            },
+            payload,
+            metadata,
+            preview,
+        }))
+    }
+
+    pub(crate) fn section(&self, section: DocSection) -> &CompressedSection {
+        match section {
+            DocSection::Payload => &self.payload,
+            DocSection::Metadata => &self.metadata,
+            DocSection::Preview => &self.preview,
+        }
+    }
+}
+
+/// Last run of blocks decompressed by [`BlockCompression::read_range`]; callers reading rows in
+/// payload order decompress every block once.
+#[derive(Debug, Default)]
+pub(crate) struct BlockCache {
+    blocks: Option<(Range<usize>, Vec<u8>)>,
+}
+
+/// Section bytes of a row: read from the object for plain sections, from the block cache for
+/// compressed ones.
+pub(crate) fn section_bytes<'a>(
+    section: &'a [u8],
+    compressed: Option<(&BlockCompression, &CompressedSection)>,
+    offset: u64,
+    length: u64,
+    cache: &'a mut BlockCache,
+    label: &str,
+) -> Result<&'a [u8], DocstoreError> {
+    match compressed {
+        Some((compression, index)) => {
+            compression.read_range(section, index, offset, length, cache, label)
+        }
+        None => crate::read_section_bytes(section, offset, length, label),
+    }
+}
+
+/// Appends `section` to `out` as compressed blocks and returns its block index.
+pub(crate) fn compress_section(
+    codec: DocCompression,
+    section: &[u8],
+    out: &mut Vec<u8>,
+) -> Result<CompressedSection, DocstoreError> {
+    let mut writer = BlockWriter::new(codec, out);
+    writer.write_all(section)?;
+    writer.finish()
+}
+
+/// Splits an uncompressed section stream into fixed-size blocks and writes each block compressed.
+pub(crate) struct BlockWriter<'w, W: Write> {
+    codec: DocCompression,
+    out: &'w mut W,
+    pending: Vec<u8>,
+    written: u64,
+    uncompressed_length: u64,
+    block_offsets: Vec<u64>,
+}
+
+impl<'w, W: Write> BlockWriter<'w, W> {
+    pub(crate) fn new(codec: DocCompression, out: &'w mut W) -> Self {
+        Self {
+            codec,
+            out,
+            pending: Vec::with_capacity(DOC_COMPRESSION_BLOCK_LENGTH),
+            written: 0,
+            uncompressed_length: 0,
+            block_offsets: vec![0],
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> Result<CompressedSection, DocstoreError> {
+        if !self.pending.is_empty() {
+            self.flush_block()?;
+        }
+        Ok(CompressedSection {
+            uncompressed_length: self.uncompressed_length,
+            block_offsets: self.block_offsets,
+        })
+    }
+
+    fn flush_block(&mut self) -> Result<(), DocstoreError> {
+        let block = self.codec.compress_block(&self.pending)?;
+        self.out.write_all(&block)?;
+        self.written += block.len() as u64;
+        self.block_offsets.push(self.written);
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for BlockWriter<'_, W> {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        let take = bytes
+            .len()
+            .min(DOC_COMPRESSION_BLOCK_LENGTH - self.pending.len());
+        self.pending.extend_from_slice(&bytes[..take]);
+        self.uncompressed_length += take as u64;
+        if self.pending.len() == DOC_COMPRESSION_BLOCK_LENGTH {
+            self.flush_block().map_err(|error| match error {
+                DocstoreError::Io(message)
+                | DocstoreError::Json(message)
+                | DocstoreError::InvalidDocument(message) => std::io::Error::other(message),
+                DocstoreError::MissingDocumentsFile => {
+                    std::io::Error::other("documents file missing")
+                }
+            })?;
+        }
+        Ok(take)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DocstoreError;
+
+    use super::{
+        compress_section, BlockCache, BlockCompression, CompressedSection, DocCompression,
+        DOC_COMPRESSION_BLOCK_LENGTH,
+    };
+
+    #[test]
+    fn read_range_decompresses_only_overlapping_blocks() {
+        let section = (0..3 * DOC_COMPRESSION_BLOCK_LENGTH + 100)
+            .map(|index| (index % 251) as u8)
+            .collect::<Vec<_>>();
+        for codec in [DocCompression::Lz4, DocCompression::Zstd] {
+            let mut compressed = Vec::new();
+            let index = compress_section(codec, &section, &mut compressed).unwrap();
+            assert_eq!(index.block_offsets.len(), 5);
+            let blocks = BlockCompression {
+                codec,
+                block_length: DOC_COMPRESSION_BLOCK_LENGTH,
+            };
+            let mut cache = BlockCache::default();
+
+            let start = DOC_COMPRESSION_BLOCK_LENGTH as u64 - 10;
+            let bytes = blocks
+                .read_range(&compressed, &index, start, 20, &mut cache, "payload")
+                .unwrap();
+            assert_eq!(bytes, &section[start as usize..start as usize + 20]);
+            assert_eq!(cache.blocks.as_ref().unwrap().0, 0..2);
+
+            let tail = section.len() as u64 - 50;
+            let bytes = blocks
+                .read_range(&compressed, &index, tail, 50, &mut cache, "payload")
+                .unwrap();
+            assert_eq!(bytes, &section[tail as usize..]);
+            assert_eq!(cache.blocks.as_ref().unwrap().0, 3..4);
+            assert!(blocks
+                .read_range(&compressed, &index, tail, 51, &mut cache, "payload")
+                .is_err());
+            assert_eq!(
+                blocks.decompress_section(&compressed, &index).unwrap(),
+                section
+            );
+        }
+    }
+
+    #[test]
+    fn decode_index_rejects_offsets_outside_the_section() {
+        let mut compressed = Vec::new();
+        let index =
+            compress_section(DocCompression::Lz4, b"short section", &mut compressed).unwrap();
+        let mut encoded = Vec::new();
+        index.encode_index(&mut encoded);
+
+        let mut cursor = 0;
+        assert_eq!(
+            CompressedSection::decode_index(
+                &encoded,
+                &mut cursor,
+                compressed.len(),
+                DOC_COMPRESSION_BLOCK_LENGTH,
+            )
+            .unwrap(),
+            index
+        );
+        let mut cursor = 0;
+        assert!(matches!(
+            CompressedSection::decode_index(
+                &encoded,
+                &mut cursor,
+                compressed.len() + 1,
+                DOC_COMPRESSION_BLOCK_LENGTH,
+            ),
+            Err(DocstoreError::InvalidDocument(message))
+                if message == "doc segment block index is invalid"
+        ));
+    }
+}
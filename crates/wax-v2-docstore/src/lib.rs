@@ -12,11 +12,29 @@ use sha2::{Digest, Sha256};
 use wax_bench_model::DatasetPackManifest;
 use wax_v2_core::{OpenedStore, PendingSegmentDescriptor, PendingSegmentWrite, SegmentKind};
 
+mod compression;
+mod metadata;
+mod stream;
+
+pub use compression::DocCompression;
+pub use metadata::{DocMetadata, MetadataValue};
+pub use stream::{
+    DocIdSpill, SortedDocIds, StreamingDocSegmentBuilder, DEFAULT_DOC_ID_SPILL_RUN_BYTES,
+};
+
+use compression::{
+    compress_section, section_bytes, BlockCache, SectionCompression, DOC_COMPRESSION_BLOCK_LENGTH,
+};
+
 const DOC_SEGMENT_MAGIC: &[u8; 4] = b"WXDG";
 const DOC_SEGMENT_MAJOR: u16 = 1;
-const DOC_SEGMENT_MINOR: u16 = 1;
+/// Minor 3 stores metadata in the [`DocMetadata`] encoding; earlier minors store JSON.
+const DOC_SEGMENT_MINOR: u16 = 3;
 const DOC_SEGMENT_MINOR_V0_HEADER_LENGTH: usize = 80;
-const DOC_SEGMENT_HEADER_LENGTH: usize = 88;
+const DOC_SEGMENT_MINOR_V1_HEADER_LENGTH: usize = 88;
+/// First minor with the extended header that records a block codec.
+const DOC_SEGMENT_COMPRESSED_MINOR: u16 = 2;
+const DOC_SEGMENT_HEADER_LENGTH: usize = 104;
 const DOC_SEGMENT_VERSION_PREFIX_LENGTH: usize = 8;
 const DOC_ROW_LENGTH: usize = 56;
 const MAX_DOCUMENT_OFFSET_ENTRY_LENGTH: u64 = 16 * 1024 * 1024;
@@ -248,6 +266,14 @@ pub struct BinaryDocSegment {
 
 impl BinaryDocSegment {
     pub fn encode(&self) -> Result<Vec<u8>, DocstoreError> {
+        self.encode_with_compression(DocCompression::None)
+    }
+
+    /// Encodes minor 1 for [`DocCompression::None`] and the block-compressed minor 2 otherwise.
+    pub fn encode_with_compression(
+        &self,
+        compression: DocCompression,
+    ) -> Result<Vec<u8>, DocstoreError> {
         validate_doc_id_order(&self.records)?;
 
         let mut payload_section = Vec::new();
@@ -274,7 +300,7 @@ impl BinaryDocSegment {
 
             metadata_entries.push((
                 record.row.metadata_ref,
-                serde_json::to_vec(&record.metadata)?,
+                DocMetadata::encode(&record.metadata).into_bytes(),
             ));
             preview_entries.push((
                 record.row.preview_ref,
@@ -297,12 +323,8 @@ impl BinaryDocSegment {
 
         let metadata_section = build_ref_section(&metadata_entries, "metadata")?;
         let preview_section = build_ref_section(&preview_entries, "preview")?;
-        let payload_bytes_offset = DOC_SEGMENT_HEADER_LENGTH as u64;
-        let metadata_bytes_offset = payload_bytes_offset + payload_section.len() as u64;
-        let preview_bytes_offset = metadata_bytes_offset + metadata_section.len() as u64;
         let binding_section = self.doc_id_map.encode_json()?;
-        let binding_bytes_offset = preview_bytes_offset + preview_section.len() as u64;
-        let row_table_offset = binding_bytes_offset + binding_section.len() as u64;
+        let header_length = DOC_SEGMENT_HEADER_LENGTH;
 
         let mut body = Vec::with_capacity(
             payload_section.len()
@@ -311,128 +333,51 @@ impl BinaryDocSegment {
                 + binding_section.len()
                 + encoded_rows.len(),
         );
-        body.extend_from_slice(&payload_section);
-        body.extend_from_slice(&metadata_section);
-        body.extend_from_slice(&preview_section);
+        let mut block_index = Vec::new();
+        let mut section_offsets = [0u64; 3];
+        for (section_offset, section) in
+            section_offsets
+                .iter_mut()
+                .zip([&payload_section, &metadata_section, &preview_section])
+        {
+            *section_offset = (header_length + body.len()) as u64;
+            if compression == DocCompression::None {
+                body.extend_from_slice(section);
+            } else {
+                compress_section(compression, section, &mut body)?.encode_index(&mut block_index);
+            }
+        }
+        let binding_bytes_offset = (header_length + body.len()) as u64;
         body.extend_from_slice(&binding_section);
+        let block_index_offset = (header_length + body.len()) as u64;
+        body.extend_from_slice(&block_index);
+        let row_table_offset = (header_length + body.len()) as u64;
         body.extend_from_slice(&encoded_rows);
-        let checksum = sha256(&body);
 
-        let mut bytes = Vec::with_capacity(DOC_SEGMENT_HEADER_LENGTH + body.len());
-        bytes.extend_from_slice(DOC_SEGMENT_MAGIC);
-        bytes.extend_from_slice(&DOC_SEGMENT_MAJOR.to_le_bytes());
-        bytes.extend_from_slice(&DOC_SEGMENT_MINOR.to_le_bytes());
-        bytes.extend_from_slice(&(self.records.len() as u64).to_le_bytes());
-        bytes.extend_from_slice(&payload_bytes_offset.to_le_bytes());
-        bytes.extend_from_slice(&metadata_bytes_offset.to_le_bytes());
-        bytes.extend_from_slice(&preview_bytes_offset.to_le_bytes());
-        bytes.extend_from_slice(&binding_bytes_offset.to_le_bytes());
-        bytes.extend_from_slice(&row_table_offset.to_le_bytes());
-        bytes.extend_from_slice(&checksum);
+        let mut bytes = DocSegmentHeader {
+            row_count: self.records.len() as u64,
+            payload_offset: section_offsets[0],
+            metadata_offset: section_offsets[1],
+            preview_offset: section_offsets[2],
+            binding_offset: binding_bytes_offset,
+            row_table_offset,
+            checksum: sha256(&body),
+            compression,
+            block_index_offset,
+        }
+        .encode();
         bytes.extend_from_slice(&body);
         Ok(bytes)
     }
 
     pub fn decode(bytes: &[u8]) -> Result<Self, DocstoreError> {
-        if bytes.len() < DOC_SEGMENT_VERSION_PREFIX_LENGTH {
-            return Err(DocstoreError::InvalidDocument(format!(
-                "doc segment too short: expected at least {DOC_SEGMENT_VERSION_PREFIX_LENGTH} bytes"
-            )));
-        }
-        if &bytes[..4] != DOC_SEGMENT_MAGIC {
-            return Err(DocstoreError::InvalidDocument(
-                "doc segment magic mismatch".to_owned(),
-            ));
-        }
-        let major = read_u16(bytes, 4);
-        let minor = read_u16(bytes, 6);
-        if major != DOC_SEGMENT_MAJOR || minor > DOC_SEGMENT_MINOR {
-            return Err(DocstoreError::InvalidDocument(
-                "unsupported doc segment version".to_owned(),
-            ));
-        }
-        let header_length = if minor == 0 {
-            DOC_SEGMENT_MINOR_V0_HEADER_LENGTH
-        } else {
-            DOC_SEGMENT_HEADER_LENGTH
-        };
-        if bytes.len() < header_length {
-            return Err(DocstoreError::InvalidDocument(format!(
-                "doc segment too short: expected at least {header_length} bytes"
-            )));
-        }
-
-        let row_count = read_u64_as_usize(bytes, 8, "doc segment row count")?;
-        if row_count > bytes.len() / DOC_ROW_LENGTH {
-            return Err(DocstoreError::InvalidDocument(
-                "doc segment row count exceeds possible rows in slice".to_owned(),
-            ));
-        }
-        let payload_bytes_offset = read_u64_as_usize(bytes, 16, "payload offset")?;
-        let metadata_bytes_offset = read_u64_as_usize(bytes, 24, "metadata offset")?;
-        let preview_bytes_offset = read_u64_as_usize(bytes, 32, "preview offset")?;
-        let (binding_bytes_offset, row_table_offset, checksum_start, checksum_end, header_length) =
-            if minor == 0 {
-                let row_table_offset = read_u64_as_usize(bytes, 40, "row table offset")?;
-                (
-                    row_table_offset,
-                    row_table_offset,
-                    48,
-                    80,
-                    DOC_SEGMENT_MINOR_V0_HEADER_LENGTH,
-                )
-            } else {
-                (
-                    read_u64_as_usize(bytes, 40, "binding offset")?,
-                    read_u64_as_usize(bytes, 48, "row table offset")?,
-                    56,
-                    88,
-                    DOC_SEGMENT_HEADER_LENGTH,
-                )
-            };
-        if payload_bytes_offset != header_length
-            || payload_bytes_offset > metadata_bytes_offset
-            || metadata_bytes_offset > preview_bytes_offset
-            || preview_bytes_offset > binding_bytes_offset
-            || binding_bytes_offset > row_table_offset
-            || preview_bytes_offset > row_table_offset
-            || row_table_offset > bytes.len()
-        {
-            return Err(DocstoreError::InvalidDocument(
-                "doc segment section offsets are invalid".to_owned(),
-            ));
-        }
-
-        let mut contents_checksum = [0u8; 32];
-        contents_checksum.copy_from_slice(&bytes[checksum_start..checksum_end]);
-        let body = &bytes[header_length..];
-        if sha256(body) != contents_checksum {
-            return Err(DocstoreError::InvalidDocument(
-                "doc segment checksum mismatch".to_owned(),
-            ));
-        }
-
-        let row_table_length = row_count.checked_mul(DOC_ROW_LENGTH).ok_or_else(|| {
-            DocstoreError::InvalidDocument("row table length overflow".to_owned())
-        })?;
-        let row_table_end = row_table_offset
-            .checked_add(row_table_length)
-            .ok_or_else(|| DocstoreError::InvalidDocument("row table range overflow".to_owned()))?;
-        if row_table_end != bytes.len() {
-            return Err(DocstoreError::InvalidDocument(
-                "doc segment row table length mismatch".to_owned(),
-            ));
-        }
-        let payload_section = &bytes[payload_bytes_offset..metadata_bytes_offset];
-        let metadata_section = &bytes[metadata_bytes_offset..preview_bytes_offset];
-        let preview_section = &bytes[preview_bytes_offset..binding_bytes_offset];
-        let binding_section = &bytes[binding_bytes_offset..row_table_offset];
-        let row_table = &bytes[row_table_offset..row_table_end];
-        if row_count > row_table.len() / DOC_ROW_LENGTH {
-            return Err(DocstoreError::InvalidDocument(
-                "doc segment row count exceeds possible rows in slice".to_owned(),
-            ));
-        }
+        let layout = DocSegmentLayout::parse(bytes)?;
+        let row_count = layout.row_count;
+        let payload_section = layout.decompressed_section(bytes, DocSection::Payload)?;
+        let metadata_section = layout.decompressed_section(bytes, DocSection::Metadata)?;
+        let preview_section = layout.decompressed_section(bytes, DocSection::Preview)?;
+        let binding_section = &bytes[layout.binding_range.clone()];
+        let row_table = &bytes[layout.row_table_range.clone()];
 
         let mut records = Vec::with_capacity(row_count);
         let mut previous_doc_id = None;
@@ -455,21 +400,21 @@ impl BinaryDocSegment {
             previous_doc_id = Some(row.doc_id);
 
             let payload = read_section_bytes(
-                payload_section,
+                &payload_section,
                 row.payload_offset,
                 row.payload_length,
                 "payload",
             )?
             .to_vec();
             let metadata_bytes = read_section_bytes(
-                metadata_section,
+                &metadata_section,
                 row.metadata_ref.offset() as u64,
                 row.metadata_ref.length() as u64,
                 "metadata",
             )?;
-            let metadata = serde_json::from_slice(metadata_bytes)?;
+            let metadata = decode_doc_metadata(layout.minor, metadata_bytes)?;
             let preview_bytes = read_section_bytes(
-                preview_section,
+                &preview_section,
                 row.preview_ref.offset() as u64,
                 row.preview_ref.length() as u64,
                 "preview",
@@ -491,7 +436,7 @@ impl BinaryDocSegment {
             });
         }
 
-        let doc_id_map = if minor == 0 {
+        let doc_id_map = if layout.minor == 0 {
             doc_id_map_from_records(&records)?
         } else {
             DocIdMap::decode_json(binding_section)?
@@ -504,35 +449,76 @@ impl BinaryDocSegment {
     }
 }
 
-#[derive(Debug)]
-pub struct Docstore {
-    source: DocstoreSource,
+/// Fixed header fields of a doc segment in the current minor version.
+struct DocSegmentHeader {
+    row_count: u64,
+    payload_offset: u64,
+    metadata_offset: u64,
+    preview_offset: u64,
+    binding_offset: u64,
+    row_table_offset: u64,
+    checksum: [u8; 32],
+    compression: DocCompression,
+    block_index_offset: u64,
 }
 
-#[derive(Debug)]
-enum DocstoreSource {
-    DatasetPack {
-        documents_path: PathBuf,
-        offset_index: Option<HashMap<String, DocumentOffsetEntry>>,
-    },
-    Store {
-        segment: StoreDocSegment,
-    },
+impl DocSegmentHeader {
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(DOC_SEGMENT_HEADER_LENGTH);
+        bytes.extend_from_slice(DOC_SEGMENT_MAGIC);
+        bytes.extend_from_slice(&DOC_SEGMENT_MAJOR.to_le_bytes());
+        bytes.extend_from_slice(&DOC_SEGMENT_MINOR.to_le_bytes());
+        bytes.extend_from_slice(&self.row_count.to_le_bytes());
+        bytes.extend_from_slice(&self.payload_offset.to_le_bytes());
+        bytes.extend_from_slice(&self.metadata_offset.to_le_bytes());
+        bytes.extend_from_slice(&self.preview_offset.to_le_bytes());
+        bytes.extend_from_slice(&self.binding_offset.to_le_bytes());
+        bytes.extend_from_slice(&self.row_table_offset.to_le_bytes());
+        bytes.extend_from_slice(&self.checksum);
+        let block_length = if self.compression == DocCompression::None {
+            0
+        } else {
+            DOC_COMPRESSION_BLOCK_LENGTH as u32
+        };
+        bytes.extend_from_slice(&self.compression.code().to_le_bytes());
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+        bytes.extend_from_slice(&block_length.to_le_bytes());
+        bytes.extend_from_slice(&self.block_index_offset.to_le_bytes());
+        bytes
+    }
+}
+
+/// Decodes a row's metadata bytes, which minors before 3 store as JSON.
+fn decode_doc_metadata(minor: u16, bytes: &[u8]) -> Result<Value, DocstoreError> {
+    if minor >= DOC_SEGMENT_MINOR {
+        Ok(DocMetadata::from_bytes(bytes.to_vec())?.to_value())
+    } else {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum DocSection {
+    Payload,
+    Metadata,
+    Preview,
 }
 
+/// Validated section ranges of a doc segment object of any supported minor version.
 #[derive(Debug)]
-struct StoreDocSegment {
+struct DocSegmentLayout {
     minor: u16,
-    bytes: Arc<wax_v2_core::SegmentObject>,
+    row_count: usize,
     payload_range: Range<usize>,
+    metadata_range: Range<usize>,
+    preview_range: Range<usize>,
+    binding_range: Range<usize>,
     row_table_range: Range<usize>,
-    row_count: usize,
-    doc_id_map: Option<DocIdMap>,
+    compression: Option<SectionCompression>,
 }
 
-impl StoreDocSegment {
-    fn open(bytes: wax_v2_core::SegmentObject) -> Result<Self, DocstoreError> {
-        let bytes = Arc::new(bytes);
+impl DocSegmentLayout {
+    fn parse(bytes: &[u8]) -> Result<Self, DocstoreError> {
         if bytes.len() < DOC_SEGMENT_VERSION_PREFIX_LENGTH {
             return Err(DocstoreError::InvalidDocument(format!(
                 "doc segment too short: expected at least {DOC_SEGMENT_VERSION_PREFIX_LENGTH} bytes"
@@ -543,17 +529,17 @@ impl StoreDocSegment {
                 "doc segment magic mismatch".to_owned(),
             ));
         }
-        let major = read_u16(bytes.as_ref(), 4);
-        let minor = read_u16(bytes.as_ref(), 6);
+        let major = read_u16(bytes, 4);
+        let minor = read_u16(bytes, 6);
         if major != DOC_SEGMENT_MAJOR || minor > DOC_SEGMENT_MINOR {
             return Err(DocstoreError::InvalidDocument(
                 "unsupported doc segment version".to_owned(),
             ));
         }
-        let header_length = if minor == 0 {
-            DOC_SEGMENT_MINOR_V0_HEADER_LENGTH
-        } else {
-            DOC_SEGMENT_HEADER_LENGTH
+        let header_length = match minor {
+            0 => DOC_SEGMENT_MINOR_V0_HEADER_LENGTH,
+            1 => DOC_SEGMENT_MINOR_V1_HEADER_LENGTH,
+            _ => DOC_SEGMENT_HEADER_LENGTH,
         };
         if bytes.len() < header_length {
             return Err(DocstoreError::InvalidDocument(format!(
@@ -561,34 +547,36 @@ impl StoreDocSegment {
             )));
         }
 
-        let row_count = read_u64_as_usize(bytes.as_ref(), 8, "doc segment row count")?;
-        let payload_bytes_offset = read_u64_as_usize(bytes.as_ref(), 16, "payload offset")?;
-        let metadata_bytes_offset = read_u64_as_usize(bytes.as_ref(), 24, "metadata offset")?;
-        let preview_bytes_offset = read_u64_as_usize(bytes.as_ref(), 32, "preview offset")?;
-        let (binding_bytes_offset, row_table_offset, checksum_start, checksum_end, header_length) =
-            if minor == 0 {
-                let row_table_offset = read_u64_as_usize(bytes.as_ref(), 40, "row table offset")?;
-                (
-                    row_table_offset,
-                    row_table_offset,
-                    48,
-                    80,
-                    DOC_SEGMENT_MINOR_V0_HEADER_LENGTH,
-                )
-            } else {
-                (
-                    read_u64_as_usize(bytes.as_ref(), 40, "binding offset")?,
-                    read_u64_as_usize(bytes.as_ref(), 48, "row table offset")?,
-                    56,
-                    88,
-                    DOC_SEGMENT_HEADER_LENGTH,
-                )
-            };
+        let row_count = read_u64_as_usize(bytes, 8, "doc segment row count")?;
+        if row_count > bytes.len() / DOC_ROW_LENGTH {
+            return Err(DocstoreError::InvalidDocument(
+                "doc segment row count exceeds possible rows in slice".to_owned(),
+            ));
+        }
+        let payload_bytes_offset = read_u64_as_usize(bytes, 16, "payload offset")?;
+        let metadata_bytes_offset = read_u64_as_usize(bytes, 24, "metadata offset")?;
+        let preview_bytes_offset = read_u64_as_usize(bytes, 32, "preview offset")?;
+        let (binding_bytes_offset, row_table_offset, checksum_start) = if minor == 0 {
+            let row_table_offset = read_u64_as_usize(bytes, 40, "row table offset")?;
+            (row_table_offset, row_table_offset, 48)
+        } else {
+            (
+                read_u64_as_usize(bytes, 40, "binding offset")?,
+                read_u64_as_usize(bytes, 48, "row table offset")?,
+                56,
+            )
+        };
+        let block_index_offset = if minor >= DOC_SEGMENT_COMPRESSED_MINOR {
+            read_u64_as_usize(bytes, 96, "block index offset")?
+        } else {
+            row_table_offset
+        };
         if payload_bytes_offset != header_length
             || payload_bytes_offset > metadata_bytes_offset
             || metadata_bytes_offset > preview_bytes_offset
             || preview_bytes_offset > binding_bytes_offset
-            || binding_bytes_offset > row_table_offset
+            || binding_bytes_offset > block_index_offset
+            || block_index_offset > row_table_offset
             || row_table_offset > bytes.len()
         {
             return Err(DocstoreError::InvalidDocument(
@@ -597,7 +585,7 @@ impl StoreDocSegment {
         }
 
         let mut contents_checksum = [0u8; 32];
-        contents_checksum.copy_from_slice(&bytes[checksum_start..checksum_end]);
+        contents_checksum.copy_from_slice(&bytes[checksum_start..checksum_start + 32]);
         if sha256(&bytes[header_length..]) != contents_checksum {
             return Err(DocstoreError::InvalidDocument(
                 "doc segment checksum mismatch".to_owned(),
@@ -616,7 +604,96 @@ impl StoreDocSegment {
             ));
         }
 
-        let binding_section = &bytes[binding_bytes_offset..row_table_offset];
+        let payload_range = payload_bytes_offset..metadata_bytes_offset;
+        let metadata_range = metadata_bytes_offset..preview_bytes_offset;
+        let preview_range = preview_bytes_offset..binding_bytes_offset;
+        let compression = if minor >= DOC_SEGMENT_COMPRESSED_MINOR {
+            SectionCompression::decode(
+                bytes,
+                minor,
+                [&payload_range, &metadata_range, &preview_range],
+                block_index_offset..row_table_offset,
+            )?
+        } else {
+            None
+        };
+        Ok(Self {
+            minor,
+            row_count,
+            payload_range,
+            metadata_range,
+            preview_range,
+            binding_range: binding_bytes_offset..block_index_offset,
+            row_table_range: row_table_offset..row_table_end,
+            compression,
+        })
+    }
+
+    /// Uncompressed bytes of a whole section; plain sections are borrowed from the object.
+    fn decompressed_section<'a>(
+        &self,
+        bytes: &'a [u8],
+        section: DocSection,
+    ) -> Result<Cow<'a, [u8]>, DocstoreError> {
+        let range = match section {
+            DocSection::Payload => self.payload_range.clone(),
+            DocSection::Metadata => self.metadata_range.clone(),
+            DocSection::Preview => self.preview_range.clone(),
+        };
+        match self.compression.as_ref() {
+            Some(compression) => compression
+                .blocks
+                .decompress_section(&bytes[range], compression.section(section))
+                .map(Cow::Owned),
+            None => Ok(Cow::Borrowed(&bytes[range])),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Docstore {
+    source: DocstoreSource,
+}
+
+#[derive(Debug)]
+enum DocstoreSource {
+    DatasetPack {
+        documents_path: PathBuf,
+        offset_index: Option<HashMap<String, DocumentOffsetEntry>>,
+    },
+    Store {
+        segment: StoreDocSegment,
+    },
+}
+
+#[derive(Debug)]
+struct StoreDocSegment {
+    minor: u16,
+    bytes: Arc<wax_v2_core::SegmentObject>,
+    payload_range: Range<usize>,
+    metadata_range: Range<usize>,
+    compression: Option<Box<SectionCompression>>,
+    row_table_range: Range<usize>,
+    row_count: usize,
+    doc_id_map: Option<DocIdMap>,
+}
+
+impl StoreDocSegment {
+    fn open(bytes: wax_v2_core::SegmentObject) -> Result<Self, DocstoreError> {
+        let bytes = Arc::new(bytes);
+        let DocSegmentLayout {
+            minor,
+            row_count,
+            payload_range,
+            metadata_range,
+            binding_range,
+            row_table_range,
+            compression,
+            ..
+        } = DocSegmentLayout::parse(bytes.as_ref())?;
+        let row_table_offset = row_table_range.start;
+
+        let binding_section = &bytes[binding_range];
         let mut previous_doc_id = None;
         let doc_id_map = if minor == 0 {
             None
@@ -649,8 +726,10 @@ impl StoreDocSegment {
         Ok(Self {
             minor,
             bytes,
-            payload_range: payload_bytes_offset..metadata_bytes_offset,
-            row_table_range: row_table_offset..row_table_end,
+            payload_range,
+            metadata_range,
+            compression: compression.map(Box::new),
+            row_table_range,
             row_count,
             doc_id_map,
         })
@@ -664,28 +743,77 @@ impl StoreDocSegment {
             return self.load_documents_by_id_minor_v0(target_doc_ids);
         }
 
+        let mut targets = self.target_rows(target_doc_ids)?;
+        // Payload order lets compressed segments decompress each needed block once.
+        targets.sort_by_key(|(_, row)| row.payload_offset);
+        let mut cache = BlockCache::default();
         let mut documents = HashMap::new();
-        let doc_id_map = self.doc_id_map.as_ref().ok_or_else(|| {
-            DocstoreError::InvalidDocument("store doc segment missing doc_id map".to_owned())
-        })?;
-        for doc_id in target_doc_ids {
-            let Some(wax_doc_id) = doc_id_map.wax_doc_id(doc_id) else {
-                continue;
-            };
-            let Some(row) = self.find_row_by_wax_doc_id(wax_doc_id) else {
-                continue;
-            };
-            let (_, value) = self.parse_payload_document(&row)?;
+        for (doc_id, row) in targets {
+            let (_, value) = self.parse_payload_document(&row, &mut cache)?;
             documents.insert(doc_id.clone(), value);
         }
         Ok(documents)
     }
 
+    fn load_metadata_by_id(
+        &self,
+        target_doc_ids: &[String],
+    ) -> Result<HashMap<String, DocMetadata>, DocstoreError> {
+        if self.minor == 0 {
+            return Ok(self
+                .load_documents_by_id_minor_v0(target_doc_ids)?
+                .into_iter()
+                .map(|(doc_id, document)| (doc_id, document_metadata(&document)))
+                .collect());
+        }
+
+        let mut targets = self.target_rows(target_doc_ids)?;
+        targets.sort_by_key(|(_, row)| row.metadata_ref.offset());
+        let mut cache = BlockCache::default();
+        let mut metadata = HashMap::new();
+        for (doc_id, row) in targets {
+            let bytes = section_bytes(
+                &self.bytes[self.metadata_range.clone()],
+                self.compression
+                    .as_ref()
+                    .map(|compression| (&compression.blocks, &compression.metadata)),
+                row.metadata_ref.offset() as u64,
+                row.metadata_ref.length() as u64,
+                &mut cache,
+                "metadata",
+            )?;
+            let value = if self.minor >= DOC_SEGMENT_MINOR {
+                DocMetadata::from_bytes(bytes.to_vec())?
+            } else {
+                DocMetadata::encode(&serde_json::from_slice(bytes)?)
+            };
+            metadata.insert(doc_id.clone(), value);
+        }
+        Ok(metadata)
+    }
+
+    fn target_rows<'a>(
+        &self,
+        target_doc_ids: &'a [String],
+    ) -> Result<Vec<(&'a String, DocRow)>, DocstoreError> {
+        let doc_id_map = self.doc_id_map.as_ref().ok_or_else(|| {
+            DocstoreError::InvalidDocument("store doc segment missing doc_id map".to_owned())
+        })?;
+        Ok(target_doc_ids
+            .iter()
+            .filter_map(|doc_id| {
+                let wax_doc_id = doc_id_map.wax_doc_id(doc_id)?;
+                Some((doc_id, self.find_row_by_wax_doc_id(wax_doc_id)?))
+            })
+            .collect())
+    }
+
     fn ordered_documents(&self) -> Result<Vec<(String, Value)>, DocstoreError> {
         if self.minor == 0 {
+            let mut cache = BlockCache::default();
             return self
                 .rows()
-                .map(|row| self.parse_payload_document(&row))
+                .map(|row| self.parse_payload_document(&row, &mut cache))
                 .collect();
         }
 
@@ -706,9 +834,10 @@ impl StoreDocSegment {
 
     fn load_document_ids(&self) -> Result<Vec<String>, DocstoreError> {
         if self.minor == 0 {
+            let mut cache = BlockCache::default();
             return self
                 .rows()
-                .map(|row| self.parse_payload_doc_id(&row))
+                .map(|row| self.parse_payload_doc_id(&row, &mut cache))
                 .collect();
         }
 
@@ -735,10 +864,11 @@ impl StoreDocSegment {
             return Ok(doc_id_map.clone());
         }
 
+        let mut cache = BlockCache::default();
         let bindings = self
             .rows()
             .map(|row| {
-                self.parse_payload_doc_id(&row)
+                self.parse_payload_doc_id(&row, &mut cache)
                     .map(|external_doc_id| DocIdBinding::new(row.doc_id, external_doc_id))
             })
             .collect::<Result<Vec<_>, _>>()?;
@@ -755,8 +885,9 @@ impl StoreDocSegment {
             .collect::<HashSet<_>>();
         let mut documents = HashMap::new();
 
+        let mut cache = BlockCache::default();
         for row in self.rows() {
-            let (external_doc_id, value) = self.parse_payload_document(&row)?;
+            let (external_doc_id, value) = self.parse_payload_document(&row, &mut cache)?;
             if remaining.remove(external_doc_id.as_str()) {
                 documents.insert(external_doc_id, value);
                 if remaining.is_empty() {
@@ -792,8 +923,12 @@ impl StoreDocSegment {
         None
     }
 
-    fn parse_payload_doc_id(&self, row: &DocRow) -> Result<String, DocstoreError> {
-        let payload = self.payload_bytes(row)?;
+    fn parse_payload_doc_id(
+        &self,
+        row: &DocRow,
+        cache: &mut BlockCache,
+    ) -> Result<String, DocstoreError> {
+        let payload = self.payload_bytes(row, cache)?;
         let payload_text = std::str::from_utf8(payload)
             .map_err(|error| DocstoreError::InvalidDocument(error.to_string()))?;
         parse_document_id(payload_text, "store-backed document payload")
@@ -801,8 +936,12 @@ impl StoreDocSegment {
             .map_err(DocstoreError::InvalidDocument)
     }
 
-    fn parse_payload_document(&self, row: &DocRow) -> Result<(String, Value), DocstoreError> {
-        let payload = self.payload_bytes(row)?;
+    fn parse_payload_document(
+        &self,
+        row: &DocRow,
+        cache: &mut BlockCache,
+    ) -> Result<(String, Value), DocstoreError> {
+        let payload = self.payload_bytes(row, cache)?;
         let value: Value = serde_json::from_slice(payload)?;
         let object = value.as_object().ok_or_else(|| {
             DocstoreError::InvalidDocument("document line must be a json object".to_owned())
@@ -819,11 +958,19 @@ impl StoreDocSegment {
         Ok((external_doc_id, Value::Object(object.clone())))
     }
 
-    fn payload_bytes<'a>(&'a self, row: &DocRow) -> Result<&'a [u8], DocstoreError> {
-        read_section_bytes(
+    fn payload_bytes<'a>(
+        &'a self,
+        row: &DocRow,
+        cache: &'a mut BlockCache,
+    ) -> Result<&'a [u8], DocstoreError> {
+        section_bytes(
             &self.bytes[self.payload_range.clone()],
+            self.compression
+                .as_ref()
+                .map(|compression| (&compression.blocks, &compression.payload)),
             row.payload_offset,
             row.payload_length,
+            cache,
             "payload",
         )
     }
@@ -843,9 +990,18 @@ fn read_doc_row(row_bytes: &[u8]) -> DocRow {
 
 impl Docstore {
     pub fn open(mount_root: &Path, manifest: &DatasetPackManifest) -> Result<Self, DocstoreError> {
+        Self::open_at_generation(mount_root, manifest, None)
+    }
+
+    /// Opens the doc segment visible at a store manifest generation; `None` is the active one.
+    pub fn open_at_generation(
+        mount_root: &Path,
+        manifest: &DatasetPackManifest,
+        generation: Option<u64>,
+    ) -> Result<Self, DocstoreError> {
         let store_path = mount_root.join("store.wax");
         if store_path.exists() {
-            let opened = wax_v2_core::open_store(&store_path)
+            let opened = wax_v2_core::open_store_at_generation(&store_path, generation)
                 .map_err(|error| DocstoreError::InvalidDocument(error.to_string()))?;
             if let Some(descriptor) = opened
                 .manifest
@@ -940,6 +1096,22 @@ impl Docstore {
         }
     }
 
+    /// Loads the binary metadata of each found document without parsing its payload when the
+    /// doc segment stores metadata in the [`DocMetadata`] encoding.
+    pub fn load_metadata_by_id(
+        &self,
+        target_doc_ids: &[String],
+    ) -> Result<HashMap<String, DocMetadata>, DocstoreError> {
+        match &self.source {
+            DocstoreSource::DatasetPack { .. } => Ok(self
+                .load_documents_by_id(target_doc_ids)?
+                .into_iter()
+                .map(|(doc_id, document)| (doc_id, document_metadata(&document)))
+                .collect()),
+            DocstoreSource::Store { segment } => segment.load_metadata_by_id(target_doc_ids),
+        }
+    }
+
     pub fn load_document_ids(&self) -> Result<Vec<String>, DocstoreError> {
         match &self.source {
             DocstoreSource::DatasetPack { documents_path, .. } => {
@@ -1013,25 +1185,18 @@ impl Docstore {
             let wax_doc_id = binding.wax_doc_id;
             let document = documents_by_external_id
                 .get(&external_doc_id)
-                .cloned()
                 .ok_or_else(|| {
                     DocstoreError::InvalidDocument(format!(
                         "missing document payload for {external_doc_id}"
                     ))
                 })?;
-            let object = document.as_object().ok_or_else(|| {
-                DocstoreError::InvalidDocument("document line must be a json object".to_owned())
-            })?;
-            let payload = serde_json::to_vec(&document)?;
-            let metadata = object
-                .get("metadata")
-                .cloned()
-                .unwrap_or_else(|| Value::Object(Default::default()));
-            let metadata_bytes = serde_json::to_vec(&metadata)?;
-            let preview = object
-                .get("text")
-                .and_then(Value::as_str)
-                .map(ToOwned::to_owned);
+            let EncodedDocument {
+                payload,
+                metadata,
+                metadata_bytes,
+                preview,
+                timestamp_ms,
+            } = EncodedDocument::encode(document)?;
             let preview_length = preview.as_ref().map(|value| value.len()).unwrap_or(0);
             let metadata_length = checked_section_u32(metadata_bytes.len(), "metadata length")?;
             let preview_length = checked_section_u32(preview_length, "preview length")?;
@@ -1039,10 +1204,7 @@ impl Docstore {
             records.push(DocSegmentRecord {
                 row: DocRow {
                     doc_id: wax_doc_id,
-                    timestamp_ms: object
-                        .get("timestamp_ms")
-                        .and_then(Value::as_u64)
-                        .unwrap_or(0),
+                    timestamp_ms,
                     flags: 0,
                     payload_offset,
                     payload_length: payload.len() as u64,
@@ -1243,25 +1405,18 @@ fn build_binary_doc_segment_from_documents(
         let wax_doc_id = binding.wax_doc_id;
         let document = documents_by_external_id
             .get(&external_doc_id)
-            .cloned()
             .ok_or_else(|| {
                 DocstoreError::InvalidDocument(format!(
                     "missing document payload for {external_doc_id}"
                 ))
             })?;
-        let object = document.as_object().ok_or_else(|| {
-            DocstoreError::InvalidDocument("document line must be a json object".to_owned())
-        })?;
-        let payload = serde_json::to_vec(&document)?;
-        let metadata = object
-            .get("metadata")
-            .cloned()
-            .unwrap_or_else(|| Value::Object(Default::default()));
-        let metadata_bytes = serde_json::to_vec(&metadata)?;
-        let preview = object
-            .get("text")
-            .and_then(Value::as_str)
-            .map(ToOwned::to_owned);
+        let EncodedDocument {
+            payload,
+            metadata,
+            metadata_bytes,
+            preview,
+            timestamp_ms,
+        } = EncodedDocument::encode(document)?;
         let preview_length = preview.as_ref().map(|value| value.len()).unwrap_or(0);
         let metadata_length = checked_section_u32(metadata_bytes.len(), "metadata length")?;
         let preview_length = checked_section_u32(preview_length, "preview length")?;
@@ -1269,10 +1424,7 @@ fn build_binary_doc_segment_from_documents(
         records.push(DocSegmentRecord {
             row: DocRow {
                 doc_id: wax_doc_id,
-                timestamp_ms: object
-                    .get("timestamp_ms")
-                    .and_then(Value::as_u64)
-                    .unwrap_or(0),
+                timestamp_ms,
                 flags: 0,
                 payload_offset,
                 payload_length: payload.len() as u64,
@@ -1299,7 +1451,49 @@ fn build_binary_doc_segment_from_documents(
     })
 }
 
-fn load_persisted_doc_id_map_from_store(
+fn document_metadata(document: &Value) -> DocMetadata {
+    match document.get("metadata") {
+        Some(metadata) => DocMetadata::encode(metadata),
+        None => DocMetadata::encode(&Value::Object(Default::default())),
+    }
+}
+
+/// Row fields and section bytes of one document, shared by the in-memory and streaming doc
+/// segment builders so both encode identical bytes.
+pub(crate) struct EncodedDocument {
+    pub(crate) payload: Vec<u8>,
+    pub(crate) metadata: Value,
+    pub(crate) metadata_bytes: Vec<u8>,
+    pub(crate) preview: Option<String>,
+    pub(crate) timestamp_ms: u64,
+}
+
+impl EncodedDocument {
+    pub(crate) fn encode(document: &Value) -> Result<Self, DocstoreError> {
+        let object = document.as_object().ok_or_else(|| {
+            DocstoreError::InvalidDocument("document line must be a json object".to_owned())
+        })?;
+        let metadata = object
+            .get("metadata")
+            .cloned()
+            .unwrap_or_else(|| Value::Object(Default::default()));
+        Ok(Self {
+            payload: serde_json::to_vec(document)?,
+            metadata_bytes: DocMetadata::encode(&metadata).into_bytes(),
+            metadata,
+            preview: object
+                .get("text")
+                .and_then(Value::as_str)
+                .map(ToOwned::to_owned),
+            timestamp_ms: object
+                .get("timestamp_ms")
+                .and_then(Value::as_u64)
+                .unwrap_or(0),
+        })
+    }
+}
+
+pub(crate) fn load_persisted_doc_id_map_from_store(
     store_path: &Path,
 ) -> Result<Option<DocIdMap>, DocstoreError> {
     if !store_path.exists() {
@@ -1343,12 +1537,24 @@ pub fn load_document_ids_from_documents(path: &Path) -> Result<Vec<String>, Docs
 pub fn prepare_raw_documents_segment(
     store_path: &Path,
     ordered_documents: Vec<(String, Value)>,
+) -> Result<PendingSegmentWrite, DocstoreError> {
+    prepare_raw_documents_segment_with_compression(
+        store_path,
+        ordered_documents,
+        DocCompression::None,
+    )
+}
+
+pub fn prepare_raw_documents_segment_with_compression(
+    store_path: &Path,
+    ordered_documents: Vec<(String, Value)>,
+    compression: DocCompression,
 ) -> Result<PendingSegmentWrite, DocstoreError> {
     let doc_id_map = load_persisted_doc_id_map_from_store(store_path)?.unwrap_or_else(|| {
         DocIdMap::from_bindings(Vec::new()).expect("empty doc id map should be valid")
     });
     let segment = build_binary_doc_segment_from_documents(ordered_documents, doc_id_map)?;
-    let object_bytes = segment.encode()?;
+    let object_bytes = segment.encode_with_compression(compression)?;
     let descriptor = pending_doc_segment_descriptor(&segment);
     Ok(PendingSegmentWrite {
         descriptor,
@@ -1681,10 +1887,13 @@ mod tests {
     };
 
     use crate::{
-        build_ref_section, parse_document_id, read_u64, sha256, BinaryDocSegment, DocIdBinding,
-        DocIdMap, DocRow, DocSegmentRecord, Docstore, DocstoreError, DocstoreSource, SectionRef,
-        DOC_SEGMENT_HEADER_LENGTH, DOC_SEGMENT_MAGIC, DOC_SEGMENT_MAJOR, DOC_SEGMENT_MINOR,
-        MAX_DOCUMENT_OFFSET_ENTRY_LENGTH, MAX_DOCUMENT_REF_SECTION_LENGTH,
+        build_ref_section, checked_section_u32, parse_document_id, prepare_raw_documents_segment,
+        prepare_raw_documents_segment_with_compression, read_u16, read_u64, sha256,
+        BinaryDocSegment, DocCompression, DocIdBinding, DocIdMap, DocMetadata, DocRow,
+        DocSegmentRecord, Docstore, DocstoreError, DocstoreSource, SectionRef, DOC_ROW_LENGTH,
+        DOC_SEGMENT_COMPRESSED_MINOR, DOC_SEGMENT_HEADER_LENGTH, DOC_SEGMENT_MAGIC,
+        DOC_SEGMENT_MAJOR, DOC_SEGMENT_MINOR, MAX_DOCUMENT_OFFSET_ENTRY_LENGTH,
+        MAX_DOCUMENT_REF_SECTION_LENGTH,
     };
 
     #[test]
@@ -1759,7 +1968,7 @@ mod tests {
                     flags: 0,
                     payload_offset: 0,
                     payload_length: 35,
-                    metadata_ref: SectionRef::new(0, 2),
+                    metadata_ref: SectionRef::new(0, 3),
                     preview_ref: SectionRef::new(0, 13),
                 },
                 payload: br#"{"doc_id":"doc-001","text":"alpha"}"#.to_vec(),
@@ -2104,7 +2313,7 @@ mod tests {
             .position(|window| window == b"doc-900")
             .unwrap();
         bytes[replaced..replaced + 7].copy_from_slice(b"doc-999");
-        let checksum = sha256(&bytes[88..]);
+        let checksum = sha256(&bytes[DOC_SEGMENT_HEADER_LENGTH..]);
         bytes[56..88].copy_from_slice(&checksum);
         publish_segment(
             &store_path,
@@ -2155,7 +2364,7 @@ mod tests {
                         flags: 0,
                         payload_offset: 0,
                         payload_length: first_payload_len,
-                        metadata_ref: SectionRef::new(0, 2),
+                        metadata_ref: SectionRef::new(0, 3),
                         preview_ref: SectionRef::new(0, 5),
                     },
                     payload: first_payload,
@@ -2169,7 +2378,7 @@ mod tests {
                         flags: 0,
                         payload_offset: first_payload_len,
                         payload_length: second_payload_len,
-                        metadata_ref: SectionRef::new(2, 2),
+                        metadata_ref: SectionRef::new(3, 3),
                         preview_ref: SectionRef::new(5, 4),
                     },
                     payload: second_payload,
@@ -2284,8 +2493,8 @@ mod tests {
 
     #[test]
     fn binary_doc_segment_round_trips_records_with_tombstones() {
-        let metadata_note = serde_json::to_vec(&json!({"kind":"note"})).unwrap();
-        let metadata_deleted = serde_json::to_vec(&json!({"kind":"deleted"})).unwrap();
+        let metadata_note = DocMetadata::encode(&json!({"kind":"note"})).into_bytes();
+        let metadata_deleted = DocMetadata::encode(&json!({"kind":"deleted"})).into_bytes();
         let segment = BinaryDocSegment {
             doc_id_map: test_doc_id_map(&[(10, "doc-010"), (11, "doc-011")]),
             records: vec![
@@ -2330,10 +2539,165 @@ mod tests {
         assert!(decoded.records[1].row.is_tombstone());
     }
 
+    #[test]
+    fn compressed_doc_segment_round_trips_through_decode() {
+        let temp_dir = tempdir().unwrap();
+        let store_path = temp_dir.path().join("store.wax");
+        let documents = compression_test_documents(400);
+        let plain = prepare_raw_documents_segment(&store_path, documents.clone()).unwrap();
+        let expected = BinaryDocSegment::decode(&plain.object_bytes).unwrap();
+
+        for compression in [DocCompression::Lz4, DocCompression::Zstd] {
+            let compressed = prepare_raw_documents_segment_with_compression(
+                &store_path,
+                documents.clone(),
+                compression,
+            )
+            .unwrap();
+
+            assert_eq!(read_u16(&compressed.object_bytes, 6), DOC_SEGMENT_MINOR);
+            assert_eq!(read_u16(&compressed.object_bytes, 88), compression.code());
+            assert!(compressed.object_bytes.len() < plain.object_bytes.len());
+            assert_eq!(compressed.descriptor, plain.descriptor);
+            assert_eq!(
+                BinaryDocSegment::decode(&compressed.object_bytes).unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn compressed_store_segment_loads_documents_across_blocks() {
+        let documents = compression_test_documents(2_000);
+        for compression in [DocCompression::Lz4, DocCompression::Zstd] {
+            let temp_dir = tempdir().unwrap();
+            let store_path = temp_dir.path().join("store.wax");
+            create_empty_store(&store_path).unwrap();
+            let prepared = prepare_raw_documents_segment_with_compression(
+                &store_path,
+                documents.clone(),
+                compression,
+            )
+            .unwrap();
+            publish_segment(&store_path, prepared.descriptor, &prepared.object_bytes).unwrap();
+
+            let docstore = Docstore::open(temp_dir.path(), &test_manifest(false)).unwrap();
+            let DocstoreSource::Store { segment } = &docstore.source else {
+                panic!("expected store-backed docstore");
+            };
+            let compression_index = segment.compression.as_ref().unwrap();
+            assert!(compression_index.payload.block_offsets.len() > 3);
+
+            let targets = vec![
+                "doc-1999".to_owned(),
+                "doc-0000".to_owned(),
+                "doc-1024".to_owned(),
+                "doc-missing".to_owned(),
+            ];
+            let loaded = docstore.load_documents_by_id(&targets).unwrap();
+            assert_eq!(loaded.len(), 3);
+            for doc_id in &targets[..3] {
+                let (_, expected) = documents
+                    .iter()
+                    .find(|(expected_id, _)| expected_id == doc_id)
+                    .unwrap();
+                assert_eq!(&loaded[doc_id], expected);
+            }
+            assert_eq!(docstore.load_document_ids().unwrap().len(), 2_000);
+        }
+    }
+
+    #[test]
+    fn store_segment_loads_binary_metadata_without_hydrating_payloads() {
+        let documents = compression_test_documents(500);
+        for compression in [
+            DocCompression::None,
+            DocCompression::Lz4,
+            DocCompression::Zstd,
+        ] {
+            let temp_dir = tempdir().unwrap();
+            let store_path = temp_dir.path().join("store.wax");
+            create_empty_store(&store_path).unwrap();
+            let prepared = prepare_raw_documents_segment_with_compression(
+                &store_path,
+                documents.clone(),
+                compression,
+            )
+            .unwrap();
+            publish_segment(&store_path, prepared.descriptor, &prepared.object_bytes).unwrap();
+            let docstore = Docstore::open(temp_dir.path(), &test_manifest(false)).unwrap();
+
+            let targets = vec![
+                "doc-0499".to_owned(),
+                "doc-0003".to_owned(),
+                "doc-missing".to_owned(),
+            ];
+            let metadata = docstore.load_metadata_by_id(&targets).unwrap();
+
+            assert_eq!(metadata.len(), 2);
+            assert_eq!(
+                metadata["doc-0499"]
+                    .get("bucket")
+                    .and_then(|value| value.as_u64()),
+                Some(499 % 7)
+            );
+            assert_eq!(metadata["doc-0003"].to_value(), json!({"bucket": 3}));
+        }
+    }
+
+    #[test]
+    fn minor_v0_store_segment_loads_metadata_from_payloads() {
+        let temp_dir = tempdir().unwrap();
+        let store_path = temp_dir.path().join("store.wax");
+        create_empty_store(&store_path).unwrap();
+        let prepared =
+            prepare_raw_documents_segment(&store_path, compression_test_documents(5)).unwrap();
+        let segment = BinaryDocSegment::decode(&prepared.object_bytes).unwrap();
+        let bytes = encode_minor_v0_segment(&segment).unwrap();
+        publish_segment(&store_path, prepared.descriptor, &bytes).unwrap();
+        let docstore = Docstore::open(temp_dir.path(), &test_manifest(false)).unwrap();
+
+        let metadata = docstore
+            .load_metadata_by_id(&["doc-0004".to_owned()])
+            .unwrap();
+
+        assert_eq!(metadata["doc-0004"].to_value(), json!({"bucket": 4}));
+    }
+
+    #[test]
+    fn compressed_doc_segment_rejects_corrupt_block_index() {
+        let temp_dir = tempdir().unwrap();
+        let store_path = temp_dir.path().join("store.wax");
+        let mut bytes = prepare_raw_documents_segment_with_compression(
+            &store_path,
+            compression_test_documents(10),
+            DocCompression::Zstd,
+        )
+        .unwrap()
+        .object_bytes;
+        // Codec 0 cannot describe a segment that carries a block index.
+        bytes[88..90].copy_from_slice(&0u16.to_le_bytes());
+
+        assert!(matches!(
+            BinaryDocSegment::decode(&bytes),
+            Err(DocstoreError::InvalidDocument(message))
+                if message == "uncompressed doc segment must not carry a block index"
+        ));
+
+        // Minor 2 segments must always name a codec.
+        bytes[6..8].copy_from_slice(&DOC_SEGMENT_COMPRESSED_MINOR.to_le_bytes());
+
+        assert!(matches!(
+            BinaryDocSegment::decode(&bytes),
+            Err(DocstoreError::InvalidDocument(message))
+                if message == "compressed doc segment must name a compression codec"
+        ));
+    }
+
     #[test]
     fn binary_doc_segment_decode_uses_metadata_and_preview_refs() {
-        let metadata_alpha = serde_json::to_vec(&json!({"kind":"alpha"})).unwrap();
-        let metadata_beta = serde_json::to_vec(&json!({"kind":"beta"})).unwrap();
+        let metadata_alpha = DocMetadata::encode(&json!({"kind":"alpha"})).into_bytes();
+        let metadata_beta = DocMetadata::encode(&json!({"kind":"beta"})).into_bytes();
         let preview_alpha = b"alpha".to_vec();
         let preview_beta = b"beta".to_vec();
         let segment = BinaryDocSegment {
@@ -2394,7 +2758,7 @@ mod tests {
 
     #[test]
     fn binary_doc_segment_rejects_out_of_bounds_metadata_refs() {
-        let metadata_note = serde_json::to_vec(&json!({"kind":"note"})).unwrap();
+        let metadata_note = DocMetadata::encode(&json!({"kind":"note"})).into_bytes();
         let segment = BinaryDocSegment {
             doc_id_map: test_doc_id_map(&[(10, "doc-010")]),
             records: vec![DocSegmentRecord {
@@ -2439,7 +2803,7 @@ mod tests {
                         flags: 0,
                         payload_offset: 0,
                         payload_length: 4,
-                        metadata_ref: SectionRef::new(0, 2),
+                        metadata_ref: SectionRef::new(0, 3),
                         preview_ref: SectionRef::new(0, 4),
                     },
                     payload: b"beta".to_vec(),
@@ -2453,7 +2817,7 @@ mod tests {
                         flags: 0,
                         payload_offset: 4,
                         payload_length: 5,
-                        metadata_ref: SectionRef::new(2, 2),
+                        metadata_ref: SectionRef::new(3, 3),
                         preview_ref: SectionRef::new(4, 5),
                     },
                     payload: b"alpha".to_vec(),
@@ -2487,16 +2851,32 @@ mod tests {
     fn encode_minor_v0_segment(segment: &BinaryDocSegment) -> Result<Vec<u8>, DocstoreError> {
         let minor_v1 = segment.encode()?;
         let payload_bytes_offset = read_u64(&minor_v1, 16) as usize;
-        let metadata_bytes_offset = read_u64(&minor_v1, 24) as usize;
         let preview_bytes_offset = read_u64(&minor_v1, 32) as usize;
         let binding_bytes_offset = read_u64(&minor_v1, 40) as usize;
         let row_table_offset = read_u64(&minor_v1, 48) as usize;
         let row_count = read_u64(&minor_v1, 8);
 
-        let payload_section = &minor_v1[payload_bytes_offset..metadata_bytes_offset];
-        let metadata_section = &minor_v1[metadata_bytes_offset..preview_bytes_offset];
+        let payload_section = &minor_v1[payload_bytes_offset..read_u64(&minor_v1, 24) as usize];
         let preview_section = &minor_v1[preview_bytes_offset..binding_bytes_offset];
-        let row_table = &minor_v1[row_table_offset..];
+
+        // Minor 0 stores metadata as JSON, so rebuild that section and repoint the row refs.
+        let mut metadata_section = Vec::new();
+        let mut row_table = minor_v1[row_table_offset..].to_vec();
+        for (record, row) in segment
+            .records
+            .iter()
+            .zip(row_table.chunks_exact_mut(DOC_ROW_LENGTH))
+        {
+            let metadata = serde_json::to_vec(&record.metadata)?;
+            let metadata_ref = SectionRef::new(
+                checked_section_u32(metadata_section.len(), "metadata offset")?,
+                checked_section_u32(metadata.len(), "metadata length")?,
+            );
+            row[40..48].copy_from_slice(&metadata_ref.packed().to_le_bytes());
+            metadata_section.extend_from_slice(&metadata);
+        }
+        let metadata_section = metadata_section.as_slice();
+        let row_table = row_table.as_slice();
         let minor_v0_row_table_offset =
             80 + payload_section.len() + metadata_section.len() + preview_section.len();
 
@@ -2528,6 +2908,21 @@ mod tests {
         Ok(bytes)
     }
 
+    fn compression_test_documents(count: usize) -> Vec<(String, serde_json::Value)> {
+        (0..count)
+            .map(|index| {
+                let doc_id = format!("doc-{index:04}");
+                let document = json!({
+                    "doc_id": doc_id,
+                    "text": format!("document {index} repeats the shared body text {index}"),
+                    "metadata": {"bucket": index % 7},
+                    "timestamp_ms": index as u64,
+                });
+                (doc_id, document)
+            })
+            .collect()
+    }
+
     fn test_manifest(include_documents: bool) -> DatasetPackManifest {
         let mut files = vec![
             json!({"path":"document-offsets.jsonl","kind":"document_offsets","format":"jsonl","record_count":1,"checksum":"sha256:offsets"}),
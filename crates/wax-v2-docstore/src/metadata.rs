@@ -0,0 +1,426 @@
+use serde_json::{Map, Number, Value};
+
+use crate::DocstoreError;
+
+const TAG_NULL: u8 = 0;
+const TAG_FALSE: u8 = 1;
+const TAG_TRUE: u8 = 2;
+const TAG_UNSIGNED: u8 = 3;
+const TAG_SIGNED: u8 = 4;
+const TAG_FLOAT: u8 = 5;
+const TAG_STRING: u8 = 6;
+const TAG_ARRAY: u8 = 7;
+const TAG_OBJECT: u8 = 8;
+/// Nesting limit when validating, matching serde_json's default recursion limit.
+const MAX_METADATA_DEPTH: usize = 128;
+
+/// Document metadata in the typed binary encoding of doc segment metadata sections.
+///
+/// Each value is a tag byte followed by its body: LEB128 for unsigned integers and lengths,
+/// zigzag LEB128 for negative integers, little-endian `f64` for floats, and length-prefixed UTF-8
+/// for strings. Arrays and objects carry their body length and item count so lookups skip
+/// unrelated fields without decoding them; object keys are length-prefixed strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocMetadata {
+    bytes: Vec<u8>,
+}
+
+impl DocMetadata {
+    pub fn encode(value: &Value) -> Self {
+        let mut bytes = Vec::new();
+        encode_value(value, &mut bytes);
+        Self { bytes }
+    }
+
+    /// Wraps encoded bytes after checking that they hold exactly one well-formed value.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, DocstoreError> {
+        let end = validate_value(&bytes, 0, 0)?;
+        if end != bytes.len() {
+            return Err(invalid_metadata());
+        }
+        Ok(Self { bytes })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn root(&self) -> MetadataValue<'_> {
+        MetadataValue { bytes: &self.bytes }
+    }
+
+    /// Field of the top-level object; dots in `path` descend into nested objects when the
+    /// field itself is absent, matching how metadata filters address nested values.
+    pub fn get(&self, path: &str) -> Option<MetadataValue<'_>> {
+        let root = self.root();
+        root.get(path).or_else(|| {
+            if !path.contains('.') {
+                return None;
+            }
+            path.split('.')
+                .try_fold(root, |value, segment| value.get(segment))
+        })
+    }
+
+    pub fn to_value(&self) -> Value {
+        self.root().to_value()
+    }
+}
+
+/// Borrowed view of one encoded value inside a [`DocMetadata`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetadataValue<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> MetadataValue<'a> {
+    pub fn is_null(self) -> bool {
+        self.bytes[0] == TAG_NULL
+    }
+
+    pub fn as_str(self) -> Option<&'a str> {
+        if self.bytes[0] != TAG_STRING {
+            return None;
+        }
+        let (text, _) = read_str(self.bytes, 1);
+        Some(text)
+    }
+
+    pub fn as_bool(self) -> Option<bool> {
+        match self.bytes[0] {
+            TAG_FALSE => Some(false),
+            TAG_TRUE => Some(true),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(self) -> Option<u64> {
+        (self.bytes[0] == TAG_UNSIGNED).then(|| read_varint(self.bytes, 1).0)
+    }
+
+    /// Field of an object value; `None` for other value kinds.
+    pub fn get(self, field: &str) -> Option<MetadataValue<'a>> {
+        if self.bytes[0] != TAG_OBJECT {
+            return None;
+        }
+        let (_, cursor) = read_varint(self.bytes, 1);
+        let (count, mut cursor) = read_varint(self.bytes, cursor);
+        for _ in 0..count {
+            let (key, value_start) = read_str(self.bytes, cursor);
+            let value_end = skip_value(self.bytes, value_start);
+            if key == field {
+                return Some(MetadataValue {
+                    bytes: &self.bytes[value_start..value_end],
+                });
+            }
+            cursor = value_end;
+        }
+        None
+    }
+
+    pub fn to_value(self) -> Value {
+        decode_value(self.bytes, 0).0
+    }
+}
+
+fn encode_value(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Null => out.push(TAG_NULL),
+        Value::Bool(false) => out.push(TAG_FALSE),
+        Value::Bool(true) => out.push(TAG_TRUE),
+        Value::Number(number) => {
+            if let Some(value) = number.as_u64() {
+                out.push(TAG_UNSIGNED);
+                write_varint(value, out);
+            } else if let Some(value) = number.as_i64() {
+                out.push(TAG_SIGNED);
+                write_varint(((value << 1) ^ (value >> 63)) as u64, out);
+            } else {
+                out.push(TAG_FLOAT);
+                out.extend_from_slice(&number.as_f64().unwrap_or(0.0).to_le_bytes());
+            }
+        }
+        Value::String(value) => {
+            out.push(TAG_STRING);
+            write_str(value, out);
+        }
+        Value::Array(items) => {
+            let mut body = Vec::new();
+            for item in items {
+                encode_value(item, &mut body);
+            }
+            write_container(TAG_ARRAY, items.len(), &body, out);
+        }
+        Value::Object(fields) => {
+            let mut body = Vec::new();
+            for (key, value) in fields {
+                write_str(key, &mut body);
+                encode_value(value, &mut body);
+            }
+            write_container(TAG_OBJECT, fields.len(), &body, out);
+        }
+    }
+}
+
+fn write_container(tag: u8, count: usize, body: &[u8], out: &mut Vec<u8>) {
+    let mut counted = Vec::with_capacity(body.len() + 10);
+    write_varint(count as u64, &mut counted);
+    counted.extend_from_slice(body);
+    out.push(tag);
+    write_varint(counted.len() as u64, out);
+    out.extend_from_slice(&counted);
+}
+
+fn write_str(value: &str, out: &mut Vec<u8>) {
+    write_varint(value.len() as u64, out);
+    out.extend_from_slice(value.as_bytes());
+}
+
+fn write_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Checks the value at `offset` and returns the offset just past it.
+fn validate_value(bytes: &[u8], offset: usize, depth: usize) -> Result<usize, DocstoreError> {
+    if depth > MAX_METADATA_DEPTH {
+        return Err(DocstoreError::InvalidDocument(
+            "metadata nesting exceeds the depth limit".to_owned(),
+        ));
+    }
+    let tag = *bytes.get(offset).ok_or_else(invalid_metadata)?;
+    let cursor = offset + 1;
+    match tag {
+        TAG_NULL | TAG_FALSE | TAG_TRUE => Ok(cursor),
+        TAG_UNSIGNED | TAG_SIGNED => checked_varint(bytes, cursor).map(|(_, end)| end),
+        TAG_FLOAT => {
+            let end = cursor + 8;
+            if end > bytes.len() {
+                return Err(invalid_metadata());
+            }
+            let value = f64::from_le_bytes(bytes[cursor..end].try_into().expect("f64 slice"));
+            if !value.is_finite() {
+                return Err(invalid_metadata());
+            }
+            Ok(end)
+        }
+        TAG_STRING => checked_str(bytes, cursor),
+        TAG_ARRAY | TAG_OBJECT => {
+            let (body_length, body_start) = checked_varint(bytes, cursor)?;
+            let body_end = usize::try_from(body_length)
+                .ok()
+                .and_then(|length| body_start.checked_add(length))
+                .filter(|end| *end <= bytes.len())
+                .ok_or_else(invalid_metadata)?;
+            let body = &bytes[..body_end];
+            let (count, mut cursor) = checked_varint(body, body_start)?;
+            for _ in 0..count {
+                if tag == TAG_OBJECT {
+                    cursor = checked_str(body, cursor)?;
+                }
+                cursor = validate_value(body, cursor, depth + 1)?;
+            }
+            if cursor != body_end {
+                return Err(invalid_metadata());
+            }
+            Ok(body_end)
+        }
+        _ => Err(invalid_metadata()),
+    }
+}
+
+fn checked_varint(bytes: &[u8], offset: usize) -> Result<(u64, usize), DocstoreError> {
+    let mut value = 0u64;
+    for (index, byte) in bytes.iter().skip(offset).take(10).enumerate() {
+        let bits = u64::from(byte & 0x7f);
+        if index == 9 && bits > 1 {
+            return Err(invalid_metadata());
+        }
+        value |= bits << (7 * index);
+        if byte & 0x80 == 0 {
+            return Ok((value, offset + index + 1));
+        }
+    }
+    Err(invalid_metadata())
+}
+
+fn checked_str(bytes: &[u8], offset: usize) -> Result<usize, DocstoreError> {
+    let (length, start) = checked_varint(bytes, offset)?;
+    let end = usize::try_from(length)
+        .ok()
+        .and_then(|length| start.checked_add(length))
+        .filter(|end| *end <= bytes.len())
+        .ok_or_else(invalid_metadata)?;
+    std::str::from_utf8(&bytes[start..end]).map_err(|_| invalid_metadata())?;
+    Ok(end)
+}
+
+// The readers below run on validated bytes only.
+
+fn read_varint(bytes: &[u8], offset: usize) -> (u64, usize) {
+    let mut value = 0u64;
+    let mut cursor = offset;
+    loop {
+        let byte = bytes[cursor];
+        value |= u64::from(byte & 0x7f) << (7 * (cursor - offset));
+        cursor += 1;
+        if byte & 0x80 == 0 {
+            return (value, cursor);
+        }
+    }
+}
+
+fn read_str(bytes: &[u8], offset: usize) -> (&str, usize) {
+    let (length, start) = read_varint(bytes, offset);
+    let end = start + length as usize;
+    (
+        std::str::from_utf8(&bytes[start..end]).expect("validated metadata string"),
+        end,
+    )
+}
+
+fn skip_value(bytes: &[u8], offset: usize) -> usize {
+    match bytes[offset] {
+        TAG_UNSIGNED | TAG_SIGNED => read_varint(bytes, offset + 1).1,
+        TAG_FLOAT => offset + 9,
+        TAG_STRING => read_str(bytes, offset + 1).1,
+        TAG_ARRAY | TAG_OBJECT => {
+            let (length, start) = read_varint(bytes, offset + 1);
+            start + length as usize
+        }
+        _ => offset + 1,
+    }
+}
+
+fn decode_value(bytes: &[u8], offset: usize) -> (Value, usize) {
+    let cursor = offset + 1;
+    match bytes[offset] {
+        TAG_NULL => (Value::Null, cursor),
+        TAG_FALSE => (Value::Bool(false), cursor),
+        TAG_TRUE => (Value::Bool(true), cursor),
+        TAG_UNSIGNED => {
+            let (value, end) = read_varint(bytes, cursor);
+            (Value::from(value), end)
+        }
+        TAG_SIGNED => {
+            let (value, end) = read_varint(bytes, cursor);
+            (
+                Value::from((value >> 1) as i64 ^ -((value & 1) as i64)),
+                end,
+            )
+        }
+        TAG_FLOAT => {
+            let value = f64::from_le_bytes(bytes[cursor..cursor + 8].try_into().expect("f64"));
+            let number = Number::from_f64(value).expect("validated finite float");
+            (Value::Number(number), cursor + 8)
+        }
+        TAG_STRING => {
+            let (value, end) = read_str(bytes, cursor);
+            (Value::String(value.to_owned()), end)
+        }
+        tag => {
+            let (_, cursor) = read_varint(bytes, cursor);
+            let (count, mut cursor) = read_varint(bytes, cursor);
+            if tag == TAG_ARRAY {
+                let mut items = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let (item, end) = decode_value(bytes, cursor);
+                    items.push(item);
+                    cursor = end;
+                }
+                (Value::Array(items), cursor)
+            } else {
+                let mut fields = Map::new();
+                for _ in 0..count {
+                    let (key, value_start) = read_str(bytes, cursor);
+                    let (value, end) = decode_value(bytes, value_start);
+                    fields.insert(key.to_owned(), value);
+                    cursor = end;
+                }
+                (Value::Object(fields), cursor)
+            }
+        }
+    }
+}
+
+fn invalid_metadata() -> DocstoreError {
+    DocstoreError::InvalidDocument("doc metadata encoding is invalid".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::DocMetadata;
+
+    #[test]
+    fn binary_metadata_round_trips_json_values() {
+        let value = json!({
+            "kind": "note",
+            "tags": ["alpha", 7, -3, 1.5, null, true, false],
+            "nested": {"workspace": {"id": "w1"}, "empty": {}},
+            "count": u64::MAX,
+            "offset": i64::MIN,
+            "unicode": "caf\u{e9}",
+        });
+        let metadata = DocMetadata::encode(&value);
+
+        assert!(metadata.as_bytes().len() < serde_json::to_vec(&value).unwrap().len());
+        assert_eq!(metadata.to_value(), value);
+        assert_eq!(
+            DocMetadata::from_bytes(metadata.as_bytes().to_vec()).unwrap(),
+            metadata
+        );
+    }
+
+    #[test]
+    fn binary_metadata_reads_fields_without_decoding_the_document() {
+        let metadata = DocMetadata::encode(&json!({
+            "kind": "note",
+            "count": 3,
+            "nested": {"workspace": "w1"},
+            "dotted.key": "direct",
+        }));
+
+        assert_eq!(
+            metadata.get("kind").and_then(|value| value.as_str()),
+            Some("note")
+        );
+        assert_eq!(
+            metadata.get("count").and_then(|value| value.as_u64()),
+            Some(3)
+        );
+        assert_eq!(metadata.get("count").and_then(|value| value.as_str()), None);
+        assert_eq!(
+            metadata
+                .get("nested.workspace")
+                .and_then(|value| value.as_str()),
+            Some("w1")
+        );
+        assert_eq!(
+            metadata.get("dotted.key").and_then(|value| value.as_str()),
+            Some("direct")
+        );
+        assert_eq!(
+            metadata.get("nested").unwrap().to_value(),
+            json!({"workspace": "w1"})
+        );
+        assert!(metadata.get("missing").is_none());
+    }
+
+    #[test]
+    fn binary_metadata_rejects_truncated_or_trailing_bytes() {
+        let bytes = DocMetadata::encode(&json!({"kind": "note"})).into_bytes();
+
+        assert!(DocMetadata::from_bytes(bytes[..bytes.len() - 1].to_vec()).is_err());
+        assert!(DocMetadata::from_bytes([bytes.as_slice(), &[0]].concat()).is_err());
+        assert!(DocMetadata::from_bytes(vec![9]).is_err());
+    }
+}
@@ -0,0 +1,568 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use wax_v2_core::{PendingSegmentDescriptor, SegmentKind, SpilledSegmentWrite};
+
+use crate::compression::BlockWriter;
+use crate::{
+    load_persisted_doc_id_map_from_store, DocCompression, DocIdMap, DocSection, DocSegmentHeader,
+    DocstoreError, EncodedDocument, SectionRef, DOC_SEGMENT_HEADER_LENGTH,
+    MAX_DOCUMENT_REF_SECTION_LENGTH,
+};
+
+/// Default in-memory budget for doc ids buffered by [`DocIdSpill`] before a sorted run is spilled.
+pub const DEFAULT_DOC_ID_SPILL_RUN_BYTES: usize = 16 * 1024 * 1024;
+
+/// Builds a doc segment without holding document bodies in memory.
+///
+/// Payload, metadata and preview bytes are appended to anonymous spill files as documents
+/// arrive; only the doc id and section offsets of each document stay resident. `finish` lays the
+/// sections out in wax doc id order, so the object is byte-identical to the one
+/// [`crate::prepare_raw_documents_segment_with_compression`] builds for the same documents.
+pub struct StreamingDocSegmentBuilder {
+    doc_id_map: DocIdMap,
+    spill_dir: PathBuf,
+    compression: DocCompression,
+    payloads: SpillSection,
+    metadata: SpillSection,
+    previews: SpillSection,
+    documents: Vec<StreamedDocument>,
+}
+
+struct StreamedDocument {
+    doc_id: String,
+    timestamp_ms: u64,
+    payload: (u64, u64),
+    metadata: (u64, u64),
+    preview: (u64, u64),
+}
+
+impl StreamedDocument {
+    fn range(&self, section: DocSection) -> (u64, u64) {
+        match section {
+            DocSection::Payload => self.payload,
+            DocSection::Metadata => self.metadata,
+            DocSection::Preview => self.preview,
+        }
+    }
+}
+
+struct SpillSection {
+    writer: BufWriter<File>,
+    length: u64,
+}
+
+impl SpillSection {
+    fn new(spill_dir: &Path) -> Result<Self, DocstoreError> {
+        Ok(Self {
+            writer: BufWriter::new(tempfile::tempfile_in(spill_dir)?),
+            length: 0,
+        })
+    }
+
+    fn append(&mut self, bytes: &[u8]) -> Result<(u64, u64), DocstoreError> {
+        self.writer.write_all(bytes)?;
+        let range = (self.length, bytes.len() as u64);
+        self.length += bytes.len() as u64;
+        Ok(range)
+    }
+
+    fn into_file(self) -> Result<File, DocstoreError> {
+        self.writer
+            .into_inner()
+            .map_err(|error| DocstoreError::Io(error.error().to_string()))
+    }
+}
+
+impl StreamingDocSegmentBuilder {
+    /// Starts a segment that keeps the wax doc ids persisted in the store at `store_path`;
+    /// spill files are created in `spill_dir` and removed when they are dropped.
+    pub fn new(store_path: &Path, spill_dir: &Path) -> Result<Self, DocstoreError> {
+        let doc_id_map = load_persisted_doc_id_map_from_store(store_path)?
+            .map_or_else(|| DocIdMap::from_bindings(Vec::new()), Ok)?;
+        Ok(Self {
+            doc_id_map,
+            spill_dir: spill_dir.to_path_buf(),
+            compression: DocCompression::None,
+            payloads: SpillSection::new(spill_dir)?,
+            metadata: SpillSection::new(spill_dir)?,
+            previews: SpillSection::new(spill_dir)?,
+            documents: Vec::new(),
+        })
+    }
+
+    pub fn with_compression(mut self, compression: DocCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Appends one document; callers reject duplicate doc ids before pushing.
+    pub fn push_document(&mut self, doc_id: &str, document: &Value) -> Result<(), DocstoreError> {
+        let encoded = EncodedDocument::encode(document)?;
+        let payload = self.payloads.append(&encoded.payload)?;
+        let metadata = self.metadata.append(&encoded.metadata_bytes)?;
+        let preview = self.previews.append(
+            encoded
+                .preview
+                .as_ref()
+                .map_or(&[][..], |preview| preview.as_bytes()),
+        )?;
+        self.documents.push(StreamedDocument {
+            doc_id: doc_id.to_owned(),
+            timestamp_ms: encoded.timestamp_ms,
+            payload,
+            metadata,
+            preview,
+        });
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Writes the segment object to a spill file. Documents not yet bound to a wax doc id are
+    /// assigned the next ids in push order; the returned map covers every pushed document.
+    pub fn finish(self) -> Result<(SpilledSegmentWrite, DocIdMap), DocstoreError> {
+        let Self {
+            doc_id_map,
+            spill_dir,
+            compression,
+            payloads,
+            metadata,
+            previews,
+            documents,
+        } = self;
+        let document_ids = documents
+            .iter()
+            .map(|document| document.doc_id.clone())
+            .collect::<Vec<_>>();
+        let doc_id_map = doc_id_map.extend_to_cover_document_order(&document_ids)?;
+        let bindings = doc_id_map.bindings_for_external_doc_ids_sorted(&document_ids)?;
+        drop(document_ids);
+        let document_index = documents
+            .iter()
+            .enumerate()
+            .map(|(index, document)| (document.doc_id.as_str(), index))
+            .collect::<HashMap<_, _>>();
+        let ordered = bindings
+            .iter()
+            .map(|binding| {
+                let index = document_index[binding.external_doc_id.as_str()];
+                (binding.wax_doc_id, &documents[index])
+            })
+            .collect::<Vec<_>>();
+        if metadata.length > MAX_DOCUMENT_REF_SECTION_LENGTH as u64 {
+            return Err(DocstoreError::InvalidDocument(
+                "metadata ref section exceeds maximum length".to_owned(),
+            ));
+        }
+        if previews.length > MAX_DOCUMENT_REF_SECTION_LENGTH as u64 {
+            return Err(DocstoreError::InvalidDocument(
+                "preview ref section exceeds maximum length".to_owned(),
+            ));
+        }
+
+        let header_length = DOC_SEGMENT_HEADER_LENGTH;
+        let mut object = BufWriter::new(tempfile::tempfile_in(&spill_dir)?);
+        object.write_all(&vec![0u8; header_length])?;
+        let mut body = HashingWriter {
+            inner: &mut object,
+            hasher: Sha256::new(),
+            written: 0,
+        };
+        let mut block_index = Vec::new();
+        let mut section_offsets = [0u64; 3];
+        for (section_offset, (spill, section)) in section_offsets.iter_mut().zip([
+            (payloads, DocSection::Payload),
+            (metadata, DocSection::Metadata),
+            (previews, DocSection::Preview),
+        ]) {
+            *section_offset = header_length as u64 + body.written;
+            let mut file = spill.into_file()?;
+            if compression == DocCompression::None {
+                for (_, document) in &ordered {
+                    copy_spilled_range(&mut file, document.range(section), &mut body)?;
+                }
+            } else {
+                let mut blocks = BlockWriter::new(compression, &mut body);
+                for (_, document) in &ordered {
+                    copy_spilled_range(&mut file, document.range(section), &mut blocks)?;
+                }
+                blocks.finish()?.encode_index(&mut block_index);
+            }
+        }
+        let binding_bytes_offset = header_length as u64 + body.written;
+        body.write_all(&doc_id_map.encode_json()?)?;
+        let block_index_offset = header_length as u64 + body.written;
+        body.write_all(&block_index)?;
+        let row_table_offset = header_length as u64 + body.written;
+
+        let mut payload_offset = 0u64;
+        let mut metadata_offset = 0u32;
+        let mut preview_offset = 0u32;
+        for (wax_doc_id, document) in &ordered {
+            let metadata_length = document.metadata.1 as u32;
+            let preview_length = document.preview.1 as u32;
+            body.write_all(&wax_doc_id.to_le_bytes())?;
+            body.write_all(&document.timestamp_ms.to_le_bytes())?;
+            body.write_all(&0u32.to_le_bytes())?;
+            body.write_all(&0u32.to_le_bytes())?;
+            body.write_all(&payload_offset.to_le_bytes())?;
+            body.write_all(&document.payload.1.to_le_bytes())?;
+            body.write_all(
+                &SectionRef::new(metadata_offset, metadata_length)
+                    .packed()
+                    .to_le_bytes(),
+            )?;
+            body.write_all(
+                &SectionRef::new(preview_offset, preview_length)
+                    .packed()
+                    .to_le_bytes(),
+            )?;
+            payload_offset += document.payload.1;
+            metadata_offset += metadata_length;
+            preview_offset += preview_length;
+        }
+        let checksum = body.hasher.finalize();
+
+        let header = DocSegmentHeader {
+            row_count: ordered.len() as u64,
+            payload_offset: section_offsets[0],
+            metadata_offset: section_offsets[1],
+            preview_offset: section_offsets[2],
+            binding_offset: binding_bytes_offset,
+            row_table_offset,
+            checksum: checksum.into(),
+            compression,
+            block_index_offset,
+        }
+        .encode();
+        let mut object = object
+            .into_inner()
+            .map_err(|error| DocstoreError::Io(error.error().to_string()))?;
+        object.seek(SeekFrom::Start(0))?;
+        object.write_all(&header)?;
+
+        let descriptor = PendingSegmentDescriptor {
+            family: SegmentKind::Doc,
+            family_version: 1,
+            flags: 0,
+            doc_id_start: ordered.first().map_or(0, |(wax_doc_id, _)| *wax_doc_id),
+            doc_id_end_exclusive: ordered.last().map_or(0, |(wax_doc_id, _)| wax_doc_id + 1),
+            min_timestamp_ms: ordered
+                .iter()
+                .map(|(_, document)| document.timestamp_ms)
+                .min()
+                .unwrap_or(0),
+            max_timestamp_ms: ordered
+                .iter()
+                .map(|(_, document)| document.timestamp_ms)
+                .max()
+                .unwrap_or(0),
+            live_items: ordered.len() as u64,
+            tombstoned_items: 0,
+            backend_id: 0,
+            backend_aux: 0,
+        };
+        Ok((SpilledSegmentWrite { descriptor, object }, doc_id_map))
+    }
+}
+
+/// Collects external doc ids with bounded memory.
+///
+/// Ids are buffered until they exceed the run budget and then spilled as a sorted run;
+/// [`Self::into_sorted`] merges the runs, so a repeated id comes out next to itself and a sorted
+/// id list can be diffed against the set without loading it.
+pub struct DocIdSpill {
+    spill_dir: PathBuf,
+    run_bytes: usize,
+    pending: Vec<String>,
+    pending_bytes: usize,
+    runs: Vec<File>,
+    len: usize,
+}
+
+impl DocIdSpill {
+    pub fn new(spill_dir: &Path) -> Self {
+        Self {
+            spill_dir: spill_dir.to_path_buf(),
+            run_bytes: DEFAULT_DOC_ID_SPILL_RUN_BYTES,
+            pending: Vec::new(),
+            pending_bytes: 0,
+            runs: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn with_run_bytes(mut self, run_bytes: usize) -> Self {
+        self.run_bytes = run_bytes.max(1);
+        self
+    }
+
+    pub fn push(&mut self, doc_id: &str) -> Result<(), DocstoreError> {
+        self.len += 1;
+        self.pending_bytes += doc_id.len() + std::mem::size_of::<String>();
+        self.pending.push(doc_id.to_owned());
+        if self.pending_bytes >= self.run_bytes {
+            self.spill_run()?;
+        }
+        Ok(())
+    }
+
+    /// Number of ids pushed, counting repeats.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of sorted runs spilled so far.
+    pub fn spilled_runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Merges the spilled runs into one ascending stream of every pushed id, repeats included.
+    pub fn into_sorted(mut self) -> Result<SortedDocIds, DocstoreError> {
+        self.spill_run()?;
+        let mut runs = self
+            .runs
+            .into_iter()
+            .map(BufReader::new)
+            .collect::<Vec<_>>();
+        let mut heap = BinaryHeap::with_capacity(runs.len());
+        for (index, run) in runs.iter_mut().enumerate() {
+            if let Some(doc_id) = read_spilled_doc_id(run)? {
+                heap.push(Reverse((doc_id, index)));
+            }
+        }
+        Ok(SortedDocIds { runs, heap })
+    }
+
+    fn spill_run(&mut self) -> Result<(), DocstoreError> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        self.pending.sort_unstable();
+        let mut run = BufWriter::new(tempfile::tempfile_in(&self.spill_dir)?);
+        for doc_id in self.pending.drain(..) {
+            run.write_all(&(doc_id.len() as u32).to_le_bytes())?;
+            run.write_all(doc_id.as_bytes())?;
+        }
+        let mut run = run
+            .into_inner()
+            .map_err(|error| DocstoreError::Io(error.error().to_string()))?;
+        run.seek(SeekFrom::Start(0))?;
+        self.runs.push(run);
+        self.pending_bytes = 0;
+        Ok(())
+    }
+}
+
+/// Ascending doc ids merged from the runs of a [`DocIdSpill`].
+pub struct SortedDocIds {
+    runs: Vec<BufReader<File>>,
+    heap: BinaryHeap<Reverse<(String, usize)>>,
+}
+
+impl Iterator for SortedDocIds {
+    type Item = Result<String, DocstoreError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((doc_id, index)) = self.heap.pop()?;
+        match read_spilled_doc_id(&mut self.runs[index]) {
+            Ok(Some(next)) => self.heap.push(Reverse((next, index))),
+            Ok(None) => {}
+            Err(error) => return Some(Err(error)),
+        }
+        Some(Ok(doc_id))
+    }
+}
+
+fn read_spilled_doc_id(run: &mut BufReader<File>) -> Result<Option<String>, DocstoreError> {
+    let mut length = [0u8; 4];
+    match run.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(error) if error.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error.into()),
+    }
+    let mut bytes = vec![0u8; u32::from_le_bytes(length) as usize];
+    run.read_exact(&mut bytes)?;
+    String::from_utf8(bytes)
+        .map(Some)
+        .map_err(|error| DocstoreError::Io(format!("doc id spill run is corrupt: {error}")))
+}
+
+struct HashingWriter<'a, W: Write> {
+    inner: &'a mut W,
+    hasher: Sha256,
+    written: u64,
+}
+
+impl<W: Write> Write for HashingWriter<'_, W> {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(bytes)?;
+        self.hasher.update(&bytes[..written]);
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn copy_spilled_range(
+    spill: &mut File,
+    (offset, length): (u64, u64),
+    out: &mut impl Write,
+) -> Result<(), DocstoreError> {
+    spill.seek(SeekFrom::Start(offset))?;
+    let copied = std::io::copy(&mut (&mut *spill).take(length), out)?;
+    if copied != length {
+        return Err(DocstoreError::Io(
+            "doc segment spill file ended early".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Seek, SeekFrom};
+
+    use serde_json::json;
+    use tempfile::tempdir;
+    use wax_v2_core::{create_empty_store, publish_segments};
+
+    use crate::{
+        prepare_raw_documents_segment, prepare_raw_documents_segment_with_compression,
+        DocCompression, DocIdSpill, StreamingDocSegmentBuilder,
+    };
+
+    #[test]
+    fn streaming_builder_matches_the_in_memory_doc_segment() {
+        let temp_dir = tempdir().unwrap();
+        let store_path = temp_dir.path().join("store.wax");
+        create_empty_store(&store_path).unwrap();
+        let document = |doc_id: &str, text: &str, timestamp_ms: u64| {
+            (
+                doc_id.to_owned(),
+                json!({
+                    "doc_id": doc_id,
+                    "text": text,
+                    "metadata": {"source": doc_id},
+                    "timestamp_ms": timestamp_ms,
+                }),
+            )
+        };
+        publish_segments(
+            &store_path,
+            vec![prepare_raw_documents_segment(
+                &store_path,
+                vec![
+                    document("doc-b", "beta", 20),
+                    document("doc-a", "alpha", 10),
+                ],
+            )
+            .unwrap()],
+        )
+        .unwrap();
+
+        // doc-a keeps its persisted wax id, so spill order and wax order differ.
+        let documents = vec![
+            document("doc-c", "gamma", 30),
+            document("doc-a", "alpha again", 15),
+            document("doc-d", "", 5),
+        ];
+        let expected = prepare_raw_documents_segment(&store_path, documents.clone()).unwrap();
+        let mut builder = StreamingDocSegmentBuilder::new(&store_path, temp_dir.path()).unwrap();
+        for (doc_id, document) in &documents {
+            builder.push_document(doc_id, document).unwrap();
+        }
+        assert_eq!(builder.len(), 3);
+        let (mut spilled, doc_id_map) = builder.finish().unwrap();
+        let mut object_bytes = Vec::new();
+        spilled.object.seek(SeekFrom::Start(0)).unwrap();
+        spilled.object.read_to_end(&mut object_bytes).unwrap();
+
+        assert_eq!(spilled.descriptor, expected.descriptor);
+        assert_eq!(object_bytes, expected.object_bytes);
+        assert_eq!(doc_id_map.wax_doc_id("doc-a"), Some(1));
+        assert_eq!(doc_id_map.wax_doc_id("doc-c"), Some(2));
+    }
+
+    #[test]
+    fn compressed_streaming_builder_matches_the_in_memory_doc_segment() {
+        let temp_dir = tempdir().unwrap();
+        let store_path = temp_dir.path().join("store.wax");
+        // Enough documents that each section spans several compression blocks.
+        let documents = (0..3_000)
+            .map(|index| {
+                let doc_id = format!("doc-{index:04}");
+                let document = json!({
+                    "doc_id": doc_id,
+                    "text": format!("streamed body {index}"),
+                    "metadata": {"index": index},
+                });
+                (doc_id, document)
+            })
+            .collect::<Vec<_>>();
+
+        for compression in [DocCompression::Lz4, DocCompression::Zstd] {
+            let expected = prepare_raw_documents_segment_with_compression(
+                &store_path,
+                documents.clone(),
+                compression,
+            )
+            .unwrap();
+            let mut builder = StreamingDocSegmentBuilder::new(&store_path, temp_dir.path())
+                .unwrap()
+                .with_compression(compression);
+            for (doc_id, document) in &documents {
+                builder.push_document(doc_id, document).unwrap();
+            }
+            let (mut spilled, _) = builder.finish().unwrap();
+            let mut object_bytes = Vec::new();
+            spilled.object.seek(SeekFrom::Start(0)).unwrap();
+            spilled.object.read_to_end(&mut object_bytes).unwrap();
+
+            assert_eq!(spilled.descriptor, expected.descriptor);
+            assert_eq!(object_bytes, expected.object_bytes);
+        }
+    }
+
+    #[test]
+    fn doc_id_spill_merges_runs_in_sorted_order_with_repeats() {
+        let temp_dir = tempdir().unwrap();
+        let mut spill = DocIdSpill::new(temp_dir.path()).with_run_bytes(64);
+        let doc_ids = (0..40)
+            .map(|index| format!("doc-{:03}", (index * 7) % 37))
+            .collect::<Vec<_>>();
+        for doc_id in &doc_ids {
+            spill.push(doc_id).unwrap();
+        }
+        assert_eq!(spill.len(), 40);
+        assert!(spill.spilled_runs() > 1);
+
+        let merged = spill
+            .into_sorted()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let mut expected = doc_ids;
+        expected.sort();
+        assert_eq!(merged, expected);
+    }
+}
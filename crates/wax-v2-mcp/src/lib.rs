@@ -2,7 +2,8 @@ use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 use wax_v2_broker::{
-    SessionNewDocument, SessionNewDocumentVector, SessionSearchRequest, WaxBroker,
+    SessionEmbeddingIdentity, SessionNewDocument, SessionNewDocumentVector, SessionSearchRequest,
+    WaxBroker,
 };
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -22,6 +23,13 @@ pub struct McpNewDocumentVector {
     pub values: Vec<f32>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpEmbeddingIdentity {
+    pub spec_id: String,
+    pub model_version: String,
+    pub model_hash: String,
+}
+
 fn empty_metadata_object() -> serde_json::Value {
     serde_json::Value::Object(serde_json::Map::new())
 }
@@ -47,6 +55,7 @@ pub enum McpRequest {
     },
     IngestVectors {
         session_id: u64,
+        embedding: McpEmbeddingIdentity,
         vectors: Vec<McpNewDocumentVector>,
     },
     CloseSession {
@@ -187,6 +196,7 @@ impl WaxMcpSurface {
                             wax_v2_broker::BrokerPublishFamily::Doc => "doc".to_owned(),
                             wax_v2_broker::BrokerPublishFamily::Text => "text".to_owned(),
                             wax_v2_broker::BrokerPublishFamily::Vector => "vector".to_owned(),
+                            wax_v2_broker::BrokerPublishFamily::Sparse => "sparse".to_owned(),
                         })
                         .collect(),
                 })
@@ -220,18 +230,25 @@ impl WaxMcpSurface {
                             wax_v2_broker::BrokerPublishFamily::Doc => "doc".to_owned(),
                             wax_v2_broker::BrokerPublishFamily::Text => "text".to_owned(),
                             wax_v2_broker::BrokerPublishFamily::Vector => "vector".to_owned(),
+                            wax_v2_broker::BrokerPublishFamily::Sparse => "sparse".to_owned(),
                         })
                         .collect(),
                 })
             }
             McpRequest::IngestVectors {
                 session_id,
+                embedding,
                 vectors,
             } => {
                 let report = self
                     .broker
                     .ingest_vectors(
                         wax_v2_broker::SessionId::from_u64(session_id),
+                        SessionEmbeddingIdentity {
+                            spec_id: embedding.spec_id,
+                            model_version: embedding.model_version,
+                            model_hash: embedding.model_hash,
+                        },
                         vectors
                             .into_iter()
                             .map(|vector| SessionNewDocumentVector {
@@ -250,6 +267,7 @@ impl WaxMcpSurface {
                             wax_v2_broker::BrokerPublishFamily::Doc => "doc".to_owned(),
                             wax_v2_broker::BrokerPublishFamily::Text => "text".to_owned(),
                             wax_v2_broker::BrokerPublishFamily::Vector => "vector".to_owned(),
+                            wax_v2_broker::BrokerPublishFamily::Sparse => "sparse".to_owned(),
                         })
                         .collect(),
                 })
@@ -0,0 +1,374 @@
+use std::path::Path;
+
+use wax_v2_docstore::DocIdMap;
+
+use crate::{
+    family_segment_offsets_from_store, vector_inputs_sorted_by_wax_doc_id, NewDocumentVector,
+    RuntimeError, RuntimePublishFamily,
+};
+
+/// Vector and sparse segments that carry the current rows across a document publish.
+pub(crate) struct CarriedRowSegments {
+    pub(crate) pending: Vec<wax_v2_core::PendingSegmentWrite>,
+    /// Object offsets of the current segments the publish replaces or drops.
+    superseded_offsets: std::collections::HashSet<u64>,
+}
+
+impl CarriedRowSegments {
+    /// Whether a document publish keeps the active `segment`: doc and text segments are always
+    /// replaced, and vector and sparse segments unless the carried segments supersede them.
+    pub(crate) fn retains(&self, segment: &wax_v2_core::SegmentDescriptor) -> bool {
+        match segment.family {
+            wax_v2_core::SegmentKind::Doc | wax_v2_core::SegmentKind::Txt => false,
+            wax_v2_core::SegmentKind::Vec | wax_v2_core::SegmentKind::Spr => {
+                !self.superseded_offsets.contains(&segment.object_offset)
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Carries the vector spaces and the sparse segment across a document publish (see
+/// [`carried_vector_segments`]) and records the families the carried segments publish.
+pub(crate) fn carried_row_segments(
+    store_path: &Path,
+    is_carried: impl Fn(&str) -> bool,
+    doc_id_map: &DocIdMap,
+    replaced_space: Option<&str>,
+    published_families: &mut Vec<RuntimePublishFamily>,
+) -> Result<CarriedRowSegments, RuntimeError> {
+    let mut carried = carried_vector_segments(store_path, &is_carried, doc_id_map, replaced_space)?;
+    carry_sparse_segment(store_path, &is_carried, doc_id_map, &mut carried)?;
+    for segment in &carried.pending {
+        let family = match segment.descriptor.family {
+            wax_v2_core::SegmentKind::Spr => RuntimePublishFamily::Sparse,
+            _ => RuntimePublishFamily::Vector,
+        };
+        if !published_families.contains(&family) {
+            published_families.push(family);
+        }
+    }
+    Ok(carried)
+}
+
+/// Rebuilds every vector space except `replaced_space` over its rows for the documents a publish
+/// leaves unchanged, those accepted by `is_carried`; other documents have no vector in the new generation.
+/// A space left without rows is dropped, and a space that is already stale is kept as it is.
+fn carried_vector_segments(
+    store_path: &Path,
+    is_carried: impl Fn(&str) -> bool,
+    doc_id_map: &DocIdMap,
+    replaced_space: Option<&str>,
+) -> Result<CarriedRowSegments, RuntimeError> {
+    let mut carried = CarriedRowSegments {
+        pending: Vec::new(),
+        superseded_offsets: std::collections::HashSet::new(),
+    };
+    for current in wax_v2_vector::store_vector_spaces(store_path).map_err(RuntimeError::Storage)? {
+        let name = current.spec.name.as_str();
+        if replaced_space == Some(name) {
+            carried
+                .superseded_offsets
+                .insert(current.descriptor.object_offset);
+            continue;
+        }
+        let pending = if current.multi_vector {
+            let Some(rows) = wax_v2_vector::load_current_store_multi_vector_rows(store_path, name)
+                .map_err(RuntimeError::Storage)?
+            else {
+                continue;
+            };
+            let mut vector_inputs = rows
+                .rows
+                .into_iter()
+                .filter(|(doc_id, _)| is_carried(doc_id))
+                .map(|(doc_id, vectors)| {
+                    let wax_doc_id = doc_id_map.wax_doc_id(&doc_id).ok_or_else(|| {
+                        RuntimeError::Storage(format!("missing wax doc id binding for {doc_id}"))
+                    })?;
+                    Ok((wax_doc_id, doc_id, vectors))
+                })
+                .collect::<Result<Vec<_>, RuntimeError>>()?;
+            vector_inputs.sort_by_key(|(wax_doc_id, _, _)| *wax_doc_id);
+            (!vector_inputs.is_empty())
+                .then(|| {
+                    wax_v2_vector::prepare_raw_multi_vector_segment(&rows.spec, &vector_inputs)
+                        .map_err(RuntimeError::Storage)
+                })
+                .transpose()?
+        } else {
+            let Some(rows) = wax_v2_vector::load_current_store_vector_rows(store_path, name)
+                .map_err(RuntimeError::Storage)?
+            else {
+                continue;
+            };
+            let vectors = rows
+                .rows
+                .into_iter()
+                .filter(|(doc_id, _)| is_carried(doc_id))
+                .map(|(doc_id, values)| NewDocumentVector::new(doc_id, values))
+                .collect::<Vec<_>>();
+            let (doc_id_start, doc_id_end_exclusive, vector_inputs) =
+                vector_inputs_sorted_by_wax_doc_id(vectors, doc_id_map)?;
+            (!vector_inputs.is_empty())
+                .then(|| {
+                    let mut pending = wax_v2_vector::prepare_raw_vector_segment_for_space(
+                        &rows.spec,
+                        &vector_inputs,
+                    )
+                    .map_err(RuntimeError::Storage)?;
+                    pending.descriptor.doc_id_start = doc_id_start;
+                    pending.descriptor.doc_id_end_exclusive = doc_id_end_exclusive;
+                    Ok(pending)
+                })
+                .transpose()?
+        };
+        carried
+            .superseded_offsets
+            .insert(current.descriptor.object_offset);
+        carried.pending.extend(pending);
+    }
+    Ok(carried)
+}
+
+/// Sparse counterpart of [`carried_vector_segments`]: rebuilds the sparse segment over its rows
+/// for the carried documents into `carried`, dropping it when no row survives.
+fn carry_sparse_segment(
+    store_path: &Path,
+    is_carried: impl Fn(&str) -> bool,
+    doc_id_map: &DocIdMap,
+    carried: &mut CarriedRowSegments,
+) -> Result<(), RuntimeError> {
+    let Some(rows) =
+        wax_v2_sparse::load_current_store_sparse_rows(store_path).map_err(RuntimeError::Storage)?
+    else {
+        return Ok(());
+    };
+    let mut rows = rows
+        .into_iter()
+        .filter(|(doc_id, _)| is_carried(doc_id))
+        .map(|(doc_id, terms)| {
+            let wax_doc_id = doc_id_map.wax_doc_id(&doc_id).ok_or_else(|| {
+                RuntimeError::Storage(format!("missing wax doc id binding for {doc_id}"))
+            })?;
+            Ok((wax_doc_id, doc_id, terms))
+        })
+        .collect::<Result<Vec<_>, RuntimeError>>()?;
+    carried
+        .superseded_offsets
+        .extend(family_segment_offsets_from_store(
+            store_path,
+            wax_v2_core::SegmentKind::Spr,
+        )?);
+    if rows.is_empty() {
+        return Ok(());
+    }
+    rows.sort_by_key(|(wax_doc_id, _, _)| *wax_doc_id);
+    let doc_id_start = rows.first().map_or(0, |(wax_doc_id, _, _)| *wax_doc_id);
+    let doc_id_end_exclusive = rows.last().map_or(0, |(wax_doc_id, _, _)| wax_doc_id + 1);
+    let rows = rows
+        .into_iter()
+        .map(|(_, doc_id, terms)| (doc_id, terms))
+        .collect::<Vec<_>>();
+    let mut pending =
+        wax_v2_sparse::prepare_raw_sparse_segment(&rows).map_err(RuntimeError::Storage)?;
+    pending.descriptor.doc_id_start = doc_id_start;
+    pending.descriptor.doc_id_end_exclusive = doc_id_end_exclusive;
+    carried.pending.push(pending);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+    use wax_bench_model::embed_text;
+    use wax_bench_packer::{pack_adhoc_dataset, AdhocPackRequest};
+
+    use crate::tests::{manifest_embedding, test_embedding};
+    use crate::{
+        NewDocument, NewDocumentVector, RuntimeSearchMode, RuntimeSearchRequest, RuntimeStore,
+        RuntimeVectorMetric, RuntimeVectorSpace, RuntimeVectorSpaceQuery,
+    };
+
+    #[test]
+    fn publish_raw_snapshot_with_vectors_keeps_named_spaces_for_unchanged_documents() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(&docs_path, "{\"doc_id\":\"seed-001\",\"text\":\"seed\"}\n").unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_snapshot(
+                vec![
+                    NewDocument::new("doc-001", "alpha"),
+                    NewDocument::new("doc-002", "beta"),
+                ],
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    vec![
+                        NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
+                        NewDocumentVector::new("doc-002", embed_text("beta", 384)),
+                    ],
+                )),
+            )
+            .unwrap();
+        let images = RuntimeVectorSpace::new("images", 2, RuntimeVectorMetric::L2)
+            .with_embedding(test_embedding("images"));
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_vectors_to_space(
+                images.clone(),
+                vec![
+                    NewDocumentVector::new("doc-001", vec![0.0, 0.0]),
+                    NewDocumentVector::new("doc-002", vec![1.0, 1.0]),
+                ],
+            )
+            .unwrap();
+
+        // The snapshot drops doc-002 and adds doc-003, replacing only the default space.
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_snapshot(
+                vec![
+                    NewDocument::new("doc-001", "alpha"),
+                    NewDocument::new("doc-003", "gamma"),
+                ],
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    vec![
+                        NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
+                        NewDocumentVector::new("doc-003", embed_text("gamma", 384)),
+                    ],
+                )),
+            )
+            .unwrap();
+
+        let spaces = runtime.vector_spaces().unwrap();
+        assert_eq!(spaces.len(), 2);
+        assert_eq!(spaces[1], images);
+        let image_hits = runtime
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Vector,
+                top_k: 3,
+                vector_space_queries: vec![RuntimeVectorSpaceQuery::new("images", vec![1.0, 1.0])
+                    .with_embedding(test_embedding("images"))],
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(
+            image_hits
+                .hits
+                .iter()
+                .map(|hit| hit.doc_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["doc-001"]
+        );
+    }
+
+    #[test]
+    fn vector_less_document_upserts_keep_every_space_for_unchanged_documents() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(&docs_path, "{\"doc_id\":\"seed-001\",\"text\":\"seed\"}\n").unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_snapshot(
+                vec![
+                    NewDocument::new("doc-001", "alpha"),
+                    NewDocument::new("doc-002", "beta"),
+                ],
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    vec![
+                        NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
+                        NewDocumentVector::new("doc-002", embed_text("beta", 384)),
+                    ],
+                )),
+            )
+            .unwrap();
+        let images = RuntimeVectorSpace::new("images", 2, RuntimeVectorMetric::L2)
+            .with_embedding(test_embedding("images"));
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_vectors_to_space(
+                images.clone(),
+                vec![
+                    NewDocumentVector::new("doc-001", vec![0.0, 0.0]),
+                    NewDocumentVector::new("doc-002", vec![1.0, 1.0]),
+                ],
+            )
+            .unwrap();
+
+        // doc-002 changes and doc-003 is new, so neither has a vector afterwards.
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![
+                NewDocument::new("doc-002", "beta revised"),
+                NewDocument::new("doc-003", "gamma"),
+            ])
+            .unwrap();
+
+        let spaces = runtime.vector_spaces().unwrap();
+        assert_eq!(spaces.len(), 2);
+        assert_eq!(spaces[1], images);
+        let default_hits = runtime
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Vector,
+                vector_query: Some(embed_text("alpha", 384)),
+                top_k: 3,
+                vector_embedding: Some(runtime.embedding_identity()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(
+            default_hits
+                .hits
+                .iter()
+                .map(|hit| hit.doc_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["doc-001"]
+        );
+        let image_hits = runtime
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Vector,
+                top_k: 3,
+                vector_space_queries: vec![RuntimeVectorSpaceQuery::new("images", vec![1.0, 1.0])
+                    .with_embedding(test_embedding("images"))],
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(
+            image_hits
+                .hits
+                .iter()
+                .map(|hit| hit.doc_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["doc-001"]
+        );
+    }
+}
@@ -0,0 +1,856 @@
+use std::collections::HashSet;
+use std::sync::PoisonError;
+
+use crate::{
+    fuses_hit_lists, without_scores, RuntimeEmbeddingIdentity, RuntimeError, RuntimeSearchMode,
+    RuntimeSearchRequest, RuntimeSearchResponse, RuntimeStore, RuntimeVectorRadius,
+};
+
+impl RuntimeStore {
+    /// Serves the follow-up page `cursor` points at, once it checks out against the request, at
+    /// the cursor's generation: from this handle while it is on that generation, otherwise from
+    /// a read-only handle opened at it. Segment objects are append-only, so older generations
+    /// stay readable after later publishes.
+    pub(crate) fn search_cursor_page(
+        &self,
+        request: RuntimeSearchRequest,
+        cursor: &str,
+    ) -> Result<RuntimeSearchResponse, RuntimeError> {
+        let cursor = &SearchCursor::decode(cursor)?;
+        if cursor.fingerprint != search_fingerprint(&request) {
+            return Err(RuntimeError::InvalidRequest(
+                "cursor was issued for a different search request".to_owned(),
+            ));
+        }
+        if cursor.generation == self.store_generation {
+            return self.search_page(&request, cursor);
+        }
+        let Some(generation) = cursor.generation else {
+            return Err(RuntimeError::InvalidRequest(
+                "cursor was issued before the store was created".to_owned(),
+            ));
+        };
+        let cached = self
+            .cursor_store
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        let store = match cached {
+            Some(store) if store.store_generation == Some(generation) => store,
+            _ => Box::new(self.open_pinned_generation(generation)?),
+        };
+        let response = store.search_page(&request, cursor);
+        *self
+            .cursor_store
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(store);
+        response
+    }
+
+    /// Keeps the hits that rank strictly after the cursor's last hit by (score, doc_id). Fused
+    /// searches walk the lanes tier by tier, see [`Self::fused_search_page`]; single-lane searches
+    /// widen the lane budget until a full page follows the cursor or the lane runs dry.
+    fn search_page(
+        &self,
+        request: &RuntimeSearchRequest,
+        cursor: &SearchCursor,
+    ) -> Result<RuntimeSearchResponse, RuntimeError> {
+        if fuses_hit_lists(request) {
+            return self.fused_search_page(request, cursor);
+        }
+        let top_k = request.top_k;
+        let live_doc_count = self.live_doc_count()?;
+        let mut ranked_request = RuntimeSearchRequest {
+            top_k: top_k.saturating_mul(2),
+            ..request.clone()
+        };
+        loop {
+            let ranked = self.ranked_hits(&ranked_request)?;
+            let exhausted =
+                ranked.len() < ranked_request.top_k || ranked_request.top_k >= live_doc_count;
+            let page = ranked
+                .into_iter()
+                .filter(|(doc_id, score)| cursor.ranks_before(doc_id, *score))
+                .take(top_k)
+                .collect::<Vec<_>>();
+            if page.len() == top_k || exhausted {
+                return self.page_response(
+                    page,
+                    request,
+                    cursor.fingerprint,
+                    cursor.fusion_depth,
+                    cursor.served_depth,
+                );
+            }
+            ranked_request.top_k = ranked_request.top_k.saturating_mul(2);
+        }
+    }
+
+    /// Fused scores depend on how deep each lane runs, so fused pages walk the lanes in tiers:
+    /// a tier holds the hits the lanes give at its fusion depth that the previous tier's depth
+    /// did not, ranked at that depth. Once a tier runs out the next one fuses twice as deep, until
+    /// the lanes run dry. Every tier keeps one ranking, so pages neither repeat nor skip hits.
+    fn fused_search_page(
+        &self,
+        request: &RuntimeSearchRequest,
+        cursor: &SearchCursor,
+    ) -> Result<RuntimeSearchResponse, RuntimeError> {
+        let top_k = request.top_k;
+        let live_doc_count = self.live_doc_count()?;
+        let unbounded = RuntimeSearchRequest {
+            top_k: usize::MAX,
+            ..request.clone()
+        };
+        let mut served = match cursor.served_depth {
+            0 => HashSet::new(),
+            depth => without_scores(self.ranked_hits_matching(&unbounded, depth, None)?)
+                .into_iter()
+                .collect(),
+        };
+        let (mut fusion_depth, mut served_depth) = (cursor.fusion_depth, cursor.served_depth);
+        let mut after = Some(cursor);
+        let mut page = Vec::new();
+        loop {
+            let ranked = self.ranked_hits_matching(&unbounded, fusion_depth, None)?;
+            let exhausted = ranked.len() < fusion_depth || fusion_depth >= live_doc_count;
+            page.extend(
+                ranked
+                    .iter()
+                    .filter(|(doc_id, score)| {
+                        !served.contains(doc_id)
+                            && after.is_none_or(|cursor| cursor.ranks_before(doc_id, *score))
+                    })
+                    .take(top_k - page.len())
+                    .cloned(),
+            );
+            if page.len() == top_k || exhausted {
+                return self.page_response(
+                    page,
+                    request,
+                    cursor.fingerprint,
+                    fusion_depth,
+                    served_depth,
+                );
+            }
+            served = without_scores(ranked).into_iter().collect();
+            served_depth = fusion_depth;
+            fusion_depth = fusion_depth.saturating_mul(2);
+            after = None;
+        }
+    }
+
+    /// Hydrates one page of a plain search and issues a cursor after its last hit when the page
+    /// came back full.
+    pub(crate) fn page_response(
+        &self,
+        ranked: Vec<(String, f64)>,
+        request: &RuntimeSearchRequest,
+        fingerprint: u64,
+        fusion_depth: usize,
+        served_depth: usize,
+    ) -> Result<RuntimeSearchResponse, RuntimeError> {
+        let next_cursor = match ranked.last() {
+            Some((last_doc_id, last_score)) if ranked.len() == request.top_k => Some(
+                SearchCursor {
+                    generation: self.store_generation,
+                    fingerprint,
+                    fusion_depth,
+                    served_depth,
+                    last_score: *last_score,
+                    last_doc_id: last_doc_id.clone(),
+                }
+                .encode(),
+            ),
+            _ => None,
+        };
+        let doc_ids = ranked
+            .into_iter()
+            .map(|(doc_id, _)| doc_id)
+            .collect::<Vec<_>>();
+        Ok(RuntimeSearchResponse {
+            hits: self.hydrate_hits(&doc_ids, request.include_preview)?,
+            groups: Vec::new(),
+            facets: Vec::new(),
+            next_cursor,
+        })
+    }
+}
+
+/// Decoded form of the opaque search cursor: the generation the ranking was pinned at, a
+/// fingerprint of the query, and the score and doc_id of the last hit returned.
+#[derive(Debug, Clone, PartialEq)]
+struct SearchCursor {
+    generation: Option<u64>,
+    fingerprint: u64,
+    /// Lane depth the last hit's tier fused at; see [`RuntimeStore::fused_search_page`].
+    fusion_depth: usize,
+    /// Depth of the tier before it, whose hits were served already; 0 on the first tier.
+    served_depth: usize,
+    last_score: f64,
+    last_doc_id: String,
+}
+
+impl SearchCursor {
+    const VERSION: &'static str = "c4";
+
+    /// Whether a hit ranks strictly after the last hit returned: a lower score, or the same
+    /// score and a later doc_id.
+    fn ranks_before(&self, doc_id: &str, score: f64) -> bool {
+        score
+            .total_cmp(&self.last_score)
+            .then_with(|| self.last_doc_id.as_str().cmp(doc_id))
+            .is_lt()
+    }
+
+    fn encode(&self) -> String {
+        let plain = format!(
+            "{}:{}:{:016x}:{}:{}:{:016x}:{}",
+            Self::VERSION,
+            describe_generation(self.generation),
+            self.fingerprint,
+            self.fusion_depth,
+            self.served_depth,
+            self.last_score.to_bits(),
+            self.last_doc_id
+        );
+        plain.bytes().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    fn decode(cursor: &str) -> Result<Self, RuntimeError> {
+        let invalid = || RuntimeError::InvalidRequest("search cursor is malformed".to_owned());
+        if !cursor.len().is_multiple_of(2) || !cursor.is_ascii() {
+            return Err(invalid());
+        }
+        let bytes = (0..cursor.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&cursor[index..index + 2], 16))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid())?;
+        let plain = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let mut parts = plain.splitn(7, ':');
+        if parts.next() != Some(Self::VERSION) {
+            return Err(invalid());
+        }
+        let generation = match parts.next().ok_or_else(invalid)? {
+            "none" => None,
+            generation => Some(generation.parse().map_err(|_| invalid())?),
+        };
+        let fingerprint =
+            u64::from_str_radix(parts.next().ok_or_else(invalid)?, 16).map_err(|_| invalid())?;
+        let fusion_depth = parts
+            .next()
+            .ok_or_else(invalid)?
+            .parse()
+            .map_err(|_| invalid())?;
+        let served_depth = parts
+            .next()
+            .ok_or_else(invalid)?
+            .parse()
+            .map_err(|_| invalid())?;
+        let last_score = f64::from_bits(
+            u64::from_str_radix(parts.next().ok_or_else(invalid)?, 16).map_err(|_| invalid())?,
+        );
+        let last_doc_id = parts.next().ok_or_else(invalid)?.to_owned();
+        Ok(Self {
+            generation,
+            fingerprint,
+            fusion_depth,
+            served_depth,
+            last_score,
+            last_doc_id,
+        })
+    }
+}
+
+fn describe_generation(generation: Option<u64>) -> String {
+    generation.map_or_else(|| "none".to_owned(), |generation| generation.to_string())
+}
+
+/// FNV-1a over the parts of the request that decide the ranking; page size, previews, facets and
+/// the cursor itself may change between pages. Fields are written in a fixed order as explicit
+/// bytes so issued cursors survive changes to the request's `Debug` output.
+pub(crate) fn search_fingerprint(request: &RuntimeSearchRequest) -> u64 {
+    let RuntimeSearchRequest {
+        mode,
+        text_query,
+        vector_query,
+        top_k: _,
+        include_preview: _,
+        vector_embedding,
+        vector_radius,
+        mmr_lambda,
+        collapse_by,
+        facets: _,
+        cursor: _,
+        sparse_query,
+        vector_space_queries,
+    } = request;
+    let mut hash = SearchFingerprint::default();
+    hash.write_u8(match mode {
+        RuntimeSearchMode::Text => 0,
+        RuntimeSearchMode::Vector => 1,
+        RuntimeSearchMode::Hybrid => 2,
+        RuntimeSearchMode::Sparse => 3,
+    });
+    hash.write_option(text_query.as_deref(), SearchFingerprint::write_str);
+    hash.write_option(vector_query.as_deref(), SearchFingerprint::write_f32s);
+    hash.write_option(
+        vector_embedding.as_ref(),
+        SearchFingerprint::write_embedding,
+    );
+    hash.write_option(vector_radius.as_ref(), |hash, radius| match radius {
+        RuntimeVectorRadius::MinSimilarity(similarity) => {
+            hash.write_u8(0);
+            hash.write_f32(*similarity);
+        }
+        RuntimeVectorRadius::MaxDistance(distance) => {
+            hash.write_u8(1);
+            hash.write_f32(*distance);
+        }
+    });
+    hash.write_option(*mmr_lambda, SearchFingerprint::write_f32);
+    hash.write_option(collapse_by.as_ref(), |hash, collapse| {
+        hash.write_str(&collapse.field);
+        hash.write_len(collapse.max_hits_per_group);
+    });
+    hash.write_option(sparse_query.as_deref(), |hash, terms| {
+        hash.write_len(terms.len());
+        for (term_id, weight) in terms {
+            hash.write_bytes(&term_id.to_le_bytes());
+            hash.write_f32(*weight);
+        }
+    });
+    hash.write_len(vector_space_queries.len());
+    for query in vector_space_queries {
+        hash.write_str(&query.space);
+        hash.write_len(query.vectors.len());
+        for vector in &query.vectors {
+            hash.write_f32s(vector);
+        }
+        hash.write_option(query.embedding.as_ref(), SearchFingerprint::write_embedding);
+    }
+    hash.0
+}
+
+/// FNV-1a state behind [`search_fingerprint`]. Variable-length values are written after their
+/// length and optional values after a presence byte, so adjacent fields cannot run together.
+struct SearchFingerprint(u64);
+
+impl Default for SearchFingerprint {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl SearchFingerprint {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn write_u8(&mut self, value: u8) {
+        self.write_bytes(&[value]);
+    }
+
+    fn write_len(&mut self, len: usize) {
+        self.write_bytes(&(len as u64).to_le_bytes());
+    }
+
+    fn write_str(&mut self, value: &str) {
+        self.write_len(value.len());
+        self.write_bytes(value.as_bytes());
+    }
+
+    fn write_f32(&mut self, value: f32) {
+        self.write_bytes(&value.to_bits().to_le_bytes());
+    }
+
+    fn write_f32s(&mut self, values: &[f32]) {
+        self.write_len(values.len());
+        for value in values {
+            self.write_f32(*value);
+        }
+    }
+
+    fn write_embedding(&mut self, embedding: &RuntimeEmbeddingIdentity) {
+        self.write_str(&embedding.spec_id);
+        self.write_str(&embedding.model_version);
+        self.write_str(&embedding.model_hash);
+    }
+
+    fn write_option<T>(&mut self, value: Option<T>, write: impl FnOnce(&mut Self, T)) {
+        match value {
+            Some(value) => {
+                self.write_u8(1);
+                write(self, value);
+            }
+            None => self.write_u8(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+    use wax_bench_packer::{pack_adhoc_dataset, AdhocPackRequest};
+
+    use super::search_fingerprint;
+    use crate::tests::{manifest_embedding, test_embedding};
+    use crate::{
+        read_manifest, FeatureHashEmbedder, NewDocument, NewDocumentVector, RuntimeSearchMode,
+        RuntimeSearchRequest, RuntimeSearchResponse, RuntimeStore, RuntimeVectorMetric,
+        RuntimeVectorSpace, RuntimeVectorSpaceQuery, FUSION_MIN_LANE_DEPTH,
+    };
+
+    #[test]
+    fn search_cursor_pages_stay_on_the_pinned_generation_across_publishes() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{}}\n",
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(
+                (1..=7)
+                    .map(|index| NewDocument::new(format!("doc-{index}"), "lantern"))
+                    .collect(),
+            )
+            .unwrap();
+        let request = |top_k, cursor| RuntimeSearchRequest {
+            mode: RuntimeSearchMode::Text,
+            text_query: Some("lantern".to_owned()),
+            top_k,
+            cursor,
+            ..Default::default()
+        };
+        let doc_ids = |response: &RuntimeSearchResponse| {
+            response
+                .hits
+                .iter()
+                .map(|hit| hit.doc_id.clone())
+                .collect::<Vec<_>>()
+        };
+        let everything = doc_ids(&runtime.search(request(10, None)).unwrap());
+        assert_eq!(everything.len(), 7);
+
+        let first = runtime.search(request(3, None)).unwrap();
+        assert_eq!(doc_ids(&first), everything[..3]);
+        let cursor = first.next_cursor.clone().unwrap();
+
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![NewDocument::new("doc-45", "lantern")])
+            .unwrap();
+        // Ties rank by doc_id, so the new document lands inside the second page.
+        assert_eq!(
+            doc_ids(&runtime.search(request(10, None)).unwrap())[3..6],
+            ["doc-4", "doc-45", "doc-5"]
+        );
+
+        let second = runtime.search(request(3, Some(cursor.clone()))).unwrap();
+        assert_eq!(doc_ids(&second), everything[3..6]);
+        let third = runtime
+            .search(request(3, second.next_cursor.clone()))
+            .unwrap();
+        assert_eq!(doc_ids(&third), everything[6..]);
+        assert_eq!(third.next_cursor, None);
+
+        let mut changed_query = request(3, Some(cursor.clone()));
+        changed_query.text_query = Some("harbor".to_owned());
+        let error = runtime.search(changed_query).unwrap_err();
+        assert!(
+            matches!(error, crate::RuntimeError::InvalidRequest(message) if message.contains("different search request"))
+        );
+        let error = runtime
+            .search(request(3, Some("not-a-cursor".to_owned())))
+            .unwrap_err();
+        assert!(
+            matches!(error, crate::RuntimeError::InvalidRequest(message) if message.contains("malformed"))
+        );
+
+        // A fresh handle serves the old cursor from its generation, before and after issuing a
+        // cursor at the newer one, and follow-up pages skip facets.
+        let mut reopened = RuntimeStore::open(dataset_dir.path()).unwrap();
+        let mut faceted = request(3, Some(cursor.clone()));
+        faceted.facets = vec!["topic".to_owned()];
+        let second = reopened.search(faceted).unwrap();
+        assert_eq!(doc_ids(&second), everything[3..6]);
+        assert!(second.facets.is_empty());
+        let newer = reopened.search(request(3, None)).unwrap();
+        assert_eq!(doc_ids(&newer), everything[..3]);
+        assert_eq!(
+            doc_ids(&reopened.search(request(3, Some(cursor))).unwrap()),
+            everything[3..6]
+        );
+        assert_eq!(
+            doc_ids(&reopened.search(request(3, newer.next_cursor)).unwrap()),
+            ["doc-4", "doc-45", "doc-5"]
+        );
+    }
+
+    #[test]
+    fn search_fingerprint_hashes_ranking_fields_as_explicit_bytes() {
+        let request = RuntimeSearchRequest {
+            mode: RuntimeSearchMode::Hybrid,
+            text_query: Some("lantern".to_owned()),
+            vector_query: Some(vec![0.5, -0.0]),
+            vector_embedding: Some(test_embedding("default")),
+            sparse_query: Some(vec![(7, 1.5)]),
+            vector_space_queries: vec![RuntimeVectorSpaceQuery::new("images", vec![1.0])],
+            ..Default::default()
+        };
+        // Pinned so a change to the byte layout, which would strand issued cursors, is deliberate.
+        assert_eq!(search_fingerprint(&request), 0xd88f_37ba_65f5_4572);
+
+        let paging = RuntimeSearchRequest {
+            top_k: 3,
+            include_preview: true,
+            facets: vec!["kind".to_owned()],
+            cursor: Some("next".to_owned()),
+            ..request.clone()
+        };
+        assert_eq!(search_fingerprint(&paging), search_fingerprint(&request));
+
+        let signed_zero = RuntimeSearchRequest {
+            vector_query: Some(vec![0.5, 0.0]),
+            ..request.clone()
+        };
+        assert_ne!(
+            search_fingerprint(&signed_zero),
+            search_fingerprint(&request)
+        );
+        // Length prefixes keep bytes from moving between adjacent fields.
+        let shifted = RuntimeSearchRequest {
+            text_query: Some("lanter".to_owned()),
+            vector_space_queries: vec![RuntimeVectorSpaceQuery::new("nimages", vec![1.0])],
+            ..request.clone()
+        };
+        assert_ne!(search_fingerprint(&shifted), search_fingerprint(&request));
+    }
+
+    #[test]
+    fn search_cursor_pages_vector_hits_by_score_and_doc_id() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{}}\n",
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+        let dimensions = read_manifest(dataset_dir.path())
+            .unwrap()
+            .vector_profile
+            .embedding_dimensions as usize;
+        let mut runtime = RuntimeStore::create(dataset_dir.path())
+            .unwrap()
+            .with_embedder(Arc::new(FeatureHashEmbedder::new(dimensions)));
+        // Documents sharing a text embed identically, so every score is tied several ways.
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(
+                (0..30)
+                    .map(|index| {
+                        NewDocument::new(
+                            format!("doc-{index:03}"),
+                            format!("term{} term{}", index % 4, index % 3),
+                        )
+                    })
+                    .collect(),
+            )
+            .unwrap();
+        let request = |top_k, cursor| RuntimeSearchRequest {
+            mode: RuntimeSearchMode::Vector,
+            text_query: Some("term1 term2".to_owned()),
+            top_k,
+            cursor,
+            ..Default::default()
+        };
+        let everything = runtime
+            .search(request(30, None))
+            .unwrap()
+            .hits
+            .into_iter()
+            .map(|hit| hit.doc_id)
+            .collect::<Vec<_>>();
+
+        let mut paged = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = runtime.search(request(4, cursor)).unwrap();
+            paged.extend(page.hits.into_iter().map(|hit| hit.doc_id));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(paged, everything);
+    }
+
+    #[test]
+    fn search_cursor_pages_of_fused_searches_concatenate_to_one_large_page() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{}}\n",
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(
+                (0..8)
+                    .map(|index| {
+                        let text = if index % 2 == 0 { "lantern" } else { "harbor" };
+                        NewDocument::new(format!("doc-{index}"), text)
+                    })
+                    .collect(),
+            )
+            .unwrap();
+        // Each lane orders the documents differently, so how deep the lanes run decides which
+        // documents collect fused score from more than one lane.
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_vectors(
+                manifest_embedding(dataset_dir.path()),
+                (0..8)
+                    .map(|index| {
+                        let mut vector = vec![0.0; 384];
+                        let angle = index as f32 * 0.4;
+                        (vector[0], vector[1]) = (angle.cos(), angle.sin());
+                        NewDocumentVector::new(format!("doc-{index}"), vector)
+                    })
+                    .collect(),
+            )
+            .unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_vectors_to_space(
+                RuntimeVectorSpace::new("images", 2, RuntimeVectorMetric::L2)
+                    .with_embedding(test_embedding("images")),
+                (0..8)
+                    .map(|index| {
+                        NewDocumentVector::new(
+                            format!("doc-{index}"),
+                            vec![((index * 5) % 8) as f32, 0.0],
+                        )
+                    })
+                    .collect(),
+            )
+            .unwrap();
+        let images = RuntimeVectorSpaceQuery::new("images", vec![0.0, 0.0])
+            .with_embedding(test_embedding("images"));
+        let hybrid = RuntimeSearchRequest {
+            mode: RuntimeSearchMode::Hybrid,
+            text_query: Some("lantern".to_owned()),
+            vector_space_queries: vec![images.clone()],
+            ..Default::default()
+        };
+        let mut default_query = vec![0.0; 384];
+        default_query[0] = 1.0;
+        let multi_space = RuntimeSearchRequest {
+            mode: RuntimeSearchMode::Vector,
+            vector_query: Some(default_query),
+            vector_embedding: Some(runtime.embedding_identity()),
+            vector_space_queries: vec![images],
+            ..Default::default()
+        };
+
+        for base in [hybrid, multi_space] {
+            let everything = runtime
+                .search(RuntimeSearchRequest {
+                    top_k: 8,
+                    ..base.clone()
+                })
+                .unwrap()
+                .hits
+                .into_iter()
+                .map(|hit| hit.doc_id)
+                .collect::<Vec<_>>();
+            for page_size in [1, 3] {
+                let mut paged = Vec::new();
+                let mut cursor = None;
+                loop {
+                    let page = runtime
+                        .search(RuntimeSearchRequest {
+                            top_k: page_size,
+                            cursor,
+                            ..base.clone()
+                        })
+                        .unwrap();
+                    paged.extend(page.hits.into_iter().map(|hit| hit.doc_id));
+                    cursor = page.next_cursor;
+                    if cursor.is_none() {
+                        break;
+                    }
+                }
+                assert_eq!(paged, everything, "{:?} pages of {page_size}", base.mode);
+            }
+        }
+    }
+
+    #[test]
+    fn search_cursor_pages_of_fused_searches_deepen_the_lanes_until_they_run_dry() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{}}\n",
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+        let doc_count = 5 * FUSION_MIN_LANE_DEPTH;
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(
+                (0..doc_count)
+                    .map(|index| {
+                        let text = if index % 2 == 0 { "lantern" } else { "harbor" };
+                        NewDocument::new(format!("doc-{index:02}"), text)
+                    })
+                    .collect(),
+            )
+            .unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_vectors(
+                manifest_embedding(dataset_dir.path()),
+                (0..doc_count)
+                    .map(|index| {
+                        let mut vector = vec![0.0; 384];
+                        let angle = index as f32 * 0.05;
+                        (vector[0], vector[1]) = (angle.cos(), angle.sin());
+                        NewDocumentVector::new(format!("doc-{index:02}"), vector)
+                    })
+                    .collect(),
+            )
+            .unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_vectors_to_space(
+                RuntimeVectorSpace::new("images", 2, RuntimeVectorMetric::L2)
+                    .with_embedding(test_embedding("images")),
+                (0..doc_count)
+                    .map(|index| {
+                        NewDocumentVector::new(
+                            format!("doc-{index:02}"),
+                            vec![((index * 7) % doc_count) as f32, 0.0],
+                        )
+                    })
+                    .collect(),
+            )
+            .unwrap();
+        let images = RuntimeVectorSpaceQuery::new("images", vec![0.0, 0.0])
+            .with_embedding(test_embedding("images"));
+        let hybrid = RuntimeSearchRequest {
+            mode: RuntimeSearchMode::Hybrid,
+            text_query: Some("lantern".to_owned()),
+            vector_space_queries: vec![images.clone()],
+            ..Default::default()
+        };
+        let mut default_query = vec![0.0; 384];
+        default_query[0] = 1.0;
+        let two_spaces = RuntimeSearchRequest {
+            mode: RuntimeSearchMode::Vector,
+            vector_query: Some(default_query),
+            vector_embedding: Some(runtime.embedding_identity()),
+            vector_space_queries: vec![images],
+            ..Default::default()
+        };
+
+        for base in [hybrid, two_spaces] {
+            let mut doc_ids = |top_k| {
+                runtime
+                    .search(RuntimeSearchRequest {
+                        top_k,
+                        ..base.clone()
+                    })
+                    .unwrap()
+                    .hits
+                    .into_iter()
+                    .map(|hit| hit.doc_id)
+                    .collect::<Vec<_>>()
+            };
+            let everything = doc_ids(doc_count);
+            let first_tier = doc_ids(FUSION_MIN_LANE_DEPTH);
+            assert_eq!(everything.len(), doc_count);
+
+            let mut paged = Vec::new();
+            let mut cursor = None;
+            loop {
+                let page = runtime
+                    .search(RuntimeSearchRequest {
+                        top_k: 4,
+                        cursor,
+                        ..base.clone()
+                    })
+                    .unwrap();
+                paged.extend(page.hits.into_iter().map(|hit| hit.doc_id));
+                cursor = page.next_cursor;
+                if cursor.is_none() {
+                    break;
+                }
+            }
+            // Pages open with the ranking at the first tier's depth, then go on through deeper
+            // tiers to every hit one large request returns, each exactly once.
+            assert_eq!(
+                paged[..FUSION_MIN_LANE_DEPTH],
+                first_tier,
+                "{:?}",
+                base.mode
+            );
+            let mut sorted_paged = paged.clone();
+            sorted_paged.sort();
+            sorted_paged.dedup();
+            assert_eq!(sorted_paged.len(), paged.len(), "{:?}", base.mode);
+            let mut sorted_everything = everything;
+            sorted_everything.sort();
+            assert_eq!(sorted_paged, sorted_everything, "{:?}", base.mode);
+        }
+    }
+}
@@ -0,0 +1,522 @@
+use std::collections::HashMap;
+
+use wax_v2_search::{collapse_hits_by_metadata, CollapsedGroup, MetadataSource};
+
+use crate::{
+    docstore_error, RuntimeCollapse, RuntimeError, RuntimeFacetCounts, RuntimeFacetValue,
+    RuntimeStore,
+};
+
+impl RuntimeStore {
+    /// Aggregates `facets` over the matching set [`Self::ranked_hits_matching`] collected; MMR
+    /// and collapse only shape the returned hits.
+    pub(crate) fn facet_counts(
+        &self,
+        facets: &[String],
+        mut matching: Vec<String>,
+    ) -> Result<Vec<RuntimeFacetCounts>, RuntimeError> {
+        matching.sort_unstable();
+        matching.dedup();
+        let field_values = self.metadata_field_values(&matching, facets)?;
+        Ok(facets
+            .iter()
+            .enumerate()
+            .map(|(field_index, field)| {
+                let mut counts = HashMap::<String, usize>::new();
+                let mut missing = 0;
+                for values in matching
+                    .iter()
+                    .filter_map(|doc_id| field_values.get(doc_id))
+                {
+                    let values = facet_values(values[field_index].as_ref());
+                    if values.is_empty() {
+                        missing += 1;
+                    }
+                    for value in values {
+                        *counts.entry(value).or_default() += 1;
+                    }
+                }
+                let mut values = counts
+                    .into_iter()
+                    .map(|(value, count)| RuntimeFacetValue { value, count })
+                    .collect::<Vec<_>>();
+                values.sort_by(|left, right| {
+                    right
+                        .count
+                        .cmp(&left.count)
+                        .then_with(|| left.value.cmp(&right.value))
+                });
+                RuntimeFacetCounts {
+                    field: field.clone(),
+                    values,
+                    missing,
+                }
+            })
+            .collect())
+    }
+
+    pub(crate) fn collapsed_groups(
+        &self,
+        candidates: &[String],
+        collapse: &RuntimeCollapse,
+        group_limit: usize,
+    ) -> Result<Vec<CollapsedGroup>, RuntimeError> {
+        let field_values =
+            self.metadata_field_values(candidates, std::slice::from_ref(&collapse.field))?;
+        let keys = CollapseKeys(
+            field_values
+                .into_iter()
+                .filter_map(|(doc_id, values)| {
+                    values[0]
+                        .as_ref()
+                        .and_then(metadata_value_key)
+                        .map(|key| (doc_id, key))
+                })
+                .collect(),
+        );
+        Ok(collapse_hits_by_metadata(
+            candidates,
+            &keys,
+            &collapse.field,
+            collapse.max_hits_per_group,
+            group_limit,
+        ))
+    }
+
+    /// Values of `fields` for each found document, in `fields` order, read from binary doc
+    /// metadata without hydrating payloads. `doc_id` outside `metadata` resolves to the
+    /// document's id; any other field missing from `metadata` is missing.
+    fn metadata_field_values(
+        &self,
+        doc_ids: &[String],
+        fields: &[String],
+    ) -> Result<HashMap<String, Vec<Option<serde_json::Value>>>, RuntimeError> {
+        let metadata = self
+            .docstore
+            .load_metadata_by_id(doc_ids)
+            .map_err(|error| RuntimeError::Storage(docstore_error(error)))?;
+        Ok(metadata
+            .into_iter()
+            .map(|(doc_id, metadata)| {
+                let values = fields
+                    .iter()
+                    .map(|field| match metadata.root().get(field) {
+                        Some(value) => Some(value.to_value()),
+                        None if field == "doc_id" => {
+                            Some(serde_json::Value::String(doc_id.clone()))
+                        }
+                        None => None,
+                    })
+                    .collect();
+                (doc_id, values)
+            })
+            .collect())
+    }
+}
+
+pub(crate) fn record_facet_matches(
+    facet_matches: Option<&mut Vec<String>>,
+    hits: &[(String, f64)],
+) {
+    if let Some(matches) = facet_matches {
+        matches.extend(hits.iter().map(|(doc_id, _)| doc_id.clone()));
+    }
+}
+
+/// Collapse keys of candidate documents, keyed by doc_id.
+struct CollapseKeys(HashMap<String, String>);
+
+impl MetadataSource for CollapseKeys {
+    fn field_value(&self, doc_id: &str, _field: &str) -> Option<&str> {
+        self.0.get(doc_id).map(String::as_str)
+    }
+}
+
+fn metadata_value_key(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(value) => Some(value.clone()),
+        serde_json::Value::Null => None,
+        value => Some(value.to_string()),
+    }
+}
+
+fn facet_values(value: Option<&serde_json::Value>) -> Vec<String> {
+    let mut values = match value {
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .filter_map(metadata_value_key)
+            .collect::<Vec<_>>(),
+        Some(value) => metadata_value_key(value).into_iter().collect(),
+        None => Vec::new(),
+    };
+    values.sort();
+    values.dedup();
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+    use wax_bench_packer::{pack_adhoc_dataset, AdhocPackRequest};
+
+    use crate::{
+        hydration_probe, read_manifest, Embedder, FeatureHashEmbedder, NewDocument,
+        RuntimeCollapse, RuntimeFacetCounts, RuntimeFacetValue, RuntimeSearchMode,
+        RuntimeSearchRequest, RuntimeSearchResponse, RuntimeStore, RuntimeVectorRadius,
+    };
+
+    #[test]
+    fn collapse_by_refetches_candidates_until_top_k_groups_are_filled() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{}}\n",
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        // Every chunk of the long file outranks the other files, so the first candidate window
+        // holds a single group.
+        let mut documents = (0..30)
+            .map(|chunk| {
+                NewDocument::new(format!("long-{chunk:02}"), "lantern lantern lantern")
+                    .with_metadata(serde_json::json!({ "source_id": "long.md" }))
+            })
+            .collect::<Vec<_>>();
+        documents.push(
+            NewDocument::new("short-a", "lantern glass wick oil smoke")
+                .with_metadata(serde_json::json!({ "source_id": "short.md" })),
+        );
+        documents.push(
+            NewDocument::new("short-b", "lantern hook chain rust paint")
+                .with_metadata(serde_json::json!({ "source_id": "short.md" })),
+        );
+        documents.push(NewDocument::new(
+            "loose",
+            "lantern festival river paper boats",
+        ));
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(documents)
+            .unwrap();
+        let mut search = |collapse_by| {
+            runtime.search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Text,
+                text_query: Some("lantern".to_owned()),
+                top_k: 3,
+                collapse_by,
+                ..Default::default()
+            })
+        };
+
+        let plain = search(None).unwrap();
+        assert!(plain.groups.is_empty());
+        assert!(plain.hits.iter().all(|hit| hit.doc_id.starts_with("long-")));
+
+        let collapsed = search(Some(RuntimeCollapse::new("source_id"))).unwrap();
+        assert_eq!(collapsed.groups.len(), 3);
+        assert_eq!(collapsed.groups[0].key.as_deref(), Some("long.md"));
+        assert_eq!(collapsed.groups[0].doc_ids.len(), 1);
+        assert_eq!(collapsed.groups[0].hit_count, 30);
+        let short = collapsed
+            .groups
+            .iter()
+            .find(|group| group.key.as_deref() == Some("short.md"))
+            .unwrap();
+        assert_eq!(short.hit_count, 2);
+        let loose = collapsed
+            .groups
+            .iter()
+            .find(|group| group.key.is_none())
+            .unwrap();
+        assert_eq!(loose.doc_ids, vec!["loose"]);
+        assert_eq!(
+            collapsed
+                .hits
+                .iter()
+                .map(|hit| hit.doc_id.clone())
+                .collect::<Vec<_>>(),
+            collapsed
+                .groups
+                .iter()
+                .flat_map(|group| group.doc_ids.clone())
+                .collect::<Vec<_>>()
+        );
+
+        let two_per_group = search(Some(
+            RuntimeCollapse::new("source_id").with_max_hits_per_group(2),
+        ))
+        .unwrap();
+        assert_eq!(two_per_group.groups[0].doc_ids.len(), 2);
+        assert_eq!(two_per_group.hits.len(), 5);
+
+        let error = search(Some(
+            RuntimeCollapse::new("source_id").with_max_hits_per_group(0),
+        ))
+        .unwrap_err();
+        assert!(
+            matches!(error, crate::RuntimeError::InvalidRequest(message) if message.contains("max_hits_per_group"))
+        );
+    }
+
+    #[test]
+    fn facets_count_metadata_values_over_every_matching_document() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{}}\n",
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![
+                NewDocument::new("doc-a", "harbor lantern").with_metadata(
+                    serde_json::json!({ "kind": "note", "tags": ["sea", "light"], "year": 2024 }),
+                ),
+                NewDocument::new("doc-b", "harbor crane").with_metadata(
+                    serde_json::json!({ "kind": "note", "tags": ["sea", "sea"], "year": 2025 }),
+                ),
+                NewDocument::new("doc-c", "harbor ferry timetable")
+                    .with_metadata(serde_json::json!({ "kind": "report", "year": 2024 })),
+                NewDocument::new("doc-d", "mountain trail")
+                    .with_metadata(serde_json::json!({ "kind": "report", "tags": ["rock"] })),
+            ])
+            .unwrap();
+        let mut search = |top_k, facets: &[&str]| {
+            runtime
+                .search(RuntimeSearchRequest {
+                    mode: RuntimeSearchMode::Text,
+                    text_query: Some("harbor".to_owned()),
+                    top_k,
+                    facets: facets.iter().map(|field| (*field).to_owned()).collect(),
+                    ..Default::default()
+                })
+                .unwrap()
+        };
+        let value = |value: &str, count| RuntimeFacetValue {
+            value: value.to_owned(),
+            count,
+        };
+
+        let response = search(1, &["kind", "tags", "year"]);
+        assert_eq!(response.hits.len(), 1);
+        assert_eq!(
+            response.facets,
+            vec![
+                RuntimeFacetCounts {
+                    field: "kind".to_owned(),
+                    values: vec![value("note", 2), value("report", 1)],
+                    missing: 0,
+                },
+                RuntimeFacetCounts {
+                    field: "tags".to_owned(),
+                    values: vec![value("sea", 2), value("light", 1)],
+                    missing: 1,
+                },
+                RuntimeFacetCounts {
+                    field: "year".to_owned(),
+                    values: vec![value("2024", 2), value("2025", 1)],
+                    missing: 0,
+                },
+            ]
+        );
+
+        let counts_only = search(0, &["kind"]);
+        assert!(counts_only.hits.is_empty());
+        assert_eq!(counts_only.facets, response.facets[..1]);
+        assert!(search(3, &[]).facets.is_empty());
+
+        // `doc_id` outside the binary metadata resolves to the document's id.
+        let top_level = search(1, &["doc_id"]);
+        assert_eq!(
+            top_level.facets[0].values,
+            vec![value("doc-a", 1), value("doc-b", 1), value("doc-c", 1)]
+        );
+        assert_eq!(top_level.facets[0].missing, 0);
+    }
+
+    #[test]
+    fn facets_over_sparse_fields_read_binary_metadata_without_hydrating_documents() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{}}\n",
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(
+                (0..20)
+                    .map(|index| {
+                        let document = NewDocument::new(format!("doc-{index:02}"), "harbor")
+                            .with_extra_field("source", serde_json::json!("feed"));
+                        if index % 5 == 0 {
+                            document.with_metadata(serde_json::json!({ "kind": "note" }))
+                        } else {
+                            document
+                        }
+                    })
+                    .collect(),
+            )
+            .unwrap();
+        hydration_probe::take();
+
+        let response = runtime
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Text,
+                text_query: Some("harbor".to_owned()),
+                top_k: 3,
+                facets: vec!["kind".to_owned(), "source".to_owned()],
+                collapse_by: Some(RuntimeCollapse {
+                    field: "kind".to_owned(),
+                    max_hits_per_group: 1,
+                }),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(hydration_probe::take(), 0);
+        assert_eq!(response.groups.len(), 3);
+        assert_eq!(
+            response.facets,
+            vec![
+                RuntimeFacetCounts {
+                    field: "kind".to_owned(),
+                    values: vec![RuntimeFacetValue {
+                        value: "note".to_owned(),
+                        count: 4,
+                    }],
+                    missing: 16,
+                },
+                // Top-level payload fields are never parsed, so they count as missing.
+                RuntimeFacetCounts {
+                    field: "source".to_owned(),
+                    values: Vec::new(),
+                    missing: 20,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn facets_match_on_text_and_sparse_lanes_or_inside_the_vector_radius() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{}}\n",
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+        let dimensions = read_manifest(dataset_dir.path())
+            .unwrap()
+            .vector_profile
+            .embedding_dimensions as usize;
+        let embedder = FeatureHashEmbedder::new(dimensions);
+        let mut runtime = RuntimeStore::create(dataset_dir.path())
+            .unwrap()
+            .with_embedder(Arc::new(embedder.clone()));
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(
+                (0..12)
+                    .map(|index| {
+                        let kind = if index % 4 == 0 { "duplicate" } else { "other" };
+                        let text = if index % 4 == 0 {
+                            "duplicate note"
+                        } else {
+                            "other"
+                        };
+                        NewDocument::new(format!("doc-{index:03}"), format!("{text} {index}"))
+                            .with_metadata(serde_json::json!({ "kind": kind }))
+                    })
+                    .collect(),
+            )
+            .unwrap();
+        let kinds = |response: RuntimeSearchResponse| {
+            response.facets[0]
+                .values
+                .iter()
+                .map(|value| (value.value.clone(), value.count))
+                .collect::<Vec<_>>()
+        };
+
+        let inside_radius = runtime
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Vector,
+                text_query: Some("duplicate note".to_owned()),
+                top_k: 1,
+                vector_radius: Some(RuntimeVectorRadius::MinSimilarity(0.5)),
+                facets: vec!["kind".to_owned()],
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(inside_radius.hits.len(), 1);
+        assert_eq!(kinds(inside_radius), vec![("duplicate".to_owned(), 3)]);
+
+        let error = runtime
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Vector,
+                text_query: Some("duplicate note".to_owned()),
+                top_k: 1,
+                facets: vec!["kind".to_owned()],
+                ..Default::default()
+            })
+            .unwrap_err();
+        assert!(
+            matches!(error, crate::RuntimeError::InvalidRequest(message) if message.contains("vector_radius"))
+        );
+
+        // The vector lane ranks every document, but only text matches count.
+        let hybrid = runtime
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Hybrid,
+                text_query: Some("duplicate".to_owned()),
+                vector_query: Some(embedder.embed("other").unwrap()),
+                vector_embedding: Some(embedder.identity()),
+                top_k: 12,
+                facets: vec!["kind".to_owned()],
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(hybrid.hits.len(), 12);
+        assert_eq!(kinds(hybrid), vec![("duplicate".to_owned(), 3)]);
+    }
+}
@@ -1,21 +1,49 @@
+use std::collections::HashMap;
 use std::fmt;
 use std::fs;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::Duration;
 
 use wax_bench_model::DatasetPackManifest;
+pub use wax_v2_core::{
+    Checkpoint, ManifestGeneration, SegmentVerification, StoreVerifyReport, SuperblockVerification,
+    VerifyIssue, VerifyIssueKind, DEFAULT_WRITER_LOCK_TIMEOUT,
+};
+pub use wax_v2_docstore::DocCompression;
 use wax_v2_docstore::DocIdMap;
 use wax_v2_docstore::Docstore;
-use wax_v2_search::hybrid_search_with_diagnostics;
+use wax_v2_search::{maximal_marginal_relevance, reciprocal_rank_fusion_scored};
+use wax_v2_sparse::SparseLane;
 use wax_v2_text::TextLane;
-use wax_v2_vector::VectorLane;
-
-#[derive(Debug, Clone, PartialEq, Eq)]
+use wax_v2_vector::{
+    EmbeddingIdentity, VectorEncoding, VectorLane, VectorMetric, VectorRadius, VectorSpaceSpec,
+    DEFAULT_VECTOR_SPACE,
+};
+
+mod carry;
+mod cursor;
+mod facets;
+mod sparse;
+mod stream;
+mod transaction;
+
+pub use transaction::RuntimeTransaction;
+
+use carry::carried_row_segments;
+use cursor::search_fingerprint;
+use facets::record_facet_matches;
+use transaction::ensure_transaction_generation_unchanged;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub enum RuntimeSearchMode {
+    #[default]
     Text,
     Vector,
     Hybrid,
+    Sparse,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -25,6 +53,190 @@ pub struct RuntimeSearchRequest {
     pub vector_query: Option<Vec<f32>>,
     pub top_k: usize,
     pub include_preview: bool,
+    /// Embedding identity of `vector_query`; must match the identity stored with the default
+    /// vector space.
+    pub vector_embedding: Option<RuntimeEmbeddingIdentity>,
+    /// Turns vector search into a range search: every document inside the radius, best first,
+    /// capped at `top_k`.
+    pub vector_radius: Option<RuntimeVectorRadius>,
+    /// Maximal marginal relevance trade-off in `[0, 1]`; when set, fused hits are diversified
+    /// with the stored default-space vectors before hydration (`1.0` keeps the fused order).
+    pub mmr_lambda: Option<f32>,
+    /// Groups hits by a metadata field and returns up to `top_k` groups; candidates are fetched
+    /// until enough groups are filled or the store runs out of hits.
+    pub collapse_by: Option<RuntimeCollapse>,
+    /// Metadata fields to aggregate over every document the request matches, not only the
+    /// returned `top_k` hits: the text and sparse lane matches, or the documents inside
+    /// `vector_radius`. Vector lanes rank every document, so vector search needs a radius to
+    /// take facets. Follow-up cursor pages skip them.
+    pub facets: Vec<String>,
+    /// `next_cursor` of the previous page. The follow-up request must repeat the query; `top_k`
+    /// sets the page size and may change between pages.
+    pub cursor: Option<String>,
+    /// Learned sparse query as `(term_id, weight)` pairs; drives `Sparse` mode and joins hybrid
+    /// fusion as a third lane when present.
+    pub sparse_query: Option<Vec<(u32, f32)>>,
+    pub vector_space_queries: Vec<RuntimeVectorSpaceQuery>,
+}
+
+/// A text request for the top 10 hits with every optional feature off; set the query fields and
+/// take the rest from here with struct update syntax.
+impl Default for RuntimeSearchRequest {
+    fn default() -> Self {
+        Self {
+            mode: RuntimeSearchMode::Text,
+            text_query: None,
+            vector_query: None,
+            top_k: 10,
+            include_preview: false,
+            vector_embedding: None,
+            vector_radius: None,
+            mmr_lambda: None,
+            collapse_by: None,
+            facets: Vec::new(),
+            cursor: None,
+            sparse_query: None,
+            vector_space_queries: Vec::new(),
+        }
+    }
+}
+
+/// Query vectors for one named vector space. Hits from every queried space (and from
+/// `vector_query` on the default space) are fused with reciprocal rank fusion. Multi-vector
+/// spaces take several query vectors and rank documents by MaxSim; single-vector spaces take one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeVectorSpaceQuery {
+    pub space: String,
+    pub vectors: Vec<Vec<f32>>,
+    pub embedding: Option<RuntimeEmbeddingIdentity>,
+}
+
+impl RuntimeVectorSpaceQuery {
+    pub fn new(space: impl Into<String>, vector: Vec<f32>) -> Self {
+        Self::multi(space, vec![vector])
+    }
+
+    pub fn multi(space: impl Into<String>, vectors: Vec<Vec<f32>>) -> Self {
+        Self {
+            space: space.into(),
+            vectors,
+            embedding: None,
+        }
+    }
+
+    pub fn with_embedding(mut self, embedding: RuntimeEmbeddingIdentity) -> Self {
+        self.embedding = Some(embedding);
+        self
+    }
+}
+
+/// Embedding model behind a set of vectors. Vector publishes record it with the space and
+/// vector queries must present the same identity.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RuntimeEmbeddingIdentity {
+    pub spec_id: String,
+    pub model_version: String,
+    pub model_hash: String,
+}
+
+impl RuntimeEmbeddingIdentity {
+    pub fn new(
+        spec_id: impl Into<String>,
+        model_version: impl Into<String>,
+        model_hash: impl Into<String>,
+    ) -> Self {
+        Self {
+            spec_id: spec_id.into(),
+            model_version: model_version.into(),
+            model_hash: model_hash.into(),
+        }
+    }
+}
+
+impl fmt::Display for RuntimeEmbeddingIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}@{} ({})",
+            self.spec_id, self.model_version, self.model_hash
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimeVectorMetric {
+    Cosine,
+    Dot,
+    L2,
+}
+
+/// Range-search radius. Cosine spaces accept either form (distance is `1 - similarity`), dot
+/// spaces a minimum similarity and L2 spaces a maximum Euclidean distance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RuntimeVectorRadius {
+    MinSimilarity(f32),
+    MaxDistance(f32),
+}
+
+/// Collapse key for [`RuntimeSearchRequest::collapse_by`]. The field is read from the document's
+/// `metadata` object; `doc_id` is the only top-level field it falls back to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeCollapse {
+    pub field: String,
+    pub max_hits_per_group: usize,
+}
+
+impl RuntimeCollapse {
+    pub fn new(field: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            max_hits_per_group: 1,
+        }
+    }
+
+    pub fn with_max_hits_per_group(mut self, max_hits_per_group: usize) -> Self {
+        self.max_hits_per_group = max_hits_per_group;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeVectorSpace {
+    pub name: String,
+    pub dimensions: usize,
+    pub metric: RuntimeVectorMetric,
+    pub multi_vector: bool,
+    pub embedding: Option<RuntimeEmbeddingIdentity>,
+}
+
+impl RuntimeVectorSpace {
+    pub fn new(name: impl Into<String>, dimensions: usize, metric: RuntimeVectorMetric) -> Self {
+        Self {
+            name: name.into(),
+            dimensions,
+            metric,
+            multi_vector: false,
+            embedding: None,
+        }
+    }
+
+    /// Records the embedding model behind the space's vectors; required to publish into it.
+    pub fn with_embedding(mut self, embedding: RuntimeEmbeddingIdentity) -> Self {
+        self.embedding = Some(embedding);
+        self
+    }
+
+    /// A space that stores several vectors per document, searched by late interaction.
+    pub fn multi_vector(
+        name: impl Into<String>,
+        dimensions: usize,
+        metric: RuntimeVectorMetric,
+    ) -> Self {
+        Self {
+            multi_vector: true,
+            ..Self::new(name, dimensions, metric)
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -36,6 +248,78 @@ pub struct RuntimeSearchHit {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RuntimeSearchResponse {
     pub hits: Vec<RuntimeSearchHit>,
+    /// Filled only for collapsed searches, in best-hit order; `hits` lists the kept hits of
+    /// every group in the same order.
+    pub groups: Vec<RuntimeSearchGroup>,
+    /// One entry per requested facet field, in request order.
+    pub facets: Vec<RuntimeFacetCounts>,
+    /// Opaque cursor for the next page when this page came back full. It pins the store
+    /// generation the page was ranked at.
+    pub next_cursor: Option<String>,
+}
+
+/// Value counts of one facet field over the matching set, most frequent first with ties broken
+/// by value. Array values count once per distinct element; `missing` counts matches without the
+/// field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeFacetCounts {
+    pub field: String,
+    pub values: Vec<RuntimeFacetValue>,
+    pub missing: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeFacetValue {
+    pub value: String,
+    pub count: usize,
+}
+
+/// One collapsed group. `key` is `None` for a hit without the collapse field, which forms its
+/// own group; `hit_count` counts every fetched candidate in the group, not only the kept ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeSearchGroup {
+    pub key: Option<String>,
+    pub doc_ids: Vec<String>,
+    pub hit_count: usize,
+}
+
+/// Exact top-k for many query vectors against one single-vector space, scored in a single pass
+/// over its rows. Each query's hits match an exact vector search with the same vector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeBatchSearchRequest {
+    pub space: String,
+    pub vectors: Vec<Vec<f32>>,
+    pub top_k: usize,
+    pub include_preview: bool,
+    pub embedding: Option<RuntimeEmbeddingIdentity>,
+}
+
+impl RuntimeBatchSearchRequest {
+    pub fn new(space: impl Into<String>, vectors: Vec<Vec<f32>>, top_k: usize) -> Self {
+        Self {
+            space: space.into(),
+            vectors,
+            top_k,
+            include_preview: false,
+            embedding: None,
+        }
+    }
+
+    pub fn with_preview(mut self, include_preview: bool) -> Self {
+        self.include_preview = include_preview;
+        self
+    }
+
+    pub fn with_embedding(mut self, embedding: RuntimeEmbeddingIdentity) -> Self {
+        self.embedding = Some(embedding);
+        self
+    }
+}
+
+/// One response per query vector, in request order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeBatchSearchResponse {
+    pub results: Vec<RuntimeSearchResponse>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -43,6 +327,7 @@ pub enum RuntimePublishFamily {
     Doc,
     Text,
     Vector,
+    Sparse,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -102,9 +387,92 @@ impl NewDocumentVector {
     }
 }
 
+/// Turns text into dense vectors for the default vector space. A store with an embedder embeds
+/// documents on `publish_raw_documents` and accepts text queries in `RuntimeSearchMode::Vector`.
+pub trait Embedder: Send + Sync {
+    fn dimensions(&self) -> usize;
+
+    fn identity(&self) -> RuntimeEmbeddingIdentity;
+
+    fn embed(&self, text: &str) -> Result<Vec<f32>, String>;
+}
+
+/// Reference embedder built on the feature-hashing `embed_text` used by the bench harness.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureHashEmbedder {
+    dimensions: usize,
+    identity: RuntimeEmbeddingIdentity,
+}
+
+impl FeatureHashEmbedder {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions,
+            identity: RuntimeEmbeddingIdentity::new(
+                format!("feature-hash-{dimensions}"),
+                "feature-hash-v1",
+                "fnv1a-64",
+            ),
+        }
+    }
+
+    /// Overrides the reported identity, e.g. with the dataset's declared feature-hash identity.
+    pub fn with_identity(mut self, identity: RuntimeEmbeddingIdentity) -> Self {
+        self.identity = identity;
+        self
+    }
+}
+
+impl Embedder for FeatureHashEmbedder {
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn identity(&self) -> RuntimeEmbeddingIdentity {
+        self.identity.clone()
+    }
+
+    fn embed(&self, text: &str) -> Result<Vec<f32>, String> {
+        let dimensions = u32::try_from(self.dimensions)
+            .map_err(|_| format!("embedding dimensions {} exceed u32", self.dimensions))?;
+        Ok(wax_bench_model::embed_text(text, dimensions))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewDocumentSparseVector {
+    pub doc_id: String,
+    pub terms: Vec<(u32, f32)>,
+}
+
+impl NewDocumentSparseVector {
+    pub fn new(doc_id: impl Into<String>, terms: Vec<(u32, f32)>) -> Self {
+        Self {
+            doc_id: doc_id.into(),
+            terms,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewDocumentMultiVector {
+    pub doc_id: String,
+    pub vectors: Vec<Vec<f32>>,
+}
+
+impl NewDocumentMultiVector {
+    pub fn new(doc_id: impl Into<String>, vectors: Vec<Vec<f32>>) -> Self {
+        Self {
+            doc_id: doc_id.into(),
+            vectors,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RuntimePlatformAccelerationFamily {
     Apple,
+    CpuSimd,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -150,12 +518,51 @@ pub struct RuntimeAccelerationSelection {
 pub enum RuntimeError {
     InvalidRequest(String),
     Storage(String),
+    /// A vector write or query named a different embedding model than the one stored with the
+    /// vector space.
+    EmbeddingMismatch(Box<RuntimeEmbeddingMismatch>),
+    /// The configured embedder failed or returned a vector of the wrong length.
+    Embedding(String),
+    /// Another writer held the store lock for the whole writer lock timeout.
+    WriterBusy {
+        waited_ms: u64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeEmbeddingMismatch {
+    pub space: String,
+    pub stored: Option<RuntimeEmbeddingIdentity>,
+    pub requested: Option<RuntimeEmbeddingIdentity>,
 }
 
 impl fmt::Display for RuntimeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::InvalidRequest(message) | Self::Storage(message) => write!(f, "{message}"),
+            Self::EmbeddingMismatch(mismatch) => {
+                let RuntimeEmbeddingMismatch {
+                    space,
+                    stored,
+                    requested,
+                } = mismatch.as_ref();
+                let describe = |identity: &Option<RuntimeEmbeddingIdentity>| {
+                    identity
+                        .as_ref()
+                        .map_or_else(|| "none".to_owned(), ToString::to_string)
+                };
+                write!(
+                    f,
+                    "vector space {space} embedding mismatch: stored {}, requested {}",
+                    describe(stored),
+                    describe(requested)
+                )
+            }
+            Self::Embedding(message) => write!(f, "embedding failed: {message}"),
+            Self::WriterBusy { waited_ms } => write!(
+                f,
+                "store writer lock is held by another writer (waited {waited_ms} ms)"
+            ),
         }
     }
 }
@@ -164,12 +571,51 @@ pub struct RuntimeStore {
     root: PathBuf,
     manifest: DatasetPackManifest,
     docstore: Docstore,
-    text_lane: Option<TextLane>,
-    vector_lane: Option<VectorLane>,
+    text_lane: OnceLock<TextLane>,
+    vector_lane: OnceLock<Arc<VectorLane>>,
+    vector_space_lanes: Mutex<HashMap<String, Arc<VectorLane>>>,
+    sparse_lane: OnceLock<SparseLane>,
+    embedder: Option<Arc<dyn Embedder>>,
+    vector_scan_threads: usize,
+    writer_lock_timeout: Duration,
+    ingest_run_bytes: usize,
+    doc_compression: DocCompression,
     store_generation: Option<u64>,
+    /// Set on read-only handles opened with `open_at_generation`; reads never move past it.
+    historical_generation: Option<u64>,
+    /// Read-only handle at the generation of the last cursor served from an older generation,
+    /// kept so its follow-up pages reuse the loaded lanes.
+    cursor_store: Mutex<Option<Box<RuntimeStore>>>,
     closed: bool,
 }
 
+/// Read-only handle pinned to one store generation that can be cloned and shared across threads.
+///
+/// Unlike a plain [`RuntimeStore`], which follows later publishes on its next search, a snapshot
+/// keeps serving the generation it was taken at. Clones share the lanes the first search loads,
+/// and searches through them run in parallel.
+#[derive(Clone)]
+pub struct ReadSnapshot {
+    generation: u64,
+    store: Arc<RuntimeStore>,
+}
+
+impl ReadSnapshot {
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn search(
+        &self,
+        request: RuntimeSearchRequest,
+    ) -> Result<RuntimeSearchResponse, RuntimeError> {
+        self.store.search_loaded_generation(request)
+    }
+}
+
+/// Smallest lane depth fused searches rank at; see [`fusion_lane_depth`].
+const FUSION_MIN_LANE_DEPTH: usize = 10;
+
 pub struct RuntimeStoreWriter<'a> {
     store: &'a mut RuntimeStore,
 }
@@ -177,7 +623,10 @@ pub struct RuntimeStoreWriter<'a> {
 impl RuntimeStore {
     pub fn capabilities() -> RuntimeCapabilities {
         RuntimeCapabilities {
-            platform_acceleration: vec![apple_acceleration_capability()],
+            platform_acceleration: vec![
+                apple_acceleration_capability(),
+                cpu_simd_acceleration_capability(),
+            ],
         }
     }
 
@@ -230,159 +679,543 @@ impl RuntimeStore {
 
     pub fn open(root: &Path) -> Result<Self, RuntimeError> {
         let manifest = read_manifest(root)?;
-        Self::open_from_manifest(root, manifest)
+        Self::open_from_manifest_at_generation(root, manifest, None)
     }
 
-    pub fn writer(&mut self) -> Result<RuntimeStoreWriter<'_>, RuntimeError> {
-        if self.closed {
-            return Err(RuntimeError::InvalidRequest(
-                "runtime store is already closed".to_owned(),
-            ));
+    /// Lists the manifest generations still present in the store under `root`, oldest first.
+    pub fn generations(root: &Path) -> Result<Vec<ManifestGeneration>, RuntimeError> {
+        let store_path = root.join("store.wax");
+        if !store_path.exists() {
+            return Err(RuntimeError::InvalidRequest(format!(
+                "no store exists at {}",
+                store_path.display()
+            )));
         }
-        Ok(RuntimeStoreWriter { store: self })
+        wax_v2_core::list_manifest_generations(&store_path).map_err(runtime_core_error)
     }
 
-    pub fn store_path(&self) -> PathBuf {
-        self.root.join("store.wax")
+    /// Opens a read-only handle that searches the store exactly as it was at `generation`.
+    /// Later publishes are not observed and `writer` is rejected.
+    pub fn open_at_generation(root: &Path, generation: u64) -> Result<Self, RuntimeError> {
+        let manifest = read_manifest(root)?;
+        let store_path = root.join("store.wax");
+        if !store_path.exists() {
+            return Err(RuntimeError::InvalidRequest(format!(
+                "no store exists at {}",
+                store_path.display()
+            )));
+        }
+        Self::open_from_manifest_at_generation(root, manifest, Some(generation))
     }
 
-    fn open_from_manifest(
-        root: &Path,
-        manifest: DatasetPackManifest,
-    ) -> Result<Self, RuntimeError> {
-        let store_path = root.join("store.wax");
-        let store_generation = if store_path.exists() {
-            Some(
+    /// Generation a handle from `open_at_generation` is pinned to.
+    pub fn historical_generation(&self) -> Option<u64> {
+        self.historical_generation
+    }
+
+    /// Pins the generation currently active on disk in a [`ReadSnapshot`]. The snapshot keeps
+    /// this handle's embedder and vector scan threads.
+    pub fn read_snapshot(&self) -> Result<ReadSnapshot, RuntimeError> {
+        if self.closed {
+            return Err(RuntimeError::InvalidRequest(
+                "runtime store is already closed".to_owned(),
+            ));
+        }
+        let generation = match self.historical_generation {
+            Some(generation) => generation,
+            None => {
+                let store_path = self.store_path();
+                if !store_path.exists() {
+                    return Err(RuntimeError::InvalidRequest(format!(
+                        "no store exists at {}",
+                        store_path.display()
+                    )));
+                }
                 wax_v2_core::open_store(&store_path)
                     .map_err(runtime_core_error)?
                     .manifest
-                    .generation,
-            )
-        } else {
-            None
+                    .generation
+            }
         };
-        let docstore = Docstore::open(root, &manifest)
-            .map_err(|error| RuntimeError::Storage(docstore_error(error)))?;
-
-        Ok(Self {
-            root: root.to_path_buf(),
-            manifest,
-            docstore,
-            text_lane: None,
-            vector_lane: None,
-            store_generation,
-            closed: false,
+        Ok(ReadSnapshot {
+            generation,
+            store: Arc::new(self.open_pinned_generation(generation)?),
         })
     }
 
-    fn open_created_store(
-        root: &Path,
-        manifest: DatasetPackManifest,
-        store_path: &Path,
-    ) -> Result<Self, RuntimeError> {
-        match Self::open_from_manifest(root, manifest) {
-            Ok(store) => Ok(store),
-            Err(error) => {
-                let _ = fs::remove_file(store_path);
-                Err(error)
-            }
+    /// Read-only handle at `generation` with this handle's embedder and vector scan threads.
+    fn open_pinned_generation(&self, generation: u64) -> Result<Self, RuntimeError> {
+        let mut store = Self::open_from_manifest_at_generation(
+            &self.root,
+            self.manifest.clone(),
+            Some(generation),
+        )?
+        .with_vector_scan_threads(self.vector_scan_threads);
+        store.embedder = self.embedder.clone();
+        Ok(store)
+    }
+
+    /// Checkpoints recorded in the active store generation, ordered by name.
+    pub fn checkpoints(&self) -> Result<Vec<Checkpoint>, RuntimeError> {
+        let store_path = self.store_path();
+        if !store_path.exists() {
+            return Ok(Vec::new());
         }
+        wax_v2_core::list_checkpoints(&store_path).map_err(runtime_core_error)
     }
 
-    pub fn search(
-        &mut self,
-        request: RuntimeSearchRequest,
-    ) -> Result<RuntimeSearchResponse, RuntimeError> {
+    /// Walks the whole store under `root`: both superblocks, every object checksum, descriptor
+    /// counts against decoded rows, and doc id alignment between the doc segment and the text,
+    /// vector and sparse segments.
+    pub fn verify(root: &Path) -> Result<StoreVerifyReport, RuntimeError> {
+        let store_path = root.join("store.wax");
+        if !store_path.exists() {
+            return Err(RuntimeError::InvalidRequest(format!(
+                "no store exists at {}",
+                store_path.display()
+            )));
+        }
+        wax_v2_core::verify_store_with_decoder(&store_path, &StoreSegmentDecoder)
+            .map_err(runtime_core_error)
+    }
+
+    /// Truncates the bytes an interrupted publish left past the active manifest of the store
+    /// under `root`, waiting up to `lock_timeout` for the writer lock. Returns how many bytes
+    /// were dropped.
+    pub fn repair_torn_tail(root: &Path, lock_timeout: Duration) -> Result<u64, RuntimeError> {
+        let store_path = root.join("store.wax");
+        if !store_path.exists() {
+            return Err(RuntimeError::InvalidRequest(format!(
+                "no store exists at {}",
+                store_path.display()
+            )));
+        }
+        let recovered = wax_v2_core::open_store_with_repair(
+            &store_path,
+            wax_v2_core::TornTailRepair::Truncate,
+            lock_timeout,
+        )
+        .map_err(runtime_core_error)?;
+        Ok(if recovered.truncated {
+            recovered.torn_tail_bytes
+        } else {
+            0
+        })
+    }
+
+    pub fn writer(&mut self) -> Result<RuntimeStoreWriter<'_>, RuntimeError> {
         if self.closed {
             return Err(RuntimeError::InvalidRequest(
                 "runtime store is already closed".to_owned(),
             ));
         }
-        match &request.mode {
-            RuntimeSearchMode::Text if request.text_query.is_none() => {
-                return Err(RuntimeError::InvalidRequest(
-                    "text_query is required for text search".to_owned(),
-                ));
-            }
-            RuntimeSearchMode::Vector if request.vector_query.is_none() => {
-                return Err(RuntimeError::InvalidRequest(
-                    "vector_query is required for vector search".to_owned(),
-                ));
-            }
-            RuntimeSearchMode::Hybrid if request.text_query.is_none() => {
-                return Err(RuntimeError::InvalidRequest(
-                    "text_query is required for hybrid search".to_owned(),
-                ));
+        if let Some(generation) = self.historical_generation {
+            return Err(RuntimeError::InvalidRequest(format!(
+                "runtime store is opened read-only at generation {generation}"
+            )));
+        }
+        Ok(RuntimeStoreWriter { store: self })
+    }
+
+    /// Installs the embedder used to auto-embed published documents and vector-mode text queries.
+    pub fn with_embedder(mut self, embedder: Arc<dyn Embedder>) -> Self {
+        self.embedder = Some(embedder);
+        self
+    }
+
+    pub fn embedder(&self) -> Option<&Arc<dyn Embedder>> {
+        self.embedder.as_ref()
+    }
+
+    /// Thread budget for exact and max-sim vector scans; defaults to the available parallelism.
+    /// Hits are identical for every budget.
+    pub fn with_vector_scan_threads(mut self, threads: usize) -> Self {
+        self.vector_scan_threads = threads.max(1);
+        let lanes = self.vector_lane.get_mut().into_iter().chain(
+            self.vector_space_lanes
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner)
+                .values_mut(),
+        );
+        // Lanes are only shared while a search holds them, so a builder sees them unshared.
+        for lane in lanes.filter_map(Arc::get_mut) {
+            lane.set_exact_scan_threads(self.vector_scan_threads);
+        }
+        self
+    }
+
+    pub fn vector_scan_threads(&self) -> usize {
+        self.vector_scan_threads
+    }
+
+    /// How long publishes wait for another writer to release the store lock before failing with
+    /// [`RuntimeError::WriterBusy`].
+    pub fn with_writer_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.writer_lock_timeout = timeout;
+        self
+    }
+
+    pub fn writer_lock_timeout(&self) -> Duration {
+        self.writer_lock_timeout
+    }
+
+    /// Memory budget for text postings and doc ids buffered by
+    /// [`RuntimeStoreWriter::publish_document_stream`] before a sorted run is spilled to disk.
+    pub fn with_ingest_run_bytes(mut self, run_bytes: usize) -> Self {
+        self.ingest_run_bytes = run_bytes.max(1);
+        self
+    }
+
+    pub fn ingest_run_bytes(&self) -> usize {
+        self.ingest_run_bytes
+    }
+
+    /// Block codec for doc segments written by later publishes; existing segments keep theirs.
+    pub fn with_doc_compression(mut self, compression: DocCompression) -> Self {
+        self.doc_compression = compression;
+        self
+    }
+
+    pub fn doc_compression(&self) -> DocCompression {
+        self.doc_compression
+    }
+
+    /// Embedding identity declared by the dataset manifest for the default vector space.
+    pub fn embedding_identity(&self) -> RuntimeEmbeddingIdentity {
+        let identity = &self.manifest.identity;
+        RuntimeEmbeddingIdentity::new(
+            identity.embedding_spec_id.clone(),
+            identity.embedding_model_version.clone(),
+            identity.embedding_model_hash.clone(),
+        )
+    }
+
+    /// Facet fields the dataset manifest declares; any metadata field can still be requested.
+    pub fn facet_fields(&self) -> Vec<String> {
+        self.manifest
+            .metadata_profile
+            .facets
+            .iter()
+            .map(|facet| facet.name.clone())
+            .collect()
+    }
+
+    pub fn store_path(&self) -> PathBuf {
+        self.root.join("store.wax")
+    }
+
+    pub fn vector_spaces(&self) -> Result<Vec<RuntimeVectorSpace>, RuntimeError> {
+        wax_v2_vector::store_vector_spaces_at_generation(
+            &self.store_path(),
+            self.historical_generation,
+        )
+        .map_err(RuntimeError::Storage)
+        .map(|spaces| {
+            spaces
+                .into_iter()
+                .map(|space| RuntimeVectorSpace {
+                    name: space.spec.name,
+                    dimensions: space.spec.dimensions,
+                    metric: runtime_vector_metric(space.spec.metric),
+                    multi_vector: space.multi_vector,
+                    embedding: space.spec.embedding.map(runtime_embedding_identity),
+                })
+                .collect()
+        })
+    }
+
+    fn open_from_manifest_at_generation(
+        root: &Path,
+        manifest: DatasetPackManifest,
+        historical_generation: Option<u64>,
+    ) -> Result<Self, RuntimeError> {
+        let store_path = root.join("store.wax");
+        let store_generation = if store_path.exists() {
+            Some(
+                wax_v2_core::open_store_at_generation(&store_path, historical_generation)
+                    .map_err(runtime_core_error)?
+                    .manifest
+                    .generation,
+            )
+        } else {
+            None
+        };
+        let docstore = Docstore::open_at_generation(root, &manifest, historical_generation)
+            .map_err(|error| RuntimeError::Storage(docstore_error(error)))?;
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            manifest,
+            docstore,
+            text_lane: OnceLock::new(),
+            vector_lane: OnceLock::new(),
+            vector_space_lanes: Mutex::new(HashMap::new()),
+            sparse_lane: OnceLock::new(),
+            embedder: None,
+            vector_scan_threads: default_vector_scan_threads(),
+            writer_lock_timeout: DEFAULT_WRITER_LOCK_TIMEOUT,
+            ingest_run_bytes: wax_v2_text::DEFAULT_TEXT_SPILL_RUN_BYTES,
+            doc_compression: DocCompression::None,
+            store_generation,
+            historical_generation,
+            cursor_store: Mutex::new(None),
+            closed: false,
+        })
+    }
+
+    fn open_created_store(
+        root: &Path,
+        manifest: DatasetPackManifest,
+        store_path: &Path,
+    ) -> Result<Self, RuntimeError> {
+        match Self::open_from_manifest_at_generation(root, manifest, None) {
+            Ok(store) => Ok(store),
+            Err(error) => {
+                let _ = fs::remove_file(store_path);
+                Err(error)
+            }
+        }
+    }
+
+    pub fn search(
+        &mut self,
+        request: RuntimeSearchRequest,
+    ) -> Result<RuntimeSearchResponse, RuntimeError> {
+        if self.closed {
+            return Err(RuntimeError::InvalidRequest(
+                "runtime store is already closed".to_owned(),
+            ));
+        }
+        self.refresh_read_state_if_store_generation_changed()?;
+        self.search_loaded_generation(request)
+    }
+
+    /// Searches the generation this handle has loaded without checking the store for later
+    /// publishes; lanes load lazily behind `&self`, so a pinned handle serves parallel readers.
+    fn search_loaded_generation(
+        &self,
+        mut request: RuntimeSearchRequest,
+    ) -> Result<RuntimeSearchResponse, RuntimeError> {
+        if request
+            .mmr_lambda
+            .is_some_and(|lambda| !(0.0..=1.0).contains(&lambda))
+        {
+            return Err(RuntimeError::InvalidRequest(
+                "mmr_lambda must be between 0 and 1".to_owned(),
+            ));
+        }
+        if let Some(collapse) = &request.collapse_by {
+            if collapse.field.is_empty() {
+                return Err(RuntimeError::InvalidRequest(
+                    "collapse_by requires a metadata field".to_owned(),
+                ));
             }
-            RuntimeSearchMode::Hybrid if request.vector_query.is_none() => {
+            if collapse.max_hits_per_group == 0 {
                 return Err(RuntimeError::InvalidRequest(
-                    "vector_query is required for hybrid search".to_owned(),
+                    "collapse_by max_hits_per_group must be at least 1".to_owned(),
                 ));
             }
-            _ => {}
         }
-        if request.top_k == 0 {
-            return Ok(RuntimeSearchResponse { hits: Vec::new() });
+        if request.cursor.is_some()
+            && (request.mmr_lambda.is_some() || request.collapse_by.is_some())
+        {
+            return Err(RuntimeError::InvalidRequest(
+                "cursor pagination does not support mmr_lambda or collapse_by".to_owned(),
+            ));
         }
-        self.refresh_read_state_if_store_generation_changed()?;
-
-        let doc_ids = match request.mode {
-            RuntimeSearchMode::Text => {
-                let text_query = request.text_query.as_deref().ok_or_else(|| {
-                    RuntimeError::InvalidRequest(
-                        "text_query is required for text search".to_owned(),
-                    )
-                })?;
-                self.ensure_text_lane()?
-                    .search_with_limit(text_query, request.top_k)
+        if request.vector_radius.is_some() && request.mode != RuntimeSearchMode::Vector {
+            return Err(RuntimeError::InvalidRequest(
+                "vector_radius is only supported for vector search".to_owned(),
+            ));
+        }
+        if !request.facets.is_empty()
+            && request.mode == RuntimeSearchMode::Vector
+            && request.vector_radius.is_none()
+        {
+            return Err(RuntimeError::InvalidRequest(
+                "facets on vector search require vector_radius to bound the matching set"
+                    .to_owned(),
+            ));
+        }
+        if request.mode == RuntimeSearchMode::Vector
+            && request.vector_query.is_none()
+            && request.vector_space_queries.is_empty()
+        {
+            if let (Some(embedder), Some(text_query)) = (&self.embedder, &request.text_query) {
+                request.vector_query = Some(embed_with(
+                    embedder.as_ref(),
+                    text_query,
+                    self.manifest.vector_profile.embedding_dimensions as usize,
+                )?);
+                request.vector_embedding = Some(embedder.identity());
             }
-            RuntimeSearchMode::Vector => {
-                let vector_query = request.vector_query.as_deref().ok_or_else(|| {
-                    RuntimeError::InvalidRequest(
-                        "vector_query is required for vector search".to_owned(),
-                    )
-                })?;
-                self.ensure_vector_lane()?
-                    .search_with_query(
-                        vector_query,
-                        request.top_k,
-                        wax_bench_model::VectorQueryMode::Auto,
-                        false,
-                    )
-                    .map_err(RuntimeError::Storage)?
+        }
+        let has_vector_query =
+            request.vector_query.is_some() || !request.vector_space_queries.is_empty();
+        match &request.mode {
+            RuntimeSearchMode::Text if request.text_query.is_none() => {
+                return Err(RuntimeError::InvalidRequest(
+                    "text_query is required for text search".to_owned(),
+                ));
             }
-            RuntimeSearchMode::Hybrid => {
-                let text_query = request.text_query.as_deref().ok_or_else(|| {
-                    RuntimeError::InvalidRequest(
-                        "text_query is required for hybrid search".to_owned(),
-                    )
-                })?;
-                let vector_query = request.vector_query.as_deref().ok_or_else(|| {
-                    RuntimeError::InvalidRequest(
-                        "vector_query is required for hybrid search".to_owned(),
-                    )
-                })?;
-                let live_doc_count = self.live_doc_count()?;
-                let text_limit = hybrid_text_candidate_limit(request.top_k, live_doc_count);
-                let text_hits = self
-                    .ensure_text_lane()?
-                    .search_with_limit(text_query, text_limit);
-                let report = hybrid_search_with_diagnostics(
-                    &text_hits,
-                    self.ensure_vector_lane()?,
-                    vector_query,
-                    request.top_k,
-                    wax_bench_model::VectorQueryMode::Auto,
-                    false,
-                )
-                .map_err(RuntimeError::Storage)?;
-                report.fused_hits
+            RuntimeSearchMode::Vector if !has_vector_query => {
+                return Err(RuntimeError::InvalidRequest(
+                    "vector_query is required for vector search unless an embedder is configured for text_query"
+                        .to_owned(),
+                ));
+            }
+            RuntimeSearchMode::Hybrid if request.text_query.is_none() => {
+                return Err(RuntimeError::InvalidRequest(
+                    "text_query is required for hybrid search".to_owned(),
+                ));
+            }
+            RuntimeSearchMode::Hybrid if !has_vector_query && request.sparse_query.is_none() => {
+                return Err(RuntimeError::InvalidRequest(
+                    "vector_query is required for hybrid search unless sparse_query is provided"
+                        .to_owned(),
+                ));
+            }
+            RuntimeSearchMode::Sparse if request.sparse_query.is_none() => {
+                return Err(RuntimeError::InvalidRequest(
+                    "sparse_query is required for sparse search".to_owned(),
+                ));
+            }
+            _ => {}
+        }
+        reject_duplicate_vector_spaces(&request)?;
+        if let Some(cursor) = request.cursor.clone() {
+            return self.search_cursor_page(request, &cursor);
+        }
+        if request.top_k == 0 && request.facets.is_empty() {
+            return Ok(RuntimeSearchResponse {
+                hits: Vec::new(),
+                groups: Vec::new(),
+                facets: Vec::new(),
+                next_cursor: None,
+            });
+        }
+        let facets = request.facets.clone();
+        let mut facet_matches = (!facets.is_empty()).then(Vec::new);
+        let mut response = if request.top_k == 0 {
+            self.ranked_hits_matching(
+                &request,
+                fusion_lane_depth(request.top_k),
+                facet_matches.as_mut(),
+            )?;
+            RuntimeSearchResponse {
+                hits: Vec::new(),
+                groups: Vec::new(),
+                facets: Vec::new(),
+                next_cursor: None,
             }
+        } else if request.mmr_lambda.is_none() && request.collapse_by.is_none() {
+            let fusion_depth = fusion_lane_depth(request.top_k);
+            let ranked =
+                self.ranked_hits_matching(&request, fusion_depth, facet_matches.as_mut())?;
+            self.page_response(
+                ranked,
+                &request,
+                search_fingerprint(&request),
+                fusion_depth,
+                0,
+            )?
+        } else {
+            self.ranked_response(request, facet_matches.as_mut())?
         };
+        if let Some(matching) = facet_matches {
+            response.facets = self.facet_counts(&facets, matching)?;
+        }
+        Ok(response)
+    }
 
-        Ok(RuntimeSearchResponse {
-            hits: self.hydrate_hits(&doc_ids, request.include_preview)?,
-        })
+    /// MMR and collapse response; `facet_matches` is filled by the first candidate pass.
+    fn ranked_response(
+        &self,
+        mut request: RuntimeSearchRequest,
+        mut facet_matches: Option<&mut Vec<String>>,
+    ) -> Result<RuntimeSearchResponse, RuntimeError> {
+        let top_k = request.top_k;
+        request.top_k = mmr_candidate_limit(top_k);
+        loop {
+            let candidates = without_scores(self.ranked_hits_matching(
+                &request,
+                fusion_lane_depth(request.top_k),
+                facet_matches.take(),
+            )?);
+            let candidates = match request.mmr_lambda {
+                Some(lambda) => self.diversified_hits(&candidates, lambda, candidates.len())?,
+                None => candidates,
+            };
+            let Some(collapse) = &request.collapse_by else {
+                let doc_ids = candidates.into_iter().take(top_k).collect::<Vec<_>>();
+                return Ok(RuntimeSearchResponse {
+                    hits: self.hydrate_hits(&doc_ids, request.include_preview)?,
+                    groups: Vec::new(),
+                    facets: Vec::new(),
+                    next_cursor: None,
+                });
+            };
+            let groups = self.collapsed_groups(&candidates, collapse, top_k)?;
+            // Short candidate lists mean the lanes ran dry; refetching cannot add groups.
+            let exhausted =
+                candidates.len() < request.top_k || request.top_k >= self.live_doc_count()?;
+            if groups.len() < top_k && !exhausted {
+                request.top_k = request.top_k.saturating_mul(2);
+                continue;
+            }
+            let doc_ids = groups
+                .iter()
+                .flat_map(|group| group.doc_ids.iter().cloned())
+                .collect::<Vec<_>>();
+            return Ok(RuntimeSearchResponse {
+                hits: self.hydrate_hits(&doc_ids, request.include_preview)?,
+                groups: groups
+                    .into_iter()
+                    .map(|group| RuntimeSearchGroup {
+                        key: group.key,
+                        doc_ids: group.doc_ids,
+                        hit_count: group.hit_count,
+                    })
+                    .collect(),
+                facets: Vec::new(),
+                next_cursor: None,
+            });
+        }
+    }
+
+    pub fn search_batch(
+        &mut self,
+        request: RuntimeBatchSearchRequest,
+    ) -> Result<RuntimeBatchSearchResponse, RuntimeError> {
+        if self.closed {
+            return Err(RuntimeError::InvalidRequest(
+                "runtime store is already closed".to_owned(),
+            ));
+        }
+        self.refresh_read_state_if_store_generation_changed()?;
+
+        let lane = self.checked_vector_space_lane(&request.space, request.embedding.as_ref())?;
+        if lane.is_multi_vector() {
+            return Err(RuntimeError::InvalidRequest(format!(
+                "vector space {} stores multi-vectors; batch search takes single-vector spaces",
+                request.space
+            )));
+        }
+        let hit_lists = lane
+            .search_batch(&request.vectors, request.top_k)
+            .map_err(RuntimeError::InvalidRequest)?;
+        let results = hit_lists
+            .iter()
+            .map(|doc_ids| {
+                Ok(RuntimeSearchResponse {
+                    hits: self.hydrate_hits(doc_ids, request.include_preview)?,
+                    groups: Vec::new(),
+                    facets: Vec::new(),
+                    next_cursor: None,
+                })
+            })
+            .collect::<Result<Vec<_>, RuntimeError>>()?;
+        Ok(RuntimeBatchSearchResponse { results })
     }
 
     pub fn close(&mut self) -> Result<(), RuntimeError> {
@@ -392,19 +1225,25 @@ impl RuntimeStore {
 
     fn refresh_read_state(&mut self) -> Result<(), RuntimeError> {
         let store_path = self.store_path();
-        self.store_generation = if store_path.exists() {
+        let store_generation = if store_path.exists() {
             Some(store_manifest_generation_from_store(&store_path)?)
         } else {
             None
         };
         self.docstore = Docstore::open(&self.root, &self.manifest)
             .map_err(|error| RuntimeError::Storage(docstore_error(error)))?;
-        self.text_lane = None;
-        self.vector_lane = None;
+        self.store_generation = store_generation;
+        self.text_lane = OnceLock::new();
+        self.vector_lane = OnceLock::new();
+        self.vector_space_lanes = Mutex::new(HashMap::new());
+        self.sparse_lane = OnceLock::new();
         Ok(())
     }
 
     fn refresh_read_state_if_store_generation_changed(&mut self) -> Result<(), RuntimeError> {
+        if self.historical_generation.is_some() {
+            return Ok(());
+        }
         let store_path = self.store_path();
         if !store_path.exists() {
             if self.store_generation.is_some() {
@@ -419,71 +1258,373 @@ impl RuntimeStore {
         Ok(())
     }
 
-    fn ensure_text_lane(&mut self) -> Result<&TextLane, RuntimeError> {
-        if self.text_lane.is_none() {
-            self.text_lane =
-                Some(TextLane::load(&self.root, &self.manifest).map_err(RuntimeError::Storage)?);
+    fn ensure_text_lane(&self) -> Result<&TextLane, RuntimeError> {
+        if let Some(lane) = self.text_lane.get() {
+            return Ok(lane);
         }
-        self.text_lane
-            .as_ref()
-            .ok_or_else(|| RuntimeError::Storage("text lane not materialized".to_owned()))
+        let lane =
+            TextLane::load_at_generation(&self.root, &self.manifest, self.historical_generation)
+                .map_err(RuntimeError::Storage)?;
+        Ok(self.text_lane.get_or_init(|| lane))
     }
 
-    fn ensure_vector_lane(&mut self) -> Result<&mut VectorLane, RuntimeError> {
-        if self.vector_lane.is_none() {
-            self.vector_lane = Some(
-                VectorLane::load_runtime(
-                    &self.root,
-                    &self.manifest,
-                    wax_bench_model::VectorQueryMode::Auto,
-                )
-                .map_err(RuntimeError::Storage)?,
-            );
+    fn ensure_vector_lane(&self) -> Result<Arc<VectorLane>, RuntimeError> {
+        if let Some(lane) = self.vector_lane.get() {
+            return Ok(Arc::clone(lane));
         }
-        self.vector_lane
-            .as_mut()
-            .ok_or_else(|| RuntimeError::Storage("vector lane not materialized".to_owned()))
+        let lane = VectorLane::load_runtime_at_generation(
+            &self.root,
+            &self.manifest,
+            wax_bench_model::VectorQueryMode::Auto,
+            self.historical_generation,
+        )
+        .map_err(RuntimeError::Storage)?
+        .with_exact_scan_threads(self.vector_scan_threads);
+        Ok(Arc::clone(self.vector_lane.get_or_init(|| Arc::new(lane))))
     }
 
-    fn live_doc_count(&self) -> Result<usize, RuntimeError> {
-        self.docstore
-            .load_document_ids()
-            .map(|doc_ids| doc_ids.len())
-            .map_err(|error| RuntimeError::Storage(docstore_error(error)))
+    fn ensure_sparse_lane(&self) -> Result<&SparseLane, RuntimeError> {
+        if let Some(lane) = self.sparse_lane.get() {
+            return Ok(lane);
+        }
+        let lane = SparseLane::load_at_generation(&self.root, self.historical_generation)
+            .map_err(RuntimeError::Storage)?;
+        Ok(self.sparse_lane.get_or_init(|| lane))
     }
 
-    fn hydrate_hits(
-        &self,
-        doc_ids: &[String],
-        include_preview: bool,
-    ) -> Result<Vec<RuntimeSearchHit>, RuntimeError> {
-        if !include_preview {
-            return Ok(doc_ids
-                .iter()
-                .cloned()
-                .map(|doc_id| RuntimeSearchHit {
-                    doc_id,
-                    preview: None,
-                })
-                .collect());
+    /// Loads named spaces outside the map lock, so readers loading different spaces do not wait
+    /// on each other; a reader that loses a race for the same space keeps the first lane.
+    fn ensure_vector_space_lane(&self, space: &str) -> Result<Arc<VectorLane>, RuntimeError> {
+        if space == DEFAULT_VECTOR_SPACE {
+            return self.ensure_vector_lane();
+        }
+        if let Some(lane) = self
+            .vector_space_lanes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(space)
+        {
+            return Ok(Arc::clone(lane));
         }
+        let lane = VectorLane::load_runtime_space_at_generation(
+            &self.root,
+            &self.manifest,
+            space,
+            wax_bench_model::VectorQueryMode::Auto,
+            self.historical_generation,
+        )
+        .map_err(RuntimeError::Storage)?
+        .with_exact_scan_threads(self.vector_scan_threads);
+        Ok(Arc::clone(
+            self.vector_space_lanes
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(space.to_owned())
+                .or_insert_with(|| Arc::new(lane)),
+        ))
+    }
 
-        let documents = self
-            .docstore
-            .load_documents_by_id(doc_ids)
-            .map_err(|error| RuntimeError::Storage(docstore_error(error)))?;
-        Ok(doc_ids
+    /// Hit lists of every queried vector space, each `depth` hits deep. With `facet_matches`,
+    /// radius searches run up to the live document count and record every document inside the
+    /// radius before the lists are cut back to `depth`.
+    fn vector_hit_lists(
+        &self,
+        request: &RuntimeSearchRequest,
+        depth: usize,
+        mut facet_matches: Option<&mut Vec<String>>,
+    ) -> Result<Vec<Vec<(String, f64)>>, RuntimeError> {
+        let radius_limit = match facet_matches {
+            Some(_) if request.vector_radius.is_some() => self.live_doc_count()?,
+            _ => depth,
+        };
+        let default_query = request
+            .vector_query
+            .clone()
+            .map(|vector| (DEFAULT_VECTOR_SPACE, vec![vector]));
+        let queries = default_query
             .iter()
-            .cloned()
-            .map(|doc_id| RuntimeSearchHit {
-                preview: documents
-                    .get(&doc_id)
-                    .and_then(|document| document.get("text"))
-                    .and_then(|value| value.as_str())
-                    .map(ToOwned::to_owned),
-                doc_id,
-            })
-            .collect())
+            .map(|(space, vectors)| (*space, vectors, request.vector_embedding.as_ref()))
+            .chain(request.vector_space_queries.iter().map(|query| {
+                (
+                    query.space.as_str(),
+                    &query.vectors,
+                    query.embedding.as_ref(),
+                )
+            }));
+        let mut hit_lists = Vec::with_capacity(request.vector_space_queries.len() + 1);
+        for (space, vectors, embedding) in queries {
+            let top_k = depth;
+            let lane = self.checked_vector_space_lane(space, embedding)?;
+            let hits = if lane.is_multi_vector() {
+                if request.vector_radius.is_some() {
+                    return Err(RuntimeError::InvalidRequest(format!(
+                        "vector space {space} stores multi-vectors and does not support vector_radius"
+                    )));
+                }
+                if vectors.is_empty() {
+                    return Err(RuntimeError::InvalidRequest(format!(
+                        "vector space {space} query requires at least one vector"
+                    )));
+                }
+                lane.search_max_sim_scored(vectors, top_k)
+                    .map_err(RuntimeError::Storage)?
+            } else {
+                let [vector] = vectors.as_slice() else {
+                    return Err(RuntimeError::InvalidRequest(format!(
+                        "vector space {space} stores single vectors and takes exactly one query vector"
+                    )));
+                };
+                match request.vector_radius {
+                    Some(radius) => {
+                        let mut hits = lane
+                            .search_within_scored(
+                                vector,
+                                vector_radius(radius),
+                                Some(radius_limit),
+                                wax_bench_model::VectorQueryMode::Auto,
+                            )
+                            .map_err(RuntimeError::InvalidRequest)?;
+                        if let Some(matches) = facet_matches.as_deref_mut() {
+                            matches.extend(hits.iter().map(|(doc_id, _)| doc_id.clone()));
+                        }
+                        hits.truncate(top_k);
+                        hits
+                    }
+                    None => lane
+                        .search_scored_with_query(
+                            vector,
+                            top_k,
+                            wax_bench_model::VectorQueryMode::Auto,
+                            false,
+                        )
+                        .map_err(RuntimeError::Storage)?,
+                }
+            };
+            hit_lists.push(widened_scores(hits));
+        }
+        Ok(hit_lists)
+    }
+
+    /// Ranked hits with the score that orders them: the lane score for a single lane, the fused
+    /// score otherwise. Every lane breaks score ties by doc_id, so (score, doc_id) orders totally.
+    fn ranked_hits(
+        &self,
+        request: &RuntimeSearchRequest,
+    ) -> Result<Vec<(String, f64)>, RuntimeError> {
+        self.ranked_hits_matching(request, fusion_lane_depth(request.top_k), None)
+    }
+
+    /// [`Self::ranked_hits`] that also collects the facet matching set in the same pass: the
+    /// text and sparse lanes, and vector radius searches, run up to the live document count and
+    /// record every match in `facet_matches` before their lists are cut back to the budget the
+    /// ranking uses. Vector lanes without a radius rank every document and record nothing.
+    /// Lanes that feed reciprocal rank fusion run `fusion_depth` deep and the fused list is cut
+    /// at `top_k`; a single lane runs `top_k` deep.
+    fn ranked_hits_matching(
+        &self,
+        request: &RuntimeSearchRequest,
+        fusion_depth: usize,
+        mut facet_matches: Option<&mut Vec<String>>,
+    ) -> Result<Vec<(String, f64)>, RuntimeError> {
+        let match_limit = match facet_matches {
+            Some(_) => self.live_doc_count()?,
+            None => 0,
+        };
+        Ok(match request.mode {
+            RuntimeSearchMode::Text => {
+                let text_query = request.text_query.as_deref().ok_or_else(|| {
+                    RuntimeError::InvalidRequest(
+                        "text_query is required for text search".to_owned(),
+                    )
+                })?;
+                let mut hits = widened_scores(
+                    self.ensure_text_lane()?
+                        .search_scored_with_limit(text_query, request.top_k.max(match_limit)),
+                );
+                record_facet_matches(facet_matches, &hits);
+                hits.truncate(request.top_k);
+                hits
+            }
+            RuntimeSearchMode::Vector => {
+                let depth = if fuses_hit_lists(request) {
+                    fusion_depth
+                } else {
+                    request.top_k
+                };
+                let mut vector_hit_lists = self.vector_hit_lists(request, depth, facet_matches)?;
+                if vector_hit_lists.len() == 1 {
+                    vector_hit_lists.remove(0)
+                } else {
+                    let hit_lists = vector_hit_lists
+                        .into_iter()
+                        .map(without_scores)
+                        .collect::<Vec<_>>();
+                    reciprocal_rank_fusion_scored(&hit_lists, request.top_k)
+                }
+            }
+            RuntimeSearchMode::Hybrid => {
+                let text_query = request.text_query.as_deref().ok_or_else(|| {
+                    RuntimeError::InvalidRequest(
+                        "text_query is required for hybrid search".to_owned(),
+                    )
+                })?;
+                let live_doc_count = self.live_doc_count()?;
+                let text_limit = hybrid_text_candidate_limit(fusion_depth, live_doc_count);
+                let mut text_hits = widened_scores(
+                    self.ensure_text_lane()?
+                        .search_scored_with_limit(text_query, text_limit.max(match_limit)),
+                );
+                record_facet_matches(facet_matches.as_deref_mut(), &text_hits);
+                text_hits.truncate(text_limit);
+                let mut hit_lists = vec![without_scores(text_hits)];
+                hit_lists.extend(
+                    self.vector_hit_lists(request, fusion_depth, None)?
+                        .into_iter()
+                        .map(without_scores),
+                );
+                if let Some(sparse_query) = request.sparse_query.as_deref() {
+                    let mut sparse_hits = self
+                        .ensure_sparse_lane()?
+                        .search_scored(sparse_query, fusion_depth.max(match_limit))
+                        .map_err(RuntimeError::InvalidRequest)?;
+                    record_facet_matches(facet_matches, &sparse_hits);
+                    sparse_hits.truncate(fusion_depth);
+                    hit_lists.push(without_scores(sparse_hits));
+                }
+                reciprocal_rank_fusion_scored(&hit_lists, request.top_k)
+            }
+            RuntimeSearchMode::Sparse => {
+                let sparse_query = request.sparse_query.as_deref().ok_or_else(|| {
+                    RuntimeError::InvalidRequest(
+                        "sparse_query is required for sparse search".to_owned(),
+                    )
+                })?;
+                let mut hits = self
+                    .ensure_sparse_lane()?
+                    .search_scored(sparse_query, request.top_k.max(match_limit))
+                    .map_err(RuntimeError::InvalidRequest)?;
+                record_facet_matches(facet_matches, &hits);
+                hits.truncate(request.top_k);
+                hits
+            }
+        })
+    }
+
+    fn diversified_hits(
+        &self,
+        doc_ids: &[String],
+        lambda: f32,
+        top_k: usize,
+    ) -> Result<Vec<String>, RuntimeError> {
+        let vectors = doc_ids
+            .iter()
+            .cloned()
+            .zip(self.ensure_vector_lane()?.document_vectors(doc_ids))
+            .filter_map(|(doc_id, vector)| vector.map(|vector| (doc_id, vector)))
+            .collect::<HashMap<_, _>>();
+        Ok(maximal_marginal_relevance(doc_ids, &vectors, lambda, top_k))
+    }
+
+    /// Loads the lane of `space` after checking the query's embedding identity against it.
+    fn checked_vector_space_lane(
+        &self,
+        space: &str,
+        embedding: Option<&RuntimeEmbeddingIdentity>,
+    ) -> Result<Arc<VectorLane>, RuntimeError> {
+        let manifest_identity = self.embedding_identity();
+        let lane = self.ensure_vector_space_lane(space)?;
+        // Lanes served from compatibility sidecars carry the manifest's declared identity.
+        let stored = match lane.embedding_identity() {
+            Some(identity) => Some(runtime_embedding_identity(identity.clone())),
+            None if space == DEFAULT_VECTOR_SPACE => Some(manifest_identity),
+            None => None,
+        };
+        if stored.as_ref() != embedding {
+            return Err(RuntimeError::EmbeddingMismatch(Box::new(
+                RuntimeEmbeddingMismatch {
+                    space: space.to_owned(),
+                    stored,
+                    requested: embedding.cloned(),
+                },
+            )));
+        }
+        Ok(lane)
+    }
+
+    fn vector_encoding(&self) -> VectorEncoding {
+        VectorEncoding::from_embedding_dtype(&self.manifest.vector_profile.embedding_dtype)
+    }
+
+    /// Identity assumed for default-space rows that predate recorded embedding identities.
+    fn legacy_default_embedding(&self) -> EmbeddingIdentity {
+        let identity = self.embedding_identity();
+        EmbeddingIdentity::new(
+            identity.spec_id,
+            identity.model_version,
+            identity.model_hash,
+        )
+    }
+
+    fn default_vector_space(
+        &self,
+        embedding: RuntimeEmbeddingIdentity,
+    ) -> Result<VectorSpaceSpec, RuntimeError> {
+        Ok(VectorSpaceSpec::new(
+            DEFAULT_VECTOR_SPACE,
+            self.manifest.vector_profile.embedding_dimensions as usize,
+            VectorMetric::Cosine,
+        )
+        .with_encoding(self.vector_encoding())
+        .with_embedding(validated_embedding_identity(embedding)?))
+    }
+
+    fn live_doc_count(&self) -> Result<usize, RuntimeError> {
+        self.docstore
+            .load_document_ids()
+            .map(|doc_ids| doc_ids.len())
+            .map_err(|error| RuntimeError::Storage(docstore_error(error)))
+    }
+
+    /// Hydrates full documents; every payload the runtime parses goes through here.
+    fn load_documents(
+        &self,
+        doc_ids: &[String],
+    ) -> Result<HashMap<String, serde_json::Value>, RuntimeError> {
+        hydration_probe::record(doc_ids.len());
+        self.docstore
+            .load_documents_by_id(doc_ids)
+            .map_err(|error| RuntimeError::Storage(docstore_error(error)))
+    }
+
+    fn hydrate_hits(
+        &self,
+        doc_ids: &[String],
+        include_preview: bool,
+    ) -> Result<Vec<RuntimeSearchHit>, RuntimeError> {
+        if !include_preview {
+            return Ok(doc_ids
+                .iter()
+                .cloned()
+                .map(|doc_id| RuntimeSearchHit {
+                    doc_id,
+                    preview: None,
+                })
+                .collect());
+        }
+
+        let documents = self.load_documents(doc_ids)?;
+        Ok(doc_ids
+            .iter()
+            .cloned()
+            .map(|doc_id| RuntimeSearchHit {
+                preview: documents
+                    .get(&doc_id)
+                    .and_then(|document| document.get("text"))
+                    .and_then(|value| value.as_str())
+                    .map(ToOwned::to_owned),
+                doc_id,
+            })
+            .collect())
     }
 }
 
@@ -504,36 +1645,130 @@ impl RuntimeStoreWriter<'_> {
         )?;
 
         let expected_generation = store_manifest_generation_from_store(&store_path)?;
+        let incoming_doc_ids = documents
+            .iter()
+            .map(|document| document.doc_id.clone())
+            .collect::<std::collections::HashSet<_>>();
         let documents = self.merged_raw_documents(&store_path, expected_generation, documents)?;
+        let vectors = match self.store.embedder.clone() {
+            Some(embedder) => Some((
+                embedder.identity(),
+                self.embedded_document_vectors(
+                    &store_path,
+                    embedder.as_ref(),
+                    &documents,
+                    &incoming_doc_ids,
+                )?,
+            )),
+            None => None,
+        };
+        let carried_doc_ids = documents
+            .iter()
+            .filter(|document| !incoming_doc_ids.contains(&document.doc_id))
+            .map(|document| document.doc_id.clone())
+            .collect();
         self.publish_raw_snapshot_with_expected_generation(
             store_path,
             expected_generation,
             documents,
-            None,
+            carried_doc_ids,
+            vectors,
         )
     }
 
+    /// Embeds the incoming documents and any retained document without a default-space vector;
+    /// retained documents keep their current vectors, which must come from the same embedder.
+    fn embedded_document_vectors(
+        &self,
+        store_path: &Path,
+        embedder: &dyn Embedder,
+        documents: &[NewDocument],
+        incoming_doc_ids: &std::collections::HashSet<String>,
+    ) -> Result<Vec<NewDocumentVector>, RuntimeError> {
+        let dimensions = self.store.manifest.vector_profile.embedding_dimensions as usize;
+        let mut current_vectors = HashMap::new();
+        if let Some(current) =
+            wax_v2_vector::load_current_store_vector_rows(store_path, DEFAULT_VECTOR_SPACE)
+                .map_err(RuntimeError::Storage)?
+        {
+            let retains_rows = current
+                .rows
+                .iter()
+                .any(|(doc_id, _)| !incoming_doc_ids.contains(doc_id));
+            let embedding = validated_embedding_identity(embedder.identity())?;
+            let legacy = self.store.legacy_default_embedding();
+            ensure_embedding_unchanged(&current.spec, Some(&legacy), &embedding, retains_rows)?;
+            current_vectors.extend(current.rows);
+        }
+        documents
+            .iter()
+            .map(|document| {
+                let retained = (!incoming_doc_ids.contains(&document.doc_id))
+                    .then(|| current_vectors.remove(&document.doc_id))
+                    .flatten();
+                let values = match retained {
+                    Some(values) => values,
+                    None => embed_with(embedder, &document.text, dimensions)?,
+                };
+                Ok(NewDocumentVector::new(document.doc_id.clone(), values))
+            })
+            .collect()
+    }
+
+    /// Replaces every document; `vectors`, when given, replace the default vector space and are
+    /// recorded under the embedding identity they come with. Other vector spaces keep their rows
+    /// for documents whose text is unchanged.
     pub fn publish_raw_snapshot(
         self,
         documents: Vec<NewDocument>,
-        vectors: Option<Vec<NewDocumentVector>>,
+        vectors: Option<(RuntimeEmbeddingIdentity, Vec<NewDocumentVector>)>,
     ) -> Result<RuntimePublishReport, RuntimeError> {
         let store_path = self.require_existing_store()?;
         let expected_generation = store_manifest_generation_from_store(&store_path)?;
+        self.store.refresh_read_state()?;
+        ensure_store_generation_unchanged_from_store(&store_path, expected_generation)?;
+        let carried_doc_ids = self.unchanged_document_ids(&store_path, &documents)?;
         self.publish_raw_snapshot_with_expected_generation(
             store_path,
             expected_generation,
             documents,
+            carried_doc_ids,
             vectors,
         )
     }
 
+    /// Doc ids of `documents` whose text matches the current store, so their vectors stay valid.
+    fn unchanged_document_ids(
+        &self,
+        store_path: &Path,
+        documents: &[NewDocument],
+    ) -> Result<std::collections::HashSet<String>, RuntimeError> {
+        if latest_doc_segment_identity_from_store(store_path)?.is_none() {
+            return Ok(std::collections::HashSet::new());
+        }
+        let doc_ids = documents
+            .iter()
+            .map(|document| document.doc_id.clone())
+            .collect::<Vec<_>>();
+        let current_documents = self.store.load_documents(&doc_ids)?;
+        let mut unchanged = std::collections::HashSet::new();
+        for document in documents {
+            if let Some(value) = current_documents.get(&document.doc_id) {
+                if new_document_from_value(value)?.text == document.text {
+                    unchanged.insert(document.doc_id.clone());
+                }
+            }
+        }
+        Ok(unchanged)
+    }
+
     fn publish_raw_snapshot_with_expected_generation(
         self,
         store_path: PathBuf,
         expected_generation: u64,
         documents: Vec<NewDocument>,
-        vectors: Option<Vec<NewDocumentVector>>,
+        carried_doc_ids: std::collections::HashSet<String>,
+        vectors: Option<(RuntimeEmbeddingIdentity, Vec<NewDocumentVector>)>,
     ) -> Result<RuntimePublishReport, RuntimeError> {
         if documents.is_empty() {
             return Err(RuntimeError::InvalidRequest(
@@ -546,13 +1781,28 @@ impl RuntimeStoreWriter<'_> {
         )?;
         self.store.refresh_read_state()?;
         ensure_store_generation_unchanged_from_store(&store_path, expected_generation)?;
-        let remove_existing_vector_segment =
-            vectors.is_none() && store_has_vector_segment(&store_path, expected_generation)?;
 
+        let document_ids = documents
+            .iter()
+            .map(|document| document.doc_id.clone())
+            .collect::<Vec<_>>();
+        // Without vectors to publish or carry, no row needs a wax doc id binding.
+        let doc_id_map = if vectors.is_none() && carried_doc_ids.is_empty() {
+            DocIdMap::from_document_order(&document_ids)
+        } else {
+            self.store
+                .docstore
+                .build_doc_id_map()
+                .and_then(|doc_id_map| doc_id_map.extend_to_cover_document_order(&document_ids))
+        }
+        .map_err(|error| RuntimeError::Storage(docstore_error(error)))?;
         let ordered_documents = raw_ordered_documents(&documents);
-        let doc_pending =
-            wax_v2_docstore::prepare_raw_documents_segment(&store_path, ordered_documents)
-                .map_err(|error| RuntimeError::Storage(docstore_error(error)))?;
+        let doc_pending = wax_v2_docstore::prepare_raw_documents_segment_with_compression(
+            &store_path,
+            ordered_documents,
+            self.store.doc_compression,
+        )
+        .map_err(|error| RuntimeError::Storage(docstore_error(error)))?;
         let mut text_pending = wax_v2_text::prepare_text_segment_from_document_refs(
             documents
                 .iter()
@@ -566,27 +1816,17 @@ impl RuntimeStoreWriter<'_> {
         let mut pending_segments = vec![doc_pending, text_pending];
         let mut published_families = vec![RuntimePublishFamily::Doc, RuntimePublishFamily::Text];
 
-        if let Some(vectors) = vectors {
+        if let Some((embedding, vectors)) = vectors {
             if vectors.is_empty() {
                 return Err(RuntimeError::InvalidRequest(
                     "publish_raw_snapshot vectors must be non-empty when provided".to_owned(),
                 ));
             }
-            if vectors.len() != documents.len() {
-                return Err(RuntimeError::InvalidRequest(format!(
-                    "publish_raw_snapshot requires {} vectors to match the provided document set",
-                    documents.len()
-                )));
-            }
             reject_duplicate_doc_ids(
                 vectors.iter().map(|vector| vector.doc_id.as_str()),
                 "publish_raw_snapshot vectors",
             )?;
 
-            let document_ids = documents
-                .iter()
-                .map(|document| document.doc_id.clone())
-                .collect::<Vec<_>>();
             let document_id_set = document_ids
                 .iter()
                 .map(String::as_str)
@@ -603,16 +1843,9 @@ impl RuntimeStoreWriter<'_> {
                 )));
             }
 
-            let doc_id_map = self
-                .store
-                .docstore
-                .build_doc_id_map()
-                .map_err(|error| RuntimeError::Storage(docstore_error(error)))?
-                .extend_to_cover_document_order(&document_ids)
-                .map_err(|error| RuntimeError::Storage(docstore_error(error)))?;
             let (_, _, vector_inputs) = vector_inputs_sorted_by_wax_doc_id(vectors, &doc_id_map)?;
-            let mut vector_pending = wax_v2_vector::prepare_raw_vector_segment(
-                self.store.manifest.vector_profile.embedding_dimensions as usize,
+            let mut vector_pending = wax_v2_vector::prepare_raw_vector_segment_for_space(
+                &self.store.default_vector_space(embedding)?,
                 &vector_inputs,
             )
             .map_err(RuntimeError::Storage)?;
@@ -622,20 +1855,24 @@ impl RuntimeStoreWriter<'_> {
             published_families.push(RuntimePublishFamily::Vector);
         }
 
-        let opened = if remove_existing_vector_segment {
-            wax_v2_core::publish_segments_replacing_families_with_precondition(
-                &store_path,
-                pending_segments,
-                &[wax_v2_core::SegmentKind::Vec],
-                |manifest| ensure_store_generation_unchanged(manifest, expected_generation),
-            )
-        } else {
-            wax_v2_core::publish_segments_with_precondition(
-                &store_path,
-                pending_segments,
-                |manifest| ensure_store_generation_unchanged(manifest, expected_generation),
-            )
-        }
+        let replaced_space = published_families
+            .contains(&RuntimePublishFamily::Vector)
+            .then_some(DEFAULT_VECTOR_SPACE);
+        let mut carried = carried_row_segments(
+            &store_path,
+            |doc_id| carried_doc_ids.contains(doc_id),
+            &doc_id_map,
+            replaced_space,
+            &mut published_families,
+        )?;
+        pending_segments.append(&mut carried.pending);
+        let opened = wax_v2_core::publish_segments_retaining_with_precondition(
+            &store_path,
+            pending_segments,
+            |segment| carried.retains(segment),
+            |manifest| ensure_store_generation_unchanged(manifest, expected_generation),
+            self.store.writer_lock_timeout,
+        )
         .map_err(runtime_core_error)?;
 
         self.store.refresh_read_state()?;
@@ -679,11 +1916,7 @@ impl RuntimeStoreWriter<'_> {
             .filter(|doc_id| !incoming_by_doc_id.contains_key(doc_id.as_str()))
             .cloned()
             .collect::<Vec<_>>();
-        let current_documents = self
-            .store
-            .docstore
-            .load_documents_by_id(&retained_doc_ids)
-            .map_err(|error| RuntimeError::Storage(docstore_error(error)))?;
+        let current_documents = self.store.load_documents(&retained_doc_ids)?;
         let mut merged = Vec::with_capacity(current_doc_ids.len() + incoming_by_doc_id.len());
         for doc_id in current_doc_ids {
             if let Some(document) = incoming_by_doc_id.remove(&doc_id) {
@@ -710,47 +1943,161 @@ impl RuntimeStoreWriter<'_> {
                 .into_iter()
                 .map(|(doc_id, values)| NewDocumentVector::new(doc_id, values))
                 .collect::<Vec<_>>();
-        self.publish_raw_snapshot(documents, Some(vectors))
+        // The pack's vectors were produced by the embedding model its manifest declares.
+        let embedding = self.store.embedding_identity();
+        self.publish_raw_snapshot(documents, Some((embedding, vectors)))
     }
 
     pub fn import_compatibility_snapshot(self) -> Result<RuntimePublishReport, RuntimeError> {
         self.publish_staged_compatibility_snapshot()
     }
 
+    /// Upserts vectors into the default vector space. `embedding` names the model that produced
+    /// them and must match the identity of the rows already in the space.
     pub fn publish_raw_vectors(
         self,
+        embedding: RuntimeEmbeddingIdentity,
+        vectors: Vec<NewDocumentVector>,
+    ) -> Result<RuntimePublishReport, RuntimeError> {
+        let space = self.store.default_vector_space(embedding)?;
+        self.publish_raw_vectors_for_space(space, vectors, None)
+    }
+
+    /// Publishes vectors into a named vector space with its own dimensions and metric. Other
+    /// spaces, including the default one, are left untouched. Document publishes keep the space's
+    /// rows for unchanged documents; new or changed documents need their vectors republished.
+    pub fn publish_raw_vectors_to_space(
+        self,
+        space: RuntimeVectorSpace,
         vectors: Vec<NewDocumentVector>,
+    ) -> Result<RuntimePublishReport, RuntimeError> {
+        if space.multi_vector {
+            return Err(RuntimeError::InvalidRequest(format!(
+                "vector space {} is multi-vector; use publish_raw_multi_vectors_to_space",
+                space.name
+            )));
+        }
+        let space = validated_named_vector_space(space, "publish_raw_vectors_to_space")?;
+        self.publish_raw_vectors_for_space(space, vectors, None)
+    }
+
+    /// Publishes several vectors per document into a named multi-vector space. Documents are
+    /// upserted the same way as [`Self::publish_raw_vectors_to_space`]; search ranks them by
+    /// MaxSim over their sub-vectors.
+    pub fn publish_raw_multi_vectors_to_space(
+        self,
+        space: RuntimeVectorSpace,
+        vectors: Vec<NewDocumentMultiVector>,
+    ) -> Result<RuntimePublishReport, RuntimeError> {
+        let space = validated_named_vector_space(space, "publish_raw_multi_vectors_to_space")?;
+        if let Some(vector) = vectors.iter().find(|vector| vector.vectors.is_empty()) {
+            return Err(RuntimeError::InvalidRequest(format!(
+                "publish_raw_multi_vectors_to_space requires at least one vector for {}",
+                vector.doc_id
+            )));
+        }
+        let doc_ids = vectors
+            .iter()
+            .map(|vector| vector.doc_id.clone())
+            .collect::<Vec<_>>();
+        self.publish_vector_space_rows(&space, true, doc_ids, None, |store_path, doc_id_map| {
+            let vectors = upserted_space_multi_vectors(store_path, &space, vectors)?;
+            let mut vector_inputs = vectors
+                .into_iter()
+                .map(|vector| {
+                    let wax_doc_id = doc_id_map.wax_doc_id(&vector.doc_id).ok_or_else(|| {
+                        RuntimeError::Storage(format!(
+                            "missing wax doc id binding for {}",
+                            vector.doc_id
+                        ))
+                    })?;
+                    Ok((wax_doc_id, vector.doc_id, vector.vectors))
+                })
+                .collect::<Result<Vec<_>, RuntimeError>>()?;
+            vector_inputs.sort_by_key(|(wax_doc_id, _, _)| *wax_doc_id);
+            wax_v2_vector::prepare_raw_multi_vector_segment(&space, &vector_inputs)
+                .map_err(RuntimeError::Storage)
+        })
+    }
+
+    fn publish_raw_vectors_for_space(
+        self,
+        space: VectorSpaceSpec,
+        vectors: Vec<NewDocumentVector>,
+        expected_generation: Option<u64>,
+    ) -> Result<RuntimePublishReport, RuntimeError> {
+        let doc_ids = vectors
+            .iter()
+            .map(|vector| vector.doc_id.clone())
+            .collect::<Vec<_>>();
+        let legacy_embedding = if space.name == DEFAULT_VECTOR_SPACE {
+            Some(self.store.legacy_default_embedding())
+        } else {
+            None
+        };
+        self.publish_vector_space_rows(
+            &space,
+            false,
+            doc_ids,
+            expected_generation,
+            |store_path, doc_id_map| {
+                let vectors =
+                    upserted_space_vectors(store_path, &space, legacy_embedding.as_ref(), vectors)?;
+                let (doc_id_start, doc_id_end_exclusive, vector_inputs) =
+                    vector_inputs_sorted_by_wax_doc_id(vectors, doc_id_map)?;
+                let mut pending_segment =
+                    wax_v2_vector::prepare_raw_vector_segment_for_space(&space, &vector_inputs)
+                        .map_err(RuntimeError::Storage)?;
+                pending_segment.descriptor.doc_id_start = doc_id_start;
+                pending_segment.descriptor.doc_id_end_exclusive = doc_id_end_exclusive;
+                Ok(pending_segment)
+            },
+        )
+    }
+
+    /// Validates the target documents, lets `prepare` build the space's replacement segment and
+    /// publishes it while retaining every other vector space. With `expected_generation` the
+    /// publish also fails once any other write has landed.
+    fn publish_vector_space_rows(
+        self,
+        space: &VectorSpaceSpec,
+        multi_vector: bool,
+        doc_ids: Vec<String>,
+        expected_generation: Option<u64>,
+        prepare: impl FnOnce(&Path, &DocIdMap) -> Result<wax_v2_core::PendingSegmentWrite, RuntimeError>,
     ) -> Result<RuntimePublishReport, RuntimeError> {
         let store_path = self.require_existing_store()?;
-        if vectors.is_empty() {
+        if doc_ids.is_empty() {
             return Err(RuntimeError::InvalidRequest(
                 "publish_raw_vectors requires at least one vector".to_owned(),
             ));
         }
-        reject_duplicate_doc_ids(
-            vectors.iter().map(|vector| vector.doc_id.as_str()),
-            "publish_raw_vectors",
-        )?;
+        reject_duplicate_doc_ids(doc_ids.iter().map(String::as_str), "publish_raw_vectors")?;
         let validated_doc_segment = latest_doc_segment_identity_from_store(&store_path)?;
+        let validated_vector_segments =
+            family_segment_offsets_from_store(&store_path, wax_v2_core::SegmentKind::Vec)?;
         self.store.refresh_read_state()?;
         ensure_doc_segment_unchanged_from_store(&store_path, validated_doc_segment.as_ref())?;
 
-        let doc_ids = vectors
+        let current_spaces =
+            wax_v2_vector::store_vector_spaces(&store_path).map_err(RuntimeError::Storage)?;
+        if let Some(current) = current_spaces
             .iter()
-            .map(|vector| vector.doc_id.clone())
-            .collect::<Vec<_>>();
-        let known_documents = self
-            .store
-            .docstore
-            .load_documents_by_id(&doc_ids)
-            .map_err(|error| RuntimeError::Storage(docstore_error(error)))?;
-        let known_document_count = self
-            .store
-            .docstore
-            .load_document_ids()
-            .map_err(|error| RuntimeError::Storage(docstore_error(error)))?
-            .len();
-        if known_documents.len() != vectors.len() {
+            .find(|current| current.spec.name == space.name && current.multi_vector != multi_vector)
+        {
+            let stored = if current.multi_vector {
+                "multi-vector"
+            } else {
+                "single-vector"
+            };
+            return Err(RuntimeError::InvalidRequest(format!(
+                "vector space {} stores {stored} rows",
+                space.name
+            )));
+        }
+
+        let known_documents = self.store.load_documents(&doc_ids)?;
+        if known_documents.len() != doc_ids.len() {
             let missing = doc_ids
                 .into_iter()
                 .filter(|doc_id| !known_documents.contains_key(doc_id))
@@ -760,32 +2107,33 @@ impl RuntimeStoreWriter<'_> {
                 "publish_raw_vectors requires existing documents for all doc_ids; missing: {missing}"
             )));
         }
-        if vectors.len() != known_document_count {
-            return Err(RuntimeError::InvalidRequest(format!(
-                "publish_raw_vectors currently requires {} vectors to match the current document set",
-                known_document_count
-            )));
-        }
 
         let doc_id_map = self
             .store
             .docstore
             .build_doc_id_map()
             .map_err(|error| RuntimeError::Storage(docstore_error(error)))?;
-        let (doc_id_start, doc_id_end_exclusive, vector_inputs) =
-            vector_inputs_sorted_by_wax_doc_id(vectors, &doc_id_map)?;
-
-        let mut pending_segment = wax_v2_vector::prepare_raw_vector_segment(
-            self.store.manifest.vector_profile.embedding_dimensions as usize,
-            &vector_inputs,
-        )
-        .map_err(RuntimeError::Storage)?;
-        pending_segment.descriptor.doc_id_start = doc_id_start;
-        pending_segment.descriptor.doc_id_end_exclusive = doc_id_end_exclusive;
-        let opened = wax_v2_core::publish_segments_with_precondition(
+        let pending_segment = prepare(&store_path, &doc_id_map)?;
+        let retained_vector_segments = current_spaces
+            .into_iter()
+            .filter(|current| current.spec.name != space.name)
+            .map(|current| current.descriptor.object_offset)
+            .collect::<std::collections::HashSet<_>>();
+        let opened = wax_v2_core::publish_segments_retaining_with_precondition(
             &store_path,
             vec![pending_segment],
-            |manifest| ensure_doc_segment_unchanged(manifest, validated_doc_segment.as_ref()),
+            |segment| {
+                segment.family != wax_v2_core::SegmentKind::Vec
+                    || retained_vector_segments.contains(&segment.object_offset)
+            },
+            |manifest| {
+                if let Some(expected_generation) = expected_generation {
+                    ensure_transaction_generation_unchanged(manifest, expected_generation)?;
+                }
+                ensure_doc_segment_unchanged(manifest, validated_doc_segment.as_ref())?;
+                ensure_vector_segments_unchanged(manifest, &validated_vector_segments)
+            },
+            self.store.writer_lock_timeout,
         )
         .map_err(runtime_core_error)?;
 
@@ -796,6 +2144,62 @@ impl RuntimeStoreWriter<'_> {
         })
     }
 
+    /// Names `generation`, or the active generation when `None`, so future space reclamation
+    /// keeps it readable. Recording the checkpoint publishes a new generation.
+    pub fn create_checkpoint(
+        self,
+        name: &str,
+        generation: Option<u64>,
+    ) -> Result<Checkpoint, RuntimeError> {
+        let store_path = self.require_existing_store()?;
+        let checkpoint = wax_v2_core::create_checkpoint(
+            &store_path,
+            name,
+            generation,
+            self.store.writer_lock_timeout,
+        )
+        .map_err(runtime_core_error)?;
+        self.store.refresh_read_state()?;
+        Ok(checkpoint)
+    }
+
+    pub fn delete_checkpoint(self, name: &str) -> Result<Checkpoint, RuntimeError> {
+        let store_path = self.require_existing_store()?;
+        let checkpoint =
+            wax_v2_core::delete_checkpoint(&store_path, name, self.store.writer_lock_timeout)
+                .map_err(runtime_core_error)?;
+        self.store.refresh_read_state()?;
+        Ok(checkpoint)
+    }
+
+    /// Publishes a new generation with the segments of the checkpointed one. Nothing is
+    /// rewritten, so the report lists no published families.
+    pub fn restore_checkpoint(self, name: &str) -> Result<RuntimePublishReport, RuntimeError> {
+        let store_path = self.require_existing_store()?;
+        let opened =
+            wax_v2_core::restore_checkpoint(&store_path, name, self.store.writer_lock_timeout)
+                .map_err(runtime_core_error)?;
+        self.store.refresh_read_state()?;
+        Ok(RuntimePublishReport {
+            generation: opened.manifest.generation,
+            published_families: Vec::new(),
+        })
+    }
+
+    /// Reverts the store to an older generation by republishing its segment set as a new
+    /// generation. Later generations stay in the file and remain openable.
+    pub fn rollback_to(self, generation: u64) -> Result<RuntimePublishReport, RuntimeError> {
+        let store_path = self.require_existing_store()?;
+        let opened =
+            wax_v2_core::rollback_to(&store_path, generation, self.store.writer_lock_timeout)
+                .map_err(runtime_core_error)?;
+        self.store.refresh_read_state()?;
+        Ok(RuntimePublishReport {
+            generation: opened.manifest.generation,
+            published_families: Vec::new(),
+        })
+    }
+
     fn require_existing_store(&self) -> Result<PathBuf, RuntimeError> {
         let store_path = self.store.store_path();
         if !store_path.exists() {
@@ -807,6 +2211,27 @@ impl RuntimeStoreWriter<'_> {
     }
 }
 
+fn embed_with(
+    embedder: &dyn Embedder,
+    text: &str,
+    dimensions: usize,
+) -> Result<Vec<f32>, RuntimeError> {
+    if embedder.dimensions() != dimensions {
+        return Err(RuntimeError::InvalidRequest(format!(
+            "embedder produces {} dimensions but the store expects {dimensions}",
+            embedder.dimensions()
+        )));
+    }
+    let values = embedder.embed(text).map_err(RuntimeError::Embedding)?;
+    if values.len() != dimensions {
+        return Err(RuntimeError::Embedding(format!(
+            "embedder returned {} values but declares {dimensions} dimensions",
+            values.len()
+        )));
+    }
+    Ok(values)
+}
+
 fn raw_ordered_documents(documents: &[NewDocument]) -> Vec<(String, serde_json::Value)> {
     documents
         .iter()
@@ -869,10 +2294,155 @@ fn new_document_from_value(value: &serde_json::Value) -> Result<NewDocument, Run
     Ok(document)
 }
 
-fn vector_inputs_sorted_by_wax_doc_id(
+/// Merges `vectors` over the rows already published to `space`, so callers can update a subset
+/// of documents. Rows of a space that went stale after a document publish are not carried over.
+fn upserted_space_vectors(
+    store_path: &Path,
+    space: &VectorSpaceSpec,
+    legacy_embedding: Option<&EmbeddingIdentity>,
     vectors: Vec<NewDocumentVector>,
-    doc_id_map: &DocIdMap,
-) -> Result<(u64, u64, Vec<(String, Vec<f32>)>), RuntimeError> {
+) -> Result<Vec<NewDocumentVector>, RuntimeError> {
+    let Some(current) = wax_v2_vector::load_current_store_vector_rows(store_path, &space.name)
+        .map_err(RuntimeError::Storage)?
+    else {
+        return Ok(vectors);
+    };
+    let incoming = vectors
+        .iter()
+        .map(|vector| vector.doc_id.as_str())
+        .collect::<std::collections::HashSet<_>>();
+    let retained = current
+        .rows
+        .into_iter()
+        .filter(|(doc_id, _)| !incoming.contains(doc_id.as_str()))
+        .map(|(doc_id, values)| NewDocumentVector::new(doc_id, values))
+        .collect::<Vec<_>>();
+    if let Some(embedding) = space.embedding.as_ref() {
+        ensure_embedding_unchanged(
+            &current.spec,
+            legacy_embedding,
+            embedding,
+            !retained.is_empty(),
+        )?;
+    }
+    if !retained.is_empty() && current.spec.dimensions != space.dimensions {
+        return Err(RuntimeError::InvalidRequest(format!(
+            "vector space {} stores {} dimensions; changing dimensions requires vectors for every document already in the space",
+            space.name, current.spec.dimensions
+        )));
+    }
+    Ok(retained.into_iter().chain(vectors).collect())
+}
+
+/// Multi-vector counterpart of [`upserted_space_vectors`].
+fn upserted_space_multi_vectors(
+    store_path: &Path,
+    space: &VectorSpaceSpec,
+    vectors: Vec<NewDocumentMultiVector>,
+) -> Result<Vec<NewDocumentMultiVector>, RuntimeError> {
+    let Some(current) =
+        wax_v2_vector::load_current_store_multi_vector_rows(store_path, &space.name)
+            .map_err(RuntimeError::Storage)?
+    else {
+        return Ok(vectors);
+    };
+    let incoming = vectors
+        .iter()
+        .map(|vector| vector.doc_id.as_str())
+        .collect::<std::collections::HashSet<_>>();
+    let retained = current
+        .rows
+        .into_iter()
+        .filter(|(doc_id, _)| !incoming.contains(doc_id.as_str()))
+        .map(|(doc_id, sub_vectors)| NewDocumentMultiVector::new(doc_id, sub_vectors))
+        .collect::<Vec<_>>();
+    if let Some(embedding) = space.embedding.as_ref() {
+        ensure_embedding_unchanged(&current.spec, None, embedding, !retained.is_empty())?;
+    }
+    if !retained.is_empty() && current.spec.dimensions != space.dimensions {
+        return Err(RuntimeError::InvalidRequest(format!(
+            "vector space {} stores {} dimensions; changing dimensions requires vectors for every document already in the space",
+            space.name, current.spec.dimensions
+        )));
+    }
+    Ok(retained.into_iter().chain(vectors).collect())
+}
+
+fn validated_named_vector_space(
+    space: RuntimeVectorSpace,
+    operation: &str,
+) -> Result<VectorSpaceSpec, RuntimeError> {
+    if space.name == DEFAULT_VECTOR_SPACE {
+        return Err(RuntimeError::InvalidRequest(format!(
+            "{operation} cannot target the default vector space; use publish_raw_vectors"
+        )));
+    }
+    wax_v2_vector::validate_vector_space_name(&space.name).map_err(RuntimeError::InvalidRequest)?;
+    if space.dimensions == 0 {
+        return Err(RuntimeError::InvalidRequest(format!(
+            "vector space {} requires non-zero dimensions",
+            space.name
+        )));
+    }
+    let Some(embedding) = space.embedding else {
+        return Err(RuntimeError::InvalidRequest(format!(
+            "{operation} requires an embedding identity for vector space {}",
+            space.name
+        )));
+    };
+    Ok(
+        VectorSpaceSpec::new(space.name, space.dimensions, vector_metric(space.metric))
+            .with_embedding(validated_embedding_identity(embedding)?),
+    )
+}
+
+fn validated_embedding_identity(
+    embedding: RuntimeEmbeddingIdentity,
+) -> Result<EmbeddingIdentity, RuntimeError> {
+    let embedding = EmbeddingIdentity::new(
+        embedding.spec_id,
+        embedding.model_version,
+        embedding.model_hash,
+    );
+    embedding.validate().map_err(RuntimeError::InvalidRequest)?;
+    Ok(embedding)
+}
+
+fn runtime_embedding_identity(embedding: EmbeddingIdentity) -> RuntimeEmbeddingIdentity {
+    RuntimeEmbeddingIdentity::new(
+        embedding.spec_id,
+        embedding.model_version,
+        embedding.model_hash,
+    )
+}
+
+/// Rejects writes that would keep rows from another embedding model next to the new ones.
+/// `legacy` stands in for segments written before identities were recorded.
+fn ensure_embedding_unchanged(
+    current: &VectorSpaceSpec,
+    legacy: Option<&EmbeddingIdentity>,
+    embedding: &EmbeddingIdentity,
+    retains_rows: bool,
+) -> Result<(), RuntimeError> {
+    let stored = current.embedding.as_ref().or(legacy);
+    if retains_rows && stored != Some(embedding) {
+        return Err(RuntimeError::EmbeddingMismatch(Box::new(
+            RuntimeEmbeddingMismatch {
+                space: current.name.clone(),
+                stored: stored.cloned().map(runtime_embedding_identity),
+                requested: Some(runtime_embedding_identity(embedding.clone())),
+            },
+        )));
+    }
+    Ok(())
+}
+
+type SortedVectorInputs = (u64, u64, Vec<(String, Vec<f32>)>);
+
+fn vector_inputs_sorted_by_wax_doc_id(
+    vectors: Vec<NewDocumentVector>,
+    doc_id_map: &DocIdMap,
+) -> Result<SortedVectorInputs, RuntimeError> {
     let mut vector_inputs = vectors
         .into_iter()
         .map(|vector| {
@@ -928,18 +2498,54 @@ fn latest_doc_segment_identity_from_store(
     Ok(latest_doc_segment_identity(&opened.manifest))
 }
 
-fn store_has_vector_segment(
-    store_path: &Path,
-    expected_generation: u64,
-) -> Result<bool, RuntimeError> {
-    let opened = wax_v2_core::open_store(store_path).map_err(runtime_core_error)?;
-    ensure_store_generation_unchanged(&opened.manifest, expected_generation)
-        .map_err(runtime_core_error)?;
-    Ok(opened
-        .manifest
+fn family_segment_offsets(
+    manifest: &wax_v2_core::ActiveManifest,
+    family: wax_v2_core::SegmentKind,
+) -> Vec<u64> {
+    let mut offsets = manifest
         .segments
         .iter()
-        .any(|segment| segment.family == wax_v2_core::SegmentKind::Vec))
+        .filter(|segment| segment.family == family)
+        .map(|segment| segment.object_offset)
+        .collect::<Vec<_>>();
+    offsets.sort_unstable();
+    offsets
+}
+
+fn family_segment_offsets_from_store(
+    store_path: &Path,
+    family: wax_v2_core::SegmentKind,
+) -> Result<Vec<u64>, RuntimeError> {
+    let opened = wax_v2_core::open_store(store_path).map_err(runtime_core_error)?;
+    Ok(family_segment_offsets(&opened.manifest, family))
+}
+
+fn ensure_vector_segments_unchanged(
+    manifest: &wax_v2_core::ActiveManifest,
+    expected: &[u64],
+) -> Result<(), wax_v2_core::CoreError> {
+    if family_segment_offsets(manifest, wax_v2_core::SegmentKind::Vec) == expected {
+        return Ok(());
+    }
+
+    Err(wax_v2_core::CoreError::PublishPreconditionFailed(
+        "publish_raw_vectors vector spaces changed before vector publish; retry with latest vectors"
+            .to_owned(),
+    ))
+}
+
+fn ensure_sparse_segments_unchanged(
+    manifest: &wax_v2_core::ActiveManifest,
+    expected: &[u64],
+) -> Result<(), wax_v2_core::CoreError> {
+    if family_segment_offsets(manifest, wax_v2_core::SegmentKind::Spr) == expected {
+        return Ok(());
+    }
+
+    Err(wax_v2_core::CoreError::PublishPreconditionFailed(
+        "publish_raw_sparse_vectors sparse segment changed before sparse publish; retry with latest sparse vectors"
+            .to_owned(),
+    ))
 }
 
 fn store_manifest_generation_from_store(store_path: &Path) -> Result<u64, RuntimeError> {
@@ -1003,11 +2609,112 @@ fn ensure_store_generation_unchanged(
     ))
 }
 
+fn vector_radius(radius: RuntimeVectorRadius) -> VectorRadius {
+    match radius {
+        RuntimeVectorRadius::MinSimilarity(similarity) => VectorRadius::MinSimilarity(similarity),
+        RuntimeVectorRadius::MaxDistance(distance) => VectorRadius::MaxDistance(distance),
+    }
+}
+
+fn vector_metric(metric: RuntimeVectorMetric) -> VectorMetric {
+    match metric {
+        RuntimeVectorMetric::Cosine => VectorMetric::Cosine,
+        RuntimeVectorMetric::Dot => VectorMetric::Dot,
+        RuntimeVectorMetric::L2 => VectorMetric::L2,
+    }
+}
+
+fn runtime_vector_metric(metric: VectorMetric) -> RuntimeVectorMetric {
+    match metric {
+        VectorMetric::Cosine => RuntimeVectorMetric::Cosine,
+        VectorMetric::Dot => RuntimeVectorMetric::Dot,
+        VectorMetric::L2 => RuntimeVectorMetric::L2,
+    }
+}
+
+fn reject_duplicate_vector_spaces(request: &RuntimeSearchRequest) -> Result<(), RuntimeError> {
+    let mut seen = std::collections::HashSet::new();
+    if request.vector_query.is_some() {
+        seen.insert(DEFAULT_VECTOR_SPACE);
+    }
+    for query in &request.vector_space_queries {
+        if !seen.insert(query.space.as_str()) {
+            return Err(RuntimeError::InvalidRequest(format!(
+                "vector space {} was queried more than once",
+                query.space
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Family decoders behind [`RuntimeStore::verify`].
+struct StoreSegmentDecoder;
+
+impl wax_v2_core::SegmentDecoder for StoreSegmentDecoder {
+    fn decode_rows(
+        &self,
+        descriptor: &wax_v2_core::SegmentDescriptor,
+        payload: &[u8],
+    ) -> Result<Option<wax_v2_core::DecodedSegmentRows>, String> {
+        let counted = |doc_ids: Vec<String>| wax_v2_core::DecodedSegmentRows {
+            live_items: Some(doc_ids.len() as u64),
+            doc_ids,
+            tombstoned_items: 0,
+            doc_id_range: None,
+        };
+        Ok(Some(match descriptor.family {
+            wax_v2_core::SegmentKind::Doc => {
+                let segment =
+                    wax_v2_docstore::BinaryDocSegment::decode(payload).map_err(docstore_error)?;
+                let mut doc_ids = Vec::new();
+                let mut tombstoned_items = 0;
+                for record in &segment.records {
+                    if record.row.is_tombstone() {
+                        tombstoned_items += 1;
+                        continue;
+                    }
+                    let doc_id = segment
+                        .doc_id_map
+                        .external_doc_id(record.row.doc_id)
+                        .ok_or_else(|| {
+                            format!("doc row {} has no external doc id", record.row.doc_id)
+                        })?;
+                    doc_ids.push(doc_id.to_owned());
+                }
+                let doc_id_range = match (segment.records.first(), segment.records.last()) {
+                    (Some(first), Some(last)) => first.row.doc_id..last.row.doc_id + 1,
+                    _ => 0..0,
+                };
+                wax_v2_core::DecodedSegmentRows {
+                    live_items: Some(doc_ids.len() as u64),
+                    doc_ids,
+                    tombstoned_items,
+                    doc_id_range: Some(doc_id_range),
+                }
+            }
+            wax_v2_core::SegmentKind::Txt => wax_v2_core::DecodedSegmentRows {
+                live_items: None,
+                ..counted(wax_v2_text::decode_text_segment_doc_ids(payload)?)
+            },
+            wax_v2_core::SegmentKind::Vec => {
+                counted(wax_v2_vector::decode_vector_segment_doc_ids(payload)?)
+            }
+            wax_v2_core::SegmentKind::Spr => {
+                counted(wax_v2_sparse::decode_sparse_segment_doc_ids(payload)?)
+            }
+            // Core verifies checkpoint segments itself.
+            wax_v2_core::SegmentKind::Ckp => return Ok(None),
+        }))
+    }
+}
+
 fn runtime_core_error(error: wax_v2_core::CoreError) -> RuntimeError {
     match error {
         wax_v2_core::CoreError::PublishPreconditionFailed(message) => {
             RuntimeError::InvalidRequest(message)
         }
+        wax_v2_core::CoreError::WriterBusy { waited_ms } => RuntimeError::WriterBusy { waited_ms },
         other => RuntimeError::Storage(other.to_string()),
     }
 }
@@ -1117,6 +2824,68 @@ fn load_compatibility_raw_documents(
     .collect()
 }
 
+fn widened_scores<S: Into<f64>>(hits: Vec<(String, S)>) -> Vec<(String, f64)> {
+    hits.into_iter()
+        .map(|(doc_id, score)| (doc_id, score.into()))
+        .collect()
+}
+
+fn without_scores(hits: Vec<(String, f64)>) -> Vec<String> {
+    hits.into_iter().map(|(doc_id, _)| doc_id).collect()
+}
+
+#[cfg(not(test))]
+mod hydration_probe {
+    pub(crate) fn record(_documents: usize) {}
+}
+
+/// Per-thread count of hydrated documents, for tests that assert a path never parses payloads.
+#[cfg(test)]
+mod hydration_probe {
+    use std::cell::Cell;
+
+    thread_local! {
+        static HYDRATED: Cell<usize> = const { Cell::new(0) };
+    }
+
+    pub(crate) fn record(documents: usize) {
+        HYDRATED.with(|hydrated| hydrated.set(hydrated.get() + documents));
+    }
+
+    /// Returns the documents hydrated on this thread since the last call and resets the count.
+    pub(crate) fn take() -> usize {
+        HYDRATED.with(|hydrated| hydrated.replace(0))
+    }
+}
+
+/// Whether the request fuses several ranked lists, whose fused scores depend on how deep each
+/// list runs.
+fn fuses_hit_lists(request: &RuntimeSearchRequest) -> bool {
+    match request.mode {
+        RuntimeSearchMode::Hybrid => true,
+        RuntimeSearchMode::Vector => {
+            usize::from(request.vector_query.is_some()) + request.vector_space_queries.len() > 1
+        }
+        RuntimeSearchMode::Text | RuntimeSearchMode::Sparse => false,
+    }
+}
+
+/// Depth every lane feeding reciprocal rank fusion runs to on a first page. Pages of up to
+/// `FUSION_MIN_LANE_DEPTH` hits fuse the same lists, so their rankings agree whatever the page
+/// size; cursor pages fuse deeper once those lists run out.
+fn fusion_lane_depth(top_k: usize) -> usize {
+    top_k.max(FUSION_MIN_LANE_DEPTH)
+}
+
+/// Initial candidates for MMR and collapsed searches.
+fn mmr_candidate_limit(top_k: usize) -> usize {
+    top_k.saturating_mul(4).max(20)
+}
+
+fn default_vector_scan_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, usize::from)
+}
+
 fn apple_acceleration_capability() -> RuntimeAccelerationCapability {
     if cfg!(target_os = "macos") || cfg!(target_os = "ios") {
         RuntimeAccelerationCapability {
@@ -1133,273 +2902,1538 @@ fn apple_acceleration_capability() -> RuntimeAccelerationCapability {
     }
 }
 
+/// Distance kernels dispatch on CPU features at runtime regardless of the acceleration
+/// preference; SIMD and scalar scores are bit-identical.
+fn cpu_simd_acceleration_capability() -> RuntimeAccelerationCapability {
+    let level = wax_v2_vector::simd_level();
+    if level == wax_v2_vector::SimdLevel::Scalar {
+        RuntimeAccelerationCapability {
+            family: RuntimePlatformAccelerationFamily::CpuSimd,
+            availability: RuntimeAccelerationAvailability::UnsupportedPlatform,
+            detail: Some(
+                "no supported SIMD instruction set detected; using scalar distance kernels"
+                    .to_owned(),
+            ),
+        }
+    } else {
+        RuntimeAccelerationCapability {
+            family: RuntimePlatformAccelerationFamily::CpuSimd,
+            availability: RuntimeAccelerationAvailability::Available,
+            detail: Some(format!("{} distance kernels", level.as_str())),
+        }
+    }
+}
+
 fn read_manifest(root: &Path) -> Result<DatasetPackManifest, RuntimeError> {
     let manifest_text = fs::read_to_string(root.join("manifest.json"))
         .map_err(|error| RuntimeError::Storage(error.to_string()))?;
     serde_json_fallback_parse_manifest(&manifest_text)
 }
 
-fn serde_json_fallback_parse_manifest(text: &str) -> Result<DatasetPackManifest, RuntimeError> {
-    serde_json::from_str(text).map_err(|error| RuntimeError::Storage(error.to_string()))
-}
+fn serde_json_fallback_parse_manifest(text: &str) -> Result<DatasetPackManifest, RuntimeError> {
+    serde_json::from_str(text).map_err(|error| RuntimeError::Storage(error.to_string()))
+}
+
+fn docstore_error(error: wax_v2_docstore::DocstoreError) -> String {
+    match error {
+        wax_v2_docstore::DocstoreError::Io(message)
+        | wax_v2_docstore::DocstoreError::Json(message)
+        | wax_v2_docstore::DocstoreError::InvalidDocument(message) => message,
+        wax_v2_docstore::DocstoreError::MissingDocumentsFile => {
+            "dataset pack missing documents file".to_owned()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use fs2::FileExt;
+    use serde_json::json;
+    use tempfile::tempdir;
+    use wax_bench_model::embed_text;
+    use wax_bench_packer::{pack_adhoc_dataset, pack_dataset, AdhocPackRequest, PackRequest};
+    use wax_v2_core::{create_empty_store, map_segment_object, open_store, SegmentKind};
+    use wax_v2_docstore::Docstore;
+    use wax_v2_text::publish_compatibility_text_segment;
+    use wax_v2_vector::{publish_compatibility_vector_segment, DEFAULT_VECTOR_SPACE};
+
+    use crate::{
+        read_manifest, DocCompression, Embedder, FeatureHashEmbedder, NewDocument,
+        NewDocumentMultiVector, NewDocumentVector, RuntimeAccelerationAvailability,
+        RuntimeAccelerationPreference, RuntimeBatchSearchRequest, RuntimeEmbeddingIdentity,
+        RuntimeEmbeddingMismatch, RuntimeExecutionBackend, RuntimePlatformAccelerationFamily,
+        RuntimePublishFamily, RuntimeSearchMode, RuntimeSearchRequest, RuntimeSearchResponse,
+        RuntimeStore, RuntimeVectorMetric, RuntimeVectorRadius, RuntimeVectorSpace,
+        RuntimeVectorSpaceQuery,
+    };
+
+    #[test]
+    fn runtime_store_open_searches_and_closes_without_benchmark_workload_names() {
+        let dataset_dir = tempdir().unwrap();
+        let fixture_root =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../fixtures/bench/source/minimal");
+        let manifest = pack_dataset(&PackRequest::new(
+            &fixture_root,
+            dataset_dir.path(),
+            "small",
+            "clean",
+        ))
+        .unwrap();
+        let store_path = dataset_dir.path().join("store.wax");
+        create_empty_store(&store_path).unwrap();
+        let dataset_docstore = Docstore::open_dataset_pack(dataset_dir.path(), &manifest).unwrap();
+        dataset_docstore.publish_to_store(&store_path).unwrap();
+        publish_compatibility_text_segment(dataset_dir.path(), &manifest, &store_path).unwrap();
+        publish_compatibility_vector_segment(dataset_dir.path(), &manifest, &store_path).unwrap();
+
+        let mut runtime = RuntimeStore::open(dataset_dir.path()).unwrap();
+
+        let text = runtime
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Text,
+                text_query: Some("rust benchmark".to_owned()),
+                top_k: 1,
+                include_preview: true,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(text.hits.len(), 1);
+        assert_eq!(text.hits[0].doc_id, "doc-001");
+        assert_eq!(
+            text.hits[0].preview.as_deref(),
+            Some("rust benchmark guide")
+        );
+
+        let hybrid = runtime
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Hybrid,
+                text_query: Some("semantic latency".to_owned()),
+                vector_query: Some(embed_text("semantic latency", 384)),
+                top_k: 1,
+                vector_embedding: Some(runtime.embedding_identity()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(hybrid.hits.len(), 1);
+        assert_eq!(hybrid.hits[0].doc_id, "doc-002");
+        assert_eq!(hybrid.hits[0].preview, None);
+
+        runtime.close().unwrap();
+    }
+
+    #[test]
+    fn runtime_hybrid_search_overfetches_text_candidates_before_rrf() {
+        let dataset_dir = tempdir().unwrap();
+        let fixture_root =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../fixtures/bench/source/minimal");
+        pack_dataset(&PackRequest::new(
+            &fixture_root,
+            dataset_dir.path(),
+            "small",
+            "clean",
+        ))
+        .unwrap();
+
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_snapshot(
+                vec![
+                    NewDocument::new("doc-001", "alpha"),
+                    NewDocument::new("doc-002", "beta"),
+                    NewDocument::new("doc-003", "alpha"),
+                ],
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    // doc-001 leads the text lane and trails the vector lane, so only the
+                    // overfetched text rank lifts doc-003 past it.
+                    vec![
+                        NewDocumentVector::new(
+                            "doc-001",
+                            embed_text("alpha target", 384)
+                                .into_iter()
+                                .map(|value| -value)
+                                .collect(),
+                        ),
+                        NewDocumentVector::new("doc-002", embed_text("different", 384)),
+                        NewDocumentVector::new("doc-003", embed_text("alpha target", 384)),
+                    ],
+                )),
+            )
+            .unwrap();
+
+        let response = runtime
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Hybrid,
+                text_query: Some("alpha".to_owned()),
+                vector_query: Some(embed_text("alpha target", 384)),
+                top_k: 1,
+                vector_embedding: Some(runtime.embedding_identity()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert_eq!(response.hits.len(), 1);
+        assert_eq!(response.hits[0].doc_id, "doc-003");
+    }
+
+    #[test]
+    fn runtime_hybrid_search_handles_top_k_larger_than_corpus() {
+        let dataset_dir = tempdir().unwrap();
+        let fixture_root =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../fixtures/bench/source/minimal");
+        pack_dataset(&PackRequest::new(
+            &fixture_root,
+            dataset_dir.path(),
+            "small",
+            "clean",
+        ))
+        .unwrap();
+
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_snapshot(
+                vec![
+                    NewDocument::new("doc-001", "alpha note"),
+                    NewDocument::new("doc-002", "beta note"),
+                    NewDocument::new("doc-003", "gamma note"),
+                ],
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    vec![
+                        NewDocumentVector::new("doc-001", embed_text("alpha note", 384)),
+                        NewDocumentVector::new("doc-002", embed_text("beta note", 384)),
+                        NewDocumentVector::new("doc-003", embed_text("gamma note", 384)),
+                    ],
+                )),
+            )
+            .unwrap();
+
+        let response = runtime
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Hybrid,
+                text_query: Some("alpha".to_owned()),
+                vector_query: Some(embed_text("alpha note", 384)),
+                top_k: 5,
+                vector_embedding: Some(runtime.embedding_identity()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert!(!response.hits.is_empty());
+        assert!(response.hits.len() <= 3);
+        assert_eq!(response.hits[0].doc_id, "doc-001");
+    }
+
+    #[test]
+    fn runtime_search_validates_mode_inputs_before_zero_top_k_short_circuit() {
+        let dataset_dir = tempdir().unwrap();
+        let fixture_root =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../fixtures/bench/source/minimal");
+        pack_dataset(&PackRequest::new(
+            &fixture_root,
+            dataset_dir.path(),
+            "small",
+            "clean",
+        ))
+        .unwrap();
+
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+
+        let error = runtime
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Hybrid,
+                text_query: Some("alpha".to_owned()),
+                top_k: 0,
+                ..Default::default()
+            })
+            .unwrap_err();
+
+        assert!(
+            matches!(error, crate::RuntimeError::InvalidRequest(message) if message.contains("vector_query is required for hybrid search"))
+        );
+    }
+
+    #[test]
+    fn runtime_hybrid_search_uses_live_doc_count_for_raw_publish_overfetch() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(&docs_path, "{\"doc_id\":\"seed-001\",\"text\":\"seed\"}\n").unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_snapshot(
+                vec![
+                    NewDocument::new("doc-001", "alpha"),
+                    NewDocument::new("doc-002", "alpha"),
+                    NewDocument::new("doc-003", "gamma"),
+                ],
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    // doc-003 keeps doc-001 off the second vector rank, so doc-002 only wins
+                    // when the text lane reaches past the one-document pack manifest.
+                    vec![
+                        NewDocumentVector::new("doc-001", test_vector(0.0)),
+                        NewDocumentVector::new("doc-002", test_vector(1.0)),
+                        NewDocumentVector::new("doc-003", test_vector(0.5)),
+                    ],
+                )),
+            )
+            .unwrap();
+
+        let response = runtime
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Hybrid,
+                text_query: Some("alpha".to_owned()),
+                vector_query: Some(test_vector(1.0)),
+                top_k: 1,
+                vector_embedding: Some(runtime.embedding_identity()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert_eq!(response.hits[0].doc_id, "doc-002");
+    }
+
+    #[test]
+    fn compatibility_import_preserves_extra_document_payload_fields() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            concat!(
+                "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{\"kind\":\"note\"},",
+                "\"workspace_id\":\"workspace-a\",\"tags\":[\"one\",\"two\"]}\n",
+            ),
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .import_compatibility_snapshot()
+            .unwrap();
+
+        let loaded = runtime
+            .docstore
+            .load_documents_by_id(&["doc-001".to_owned()])
+            .unwrap();
+        assert_eq!(
+            loaded.get("doc-001").unwrap().get("workspace_id"),
+            Some(&json!("workspace-a"))
+        );
+        assert_eq!(
+            loaded.get("doc-001").unwrap().get("tags"),
+            Some(&json!(["one", "two"]))
+        );
+        assert_eq!(
+            loaded
+                .get("doc-001")
+                .unwrap()
+                .get("metadata")
+                .and_then(|metadata| metadata.get("kind")),
+            Some(&json!("note"))
+        );
+    }
+
+    #[test]
+    fn runtime_store_creates_and_publishes_compatibility_segments_for_reopen_search() {
+        let dataset_dir = tempdir().unwrap();
+        let fixture_root =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../fixtures/bench/source/minimal");
+        let manifest = pack_dataset(&PackRequest::new(
+            &fixture_root,
+            dataset_dir.path(),
+            "small",
+            "clean",
+        ))
+        .unwrap();
+
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        let publish_report = runtime
+            .writer()
+            .unwrap()
+            .import_compatibility_snapshot()
+            .unwrap();
+        assert_eq!(publish_report.generation, 1);
+        assert_eq!(
+            publish_report.published_families,
+            vec![
+                RuntimePublishFamily::Doc,
+                RuntimePublishFamily::Text,
+                RuntimePublishFamily::Vector,
+            ]
+        );
+        runtime.close().unwrap();
+
+        for kind in [
+            "documents",
+            "document_offsets",
+            "text_postings",
+            "document_ids",
+            "document_vectors",
+            "document_vectors_preview_q8",
+        ] {
+            for file in manifest.files.iter().filter(|file| file.kind == kind) {
+                fs::remove_file(dataset_dir.path().join(&file.path)).unwrap();
+            }
+        }
+
+        let mut reopened = RuntimeStore::open(dataset_dir.path()).unwrap();
+        let text_response = reopened
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Text,
+                text_query: Some("rust benchmark".to_owned()),
+                top_k: 2,
+                include_preview: true,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(text_response.hits[0].doc_id, "doc-001");
+        assert_eq!(
+            text_response.hits[0].preview.as_deref(),
+            Some("rust benchmark guide")
+        );
+
+        let vector_response = reopened
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Vector,
+                vector_query: Some(embed_text("semantic latency", 384)),
+                top_k: 2,
+                vector_embedding: Some(reopened.embedding_identity()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(vector_response.hits[0].doc_id, "doc-002");
+    }
+
+    #[test]
+    fn runtime_store_create_removes_store_when_initialization_fails() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(&docs_path, "{\"doc_id\":\"doc-001\",\"text\":\"alpha\"}\n").unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+        let manifest = read_manifest(dataset_dir.path()).unwrap();
+        let store_path = dataset_dir.path().join("store.wax");
+        fs::write(&store_path, b"not-a-valid-store").unwrap();
+
+        let error =
+            match RuntimeStore::open_created_store(dataset_dir.path(), manifest, &store_path) {
+                Ok(_) => {
+                    panic!("create cleanup path should fail when reopen cannot validate store")
+                }
+                Err(error) => error,
+            };
+
+        assert!(matches!(error, crate::RuntimeError::Storage(_)));
+        assert!(!store_path.exists());
+    }
+
+    #[test]
+    fn publish_raw_snapshot_rejects_duplicate_document_doc_ids() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            concat!(
+                "{\"doc_id\":\"doc-001\",\"text\":\"alpha\"}\n",
+                "{\"doc_id\":\"doc-002\",\"text\":\"beta\"}\n",
+            ),
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        let error = runtime
+            .writer()
+            .unwrap()
+            .publish_raw_snapshot(
+                vec![
+                    NewDocument::new("doc-001", "alpha"),
+                    NewDocument::new("doc-001", "beta"),
+                ],
+                None,
+            )
+            .unwrap_err();
+
+        assert!(
+            matches!(error, crate::RuntimeError::InvalidRequest(message) if message.contains("duplicate doc_ids"))
+        );
+    }
+
+    #[test]
+    fn publish_raw_snapshot_rejects_duplicate_vector_doc_ids() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            concat!(
+                "{\"doc_id\":\"doc-001\",\"text\":\"alpha\"}\n",
+                "{\"doc_id\":\"doc-002\",\"text\":\"beta\"}\n",
+            ),
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        let error = runtime
+            .writer()
+            .unwrap()
+            .publish_raw_snapshot(
+                vec![
+                    NewDocument::new("doc-001", "alpha"),
+                    NewDocument::new("doc-002", "beta"),
+                ],
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    vec![
+                        NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
+                        NewDocumentVector::new("doc-001", embed_text("beta", 384)),
+                    ],
+                )),
+            )
+            .unwrap_err();
+
+        assert!(
+            matches!(error, crate::RuntimeError::InvalidRequest(message) if message.contains("duplicate doc_ids"))
+        );
+    }
+
+    #[test]
+    fn publish_raw_snapshot_truncates_missing_vector_doc_ids_in_error_message() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            concat!(
+                "{\"doc_id\":\"doc-001\",\"text\":\"alpha\"}\n",
+                "{\"doc_id\":\"doc-002\",\"text\":\"beta\"}\n",
+                "{\"doc_id\":\"doc-003\",\"text\":\"gamma\"}\n",
+                "{\"doc_id\":\"doc-004\",\"text\":\"delta\"}\n",
+                "{\"doc_id\":\"doc-005\",\"text\":\"epsilon\"}\n",
+                "{\"doc_id\":\"doc-006\",\"text\":\"zeta\"}\n",
+            ),
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        let error = runtime
+            .writer()
+            .unwrap()
+            .publish_raw_snapshot(
+                vec![
+                    NewDocument::new("doc-001", "alpha"),
+                    NewDocument::new("doc-002", "beta"),
+                    NewDocument::new("doc-003", "gamma"),
+                    NewDocument::new("doc-004", "delta"),
+                    NewDocument::new("doc-005", "epsilon"),
+                    NewDocument::new("doc-006", "zeta"),
+                ],
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    vec![
+                        NewDocumentVector::new("missing-001", embed_text("alpha", 384)),
+                        NewDocumentVector::new("missing-002", embed_text("beta", 384)),
+                        NewDocumentVector::new("missing-003", embed_text("gamma", 384)),
+                        NewDocumentVector::new("missing-004", embed_text("delta", 384)),
+                        NewDocumentVector::new("missing-005", embed_text("epsilon", 384)),
+                        NewDocumentVector::new("missing-006", embed_text("zeta", 384)),
+                    ],
+                )),
+            )
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            crate::RuntimeError::InvalidRequest(message)
+                if message.contains("missing-001")
+                    && message.contains("missing-005")
+                    && message.contains("(+1 more)")
+                    && !message.contains("missing-006")
+        ));
+    }
+
+    #[test]
+    fn publish_raw_vectors_counts_only_active_documents() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            concat!(
+                "{\"doc_id\":\"doc-001\",\"text\":\"alpha\"}\n",
+                "{\"doc_id\":\"doc-002\",\"text\":\"beta\"}\n",
+            ),
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_snapshot(
+                vec![
+                    NewDocument::new("doc-001", "alpha"),
+                    NewDocument::new("doc-002", "beta"),
+                ],
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    vec![
+                        NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
+                        NewDocumentVector::new("doc-002", embed_text("beta", 384)),
+                    ],
+                )),
+            )
+            .unwrap();
+
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_snapshot(vec![NewDocument::new("doc-001", "alpha only")], None)
+            .unwrap();
+
+        let embedding = runtime.embedding_identity();
+        let report = runtime
+            .writer()
+            .unwrap()
+            .publish_raw_vectors(
+                embedding,
+                vec![NewDocumentVector::new(
+                    "doc-001",
+                    embed_text("alpha only", 384),
+                )],
+            )
+            .unwrap();
+
+        assert_eq!(
+            report.published_families,
+            vec![RuntimePublishFamily::Vector]
+        );
+    }
+
+    #[test]
+    fn publish_raw_documents_merges_with_existing_active_documents() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            concat!(
+                "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{\"workspace\":\"old\"},\"priority\":\"keep\"}\n",
+                "{\"doc_id\":\"doc-002\",\"text\":\"beta\",\"metadata\":{\"workspace\":\"old\"}}\n",
+            ),
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![
+                NewDocument::new("doc-001", "alpha")
+                    .with_metadata(serde_json::json!({"workspace":"old"}))
+                    .with_extra_field("priority", serde_json::json!("keep")),
+                NewDocument::new("doc-002", "beta")
+                    .with_metadata(serde_json::json!({"workspace":"old"})),
+            ])
+            .unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![NewDocument::new("doc-003", "gamma")])
+            .unwrap();
+
+        let reopened = RuntimeStore::open(dataset_dir.path()).unwrap();
+        let doc_ids = reopened.docstore.load_document_ids().unwrap();
+        let documents = reopened.docstore.load_documents_by_id(&doc_ids).unwrap();
+
+        assert_eq!(
+            doc_ids,
+            vec![
+                "doc-001".to_owned(),
+                "doc-002".to_owned(),
+                "doc-003".to_owned()
+            ]
+        );
+        assert_eq!(
+            documents
+                .get("doc-001")
+                .and_then(|document| document.get("priority"))
+                .and_then(serde_json::Value::as_str),
+            Some("keep")
+        );
+        assert_eq!(
+            documents
+                .get("doc-003")
+                .and_then(|document| document.get("text"))
+                .and_then(serde_json::Value::as_str),
+            Some("gamma")
+        );
+    }
+
+    #[test]
+    fn embedder_auto_embeds_published_documents_and_vector_text_queries() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{}}\n",
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+        let dimensions = read_manifest(dataset_dir.path())
+            .unwrap()
+            .vector_profile
+            .embedding_dimensions as usize;
+
+        let mut runtime = RuntimeStore::create(dataset_dir.path())
+            .unwrap()
+            .with_embedder(Arc::new(FeatureHashEmbedder::new(dimensions)));
+        let report = runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![
+                NewDocument::new("doc-001", "alpha apple orchard"),
+                NewDocument::new("doc-002", "beta banana split"),
+            ])
+            .unwrap();
+        assert!(report
+            .published_families
+            .contains(&RuntimePublishFamily::Vector));
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![NewDocument::new("doc-003", "gamma grape vine")])
+            .unwrap();
+
+        let rows = wax_v2_vector::load_current_store_vector_rows(
+            &runtime.store_path(),
+            wax_v2_vector::DEFAULT_VECTOR_SPACE,
+        )
+        .unwrap()
+        .unwrap()
+        .rows;
+        assert_eq!(
+            rows,
+            vec![
+                (
+                    "doc-001".to_owned(),
+                    embed_text("alpha apple orchard", dimensions as u32)
+                ),
+                (
+                    "doc-002".to_owned(),
+                    embed_text("beta banana split", dimensions as u32)
+                ),
+                (
+                    "doc-003".to_owned(),
+                    embed_text("gamma grape vine", dimensions as u32)
+                ),
+            ]
+        );
+
+        let vector_text_request = RuntimeSearchRequest {
+            mode: RuntimeSearchMode::Vector,
+            text_query: Some("grape vine".to_owned()),
+            top_k: 1,
+            ..Default::default()
+        };
+        let response = runtime.search(vector_text_request.clone()).unwrap();
+        assert_eq!(response.hits[0].doc_id, "doc-003");
+
+        let mut without_embedder = RuntimeStore::open(dataset_dir.path()).unwrap();
+        let error = without_embedder
+            .search(vector_text_request.clone())
+            .unwrap_err();
+        assert!(
+            matches!(error, crate::RuntimeError::InvalidRequest(message) if message.contains("vector_query is required for vector search"))
+        );
+
+        let mut mismatched = RuntimeStore::open(dataset_dir.path())
+            .unwrap()
+            .with_embedder(Arc::new(FeatureHashEmbedder::new(dimensions + 1)));
+        let error = mismatched.search(vector_text_request.clone()).unwrap_err();
+        assert!(
+            matches!(error, crate::RuntimeError::InvalidRequest(message) if message.contains("embedder produces"))
+        );
+
+        struct FailingEmbedder(usize);
+        impl Embedder for FailingEmbedder {
+            fn dimensions(&self) -> usize {
+                self.0
+            }
+
+            fn identity(&self) -> RuntimeEmbeddingIdentity {
+                FeatureHashEmbedder::new(self.0).identity()
+            }
+
+            fn embed(&self, _text: &str) -> Result<Vec<f32>, String> {
+                Err("model offline".to_owned())
+            }
+        }
+        let mut failing = RuntimeStore::open(dataset_dir.path())
+            .unwrap()
+            .with_embedder(Arc::new(FailingEmbedder(dimensions)));
+        let error = failing.search(vector_text_request).unwrap_err();
+        assert_eq!(
+            error,
+            crate::RuntimeError::Embedding("model offline".to_owned())
+        );
+    }
+
+    #[test]
+    fn vector_scan_thread_budget_applies_to_loaded_lanes_without_changing_hits() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{}}\n",
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+        let dimensions = read_manifest(dataset_dir.path())
+            .unwrap()
+            .vector_profile
+            .embedding_dimensions as usize;
+        let mut runtime = RuntimeStore::create(dataset_dir.path())
+            .unwrap()
+            .with_embedder(Arc::new(FeatureHashEmbedder::new(dimensions)));
+        assert!(runtime.vector_scan_threads() >= 1);
+        // Three times the vector lane's 4096-row minimum per scan thread. Doc ids permute the
+        // rows, so each group of identical texts ties across every thread's chunk.
+        let doc_count = 3 * 4096 + 17;
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(
+                (0..doc_count)
+                    .map(|index| {
+                        NewDocument::new(
+                            format!("doc-{:05}", index * 7919 % doc_count),
+                            format!("term{}", index % 6),
+                        )
+                    })
+                    .collect(),
+            )
+            .unwrap();
+        let request = RuntimeSearchRequest {
+            mode: RuntimeSearchMode::Vector,
+            text_query: Some("term3".to_owned()),
+            top_k: 2500,
+            ..Default::default()
+        };
+        let mut runtime = runtime.with_vector_scan_threads(1);
+        let single = runtime
+            .search(request.clone())
+            .unwrap()
+            .hits
+            .into_iter()
+            .map(|hit| hit.doc_id)
+            .collect::<Vec<_>>();
+
+        // More than the 2051 tied "term3" documents, so the page runs into the next score.
+        assert_eq!(single.len(), 2500);
+
+        let mut runtime = runtime.with_vector_scan_threads(3);
+        assert_eq!(runtime.vector_scan_threads(), 3);
+        assert_eq!(runtime.vector_lane.get().unwrap().exact_scan_threads(), 3);
+        let parallel = runtime
+            .search(request)
+            .unwrap()
+            .hits
+            .into_iter()
+            .map(|hit| hit.doc_id)
+            .collect::<Vec<_>>();
+        assert_eq!(parallel, single);
+        assert_eq!(runtime.with_vector_scan_threads(0).vector_scan_threads(), 1);
+    }
+
+    #[test]
+    fn batch_search_matches_single_vector_searches_and_checks_embedding_identity() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{}}\n",
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+        let dimensions = read_manifest(dataset_dir.path())
+            .unwrap()
+            .vector_profile
+            .embedding_dimensions as usize;
+        let embedder = FeatureHashEmbedder::new(dimensions);
+        let mut runtime = RuntimeStore::create(dataset_dir.path())
+            .unwrap()
+            .with_embedder(Arc::new(embedder.clone()));
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(
+                (0..30)
+                    .map(|index| {
+                        NewDocument::new(format!("doc-{index:03}"), format!("term{}", index % 5))
+                    })
+                    .collect(),
+            )
+            .unwrap();
+
+        let vectors = ["term1", "term4", "unrelated"]
+            .iter()
+            .map(|text| embedder.embed(text).unwrap())
+            .collect::<Vec<_>>();
+        let request = RuntimeBatchSearchRequest::new(DEFAULT_VECTOR_SPACE, vectors.clone(), 4)
+            .with_preview(true)
+            .with_embedding(embedder.identity());
+        let response = runtime.search_batch(request.clone()).unwrap();
+        assert_eq!(response.results.len(), 3);
+        for (vector, result) in vectors.iter().zip(&response.results) {
+            let single = runtime
+                .search(RuntimeSearchRequest {
+                    mode: RuntimeSearchMode::Vector,
+                    vector_query: Some(vector.clone()),
+                    top_k: 4,
+                    include_preview: true,
+                    vector_embedding: Some(embedder.identity()),
+                    ..Default::default()
+                })
+                .unwrap();
+            assert_eq!(result.hits, single.hits);
+        }
+        assert!(response.results[0].hits[0]
+            .preview
+            .as_deref()
+            .unwrap()
+            .contains("term1"));
+
+        let error = runtime
+            .search_batch(RuntimeBatchSearchRequest::new(
+                DEFAULT_VECTOR_SPACE,
+                vectors.clone(),
+                4,
+            ))
+            .unwrap_err();
+        assert!(matches!(error, crate::RuntimeError::EmbeddingMismatch(_)));
+        let error = runtime
+            .search_batch(
+                RuntimeBatchSearchRequest::new(DEFAULT_VECTOR_SPACE, vec![vec![1.0]], 4)
+                    .with_embedding(embedder.identity()),
+            )
+            .unwrap_err();
+        assert!(matches!(error, crate::RuntimeError::InvalidRequest(_)));
+    }
+
+    #[test]
+    fn vector_radius_returns_documents_inside_the_threshold_in_vector_mode_only() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{}}\n",
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+        let dimensions = read_manifest(dataset_dir.path())
+            .unwrap()
+            .vector_profile
+            .embedding_dimensions as usize;
+        let mut runtime = RuntimeStore::create(dataset_dir.path())
+            .unwrap()
+            .with_embedder(Arc::new(FeatureHashEmbedder::new(dimensions)));
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(
+                (0..12)
+                    .map(|index| {
+                        let text = if index % 4 == 0 {
+                            "duplicate note"
+                        } else {
+                            "other"
+                        };
+                        NewDocument::new(format!("doc-{index:03}"), format!("{text} {index}"))
+                    })
+                    .collect(),
+            )
+            .unwrap();
+        let request = |mode, radius, top_k| RuntimeSearchRequest {
+            mode,
+            text_query: Some("duplicate note".to_owned()),
+            top_k,
+            vector_radius: Some(radius),
+            ..Default::default()
+        };
+        let doc_ids = |response: RuntimeSearchResponse| {
+            response
+                .hits
+                .into_iter()
+                .map(|hit| hit.doc_id)
+                .collect::<Vec<_>>()
+        };
+
+        let near = runtime
+            .search(request(
+                RuntimeSearchMode::Vector,
+                RuntimeVectorRadius::MinSimilarity(0.5),
+                usize::MAX,
+            ))
+            .unwrap();
+        let mut near = doc_ids(near);
+        near.sort();
+        assert_eq!(near, vec!["doc-000", "doc-004", "doc-008"]);
+        let capped = runtime
+            .search(request(
+                RuntimeSearchMode::Vector,
+                RuntimeVectorRadius::MaxDistance(0.5),
+                2,
+            ))
+            .unwrap();
+        assert_eq!(capped.hits.len(), 2);
+
+        let error = runtime
+            .search(request(
+                RuntimeSearchMode::Hybrid,
+                RuntimeVectorRadius::MinSimilarity(0.5),
+                5,
+            ))
+            .unwrap_err();
+        assert!(
+            matches!(error, crate::RuntimeError::InvalidRequest(message) if message.contains("vector_radius"))
+        );
+        let error = runtime
+            .search(request(
+                RuntimeSearchMode::Vector,
+                RuntimeVectorRadius::MaxDistance(-1.0),
+                5,
+            ))
+            .unwrap_err();
+        assert!(matches!(error, crate::RuntimeError::InvalidRequest(_)));
+    }
+
+    #[test]
+    fn mmr_lambda_diversifies_near_duplicate_hits_before_hydration() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{}}\n",
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+        let dimensions = read_manifest(dataset_dir.path())
+            .unwrap()
+            .vector_profile
+            .embedding_dimensions as usize;
+        let mut runtime = RuntimeStore::create(dataset_dir.path())
+            .unwrap()
+            .with_embedder(Arc::new(FeatureHashEmbedder::new(dimensions)));
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![
+                NewDocument::new("dup-1", "apple orchard harvest"),
+                NewDocument::new("dup-2", "apple orchard harvest"),
+                NewDocument::new("dup-3", "apple orchard harvest"),
+                NewDocument::new("other-1", "apple cider press"),
+                NewDocument::new("other-2", "orchard ladder repair"),
+            ])
+            .unwrap();
+        let mut search = |mmr_lambda| {
+            runtime
+                .search(RuntimeSearchRequest {
+                    mode: RuntimeSearchMode::Vector,
+                    text_query: Some("apple orchard harvest".to_owned()),
+                    top_k: 3,
+                    mmr_lambda,
+                    ..Default::default()
+                })
+                .map(|response| {
+                    response
+                        .hits
+                        .into_iter()
+                        .map(|hit| hit.doc_id)
+                        .collect::<Vec<_>>()
+                })
+        };
+
+        let relevance_only = search(None).unwrap();
+        assert_eq!(relevance_only, vec!["dup-1", "dup-2", "dup-3"]);
+        assert_eq!(search(Some(1.0)).unwrap(), relevance_only);
+        let diversified = search(Some(0.3)).unwrap();
+        assert_eq!(diversified.len(), 3);
+        assert_eq!(diversified[0], "dup-1");
+        assert!(diversified[1].starts_with("other-"));
+        assert!(diversified[2].starts_with("other-"));
 
-fn docstore_error(error: wax_v2_docstore::DocstoreError) -> String {
-    match error {
-        wax_v2_docstore::DocstoreError::Io(message)
-        | wax_v2_docstore::DocstoreError::Json(message)
-        | wax_v2_docstore::DocstoreError::InvalidDocument(message) => message,
-        wax_v2_docstore::DocstoreError::MissingDocumentsFile => {
-            "dataset pack missing documents file".to_owned()
-        }
+        let error = search(Some(1.5)).unwrap_err();
+        assert!(
+            matches!(error, crate::RuntimeError::InvalidRequest(message) if message.contains("mmr_lambda"))
+        );
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use std::fs;
-    use std::path::PathBuf;
-
-    use serde_json::json;
-    use tempfile::tempdir;
-    use wax_bench_model::embed_text;
-    use wax_bench_packer::{pack_adhoc_dataset, pack_dataset, AdhocPackRequest, PackRequest};
-    use wax_v2_core::{create_empty_store, map_segment_object, open_store, SegmentKind};
-    use wax_v2_docstore::Docstore;
-    use wax_v2_text::publish_compatibility_text_segment;
-    use wax_v2_vector::publish_compatibility_vector_segment;
-
-    use crate::{
-        read_manifest, NewDocument, NewDocumentVector, RuntimeAccelerationAvailability,
-        RuntimeAccelerationPreference, RuntimeExecutionBackend, RuntimePlatformAccelerationFamily,
-        RuntimePublishFamily, RuntimeSearchMode, RuntimeSearchRequest, RuntimeStore,
-    };
 
     #[test]
-    fn runtime_store_open_searches_and_closes_without_benchmark_workload_names() {
+    fn open_at_generation_searches_the_store_as_it_was_at_that_generation() {
         let dataset_dir = tempdir().unwrap();
-        let fixture_root =
-            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../fixtures/bench/source/minimal");
-        let manifest = pack_dataset(&PackRequest::new(
-            &fixture_root,
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{}}\n",
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
             dataset_dir.path(),
             "small",
-            "clean",
         ))
         .unwrap();
-        let store_path = dataset_dir.path().join("store.wax");
-        create_empty_store(&store_path).unwrap();
-        let dataset_docstore = Docstore::open_dataset_pack(dataset_dir.path(), &manifest).unwrap();
-        dataset_docstore.publish_to_store(&store_path).unwrap();
-        publish_compatibility_text_segment(dataset_dir.path(), &manifest, &store_path).unwrap();
-        publish_compatibility_vector_segment(dataset_dir.path(), &manifest, &store_path).unwrap();
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![NewDocument::new("doc-1", "lantern")])
+            .unwrap();
+        let audited = RuntimeStore::generations(dataset_dir.path())
+            .unwrap()
+            .last()
+            .unwrap()
+            .generation;
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![NewDocument::new("doc-2", "lantern harbor")])
+            .unwrap();
 
-        let mut runtime = RuntimeStore::open(dataset_dir.path()).unwrap();
+        let generations = RuntimeStore::generations(dataset_dir.path()).unwrap();
+        assert_eq!(generations[0].generation, 0);
+        assert!(generations.last().unwrap().generation > audited);
+        assert_eq!(
+            generations
+                .iter()
+                .filter(|generation| generation.active)
+                .count(),
+            1
+        );
 
-        let text = runtime
-            .search(RuntimeSearchRequest {
-                mode: RuntimeSearchMode::Text,
-                text_query: Some("rust benchmark".to_owned()),
-                vector_query: None,
-                top_k: 1,
-                include_preview: true,
-            })
-            .unwrap();
-        assert_eq!(text.hits.len(), 1);
-        assert_eq!(text.hits[0].doc_id, "doc-001");
+        let request = |text: &str| RuntimeSearchRequest {
+            mode: RuntimeSearchMode::Text,
+            text_query: Some(text.to_owned()),
+            ..Default::default()
+        };
+        let doc_ids = |response: RuntimeSearchResponse| {
+            response
+                .hits
+                .into_iter()
+                .map(|hit| hit.doc_id)
+                .collect::<Vec<_>>()
+        };
+        let mut historical = RuntimeStore::open_at_generation(dataset_dir.path(), audited).unwrap();
+        assert_eq!(historical.historical_generation(), Some(audited));
         assert_eq!(
-            text.hits[0].preview.as_deref(),
-            Some("rust benchmark guide")
+            doc_ids(historical.search(request("lantern")).unwrap()),
+            ["doc-1"]
+        );
+        assert!(doc_ids(historical.search(request("harbor")).unwrap()).is_empty());
+        assert_eq!(
+            doc_ids(runtime.search(request("lantern")).unwrap()),
+            ["doc-1", "doc-2"]
         );
 
-        let hybrid = runtime
-            .search(RuntimeSearchRequest {
-                mode: RuntimeSearchMode::Hybrid,
-                text_query: Some("semantic latency".to_owned()),
-                vector_query: Some(embed_text("semantic latency", 384)),
-                top_k: 1,
-                include_preview: false,
-            })
+        // Publishing through another handle does not move the historical one.
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![NewDocument::new("doc-3", "lantern")])
             .unwrap();
-        assert_eq!(hybrid.hits.len(), 1);
-        assert_eq!(hybrid.hits[0].doc_id, "doc-002");
-        assert_eq!(hybrid.hits[0].preview, None);
-
-        runtime.close().unwrap();
+        assert_eq!(
+            doc_ids(historical.search(request("lantern")).unwrap()),
+            ["doc-1"]
+        );
+        let error = historical.writer().err().unwrap();
+        assert!(
+            matches!(error, crate::RuntimeError::InvalidRequest(message) if message.contains("read-only"))
+        );
+        assert!(RuntimeStore::open_at_generation(dataset_dir.path(), u64::MAX).is_err());
     }
 
     #[test]
-    fn runtime_hybrid_search_overfetches_text_candidates_before_rrf() {
+    fn read_snapshots_keep_serving_their_generation_across_threads_while_a_writer_publishes() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<crate::ReadSnapshot>();
+
         let dataset_dir = tempdir().unwrap();
-        let fixture_root =
-            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../fixtures/bench/source/minimal");
-        pack_dataset(&PackRequest::new(
-            &fixture_root,
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{}}\n",
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
             dataset_dir.path(),
             "small",
-            "clean",
         ))
         .unwrap();
-
         let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
         runtime
             .writer()
             .unwrap()
-            .publish_raw_snapshot(
-                vec![
-                    NewDocument::new("doc-001", "alpha"),
-                    NewDocument::new("doc-002", "beta"),
-                    NewDocument::new("doc-003", "alpha"),
-                ],
-                Some(vec![
-                    NewDocumentVector::new("doc-001", embed_text("other", 384)),
-                    NewDocumentVector::new("doc-002", embed_text("different", 384)),
-                    NewDocumentVector::new("doc-003", embed_text("alpha target", 384)),
-                ]),
-            )
+            .publish_raw_documents(vec![NewDocument::new("doc-1", "lantern")])
             .unwrap();
+        let snapshot = runtime.read_snapshot().unwrap();
 
-        let response = runtime
-            .search(RuntimeSearchRequest {
-                mode: RuntimeSearchMode::Hybrid,
-                text_query: Some("alpha".to_owned()),
-                vector_query: Some(embed_text("alpha target", 384)),
-                top_k: 1,
-                include_preview: false,
-            })
-            .unwrap();
+        let request = RuntimeSearchRequest {
+            mode: RuntimeSearchMode::Text,
+            text_query: Some("lantern".to_owned()),
+            ..Default::default()
+        };
+        // The readers pass the barrier together, so their searches overlap on the one handle
+        // they share and on its clones.
+        let barrier = std::sync::Barrier::new(4);
+        std::thread::scope(|scope| {
+            let readers = (0..4)
+                .map(|reader| {
+                    let shared = &snapshot;
+                    let cloned = snapshot.clone();
+                    let (barrier, request) = (&barrier, &request);
+                    scope.spawn(move || {
+                        let snapshot = if reader % 2 == 0 { shared } else { &cloned };
+                        barrier.wait();
+                        (0..20)
+                            .map(|_| {
+                                snapshot
+                                    .search(request.clone())
+                                    .unwrap()
+                                    .hits
+                                    .into_iter()
+                                    .map(|hit| hit.doc_id)
+                                    .collect::<Vec<_>>()
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            runtime
+                .writer()
+                .unwrap()
+                .publish_raw_documents(vec![NewDocument::new("doc-2", "lantern harbor")])
+                .unwrap();
+            for reader in readers {
+                for doc_ids in reader.join().unwrap() {
+                    assert_eq!(doc_ids, ["doc-1"]);
+                }
+            }
+        });
 
-        assert_eq!(response.hits.len(), 1);
-        assert_eq!(response.hits[0].doc_id, "doc-003");
+        assert_eq!(snapshot.search(request.clone()).unwrap().hits.len(), 1);
+        assert_eq!(runtime.search(request.clone()).unwrap().hits.len(), 2);
+        let later = runtime.read_snapshot().unwrap();
+        assert!(later.generation() > snapshot.generation());
+        assert_eq!(later.search(request).unwrap().hits.len(), 2);
     }
 
     #[test]
-    fn runtime_hybrid_search_handles_top_k_larger_than_corpus() {
+    fn writer_reports_writer_busy_when_the_store_lock_is_held_past_the_timeout() {
         let dataset_dir = tempdir().unwrap();
-        let fixture_root =
-            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../fixtures/bench/source/minimal");
-        pack_dataset(&PackRequest::new(
-            &fixture_root,
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(
+            &docs_path,
+            "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{}}\n",
+        )
+        .unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
             dataset_dir.path(),
             "small",
-            "clean",
         ))
         .unwrap();
+        let mut runtime = RuntimeStore::create(dataset_dir.path())
+            .unwrap()
+            .with_writer_lock_timeout(Duration::from_millis(20));
+        let holder = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(runtime.store_path())
+            .unwrap();
+        holder.lock_exclusive().unwrap();
+
+        let error = runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![NewDocument::new("doc-1", "lantern")])
+            .unwrap_err();
+        assert!(matches!(error, crate::RuntimeError::WriterBusy { waited_ms } if waited_ms >= 20));
+
+        holder.unlock().unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![NewDocument::new("doc-1", "lantern")])
+            .unwrap();
+    }
 
+    pub(crate) fn transaction_dataset() -> (tempfile::TempDir, RuntimeStore) {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(&docs_path, "{\"doc_id\":\"doc-001\",\"text\":\"alpha\"}\n").unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
         let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
         runtime
             .writer()
             .unwrap()
             .publish_raw_snapshot(
                 vec![
-                    NewDocument::new("doc-001", "alpha note"),
-                    NewDocument::new("doc-002", "beta note"),
-                    NewDocument::new("doc-003", "gamma note"),
+                    NewDocument::new("doc-001", "alpha"),
+                    NewDocument::new("doc-002", "beta"),
                 ],
-                Some(vec![
-                    NewDocumentVector::new("doc-001", embed_text("alpha note", 384)),
-                    NewDocumentVector::new("doc-002", embed_text("beta note", 384)),
-                    NewDocumentVector::new("doc-003", embed_text("gamma note", 384)),
-                ]),
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    vec![
+                        NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
+                        NewDocumentVector::new("doc-002", embed_text("beta", 384)),
+                    ],
+                )),
             )
             .unwrap();
+        (dataset_dir, runtime)
+    }
 
-        let response = runtime
-            .search(RuntimeSearchRequest {
-                mode: RuntimeSearchMode::Hybrid,
-                text_query: Some("alpha".to_owned()),
-                vector_query: Some(embed_text("alpha note", 384)),
-                top_k: 5,
-                include_preview: false,
-            })
-            .unwrap();
-
-        assert!(!response.hits.is_empty());
-        assert!(response.hits.len() <= 3);
-        assert_eq!(response.hits[0].doc_id, "doc-001");
+    pub(crate) fn default_space_doc_ids(runtime: &RuntimeStore) -> Vec<String> {
+        let mut doc_ids: Vec<String> = wax_v2_vector::load_current_store_vector_rows(
+            &runtime.store_path(),
+            DEFAULT_VECTOR_SPACE,
+        )
+        .unwrap()
+        .map(|current| current.rows.into_iter().map(|(doc_id, _)| doc_id).collect())
+        .unwrap_or_default();
+        doc_ids.sort();
+        doc_ids
     }
 
     #[test]
-    fn runtime_search_validates_mode_inputs_before_zero_top_k_short_circuit() {
-        let dataset_dir = tempdir().unwrap();
-        let fixture_root =
-            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../fixtures/bench/source/minimal");
-        pack_dataset(&PackRequest::new(
-            &fixture_root,
-            dataset_dir.path(),
-            "small",
-            "clean",
-        ))
-        .unwrap();
-
-        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+    fn compressed_doc_segments_hydrate_the_same_documents_as_uncompressed_ones() {
+        let documents = || {
+            (0..50)
+                .map(|index| {
+                    NewDocument::new(format!("doc-{index:03}"), format!("shared body {index}"))
+                        .with_metadata(serde_json::json!({"bucket": index % 3}))
+                })
+                .collect::<Vec<_>>()
+        };
+        let doc_ids = (0..50)
+            .map(|index| format!("doc-{index:03}"))
+            .collect::<Vec<_>>();
+        let mut hydrated = Vec::new();
+        for compression in [
+            DocCompression::None,
+            DocCompression::Lz4,
+            DocCompression::Zstd,
+        ] {
+            let (dataset_dir, runtime) = transaction_dataset();
+            let mut runtime = runtime.with_doc_compression(compression);
+            assert_eq!(runtime.doc_compression(), compression);
+            runtime
+                .writer()
+                .unwrap()
+                .publish_raw_snapshot(documents()[..20].to_vec(), None)
+                .unwrap();
+            runtime
+                .writer()
+                .unwrap()
+                .publish_document_stream(documents().into_iter().skip(10).map(Ok))
+                .unwrap();
 
-        let error = runtime
-            .search(RuntimeSearchRequest {
-                mode: RuntimeSearchMode::Hybrid,
-                text_query: Some("alpha".to_owned()),
-                vector_query: None,
-                top_k: 0,
-                include_preview: false,
-            })
-            .unwrap_err();
+            assert!(RuntimeStore::verify(dataset_dir.path()).unwrap().is_ok());
+            hydrated.push(runtime.docstore.load_documents_by_id(&doc_ids).unwrap());
+        }
 
-        assert!(
-            matches!(error, crate::RuntimeError::InvalidRequest(message) if message.contains("vector_query is required for hybrid search"))
-        );
+        assert_eq!(hydrated[0].len(), 50);
+        assert_eq!(hydrated[1], hydrated[0]);
+        assert_eq!(hydrated[2], hydrated[0]);
     }
 
     #[test]
-    fn runtime_hybrid_search_uses_live_doc_count_for_raw_publish_overfetch() {
+    fn rollback_to_reverts_a_bad_ingest_without_reingesting() {
         let dataset_dir = tempdir().unwrap();
         let source_dir = tempdir().unwrap();
         let docs_path = source_dir.path().join("docs.ndjson");
-        fs::write(&docs_path, "{\"doc_id\":\"seed-001\",\"text\":\"seed\"}\n").unwrap();
+        fs::write(
+            &docs_path,
+            "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{}}\n",
+        )
+        .unwrap();
         pack_adhoc_dataset(&AdhocPackRequest::new(
             &docs_path,
             dataset_dir.path(),
             "small",
         ))
         .unwrap();
-
         let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
-        runtime
+        let good = runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![NewDocument::new("doc-1", "lantern")])
+            .unwrap()
+            .generation;
+        let mut reader = RuntimeStore::open(dataset_dir.path()).unwrap();
+        let bad = runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![
+                NewDocument::new("doc-1", "garbled"),
+                NewDocument::new("doc-2", "lantern garbled"),
+            ])
+            .unwrap()
+            .generation;
+        let request = || RuntimeSearchRequest {
+            mode: RuntimeSearchMode::Text,
+            text_query: Some("lantern".to_owned()),
+            ..Default::default()
+        };
+        let doc_ids = |response: RuntimeSearchResponse| {
+            response
+                .hits
+                .into_iter()
+                .map(|hit| hit.doc_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(doc_ids(reader.search(request()).unwrap()), ["doc-2"]);
+
+        let report = runtime.writer().unwrap().rollback_to(good).unwrap();
+        assert_eq!(report.generation, bad + 1);
+        assert!(report.published_families.is_empty());
+        assert_eq!(doc_ids(runtime.search(request()).unwrap()), ["doc-1"]);
+        // Other handles pick the rollback up like any publish.
+        assert_eq!(doc_ids(reader.search(request()).unwrap()), ["doc-1"]);
+        assert_eq!(
+            doc_ids(
+                RuntimeStore::open_at_generation(dataset_dir.path(), bad)
+                    .unwrap()
+                    .search(request())
+                    .unwrap()
+            ),
+            ["doc-2"]
+        );
+
+        let error = runtime
             .writer()
             .unwrap()
-            .publish_raw_snapshot(
-                vec![
-                    NewDocument::new("doc-001", "alpha"),
-                    NewDocument::new("doc-002", "alpha"),
-                ],
-                Some(vec![
-                    NewDocumentVector::new("doc-001", test_vector(0.0)),
-                    NewDocumentVector::new("doc-002", test_vector(1.0)),
-                ]),
-            )
-            .unwrap();
-
-        let response = runtime
-            .search(RuntimeSearchRequest {
-                mode: RuntimeSearchMode::Hybrid,
-                text_query: Some("alpha".to_owned()),
-                vector_query: Some(test_vector(1.0)),
-                top_k: 1,
-                include_preview: false,
-            })
-            .unwrap();
-
-        assert_eq!(response.hits[0].doc_id, "doc-002");
+            .rollback_to(report.generation)
+            .unwrap_err();
+        assert!(
+            matches!(error, crate::RuntimeError::InvalidRequest(message) if message.contains("older than the active generation"))
+        );
     }
 
     #[test]
-    fn compatibility_import_preserves_extra_document_payload_fields() {
+    fn runtime_search_refreshes_when_another_handle_publishes_documents() {
         let dataset_dir = tempdir().unwrap();
         let source_dir = tempdir().unwrap();
         let docs_path = source_dir.path().join("docs.ndjson");
         fs::write(
             &docs_path,
             concat!(
-                "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{\"kind\":\"note\"},",
-                "\"workspace_id\":\"workspace-a\",\"tags\":[\"one\",\"two\"]}\n",
+                "{\"doc_id\":\"doc-001\",\"text\":\"alpha original\"}\n",
+                "{\"doc_id\":\"doc-002\",\"text\":\"beta original\"}\n",
             ),
         )
         .unwrap();
@@ -1410,37 +4444,43 @@ mod tests {
         ))
         .unwrap();
 
-        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
-        runtime
+        let mut reader = RuntimeStore::create(dataset_dir.path()).unwrap();
+        let mut writer = RuntimeStore::open(dataset_dir.path()).unwrap();
+        let first = reader
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Text,
+                text_query: Some("alpha".to_owned()),
+                top_k: 1,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(first.hits[0].doc_id, "doc-001");
+
+        writer
             .writer()
             .unwrap()
-            .import_compatibility_snapshot()
+            .publish_raw_documents(vec![NewDocument::new("doc-003", "fresh remote token")])
             .unwrap();
 
-        let loaded = runtime
-            .docstore
-            .load_documents_by_id(&["doc-001".to_owned()])
+        let refreshed = reader
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Text,
+                text_query: Some("fresh remote token".to_owned()),
+                top_k: 1,
+                include_preview: true,
+                ..Default::default()
+            })
             .unwrap();
+
+        assert_eq!(refreshed.hits[0].doc_id, "doc-003");
         assert_eq!(
-            loaded.get("doc-001").unwrap().get("workspace_id"),
-            Some(&json!("workspace-a"))
-        );
-        assert_eq!(
-            loaded.get("doc-001").unwrap().get("tags"),
-            Some(&json!(["one", "two"]))
-        );
-        assert_eq!(
-            loaded
-                .get("doc-001")
-                .unwrap()
-                .get("metadata")
-                .and_then(|metadata| metadata.get("kind")),
-            Some(&json!("note"))
+            refreshed.hits[0].preview.as_deref(),
+            Some("fresh remote token")
         );
     }
 
     #[test]
-    fn runtime_store_creates_and_publishes_compatibility_segments_for_reopen_search() {
+    fn runtime_search_invalidates_cached_lanes_when_store_file_is_removed() {
         let dataset_dir = tempdir().unwrap();
         let fixture_root =
             PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../fixtures/bench/source/minimal");
@@ -1453,93 +4493,50 @@ mod tests {
         .unwrap();
 
         let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
-        let publish_report = runtime
+        runtime
             .writer()
             .unwrap()
             .import_compatibility_snapshot()
             .unwrap();
-        assert_eq!(publish_report.generation, 1);
-        assert_eq!(
-            publish_report.published_families,
-            vec![
-                RuntimePublishFamily::Doc,
-                RuntimePublishFamily::Text,
-                RuntimePublishFamily::Vector,
-            ]
-        );
-        runtime.close().unwrap();
+        let first = runtime
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Text,
+                text_query: Some("rust benchmark".to_owned()),
+                top_k: 1,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(first.hits[0].doc_id, "doc-001");
 
+        fs::remove_file(dataset_dir.path().join("store.wax")).unwrap();
         for kind in [
             "documents",
             "document_offsets",
             "text_postings",
             "document_ids",
-            "document_vectors",
-            "document_vectors_preview_q8",
         ] {
             for file in manifest.files.iter().filter(|file| file.kind == kind) {
-                fs::remove_file(dataset_dir.path().join(&file.path)).unwrap();
+                let path = dataset_dir.path().join(&file.path);
+                if path.exists() {
+                    fs::remove_file(path).unwrap();
+                }
             }
         }
 
-        let mut reopened = RuntimeStore::open(dataset_dir.path()).unwrap();
-        let text_response = reopened
+        let error = runtime
             .search(RuntimeSearchRequest {
                 mode: RuntimeSearchMode::Text,
                 text_query: Some("rust benchmark".to_owned()),
-                vector_query: None,
-                top_k: 2,
-                include_preview: true,
-            })
-            .unwrap();
-        assert_eq!(text_response.hits[0].doc_id, "doc-001");
-        assert_eq!(
-            text_response.hits[0].preview.as_deref(),
-            Some("rust benchmark guide")
-        );
-
-        let vector_response = reopened
-            .search(RuntimeSearchRequest {
-                mode: RuntimeSearchMode::Vector,
-                text_query: None,
-                vector_query: Some(embed_text("semantic latency", 384)),
-                top_k: 2,
-                include_preview: false,
+                top_k: 1,
+                ..Default::default()
             })
-            .unwrap();
-        assert_eq!(vector_response.hits[0].doc_id, "doc-002");
-    }
-
-    #[test]
-    fn runtime_store_create_removes_store_when_initialization_fails() {
-        let dataset_dir = tempdir().unwrap();
-        let source_dir = tempdir().unwrap();
-        let docs_path = source_dir.path().join("docs.ndjson");
-        fs::write(&docs_path, "{\"doc_id\":\"doc-001\",\"text\":\"alpha\"}\n").unwrap();
-        pack_adhoc_dataset(&AdhocPackRequest::new(
-            &docs_path,
-            dataset_dir.path(),
-            "small",
-        ))
-        .unwrap();
-        let manifest = read_manifest(dataset_dir.path()).unwrap();
-        let store_path = dataset_dir.path().join("store.wax");
-        fs::write(&store_path, b"not-a-valid-store").unwrap();
-
-        let error =
-            match RuntimeStore::open_created_store(dataset_dir.path(), manifest, &store_path) {
-                Ok(_) => {
-                    panic!("create cleanup path should fail when reopen cannot validate store")
-                }
-                Err(error) => error,
-            };
+            .unwrap_err();
 
         assert!(matches!(error, crate::RuntimeError::Storage(_)));
-        assert!(!store_path.exists());
     }
 
     #[test]
-    fn publish_raw_snapshot_rejects_duplicate_document_doc_ids() {
+    fn publish_raw_vectors_refreshes_documents_published_by_another_handle() {
         let dataset_dir = tempdir().unwrap();
         let source_dir = tempdir().unwrap();
         let docs_path = source_dir.path().join("docs.ndjson");
@@ -1558,67 +4555,38 @@ mod tests {
         ))
         .unwrap();
 
-        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
-        let error = runtime
+        let mut stale_runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        let mut writer_runtime = RuntimeStore::open(dataset_dir.path()).unwrap();
+        writer_runtime
             .writer()
             .unwrap()
-            .publish_raw_snapshot(
-                vec![
-                    NewDocument::new("doc-001", "alpha"),
-                    NewDocument::new("doc-001", "beta"),
-                ],
-                None,
-            )
-            .unwrap_err();
-
-        assert!(
-            matches!(error, crate::RuntimeError::InvalidRequest(message) if message.contains("duplicate doc_ids"))
-        );
-    }
-
-    #[test]
-    fn publish_raw_snapshot_rejects_duplicate_vector_doc_ids() {
-        let dataset_dir = tempdir().unwrap();
-        let source_dir = tempdir().unwrap();
-        let docs_path = source_dir.path().join("docs.ndjson");
-        fs::write(
-            &docs_path,
-            concat!(
-                "{\"doc_id\":\"doc-001\",\"text\":\"alpha\"}\n",
-                "{\"doc_id\":\"doc-002\",\"text\":\"beta\"}\n",
-            ),
-        )
-        .unwrap();
-        pack_adhoc_dataset(&AdhocPackRequest::new(
-            &docs_path,
-            dataset_dir.path(),
-            "small",
-        ))
-        .unwrap();
+            .publish_raw_documents(vec![
+                NewDocument::new("doc-001", "alpha refreshed"),
+                NewDocument::new("doc-002", "beta"),
+            ])
+            .unwrap();
 
-        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
-        let error = runtime
+        let embedding = stale_runtime.embedding_identity();
+        let report = stale_runtime
             .writer()
             .unwrap()
-            .publish_raw_snapshot(
+            .publish_raw_vectors(
+                embedding,
                 vec![
-                    NewDocument::new("doc-001", "alpha"),
-                    NewDocument::new("doc-002", "beta"),
+                    NewDocumentVector::new("doc-001", embed_text("alpha refreshed", 384)),
+                    NewDocumentVector::new("doc-002", embed_text("beta", 384)),
                 ],
-                Some(vec![
-                    NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
-                    NewDocumentVector::new("doc-001", embed_text("beta", 384)),
-                ]),
             )
-            .unwrap_err();
+            .unwrap();
 
-        assert!(
-            matches!(error, crate::RuntimeError::InvalidRequest(message) if message.contains("duplicate doc_ids"))
+        assert_eq!(
+            report.published_families,
+            vec![RuntimePublishFamily::Vector]
         );
     }
 
     #[test]
-    fn publish_raw_snapshot_truncates_missing_vector_doc_ids_in_error_message() {
+    fn doc_generation_revalidation_rejects_changed_manifest_before_vector_publish() {
         let dataset_dir = tempdir().unwrap();
         let source_dir = tempdir().unwrap();
         let docs_path = source_dir.path().join("docs.ndjson");
@@ -1627,10 +4595,6 @@ mod tests {
             concat!(
                 "{\"doc_id\":\"doc-001\",\"text\":\"alpha\"}\n",
                 "{\"doc_id\":\"doc-002\",\"text\":\"beta\"}\n",
-                "{\"doc_id\":\"doc-003\",\"text\":\"gamma\"}\n",
-                "{\"doc_id\":\"doc-004\",\"text\":\"delta\"}\n",
-                "{\"doc_id\":\"doc-005\",\"text\":\"epsilon\"}\n",
-                "{\"doc_id\":\"doc-006\",\"text\":\"zeta\"}\n",
             ),
         )
         .unwrap();
@@ -1642,41 +4606,38 @@ mod tests {
         .unwrap();
 
         let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
-        let error = runtime
+        runtime
             .writer()
             .unwrap()
-            .publish_raw_snapshot(
-                vec![
-                    NewDocument::new("doc-001", "alpha"),
-                    NewDocument::new("doc-002", "beta"),
-                    NewDocument::new("doc-003", "gamma"),
-                    NewDocument::new("doc-004", "delta"),
-                    NewDocument::new("doc-005", "epsilon"),
-                    NewDocument::new("doc-006", "zeta"),
-                ],
-                Some(vec![
-                    NewDocumentVector::new("missing-001", embed_text("alpha", 384)),
-                    NewDocumentVector::new("missing-002", embed_text("beta", 384)),
-                    NewDocumentVector::new("missing-003", embed_text("gamma", 384)),
-                    NewDocumentVector::new("missing-004", embed_text("delta", 384)),
-                    NewDocumentVector::new("missing-005", embed_text("epsilon", 384)),
-                    NewDocumentVector::new("missing-006", embed_text("zeta", 384)),
-                ]),
-            )
-            .unwrap_err();
+            .publish_raw_documents(vec![NewDocument::new("doc-001", "alpha")])
+            .unwrap();
+        let store_path = dataset_dir.path().join("store.wax");
+        let validated_doc_segment =
+            super::latest_doc_segment_identity_from_store(&store_path).unwrap();
 
-        assert!(matches!(
-            error,
-            crate::RuntimeError::InvalidRequest(message)
-                if message.contains("missing-001")
-                    && message.contains("missing-005")
-                    && message.contains("(+1 more)")
-                    && !message.contains("missing-006")
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![
+                NewDocument::new("doc-001", "alpha"),
+                NewDocument::new("doc-002", "beta"),
+            ])
+            .unwrap();
+
+        let opened = open_store(&store_path).unwrap();
+        let error =
+            super::ensure_doc_segment_unchanged(&opened.manifest, validated_doc_segment.as_ref())
+                .unwrap_err();
+
+        assert!(matches!(
+            error,
+            wax_v2_core::CoreError::PublishPreconditionFailed(message)
+                if message.contains("document generation changed")
         ));
     }
 
     #[test]
-    fn publish_raw_vectors_counts_only_active_documents() {
+    fn publish_raw_snapshot_vectors_use_latest_store_doc_id_map() {
         let dataset_dir = tempdir().unwrap();
         let source_dir = tempdir().unwrap();
         let docs_path = source_dir.path().join("docs.ndjson");
@@ -1695,53 +4656,58 @@ mod tests {
         ))
         .unwrap();
 
-        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
-        runtime
+        let mut stale_runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        let mut writer_runtime = RuntimeStore::open(dataset_dir.path()).unwrap();
+        writer_runtime
             .writer()
             .unwrap()
-            .publish_raw_snapshot(
-                vec![
-                    NewDocument::new("doc-001", "alpha"),
-                    NewDocument::new("doc-002", "beta"),
-                ],
-                Some(vec![
-                    NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
-                    NewDocumentVector::new("doc-002", embed_text("beta", 384)),
-                ]),
-            )
+            .publish_raw_documents(vec![NewDocument::new("doc-002", "beta first")])
             .unwrap();
 
-        runtime
+        stale_runtime
             .writer()
             .unwrap()
-            .publish_raw_snapshot(vec![NewDocument::new("doc-001", "alpha only")], None)
+            .publish_raw_snapshot(
+                vec![
+                    NewDocument::new("doc-002", "beta first"),
+                    NewDocument::new("doc-001", "alpha second"),
+                ],
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    vec![
+                        NewDocumentVector::new("doc-002", embed_text("beta first", 384)),
+                        NewDocumentVector::new("doc-001", embed_text("alpha second", 384)),
+                    ],
+                )),
+            )
             .unwrap();
 
-        let report = runtime
-            .writer()
-            .unwrap()
-            .publish_raw_vectors(vec![NewDocumentVector::new(
-                "doc-001",
-                embed_text("alpha only", 384),
-            )])
+        let opened = open_store(&dataset_dir.path().join("store.wax")).unwrap();
+        let vector_segment = opened
+            .manifest
+            .segments
+            .iter()
+            .rfind(|segment| segment.family == SegmentKind::Vec)
             .unwrap();
+        let bytes =
+            map_segment_object(&dataset_dir.path().join("store.wax"), vector_segment).unwrap();
 
         assert_eq!(
-            report.published_families,
-            vec![RuntimePublishFamily::Vector]
+            read_vector_segment_doc_ids(&bytes),
+            vec!["doc-002".to_owned(), "doc-001".to_owned()]
         );
     }
 
     #[test]
-    fn publish_raw_documents_merges_with_existing_active_documents() {
+    fn publish_raw_snapshot_removes_vectors_for_doc_only_replace() {
         let dataset_dir = tempdir().unwrap();
         let source_dir = tempdir().unwrap();
         let docs_path = source_dir.path().join("docs.ndjson");
         fs::write(
             &docs_path,
             concat!(
-                "{\"doc_id\":\"doc-001\",\"text\":\"alpha\",\"metadata\":{\"workspace\":\"old\"},\"priority\":\"keep\"}\n",
-                "{\"doc_id\":\"doc-002\",\"text\":\"beta\",\"metadata\":{\"workspace\":\"old\"}}\n",
+                "{\"doc_id\":\"doc-001\",\"text\":\"alpha\"}\n",
+                "{\"doc_id\":\"doc-002\",\"text\":\"beta\"}\n",
             ),
         )
         .unwrap();
@@ -1756,58 +4722,46 @@ mod tests {
         runtime
             .writer()
             .unwrap()
-            .publish_raw_documents(vec![
-                NewDocument::new("doc-001", "alpha")
-                    .with_metadata(serde_json::json!({"workspace":"old"}))
-                    .with_extra_field("priority", serde_json::json!("keep")),
-                NewDocument::new("doc-002", "beta")
-                    .with_metadata(serde_json::json!({"workspace":"old"})),
-            ])
+            .publish_raw_snapshot(
+                vec![
+                    NewDocument::new("doc-001", "alpha"),
+                    NewDocument::new("doc-002", "beta"),
+                ],
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    vec![
+                        NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
+                        NewDocumentVector::new("doc-002", embed_text("beta", 384)),
+                    ],
+                )),
+            )
             .unwrap();
+
         runtime
             .writer()
             .unwrap()
-            .publish_raw_documents(vec![NewDocument::new("doc-003", "gamma")])
+            .publish_raw_snapshot(vec![NewDocument::new("doc-003", "gamma")], None)
             .unwrap();
 
-        let reopened = RuntimeStore::open(dataset_dir.path()).unwrap();
-        let doc_ids = reopened.docstore.load_document_ids().unwrap();
-        let documents = reopened.docstore.load_documents_by_id(&doc_ids).unwrap();
-
-        assert_eq!(
-            doc_ids,
-            vec![
-                "doc-001".to_owned(),
-                "doc-002".to_owned(),
-                "doc-003".to_owned()
-            ]
-        );
-        assert_eq!(
-            documents
-                .get("doc-001")
-                .and_then(|document| document.get("priority"))
-                .and_then(serde_json::Value::as_str),
-            Some("keep")
-        );
-        assert_eq!(
-            documents
-                .get("doc-003")
-                .and_then(|document| document.get("text"))
-                .and_then(serde_json::Value::as_str),
-            Some("gamma")
-        );
+        let opened = open_store(&dataset_dir.path().join("store.wax")).unwrap();
+        assert!(opened
+            .manifest
+            .segments
+            .iter()
+            .all(|segment| segment.family != SegmentKind::Vec));
     }
 
     #[test]
-    fn runtime_search_refreshes_when_another_handle_publishes_documents() {
+    fn publish_raw_vectors_persists_rows_in_wax_doc_id_order() {
         let dataset_dir = tempdir().unwrap();
         let source_dir = tempdir().unwrap();
         let docs_path = source_dir.path().join("docs.ndjson");
         fs::write(
             &docs_path,
             concat!(
-                "{\"doc_id\":\"doc-001\",\"text\":\"alpha original\"}\n",
-                "{\"doc_id\":\"doc-002\",\"text\":\"beta original\"}\n",
+                "{\"doc_id\":\"doc-001\",\"text\":\"alpha\"}\n",
+                "{\"doc_id\":\"doc-002\",\"text\":\"beta\"}\n",
+                "{\"doc_id\":\"doc-003\",\"text\":\"gamma\"}\n",
             ),
         )
         .unwrap();
@@ -1818,159 +4772,128 @@ mod tests {
         ))
         .unwrap();
 
-        let mut reader = RuntimeStore::create(dataset_dir.path()).unwrap();
-        let mut writer = RuntimeStore::open(dataset_dir.path()).unwrap();
-        let first = reader
-            .search(RuntimeSearchRequest {
-                mode: RuntimeSearchMode::Text,
-                text_query: Some("alpha".to_owned()),
-                vector_query: None,
-                top_k: 1,
-                include_preview: false,
-            })
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_snapshot(
+                vec![
+                    NewDocument::new("doc-001", "alpha"),
+                    NewDocument::new("doc-002", "beta"),
+                    NewDocument::new("doc-003", "gamma"),
+                ],
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    vec![
+                        NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
+                        NewDocumentVector::new("doc-002", embed_text("beta", 384)),
+                        NewDocumentVector::new("doc-003", embed_text("gamma", 384)),
+                    ],
+                )),
+            )
             .unwrap();
-        assert_eq!(first.hits[0].doc_id, "doc-001");
-
-        writer
+        let embedding = runtime.embedding_identity();
+        runtime
             .writer()
             .unwrap()
-            .publish_raw_documents(vec![NewDocument::new("doc-003", "fresh remote token")])
+            .publish_raw_vectors(
+                embedding,
+                vec![
+                    NewDocumentVector::new("doc-003", embed_text("gamma updated", 384)),
+                    NewDocumentVector::new("doc-001", embed_text("alpha updated", 384)),
+                    NewDocumentVector::new("doc-002", embed_text("beta updated", 384)),
+                ],
+            )
             .unwrap();
 
-        let refreshed = reader
-            .search(RuntimeSearchRequest {
-                mode: RuntimeSearchMode::Text,
-                text_query: Some("fresh remote token".to_owned()),
-                vector_query: None,
-                top_k: 1,
-                include_preview: true,
-            })
+        let opened = open_store(&dataset_dir.path().join("store.wax")).unwrap();
+        let vector_segment = opened
+            .manifest
+            .segments
+            .iter()
+            .rfind(|segment| segment.family == SegmentKind::Vec)
             .unwrap();
-
-        assert_eq!(refreshed.hits[0].doc_id, "doc-003");
+        let bytes =
+            map_segment_object(&dataset_dir.path().join("store.wax"), vector_segment).unwrap();
         assert_eq!(
-            refreshed.hits[0].preview.as_deref(),
-            Some("fresh remote token")
+            read_vector_segment_doc_ids(&bytes),
+            vec![
+                "doc-001".to_owned(),
+                "doc-002".to_owned(),
+                "doc-003".to_owned()
+            ]
         );
     }
 
     #[test]
-    fn runtime_search_invalidates_cached_lanes_when_store_file_is_removed() {
+    fn publish_raw_vectors_uses_half_precision_payload_for_f16_manifest_dtype() {
         let dataset_dir = tempdir().unwrap();
-        let fixture_root =
-            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../fixtures/bench/source/minimal");
-        let manifest = pack_dataset(&PackRequest::new(
-            &fixture_root,
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(&docs_path, "{\"doc_id\":\"seed-001\",\"text\":\"seed\"}\n").unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
             dataset_dir.path(),
             "small",
-            "clean",
         ))
         .unwrap();
+        let mut manifest = read_manifest(dataset_dir.path()).unwrap();
+        manifest.vector_profile.embedding_dtype = "f16".to_owned();
+        fs::write(
+            dataset_dir.path().join("manifest.json"),
+            serde_json::to_vec_pretty(&manifest).unwrap(),
+        )
+        .unwrap();
 
         let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
         runtime
             .writer()
             .unwrap()
-            .import_compatibility_snapshot()
-            .unwrap();
-        let first = runtime
-            .search(RuntimeSearchRequest {
-                mode: RuntimeSearchMode::Text,
-                text_query: Some("rust benchmark".to_owned()),
-                vector_query: None,
-                top_k: 1,
-                include_preview: false,
-            })
+            .publish_raw_snapshot(
+                vec![
+                    NewDocument::new("doc-001", "alpha"),
+                    NewDocument::new("doc-002", "beta"),
+                ],
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    vec![
+                        NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
+                        NewDocumentVector::new("doc-002", embed_text("beta", 384)),
+                    ],
+                )),
+            )
             .unwrap();
-        assert_eq!(first.hits[0].doc_id, "doc-001");
 
-        fs::remove_file(dataset_dir.path().join("store.wax")).unwrap();
-        for kind in [
-            "documents",
-            "document_offsets",
-            "text_postings",
-            "document_ids",
-        ] {
-            for file in manifest.files.iter().filter(|file| file.kind == kind) {
-                let path = dataset_dir.path().join(&file.path);
-                if path.exists() {
-                    fs::remove_file(path).unwrap();
-                }
-            }
-        }
+        let opened = open_store(&dataset_dir.path().join("store.wax")).unwrap();
+        let vector_segment = opened
+            .manifest
+            .segments
+            .iter()
+            .rfind(|segment| segment.family == SegmentKind::Vec)
+            .unwrap();
+        let bytes =
+            map_segment_object(&dataset_dir.path().join("store.wax"), vector_segment).unwrap();
+        let exact_vectors_offset = read_u64_at(&bytes, 32) as usize;
+        assert_eq!(bytes.len() - exact_vectors_offset, 2 * 384 * 2);
 
-        let error = runtime
+        let response = runtime
             .search(RuntimeSearchRequest {
-                mode: RuntimeSearchMode::Text,
-                text_query: Some("rust benchmark".to_owned()),
-                vector_query: None,
+                mode: RuntimeSearchMode::Vector,
+                vector_query: Some(embed_text("beta", 384)),
                 top_k: 1,
-                include_preview: false,
+                vector_embedding: Some(runtime.embedding_identity()),
+                ..Default::default()
             })
-            .unwrap_err();
-
-        assert!(matches!(error, crate::RuntimeError::Storage(_)));
-    }
-
-    #[test]
-    fn publish_raw_vectors_refreshes_documents_published_by_another_handle() {
-        let dataset_dir = tempdir().unwrap();
-        let source_dir = tempdir().unwrap();
-        let docs_path = source_dir.path().join("docs.ndjson");
-        fs::write(
-            &docs_path,
-            concat!(
-                "{\"doc_id\":\"doc-001\",\"text\":\"alpha\"}\n",
-                "{\"doc_id\":\"doc-002\",\"text\":\"beta\"}\n",
-            ),
-        )
-        .unwrap();
-        pack_adhoc_dataset(&AdhocPackRequest::new(
-            &docs_path,
-            dataset_dir.path(),
-            "small",
-        ))
-        .unwrap();
-
-        let mut stale_runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
-        let mut writer_runtime = RuntimeStore::open(dataset_dir.path()).unwrap();
-        writer_runtime
-            .writer()
-            .unwrap()
-            .publish_raw_documents(vec![
-                NewDocument::new("doc-001", "alpha refreshed"),
-                NewDocument::new("doc-002", "beta"),
-            ])
-            .unwrap();
-
-        let report = stale_runtime
-            .writer()
-            .unwrap()
-            .publish_raw_vectors(vec![
-                NewDocumentVector::new("doc-001", embed_text("alpha refreshed", 384)),
-                NewDocumentVector::new("doc-002", embed_text("beta", 384)),
-            ])
             .unwrap();
-
-        assert_eq!(
-            report.published_families,
-            vec![RuntimePublishFamily::Vector]
-        );
+        assert_eq!(response.hits[0].doc_id, "doc-002");
     }
 
     #[test]
-    fn doc_generation_revalidation_rejects_changed_manifest_before_vector_publish() {
+    fn named_vector_spaces_publish_independently_and_fuse_at_search_time() {
         let dataset_dir = tempdir().unwrap();
         let source_dir = tempdir().unwrap();
         let docs_path = source_dir.path().join("docs.ndjson");
-        fs::write(
-            &docs_path,
-            concat!(
-                "{\"doc_id\":\"doc-001\",\"text\":\"alpha\"}\n",
-                "{\"doc_id\":\"doc-002\",\"text\":\"beta\"}\n",
-            ),
-        )
-        .unwrap();
+        fs::write(&docs_path, "{\"doc_id\":\"seed-001\",\"text\":\"seed\"}\n").unwrap();
         pack_adhoc_dataset(&AdhocPackRequest::new(
             &docs_path,
             dataset_dir.path(),
@@ -1982,46 +4905,121 @@ mod tests {
         runtime
             .writer()
             .unwrap()
-            .publish_raw_documents(vec![NewDocument::new("doc-001", "alpha")])
+            .publish_raw_snapshot(
+                vec![
+                    NewDocument::new("doc-001", "alpha"),
+                    NewDocument::new("doc-002", "beta"),
+                    NewDocument::new("doc-003", "gamma"),
+                ],
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    vec![
+                        NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
+                        NewDocumentVector::new("doc-002", embed_text("beta", 384)),
+                        NewDocumentVector::new("doc-003", embed_text("gamma", 384)),
+                    ],
+                )),
+            )
             .unwrap();
-        let store_path = dataset_dir.path().join("store.wax");
-        let validated_doc_segment =
-            super::latest_doc_segment_identity_from_store(&store_path).unwrap();
-
+        let images = RuntimeVectorSpace::new("images", 2, RuntimeVectorMetric::L2)
+            .with_embedding(test_embedding("images"));
         runtime
             .writer()
             .unwrap()
-            .publish_raw_documents(vec![
-                NewDocument::new("doc-001", "alpha"),
-                NewDocument::new("doc-002", "beta"),
-            ])
+            .publish_raw_vectors_to_space(
+                images.clone(),
+                vec![
+                    NewDocumentVector::new("doc-001", vec![9.0, 9.0]),
+                    NewDocumentVector::new("doc-002", vec![5.0, 5.0]),
+                    NewDocumentVector::new("doc-003", vec![0.0, 0.0]),
+                ],
+            )
+            .unwrap();
+        // Republishing the default space must keep the named space alive.
+        let embedding = runtime.embedding_identity();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_vectors(
+                embedding,
+                vec![
+                    NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
+                    NewDocumentVector::new("doc-002", embed_text("beta", 384)),
+                    NewDocumentVector::new("doc-003", embed_text("gamma", 384)),
+                ],
+            )
             .unwrap();
 
-        let opened = open_store(&store_path).unwrap();
-        let error =
-            super::ensure_doc_segment_unchanged(&opened.manifest, validated_doc_segment.as_ref())
-                .unwrap_err();
+        let spaces = runtime.vector_spaces().unwrap();
+        assert_eq!(spaces.len(), 2);
+        assert_eq!(spaces[0].name, "default");
+        assert_eq!(spaces[0].dimensions, 384);
+        assert_eq!(spaces[1], images);
+
+        let search = |runtime: &mut RuntimeStore,
+                      vector_query: Option<Vec<f32>>,
+                      vector_space_queries: Vec<RuntimeVectorSpaceQuery>| {
+            let vector_embedding = vector_query.as_ref().map(|_| runtime.embedding_identity());
+            runtime
+                .search(RuntimeSearchRequest {
+                    mode: RuntimeSearchMode::Vector,
+                    vector_query,
+                    top_k: 3,
+                    vector_embedding,
+                    vector_space_queries,
+                    ..Default::default()
+                })
+                .map(|response| {
+                    response
+                        .hits
+                        .into_iter()
+                        .map(|hit| hit.doc_id)
+                        .collect::<Vec<_>>()
+                })
+        };
 
-        assert!(matches!(
-            error,
-            wax_v2_core::CoreError::PublishPreconditionFailed(message)
-                if message.contains("document generation changed")
-        ));
+        assert_eq!(
+            search(
+                &mut runtime,
+                None,
+                vec![RuntimeVectorSpaceQuery::new("images", vec![1.0, 1.0])
+                    .with_embedding(test_embedding("images"))],
+            )
+            .unwrap(),
+            vec!["doc-003", "doc-002", "doc-001"]
+        );
+        let fused = search(
+            &mut runtime,
+            Some(embed_text("alpha", 384)),
+            vec![RuntimeVectorSpaceQuery::new("images", vec![8.0, 8.0])
+                .with_embedding(test_embedding("images"))],
+        )
+        .unwrap();
+        assert_eq!(fused[0], "doc-001");
+
+        let error = search(
+            &mut runtime,
+            None,
+            vec![RuntimeVectorSpaceQuery::new("images", vec![1.0, 1.0, 1.0])
+                .with_embedding(test_embedding("images"))],
+        )
+        .expect_err("query dimensions must match the space");
+        assert!(error.to_string().contains("dimension"));
+        let error = search(
+            &mut runtime,
+            None,
+            vec![RuntimeVectorSpaceQuery::new("audio", vec![1.0])],
+        )
+        .expect_err("unknown spaces are rejected");
+        assert!(error.to_string().contains("vector space audio"));
     }
 
     #[test]
-    fn publish_raw_snapshot_vectors_use_latest_store_doc_id_map() {
+    fn publish_raw_vectors_to_space_upserts_rows_and_rejects_partial_dimension_changes() {
         let dataset_dir = tempdir().unwrap();
         let source_dir = tempdir().unwrap();
         let docs_path = source_dir.path().join("docs.ndjson");
-        fs::write(
-            &docs_path,
-            concat!(
-                "{\"doc_id\":\"doc-001\",\"text\":\"alpha\"}\n",
-                "{\"doc_id\":\"doc-002\",\"text\":\"beta\"}\n",
-            ),
-        )
-        .unwrap();
+        fs::write(&docs_path, "{\"doc_id\":\"seed-001\",\"text\":\"seed\"}\n").unwrap();
         pack_adhoc_dataset(&AdhocPackRequest::new(
             &docs_path,
             dataset_dir.path(),
@@ -2029,59 +5027,85 @@ mod tests {
         ))
         .unwrap();
 
-        let mut stale_runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
-        let mut writer_runtime = RuntimeStore::open(dataset_dir.path()).unwrap();
-        writer_runtime
-            .writer()
-            .unwrap()
-            .publish_raw_documents(vec![NewDocument::new("doc-002", "beta first")])
-            .unwrap();
-
-        stale_runtime
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        runtime
             .writer()
             .unwrap()
             .publish_raw_snapshot(
                 vec![
-                    NewDocument::new("doc-002", "beta first"),
-                    NewDocument::new("doc-001", "alpha second"),
+                    NewDocument::new("doc-001", "alpha"),
+                    NewDocument::new("doc-002", "beta"),
                 ],
-                Some(vec![
-                    NewDocumentVector::new("doc-002", embed_text("beta first", 384)),
-                    NewDocumentVector::new("doc-001", embed_text("alpha second", 384)),
-                ]),
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    vec![NewDocumentVector::new("doc-001", embed_text("alpha", 384))],
+                )),
+            )
+            .unwrap();
+        let space = RuntimeVectorSpace::new("images", 2, RuntimeVectorMetric::Dot)
+            .with_embedding(test_embedding("images"));
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_vectors_to_space(
+                space.clone(),
+                vec![NewDocumentVector::new("doc-001", vec![1.0, 0.0])],
+            )
+            .unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_vectors_to_space(
+                space,
+                vec![NewDocumentVector::new("doc-002", vec![0.0, 1.0])],
             )
             .unwrap();
 
         let opened = open_store(&dataset_dir.path().join("store.wax")).unwrap();
-        let vector_segment = opened
+        let vec_segments = opened
             .manifest
             .segments
             .iter()
             .filter(|segment| segment.family == SegmentKind::Vec)
-            .last()
+            .map(|segment| segment.live_items)
+            .collect::<Vec<_>>();
+        assert_eq!(vec_segments, vec![1, 2]);
+        let response = runtime
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Vector,
+                top_k: 2,
+                vector_space_queries: vec![RuntimeVectorSpaceQuery::new("images", vec![0.2, 0.8])
+                    .with_embedding(test_embedding("images"))],
+                ..Default::default()
+            })
             .unwrap();
-        let bytes =
-            map_segment_object(&dataset_dir.path().join("store.wax"), vector_segment).unwrap();
-
         assert_eq!(
-            read_vector_segment_doc_ids(&bytes),
-            vec!["doc-002".to_owned(), "doc-001".to_owned()]
+            response
+                .hits
+                .iter()
+                .map(|hit| hit.doc_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["doc-002", "doc-001"]
         );
+
+        let error = runtime
+            .writer()
+            .unwrap()
+            .publish_raw_vectors_to_space(
+                RuntimeVectorSpace::new("images", 3, RuntimeVectorMetric::Dot)
+                    .with_embedding(test_embedding("images")),
+                vec![NewDocumentVector::new("doc-002", vec![0.0, 1.0, 0.0])],
+            )
+            .expect_err("partial dimension change should be rejected");
+        assert!(error.to_string().contains("stores 2 dimensions"));
     }
 
     #[test]
-    fn publish_raw_snapshot_removes_vectors_for_doc_only_replace() {
+    fn embedding_identity_is_recorded_with_vectors_and_enforced_on_writes_and_queries() {
         let dataset_dir = tempdir().unwrap();
         let source_dir = tempdir().unwrap();
         let docs_path = source_dir.path().join("docs.ndjson");
-        fs::write(
-            &docs_path,
-            concat!(
-                "{\"doc_id\":\"doc-001\",\"text\":\"alpha\"}\n",
-                "{\"doc_id\":\"doc-002\",\"text\":\"beta\"}\n",
-            ),
-        )
-        .unwrap();
+        fs::write(&docs_path, "{\"doc_id\":\"seed-001\",\"text\":\"seed\"}\n").unwrap();
         pack_adhoc_dataset(&AdhocPackRequest::new(
             &docs_path,
             dataset_dir.path(),
@@ -2093,46 +5117,91 @@ mod tests {
         runtime
             .writer()
             .unwrap()
-            .publish_raw_snapshot(
-                vec![
-                    NewDocument::new("doc-001", "alpha"),
-                    NewDocument::new("doc-002", "beta"),
-                ],
-                Some(vec![
-                    NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
-                    NewDocumentVector::new("doc-002", embed_text("beta", 384)),
-                ]),
+            .publish_raw_documents(vec![
+                NewDocument::new("doc-001", "alpha"),
+                NewDocument::new("doc-002", "beta"),
+            ])
+            .unwrap();
+        let model_a = test_embedding("model-a");
+        let model_b = test_embedding("model-b");
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_vectors(
+                model_a.clone(),
+                vec![NewDocumentVector::new("doc-001", embed_text("alpha", 384))],
             )
             .unwrap();
+        assert_eq!(
+            runtime.vector_spaces().unwrap()[0].embedding,
+            Some(model_a.clone())
+        );
+
+        let error = runtime
+            .writer()
+            .unwrap()
+            .publish_raw_vectors(
+                model_b.clone(),
+                vec![NewDocumentVector::new("doc-002", embed_text("beta", 384))],
+            )
+            .expect_err("mixed-model upserts must be rejected");
+        assert_eq!(
+            error,
+            crate::RuntimeError::EmbeddingMismatch(Box::new(RuntimeEmbeddingMismatch {
+                space: "default".to_owned(),
+                stored: Some(model_a.clone()),
+                requested: Some(model_b.clone()),
+            }))
+        );
+        let error = runtime
+            .writer()
+            .unwrap()
+            .publish_raw_vectors_to_space(
+                RuntimeVectorSpace::new("images", 2, RuntimeVectorMetric::Dot),
+                vec![NewDocumentVector::new("doc-001", vec![1.0, 0.0])],
+            )
+            .expect_err("named spaces require an embedding identity");
+        assert!(error.to_string().contains("requires an embedding identity"));
+
+        let request = |vector_embedding| RuntimeSearchRequest {
+            mode: RuntimeSearchMode::Vector,
+            vector_query: Some(embed_text("alpha", 384)),
+            top_k: 2,
+            vector_embedding,
+            ..Default::default()
+        };
+        assert!(matches!(
+            runtime.search(request(None)),
+            Err(crate::RuntimeError::EmbeddingMismatch(mismatch)) if mismatch.requested.is_none()
+        ));
+        assert!(matches!(
+            runtime.search(request(Some(model_b.clone()))),
+            Err(crate::RuntimeError::EmbeddingMismatch(_))
+        ));
+        let response = runtime.search(request(Some(model_a))).unwrap();
+        assert_eq!(response.hits[0].doc_id, "doc-001");
 
+        // Replacing every row switches the space to the new model.
         runtime
             .writer()
             .unwrap()
-            .publish_raw_snapshot(vec![NewDocument::new("doc-003", "gamma")], None)
+            .publish_raw_vectors(
+                model_b.clone(),
+                vec![
+                    NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
+                    NewDocumentVector::new("doc-002", embed_text("beta", 384)),
+                ],
+            )
             .unwrap();
-
-        let opened = open_store(&dataset_dir.path().join("store.wax")).unwrap();
-        assert!(opened
-            .manifest
-            .segments
-            .iter()
-            .all(|segment| segment.family != SegmentKind::Vec));
+        assert!(runtime.search(request(Some(model_b))).is_ok());
     }
 
     #[test]
-    fn publish_raw_vectors_persists_rows_in_wax_doc_id_order() {
+    fn multi_vector_spaces_upsert_sub_vectors_and_rank_documents_by_max_sim() {
         let dataset_dir = tempdir().unwrap();
         let source_dir = tempdir().unwrap();
         let docs_path = source_dir.path().join("docs.ndjson");
-        fs::write(
-            &docs_path,
-            concat!(
-                "{\"doc_id\":\"doc-001\",\"text\":\"alpha\"}\n",
-                "{\"doc_id\":\"doc-002\",\"text\":\"beta\"}\n",
-                "{\"doc_id\":\"doc-003\",\"text\":\"gamma\"}\n",
-            ),
-        )
-        .unwrap();
+        fs::write(&docs_path, "{\"doc_id\":\"seed-001\",\"text\":\"seed\"}\n").unwrap();
         pack_adhoc_dataset(&AdhocPackRequest::new(
             &docs_path,
             dataset_dir.path(),
@@ -2148,43 +5217,98 @@ mod tests {
                 vec![
                     NewDocument::new("doc-001", "alpha"),
                     NewDocument::new("doc-002", "beta"),
-                    NewDocument::new("doc-003", "gamma"),
                 ],
-                Some(vec![
-                    NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
-                    NewDocumentVector::new("doc-002", embed_text("beta", 384)),
-                    NewDocumentVector::new("doc-003", embed_text("gamma", 384)),
-                ]),
+                None,
             )
             .unwrap();
+        let tokens = RuntimeVectorSpace::multi_vector("tokens", 2, RuntimeVectorMetric::Dot)
+            .with_embedding(test_embedding("tokens"));
         runtime
             .writer()
             .unwrap()
-            .publish_raw_vectors(vec![
-                NewDocumentVector::new("doc-003", embed_text("gamma updated", 384)),
-                NewDocumentVector::new("doc-001", embed_text("alpha updated", 384)),
-                NewDocumentVector::new("doc-002", embed_text("beta updated", 384)),
-            ])
+            .publish_raw_multi_vectors_to_space(
+                tokens.clone(),
+                vec![NewDocumentMultiVector::new(
+                    "doc-001",
+                    vec![vec![1.0, 0.0], vec![0.0, 0.2]],
+                )],
+            )
             .unwrap();
-
-        let opened = open_store(&dataset_dir.path().join("store.wax")).unwrap();
-        let vector_segment = opened
-            .manifest
-            .segments
-            .iter()
-            .filter(|segment| segment.family == SegmentKind::Vec)
-            .last()
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_multi_vectors_to_space(
+                tokens.clone(),
+                vec![NewDocumentMultiVector::new(
+                    "doc-002",
+                    vec![vec![0.7, 0.0], vec![0.0, 0.7], vec![0.1, 0.1]],
+                )],
+            )
             .unwrap();
-        let bytes =
-            map_segment_object(&dataset_dir.path().join("store.wax"), vector_segment).unwrap();
+        assert_eq!(runtime.vector_spaces().unwrap(), vec![tokens.clone()]);
+
+        let search = |runtime: &mut RuntimeStore, query: RuntimeVectorSpaceQuery| {
+            runtime
+                .search(RuntimeSearchRequest {
+                    mode: RuntimeSearchMode::Vector,
+                    top_k: 2,
+                    vector_space_queries: vec![query],
+                    ..Default::default()
+                })
+                .map(|response| {
+                    response
+                        .hits
+                        .into_iter()
+                        .map(|hit| hit.doc_id)
+                        .collect::<Vec<_>>()
+                })
+        };
         assert_eq!(
-            read_vector_segment_doc_ids(&bytes),
-            vec![
-                "doc-001".to_owned(),
-                "doc-002".to_owned(),
-                "doc-003".to_owned()
-            ]
+            search(
+                &mut runtime,
+                RuntimeVectorSpaceQuery::multi("tokens", vec![vec![1.0, 0.0], vec![0.0, 1.0]])
+                    .with_embedding(test_embedding("tokens")),
+            )
+            .unwrap(),
+            vec!["doc-002", "doc-001"]
+        );
+        assert_eq!(
+            search(
+                &mut runtime,
+                RuntimeVectorSpaceQuery::new("tokens", vec![1.0, 0.0])
+                    .with_embedding(test_embedding("tokens"))
+            )
+            .unwrap(),
+            vec!["doc-001", "doc-002"]
         );
+
+        let error = runtime
+            .writer()
+            .unwrap()
+            .publish_raw_vectors_to_space(
+                RuntimeVectorSpace::new("tokens", 2, RuntimeVectorMetric::Dot)
+                    .with_embedding(test_embedding("tokens")),
+                vec![NewDocumentVector::new("doc-001", vec![1.0, 0.0])],
+            )
+            .expect_err("single vectors should not overwrite a multi-vector space");
+        assert!(error.to_string().contains("stores multi-vector rows"));
+
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_vectors_to_space(
+                RuntimeVectorSpace::new("images", 2, RuntimeVectorMetric::Dot)
+                    .with_embedding(test_embedding("images")),
+                vec![NewDocumentVector::new("doc-001", vec![1.0, 0.0])],
+            )
+            .unwrap();
+        let error = search(
+            &mut runtime,
+            RuntimeVectorSpaceQuery::multi("images", vec![vec![1.0, 0.0], vec![0.0, 1.0]])
+                .with_embedding(test_embedding("images")),
+        )
+        .expect_err("single-vector spaces take one query vector");
+        assert!(error.to_string().contains("exactly one query vector"));
     }
 
     #[test]
@@ -2216,10 +5340,13 @@ mod tests {
                     NewDocument::new("doc-001", "alpha"),
                     NewDocument::new("doc-002", "beta"),
                 ],
-                Some(vec![
-                    NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
-                    NewDocumentVector::new("doc-002", embed_text("beta", 384)),
-                ]),
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    vec![
+                        NewDocumentVector::new("doc-001", embed_text("alpha", 384)),
+                        NewDocumentVector::new("doc-002", embed_text("beta", 384)),
+                    ],
+                )),
             )
             .unwrap();
 
@@ -2228,10 +5355,10 @@ mod tests {
             .unwrap()
             .publish_raw_snapshot(
                 vec![NewDocument::new("doc-003", "gamma")],
-                Some(vec![NewDocumentVector::new(
-                    "doc-003",
-                    embed_text("gamma", 384),
-                )]),
+                Some((
+                    manifest_embedding(dataset_dir.path()),
+                    vec![NewDocumentVector::new("doc-003", embed_text("gamma", 384))],
+                )),
             )
             .unwrap();
 
@@ -2286,7 +5413,34 @@ mod tests {
                 RuntimeAccelerationAvailability::UnsupportedPlatform
             );
         }
-        assert!(apple.detail.as_deref().unwrap_or("").len() > 0);
+        assert!(!apple.detail.as_deref().unwrap_or("").is_empty());
+    }
+
+    #[test]
+    fn runtime_reports_cpu_simd_kernels_alongside_apple_acceleration() {
+        let capabilities = RuntimeStore::capabilities();
+        let simd = capabilities
+            .platform_acceleration
+            .iter()
+            .find(|capability| capability.family == RuntimePlatformAccelerationFamily::CpuSimd)
+            .unwrap();
+        let level = wax_v2_vector::simd_level();
+
+        if level == wax_v2_vector::SimdLevel::Scalar {
+            assert_eq!(
+                simd.availability,
+                RuntimeAccelerationAvailability::UnsupportedPlatform
+            );
+        } else {
+            assert_eq!(
+                simd.availability,
+                RuntimeAccelerationAvailability::Available
+            );
+            assert!(simd.detail.as_deref().unwrap().starts_with(level.as_str()));
+        }
+        if cfg!(target_arch = "aarch64") {
+            assert_eq!(level, wax_v2_vector::SimdLevel::Neon);
+        }
     }
 
     #[test]
@@ -2306,7 +5460,24 @@ mod tests {
             selection.chosen_backend,
             RuntimeExecutionBackend::RustDefault
         );
-        assert!(selection.fallback_reason.as_deref().unwrap_or("").len() > 0);
+        assert!(!selection
+            .fallback_reason
+            .as_deref()
+            .unwrap_or("")
+            .is_empty());
+    }
+
+    pub(crate) fn manifest_embedding(root: &Path) -> RuntimeEmbeddingIdentity {
+        let identity = read_manifest(root).unwrap().identity;
+        RuntimeEmbeddingIdentity::new(
+            identity.embedding_spec_id,
+            identity.embedding_model_version,
+            identity.embedding_model_hash,
+        )
+    }
+
+    pub(crate) fn test_embedding(space: &str) -> RuntimeEmbeddingIdentity {
+        RuntimeEmbeddingIdentity::new(format!("{space}-encoder"), "v1", format!("sha256:{space}"))
     }
 
     fn test_vector(first_value: f32) -> Vec<f32> {
@@ -0,0 +1,230 @@
+use crate::{
+    docstore_error, ensure_doc_segment_unchanged, ensure_doc_segment_unchanged_from_store,
+    ensure_sparse_segments_unchanged, family_segment_offsets_from_store,
+    latest_doc_segment_identity_from_store, reject_duplicate_doc_ids, runtime_core_error,
+    summarize_doc_ids, NewDocumentSparseVector, RuntimeError, RuntimePublishFamily,
+    RuntimePublishReport, RuntimeStoreWriter,
+};
+
+impl RuntimeStoreWriter<'_> {
+    /// Publishes learned sparse vectors for existing documents. Like dense vector publishes this
+    /// is an upsert over the current sparse segment; document publishes keep the rows of
+    /// unchanged documents.
+    pub fn publish_raw_sparse_vectors(
+        self,
+        vectors: Vec<NewDocumentSparseVector>,
+    ) -> Result<RuntimePublishReport, RuntimeError> {
+        let store_path = self.require_existing_store()?;
+        if vectors.is_empty() {
+            return Err(RuntimeError::InvalidRequest(
+                "publish_raw_sparse_vectors requires at least one sparse vector".to_owned(),
+            ));
+        }
+        reject_duplicate_doc_ids(
+            vectors.iter().map(|vector| vector.doc_id.as_str()),
+            "publish_raw_sparse_vectors",
+        )?;
+        let mut vectors = vectors;
+        for vector in &mut vectors {
+            if vector.terms.is_empty() {
+                return Err(RuntimeError::InvalidRequest(format!(
+                    "sparse vector for {} has no terms",
+                    vector.doc_id
+                )));
+            }
+            wax_v2_sparse::validate_sparse_terms(
+                &mut vector.terms,
+                &format!("sparse vector for {}", vector.doc_id),
+            )
+            .map_err(RuntimeError::InvalidRequest)?;
+        }
+        let validated_doc_segment = latest_doc_segment_identity_from_store(&store_path)?;
+        let validated_sparse_segments =
+            family_segment_offsets_from_store(&store_path, wax_v2_core::SegmentKind::Spr)?;
+        self.store.refresh_read_state()?;
+        ensure_doc_segment_unchanged_from_store(&store_path, validated_doc_segment.as_ref())?;
+
+        let doc_ids = vectors
+            .iter()
+            .map(|vector| vector.doc_id.clone())
+            .collect::<Vec<_>>();
+        let known_documents = self.store.load_documents(&doc_ids)?;
+        if known_documents.len() != doc_ids.len() {
+            let missing = doc_ids
+                .into_iter()
+                .filter(|doc_id| !known_documents.contains_key(doc_id))
+                .collect::<Vec<_>>();
+            return Err(RuntimeError::InvalidRequest(format!(
+                "publish_raw_sparse_vectors requires existing documents for all doc_ids; missing: {}",
+                summarize_doc_ids(&missing)
+            )));
+        }
+
+        let incoming = doc_ids
+            .iter()
+            .map(String::as_str)
+            .collect::<std::collections::HashSet<_>>();
+        let retained = wax_v2_sparse::load_current_store_sparse_rows(&store_path)
+            .map_err(RuntimeError::Storage)?
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(doc_id, _)| !incoming.contains(doc_id.as_str()))
+            .map(|(doc_id, terms)| NewDocumentSparseVector::new(doc_id, terms))
+            .collect::<Vec<_>>();
+        let doc_id_map = self
+            .store
+            .docstore
+            .build_doc_id_map()
+            .map_err(|error| RuntimeError::Storage(docstore_error(error)))?;
+        let mut rows = retained
+            .into_iter()
+            .chain(vectors)
+            .map(|vector| {
+                let wax_doc_id = doc_id_map.wax_doc_id(&vector.doc_id).ok_or_else(|| {
+                    RuntimeError::Storage(format!(
+                        "missing wax doc id binding for {}",
+                        vector.doc_id
+                    ))
+                })?;
+                Ok((wax_doc_id, vector.doc_id, vector.terms))
+            })
+            .collect::<Result<Vec<_>, RuntimeError>>()?;
+        rows.sort_by_key(|(wax_doc_id, _, _)| *wax_doc_id);
+        let doc_id_start = rows.first().map_or(0, |(wax_doc_id, _, _)| *wax_doc_id);
+        let doc_id_end_exclusive = rows.last().map_or(0, |(wax_doc_id, _, _)| wax_doc_id + 1);
+        let rows = rows
+            .into_iter()
+            .map(|(_, doc_id, terms)| (doc_id, terms))
+            .collect::<Vec<_>>();
+
+        let mut pending_segment =
+            wax_v2_sparse::prepare_raw_sparse_segment(&rows).map_err(RuntimeError::Storage)?;
+        pending_segment.descriptor.doc_id_start = doc_id_start;
+        pending_segment.descriptor.doc_id_end_exclusive = doc_id_end_exclusive;
+        let opened = wax_v2_core::publish_segments_with_precondition(
+            &store_path,
+            vec![pending_segment],
+            |manifest| {
+                ensure_doc_segment_unchanged(manifest, validated_doc_segment.as_ref())?;
+                ensure_sparse_segments_unchanged(manifest, &validated_sparse_segments)
+            },
+            self.store.writer_lock_timeout,
+        )
+        .map_err(runtime_core_error)?;
+
+        self.store.refresh_read_state()?;
+        Ok(RuntimePublishReport {
+            generation: opened.manifest.generation,
+            published_families: vec![RuntimePublishFamily::Sparse],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+    use wax_bench_packer::{pack_adhoc_dataset, AdhocPackRequest};
+
+    use crate::{
+        NewDocument, NewDocumentSparseVector, RuntimePublishFamily, RuntimeSearchMode,
+        RuntimeSearchRequest, RuntimeStore,
+    };
+
+    #[test]
+    fn sparse_vectors_upsert_search_as_a_lane_and_join_hybrid_fusion() {
+        let dataset_dir = tempdir().unwrap();
+        let source_dir = tempdir().unwrap();
+        let docs_path = source_dir.path().join("docs.ndjson");
+        fs::write(&docs_path, "{\"doc_id\":\"seed-001\",\"text\":\"seed\"}\n").unwrap();
+        pack_adhoc_dataset(&AdhocPackRequest::new(
+            &docs_path,
+            dataset_dir.path(),
+            "small",
+        ))
+        .unwrap();
+
+        let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![
+                NewDocument::new("doc-001", "alpha"),
+                NewDocument::new("doc-002", "beta"),
+                NewDocument::new("doc-003", "alpha beta"),
+            ])
+            .unwrap();
+        let report = runtime
+            .writer()
+            .unwrap()
+            .publish_raw_sparse_vectors(vec![
+                NewDocumentSparseVector::new("doc-001", vec![(7, 2.0)]),
+                NewDocumentSparseVector::new("doc-002", vec![(7, 0.5), (9, 1.0)]),
+            ])
+            .unwrap();
+        assert_eq!(
+            report.published_families,
+            vec![RuntimePublishFamily::Sparse]
+        );
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_sparse_vectors(vec![NewDocumentSparseVector::new(
+                "doc-003",
+                vec![(9, 3.0)],
+            )])
+            .unwrap();
+
+        let search = |runtime: &mut RuntimeStore, mode: RuntimeSearchMode, text: Option<&str>| {
+            runtime
+                .search(RuntimeSearchRequest {
+                    mode,
+                    text_query: text.map(ToOwned::to_owned),
+                    top_k: 3,
+                    sparse_query: Some(vec![(9, 1.0), (7, 1.0)]),
+                    ..Default::default()
+                })
+                .map(|response| {
+                    response
+                        .hits
+                        .into_iter()
+                        .map(|hit| hit.doc_id)
+                        .collect::<Vec<_>>()
+                })
+        };
+        assert_eq!(
+            search(&mut runtime, RuntimeSearchMode::Sparse, None).unwrap(),
+            vec!["doc-003", "doc-001", "doc-002"]
+        );
+        // Text ranks doc-002 ahead of doc-003 but sparse puts doc-003 first and doc-002 last.
+        assert_eq!(
+            search(&mut runtime, RuntimeSearchMode::Hybrid, Some("beta")).unwrap(),
+            vec!["doc-003", "doc-002", "doc-001"]
+        );
+
+        let error = runtime
+            .writer()
+            .unwrap()
+            .publish_raw_sparse_vectors(vec![NewDocumentSparseVector::new(
+                "doc-404",
+                vec![(1, 1.0)],
+            )])
+            .expect_err("sparse vectors for unknown documents should be rejected");
+        assert!(error.to_string().contains("missing: doc-404"));
+
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![
+                NewDocument::new("doc-002", "beta revised"),
+                NewDocument::new("doc-004", "gamma"),
+            ])
+            .unwrap();
+        // Unchanged documents keep their sparse rows; changed and new ones have none.
+        assert_eq!(
+            search(&mut runtime, RuntimeSearchMode::Sparse, None).unwrap(),
+            vec!["doc-003", "doc-001"]
+        );
+    }
+}
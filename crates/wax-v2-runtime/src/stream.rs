@@ -0,0 +1,406 @@
+use wax_v2_vector::{VectorLane, DEFAULT_VECTOR_SPACE};
+
+use crate::carry::carried_row_segments;
+use crate::{
+    docstore_error, embed_with, ensure_embedding_unchanged, ensure_store_generation_unchanged,
+    ensure_store_generation_unchanged_from_store, latest_doc_segment_identity_from_store,
+    new_document_from_value, raw_ordered_documents, runtime_core_error,
+    store_manifest_generation_from_store, validated_embedding_identity, NewDocument, RuntimeError,
+    RuntimePublishFamily, RuntimePublishReport, RuntimeStoreWriter,
+};
+
+/// Retained documents loaded per batch when a document stream is merged with the store.
+const STREAM_RETAINED_CHUNK_DOCS: usize = 1024;
+
+impl RuntimeStoreWriter<'_> {
+    /// Streaming counterpart of [`Self::publish_raw_documents`]: documents are spilled to temp
+    /// files next to the store as they are read, and retained documents are copied over in
+    /// chunks, so no document payloads of either corpus are held in memory. Incoming doc ids are
+    /// checked for repeats through sorted spill runs. Retained documents keep their rows in the
+    /// other vector spaces and the sparse segment, as with the in-memory publish.
+    ///
+    /// Memory still grows with the number of stored documents, though not with their size: the
+    /// current and retained doc ids are held for the merge against the incoming ids, and the
+    /// carried rows of the other vector spaces and the sparse segment are rebuilt in memory. The
+    /// open doc segment already keeps a doc id binding per stored document, so streaming the ids
+    /// alone would not bound it.
+    pub fn publish_document_stream<I>(
+        self,
+        documents: I,
+    ) -> Result<RuntimePublishReport, RuntimeError>
+    where
+        I: IntoIterator<Item = Result<NewDocument, RuntimeError>>,
+    {
+        let store_path = self.require_existing_store()?;
+        let expected_generation = store_manifest_generation_from_store(&store_path)?;
+        self.store.refresh_read_state()?;
+        ensure_store_generation_unchanged_from_store(&store_path, expected_generation)?;
+        let spill_dir = self.store.root.clone();
+        let embedder = self.store.embedder.clone();
+        let dimensions = self.store.manifest.vector_profile.embedding_dimensions as usize;
+        let has_doc_segment = latest_doc_segment_identity_from_store(&store_path)?.is_some();
+
+        let mut doc_builder =
+            wax_v2_docstore::StreamingDocSegmentBuilder::new(&store_path, &spill_dir)
+                .map_err(|error| RuntimeError::Storage(docstore_error(error)))?
+                .with_compression(self.store.doc_compression);
+        let mut text_builder = wax_v2_text::StreamingTextSegmentBuilder::new(&spill_dir)
+            .with_run_bytes(self.store.ingest_run_bytes);
+        let mut vector_builder = match &embedder {
+            Some(embedder) => Some(
+                wax_v2_vector::StreamingVectorSegmentBuilder::new(
+                    &self.store.default_vector_space(embedder.identity())?,
+                    &spill_dir,
+                )
+                .map_err(RuntimeError::Storage)?,
+            ),
+            None => None,
+        };
+        let mut push = |document: &NewDocument,
+                        vector: Option<Vec<f32>>|
+         -> Result<(), RuntimeError> {
+            let (doc_id, value) = raw_ordered_documents(std::slice::from_ref(document))
+                .pop()
+                .ok_or_else(|| RuntimeError::Storage("document conversion failed".to_owned()))?;
+            doc_builder
+                .push_document(&doc_id, &value)
+                .map_err(|error| RuntimeError::Storage(docstore_error(error)))?;
+            text_builder
+                .push_document(&doc_id, &document.text)
+                .map_err(RuntimeError::Storage)?;
+            if let (Some(builder), Some(embedder)) = (vector_builder.as_mut(), embedder.as_ref()) {
+                let values = match vector {
+                    Some(values) => values,
+                    None => embed_with(embedder.as_ref(), &document.text, dimensions)?,
+                };
+                builder
+                    .push(&doc_id, &values)
+                    .map_err(RuntimeError::Storage)?;
+            }
+            Ok(())
+        };
+
+        let mut incoming_doc_ids = wax_v2_docstore::DocIdSpill::new(&spill_dir)
+            .with_run_bytes(self.store.ingest_run_bytes);
+        for document in documents {
+            let document = document?;
+            incoming_doc_ids
+                .push(&document.doc_id)
+                .map_err(|error| RuntimeError::Storage(docstore_error(error)))?;
+            push(&document, None)?;
+        }
+        if incoming_doc_ids.is_empty() {
+            return Err(RuntimeError::InvalidRequest(
+                "publish_document_stream requires at least one document".to_owned(),
+            ));
+        }
+
+        // Walks the sorted incoming ids against the sorted current ids, so neither side needs a
+        // hash set; repeats in the stream come out adjacent.
+        let mut current_doc_ids = if has_doc_segment {
+            self.store
+                .docstore
+                .load_document_ids()
+                .map_err(|error| RuntimeError::Storage(docstore_error(error)))?
+        } else {
+            Vec::new()
+        };
+        current_doc_ids.sort_unstable();
+        let mut current_doc_ids = current_doc_ids.into_iter().peekable();
+        let mut retained_doc_ids = Vec::new();
+        let mut previous: Option<String> = None;
+        for doc_id in incoming_doc_ids
+            .into_sorted()
+            .map_err(|error| RuntimeError::Storage(docstore_error(error)))?
+        {
+            let doc_id = doc_id.map_err(|error| RuntimeError::Storage(docstore_error(error)))?;
+            if previous.as_deref() == Some(doc_id.as_str()) {
+                return Err(RuntimeError::InvalidRequest(format!(
+                    "publish_document_stream contains duplicate doc_id {doc_id}"
+                )));
+            }
+            while let Some(current) = current_doc_ids.next_if(|current| *current < doc_id) {
+                retained_doc_ids.push(current);
+            }
+            current_doc_ids.next_if(|current| *current == doc_id);
+            previous = Some(doc_id);
+        }
+        retained_doc_ids.extend(current_doc_ids);
+
+        if !retained_doc_ids.is_empty() {
+            let current_space = if embedder.is_some() {
+                wax_v2_vector::store_vector_spaces(&store_path)
+                    .map_err(RuntimeError::Storage)?
+                    .into_iter()
+                    .find(|space| space.spec.name == DEFAULT_VECTOR_SPACE)
+            } else {
+                None
+            };
+            if let (Some(current), Some(embedder)) = (&current_space, &embedder) {
+                ensure_embedding_unchanged(
+                    &current.spec,
+                    Some(&self.store.legacy_default_embedding()),
+                    &validated_embedding_identity(embedder.identity())?,
+                    true,
+                )?;
+            }
+            // Retained ids are sorted, so one merge over the lane's rows serves every chunk.
+            let current_lane = match current_space {
+                Some(_) => Some(self.store.ensure_vector_lane()?),
+                None => None,
+            };
+            let mut current_vectors = current_lane
+                .as_deref()
+                .map(VectorLane::sorted_document_vectors);
+            for chunk in retained_doc_ids.chunks(STREAM_RETAINED_CHUNK_DOCS) {
+                let current_documents = self.store.load_documents(chunk)?;
+                for doc_id in chunk {
+                    let vector = current_vectors
+                        .as_mut()
+                        .and_then(|vectors| vectors.vector(doc_id));
+                    if let Some(value) = current_documents.get(doc_id) {
+                        push(&new_document_from_value(value)?, vector)?;
+                    }
+                }
+            }
+        }
+
+        let (doc_spilled, doc_id_map) = doc_builder
+            .finish()
+            .map_err(|error| RuntimeError::Storage(docstore_error(error)))?;
+        let active_doc_id_range =
+            doc_spilled.descriptor.doc_id_start..doc_spilled.descriptor.doc_id_end_exclusive;
+        let mut text_spilled = text_builder.finish().map_err(RuntimeError::Storage)?;
+        text_spilled.descriptor.doc_id_start = active_doc_id_range.start;
+        text_spilled.descriptor.doc_id_end_exclusive = active_doc_id_range.end;
+        let mut spilled_segments = vec![doc_spilled, text_spilled];
+        let mut published_families = vec![RuntimePublishFamily::Doc, RuntimePublishFamily::Text];
+        if let Some(builder) = vector_builder {
+            let mut vector_spilled = builder
+                .finish(|doc_id| doc_id_map.wax_doc_id(doc_id))
+                .map_err(RuntimeError::Storage)?;
+            vector_spilled.descriptor.doc_id_start = active_doc_id_range.start;
+            vector_spilled.descriptor.doc_id_end_exclusive = active_doc_id_range.end;
+            spilled_segments.push(vector_spilled);
+            published_families.push(RuntimePublishFamily::Vector);
+        }
+
+        let replaced_space = published_families
+            .contains(&RuntimePublishFamily::Vector)
+            .then_some(DEFAULT_VECTOR_SPACE);
+        let mut carried = carried_row_segments(
+            &store_path,
+            |doc_id| {
+                retained_doc_ids
+                    .binary_search_by(|retained| retained.as_str().cmp(doc_id))
+                    .is_ok()
+            },
+            &doc_id_map,
+            replaced_space,
+            &mut published_families,
+        )?;
+        let opened = wax_v2_core::publish_spilled_segments_retaining_with_precondition(
+            &store_path,
+            spilled_segments,
+            std::mem::take(&mut carried.pending),
+            |segment| carried.retains(segment),
+            |manifest| ensure_store_generation_unchanged(manifest, expected_generation),
+            self.store.writer_lock_timeout,
+        )
+        .map_err(runtime_core_error)?;
+
+        self.store.refresh_read_state()?;
+        Ok(RuntimePublishReport {
+            generation: opened.manifest.generation,
+            published_families,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::tests::{default_space_doc_ids, test_embedding, transaction_dataset};
+    use crate::{
+        FeatureHashEmbedder, NewDocument, NewDocumentSparseVector, NewDocumentVector,
+        RuntimeSearchMode, RuntimeSearchRequest, RuntimeStore, RuntimeVectorMetric,
+        RuntimeVectorSpace,
+    };
+
+    fn streamed_family_checksums(
+        runtime: &RuntimeStore,
+    ) -> Vec<(wax_v2_core::SegmentKind, [u8; 32])> {
+        let opened = wax_v2_core::open_store(&runtime.store_path()).unwrap();
+        let mut checksums = opened
+            .manifest
+            .segments
+            .iter()
+            .map(|segment| (segment.family, segment.object_checksum))
+            .collect::<Vec<_>>();
+        checksums.sort_by_key(|(family, _)| format!("{family:?}"));
+        checksums
+    }
+
+    #[test]
+    fn document_stream_publishes_the_same_segments_as_publish_raw_documents() {
+        let initial = || {
+            vec![
+                NewDocument::new("doc-001", "alpha apple orchard")
+                    .with_metadata(serde_json::json!({"workspace": "old"}))
+                    .with_extra_field("priority", serde_json::json!("keep")),
+                NewDocument::new("doc-002", "beta banana split").with_timestamp_ms(20),
+                NewDocument::new("doc-003", "gamma grape vine"),
+            ]
+        };
+        let update = || {
+            vec![
+                NewDocument::new("doc-004", "delta date palm orchard"),
+                NewDocument::new("doc-002", "beta blueberry tart").with_timestamp_ms(40),
+                NewDocument::new("doc-000", "zeta zucchini"),
+            ]
+        };
+        let mut stores = Vec::new();
+        for _ in 0..2 {
+            let (dataset_dir, runtime) = transaction_dataset();
+            let dimensions = runtime.manifest.vector_profile.embedding_dimensions as usize;
+            let mut runtime = runtime
+                .with_embedder(Arc::new(FeatureHashEmbedder::new(dimensions)))
+                .with_ingest_run_bytes(64);
+            runtime
+                .writer()
+                .unwrap()
+                .publish_raw_snapshot(initial(), None)
+                .unwrap();
+            runtime
+                .writer()
+                .unwrap()
+                .publish_raw_documents(initial())
+                .unwrap();
+            stores.push((dataset_dir, runtime));
+        }
+        let (_expected_dir, expected) = &mut stores[0];
+        let expected_report = expected
+            .writer()
+            .unwrap()
+            .publish_raw_documents(update())
+            .unwrap();
+        let expected_checksums = streamed_family_checksums(expected);
+        let (streamed_dir, streamed) = &mut stores[1];
+        let streamed_report = streamed
+            .writer()
+            .unwrap()
+            .publish_document_stream(update().into_iter().map(Ok))
+            .unwrap();
+
+        assert_eq!(streamed_report, expected_report);
+        assert_eq!(streamed_family_checksums(streamed), expected_checksums);
+        assert_eq!(
+            streamed.docstore.load_document_ids().unwrap(),
+            ["doc-001", "doc-002", "doc-003", "doc-004", "doc-000"]
+        );
+        let hits = streamed
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Text,
+                text_query: Some("orchard".to_owned()),
+                top_k: 5,
+                ..Default::default()
+            })
+            .unwrap()
+            .hits
+            .into_iter()
+            .map(|hit| hit.doc_id)
+            .collect::<Vec<_>>();
+        assert_eq!(hits.len(), 2);
+        assert!(RuntimeStore::verify(streamed_dir.path()).unwrap().is_ok());
+
+        let duplicate = streamed
+            .writer()
+            .unwrap()
+            .publish_document_stream(
+                [
+                    NewDocument::new("doc-009", "x"),
+                    NewDocument::new("doc-009", "y"),
+                ]
+                .into_iter()
+                .map(Ok),
+            )
+            .unwrap_err();
+        assert!(
+            matches!(duplicate, crate::RuntimeError::InvalidRequest(message) if message.contains("duplicate doc_id"))
+        );
+        assert!(streamed
+            .writer()
+            .unwrap()
+            .publish_document_stream(std::iter::empty())
+            .is_err());
+    }
+
+    #[test]
+    fn document_stream_keeps_every_space_and_sparse_rows_for_retained_documents() {
+        let images = RuntimeVectorSpace::new("images", 2, RuntimeVectorMetric::L2)
+            .with_embedding(test_embedding("images"));
+        let update = || {
+            vec![
+                NewDocument::new("doc-002", "beta revised"),
+                NewDocument::new("doc-003", "gamma"),
+            ]
+        };
+        let mut stores = Vec::new();
+        for _ in 0..2 {
+            let (dataset_dir, runtime) = transaction_dataset();
+            let mut runtime = runtime.with_ingest_run_bytes(16);
+            runtime
+                .writer()
+                .unwrap()
+                .publish_raw_vectors_to_space(
+                    images.clone(),
+                    vec![
+                        NewDocumentVector::new("doc-001", vec![0.0, 0.0]),
+                        NewDocumentVector::new("doc-002", vec![1.0, 1.0]),
+                    ],
+                )
+                .unwrap();
+            runtime
+                .writer()
+                .unwrap()
+                .publish_raw_sparse_vectors(vec![
+                    NewDocumentSparseVector::new("doc-001", vec![(7, 1.0)]),
+                    NewDocumentSparseVector::new("doc-002", vec![(7, 2.0)]),
+                ])
+                .unwrap();
+            stores.push((dataset_dir, runtime));
+        }
+        let (_expected_dir, expected) = &mut stores[0];
+        let expected_report = expected
+            .writer()
+            .unwrap()
+            .publish_raw_documents(update())
+            .unwrap();
+        let expected_checksums = streamed_family_checksums(expected);
+        let (_streamed_dir, streamed) = &mut stores[1];
+        let streamed_report = streamed
+            .writer()
+            .unwrap()
+            .publish_document_stream(update().into_iter().map(Ok))
+            .unwrap();
+
+        assert_eq!(streamed_report, expected_report);
+        assert_eq!(streamed_family_checksums(streamed), expected_checksums);
+        assert_eq!(streamed.vector_spaces().unwrap()[1], images);
+        assert_eq!(default_space_doc_ids(streamed), ["doc-001"]);
+        let sparse_hits = streamed
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Sparse,
+                top_k: 3,
+                sparse_query: Some(vec![(7, 1.0)]),
+                ..Default::default()
+            })
+            .unwrap()
+            .hits
+            .into_iter()
+            .map(|hit| hit.doc_id)
+            .collect::<Vec<_>>();
+        assert_eq!(sparse_hits, ["doc-001"]);
+    }
+}
@@ -0,0 +1,557 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use wax_v2_vector::DEFAULT_VECTOR_SPACE;
+
+use crate::{
+    embed_with, ensure_embedding_unchanged, reject_duplicate_doc_ids, runtime_core_error,
+    runtime_embedding_identity, store_manifest_generation_from_store, summarize_doc_ids,
+    validated_embedding_identity, NewDocument, NewDocumentVector, RuntimeEmbeddingIdentity,
+    RuntimeError, RuntimePublishReport, RuntimeStoreWriter,
+};
+
+/// Document upserts, deletes and default-space vectors staged in memory by
+/// [`RuntimeStoreWriter::begin`]. [`Self::commit`] publishes all of them as one generation;
+/// aborting or dropping the transaction discards them without touching the store.
+pub struct RuntimeTransaction<'a> {
+    writer: RuntimeStoreWriter<'a>,
+    expected_generation: u64,
+    documents: Vec<NewDocument>,
+    deleted_doc_ids: Vec<String>,
+    vector_batches: Vec<(RuntimeEmbeddingIdentity, Vec<NewDocumentVector>)>,
+}
+
+impl<'a> RuntimeStoreWriter<'a> {
+    /// Starts a transaction based on the active store generation. Its commit fails if any other
+    /// publish lands first.
+    pub fn begin(self) -> Result<RuntimeTransaction<'a>, RuntimeError> {
+        let store_path = self.require_existing_store()?;
+        let expected_generation = store_manifest_generation_from_store(&store_path)?;
+        Ok(RuntimeTransaction {
+            writer: self,
+            expected_generation,
+            documents: Vec::new(),
+            deleted_doc_ids: Vec::new(),
+            vector_batches: Vec::new(),
+        })
+    }
+}
+
+impl RuntimeTransaction<'_> {
+    /// Bases the transaction on `generation` instead of the one active at `begin`, for callers
+    /// that read the store earlier, for example through a [`ReadSnapshot`](crate::ReadSnapshot).
+    pub fn with_expected_generation(mut self, generation: u64) -> Self {
+        self.expected_generation = generation;
+        self
+    }
+
+    pub fn expected_generation(&self) -> u64 {
+        self.expected_generation
+    }
+
+    /// Stages documents that replace any current document with the same `doc_id`.
+    pub fn upsert_documents(&mut self, documents: Vec<NewDocument>) -> &mut Self {
+        self.documents.extend(documents);
+        self
+    }
+
+    /// Stages deletes of current documents; their vectors are dropped with them.
+    pub fn delete_documents<I, S>(&mut self, doc_ids: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.deleted_doc_ids
+            .extend(doc_ids.into_iter().map(Into::into));
+        self
+    }
+
+    /// Stages default-space vectors for documents that exist once the transaction commits,
+    /// including documents staged in the same transaction.
+    pub fn upsert_vectors(
+        &mut self,
+        embedding: RuntimeEmbeddingIdentity,
+        vectors: Vec<NewDocumentVector>,
+    ) -> &mut Self {
+        self.vector_batches.push((embedding, vectors));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+            && self.deleted_doc_ids.is_empty()
+            && self.vector_batches.is_empty()
+    }
+
+    /// Discards everything staged. Equivalent to dropping the transaction.
+    pub fn abort(self) {}
+
+    pub fn commit(self) -> Result<RuntimePublishReport, RuntimeError> {
+        let Self {
+            writer,
+            expected_generation,
+            documents,
+            deleted_doc_ids,
+            vector_batches,
+        } = self;
+        let store_path = writer.require_existing_store()?;
+        if documents.is_empty() && deleted_doc_ids.is_empty() && vector_batches.is_empty() {
+            return Err(RuntimeError::InvalidRequest(
+                "transaction has no staged changes".to_owned(),
+            ));
+        }
+        // Fails fast before a stale transaction merges anything. This read takes no writer lock;
+        // the publishes below recheck the generation under the lock, and only that check keeps
+        // concurrent commits apart.
+        let opened = wax_v2_core::open_store(&store_path).map_err(runtime_core_error)?;
+        ensure_transaction_generation_unchanged(&opened.manifest, expected_generation)
+            .map_err(runtime_core_error)?;
+        reject_duplicate_doc_ids(
+            documents
+                .iter()
+                .map(|document| document.doc_id.as_str())
+                .chain(deleted_doc_ids.iter().map(String::as_str)),
+            "transaction documents and deletes",
+        )?;
+
+        let mut embedding: Option<RuntimeEmbeddingIdentity> = None;
+        let mut vectors = Vec::new();
+        for (batch_embedding, batch) in vector_batches {
+            if let Some(embedding) = &embedding {
+                if embedding != &batch_embedding {
+                    return Err(RuntimeError::InvalidRequest(format!(
+                        "transaction vectors were staged for embeddings {embedding} and {batch_embedding}"
+                    )));
+                }
+            }
+            embedding = Some(batch_embedding);
+            vectors.extend(batch);
+        }
+        reject_duplicate_doc_ids(
+            vectors.iter().map(|vector| vector.doc_id.as_str()),
+            "transaction vectors",
+        )?;
+
+        let staged_vectors = embedding.map(|embedding| (embedding, vectors));
+        if documents.is_empty() && deleted_doc_ids.is_empty() {
+            // Vector-only transactions leave the doc, text and sparse segments in place.
+            let (embedding, vectors) = staged_vectors.expect("vector batches were staged");
+            let space = writer.store.default_vector_space(embedding)?;
+            return writer.publish_raw_vectors_for_space(space, vectors, Some(expected_generation));
+        }
+        writer.commit_document_transaction(
+            store_path,
+            expected_generation,
+            documents,
+            deleted_doc_ids,
+            staged_vectors,
+        )
+    }
+}
+
+impl RuntimeStoreWriter<'_> {
+    fn commit_document_transaction(
+        mut self,
+        store_path: PathBuf,
+        expected_generation: u64,
+        documents: Vec<NewDocument>,
+        deleted_doc_ids: Vec<String>,
+        staged_vectors: Option<(RuntimeEmbeddingIdentity, Vec<NewDocumentVector>)>,
+    ) -> Result<RuntimePublishReport, RuntimeError> {
+        let incoming_doc_ids = documents
+            .iter()
+            .map(|document| document.doc_id.clone())
+            .collect::<std::collections::HashSet<_>>();
+        let mut merged = self.merged_raw_documents(&store_path, expected_generation, documents)?;
+        let deleted = deleted_doc_ids
+            .iter()
+            .map(String::as_str)
+            .collect::<std::collections::HashSet<_>>();
+        let present = merged
+            .iter()
+            .map(|document| document.doc_id.as_str())
+            .collect::<std::collections::HashSet<_>>();
+        let missing = deleted_doc_ids
+            .iter()
+            .filter(|doc_id| !present.contains(doc_id.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(RuntimeError::InvalidRequest(format!(
+                "transaction deletes require existing documents; missing: {}",
+                summarize_doc_ids(&missing)
+            )));
+        }
+        merged.retain(|document| !deleted.contains(document.doc_id.as_str()));
+        if merged.is_empty() {
+            return Err(RuntimeError::InvalidRequest(
+                "transaction would leave the store without documents".to_owned(),
+            ));
+        }
+
+        let vectors =
+            self.transaction_vectors(&store_path, &merged, &incoming_doc_ids, staged_vectors)?;
+        let carried_doc_ids = merged
+            .iter()
+            .filter(|document| !incoming_doc_ids.contains(&document.doc_id))
+            .map(|document| document.doc_id.clone())
+            .collect();
+        self.publish_raw_snapshot_with_expected_generation(
+            store_path,
+            expected_generation,
+            merged,
+            carried_doc_ids,
+            vectors,
+        )
+    }
+
+    /// Default-space vectors after a document transaction, in document order: staged vectors
+    /// first, then embeddings of upserted documents, then the current rows of untouched
+    /// documents. Upserted documents without either lose their vector.
+    fn transaction_vectors(
+        &self,
+        store_path: &Path,
+        documents: &[NewDocument],
+        incoming_doc_ids: &std::collections::HashSet<String>,
+        staged_vectors: Option<(RuntimeEmbeddingIdentity, Vec<NewDocumentVector>)>,
+    ) -> Result<Option<(RuntimeEmbeddingIdentity, Vec<NewDocumentVector>)>, RuntimeError> {
+        let embedder = self.store.embedder.clone();
+        let (staged_embedding, staged_vectors) = match staged_vectors {
+            Some((embedding, vectors)) => (Some(embedding), vectors),
+            None => (None, Vec::new()),
+        };
+        if let (Some(staged), Some(embedder)) = (&staged_embedding, &embedder) {
+            if staged != &embedder.identity() {
+                return Err(RuntimeError::InvalidRequest(format!(
+                    "transaction vectors were staged for embedding {staged} but the store embedder is {}",
+                    embedder.identity()
+                )));
+            }
+        }
+        let document_ids = documents
+            .iter()
+            .map(|document| document.doc_id.as_str())
+            .collect::<std::collections::HashSet<_>>();
+        let missing = staged_vectors
+            .iter()
+            .filter(|vector| !document_ids.contains(vector.doc_id.as_str()))
+            .map(|vector| vector.doc_id.clone())
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(RuntimeError::InvalidRequest(format!(
+                "transaction vectors require documents that exist after commit; missing: {}",
+                summarize_doc_ids(&missing)
+            )));
+        }
+
+        let mut staged_rows = staged_vectors
+            .into_iter()
+            .map(|vector| (vector.doc_id, vector.values))
+            .collect::<HashMap<_, _>>();
+        let current =
+            wax_v2_vector::load_current_store_vector_rows(store_path, DEFAULT_VECTOR_SPACE)
+                .map_err(RuntimeError::Storage)?;
+        let mut current_rows = HashMap::new();
+        let mut embedding =
+            staged_embedding.or_else(|| embedder.as_ref().map(|embedder| embedder.identity()));
+        if let Some(current) = current {
+            let legacy = self.store.legacy_default_embedding();
+            let retains_rows = current.rows.iter().any(|(doc_id, _)| {
+                document_ids.contains(doc_id.as_str())
+                    && !incoming_doc_ids.contains(doc_id)
+                    && !staged_rows.contains_key(doc_id)
+            });
+            let embedding = embedding.get_or_insert_with(|| {
+                runtime_embedding_identity(current.spec.embedding.clone().unwrap_or(legacy.clone()))
+            });
+            ensure_embedding_unchanged(
+                &current.spec,
+                Some(&legacy),
+                &validated_embedding_identity(embedding.clone())?,
+                retains_rows,
+            )?;
+            current_rows.extend(current.rows);
+        }
+        let Some(embedding) = embedding else {
+            return Ok(None);
+        };
+
+        let dimensions = self.store.manifest.vector_profile.embedding_dimensions as usize;
+        let mut vectors = Vec::new();
+        for document in documents {
+            let incoming = incoming_doc_ids.contains(&document.doc_id);
+            let values = match staged_rows.remove(&document.doc_id) {
+                Some(values) => Some(values),
+                None if incoming => match &embedder {
+                    Some(embedder) => {
+                        Some(embed_with(embedder.as_ref(), &document.text, dimensions)?)
+                    }
+                    None => None,
+                },
+                None => current_rows.remove(&document.doc_id),
+            };
+            if let Some(values) = values {
+                vectors.push(NewDocumentVector::new(document.doc_id.clone(), values));
+            }
+        }
+        Ok((!vectors.is_empty()).then_some((embedding, vectors)))
+    }
+}
+
+pub(crate) fn ensure_transaction_generation_unchanged(
+    manifest: &wax_v2_core::ActiveManifest,
+    expected: u64,
+) -> Result<(), wax_v2_core::CoreError> {
+    if manifest.generation == expected {
+        return Ok(());
+    }
+
+    Err(wax_v2_core::CoreError::PublishPreconditionFailed(format!(
+        "transaction began at store generation {expected} but the store is at generation {}; begin a new transaction",
+        manifest.generation
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use wax_bench_model::embed_text;
+
+    use crate::tests::{default_space_doc_ids, test_embedding, transaction_dataset};
+    use crate::{
+        NewDocument, NewDocumentSparseVector, NewDocumentVector, RuntimeSearchMode,
+        RuntimeSearchRequest, RuntimeStore, RuntimeVectorMetric, RuntimeVectorSpace,
+        RuntimeVectorSpaceQuery,
+    };
+
+    #[test]
+    fn transaction_commits_documents_vectors_and_deletes_as_one_generation() {
+        let (dataset_dir, mut runtime) = transaction_dataset();
+        let before = RuntimeStore::generations(dataset_dir.path()).unwrap();
+        let embedding = runtime.embedding_identity();
+
+        let mut transaction = runtime.writer().unwrap().begin().unwrap();
+        let base = transaction.expected_generation();
+        transaction
+            .upsert_documents(vec![NewDocument::new("doc-003", "gamma lantern")])
+            .upsert_vectors(
+                embedding,
+                vec![NewDocumentVector::new(
+                    "doc-003",
+                    embed_text("gamma lantern", 384),
+                )],
+            )
+            .delete_documents(["doc-002"]);
+        let report = transaction.commit().unwrap();
+
+        assert_eq!(report.generation, base + 1);
+        assert_eq!(
+            RuntimeStore::generations(dataset_dir.path()).unwrap().len(),
+            before.len() + 1
+        );
+        assert_eq!(
+            runtime.docstore.load_document_ids().unwrap(),
+            ["doc-001", "doc-003"]
+        );
+        assert_eq!(default_space_doc_ids(&runtime), ["doc-001", "doc-003"]);
+    }
+
+    #[test]
+    fn aborted_transaction_leaves_the_store_untouched() {
+        let (dataset_dir, mut runtime) = transaction_dataset();
+        let generations = RuntimeStore::generations(dataset_dir.path()).unwrap();
+        let store_length = fs::metadata(runtime.store_path()).unwrap().len();
+
+        let mut transaction = runtime.writer().unwrap().begin().unwrap();
+        transaction
+            .upsert_documents(vec![NewDocument::new("doc-003", "gamma")])
+            .delete_documents(["doc-001"]);
+        assert!(!transaction.is_empty());
+        transaction.abort();
+
+        assert_eq!(
+            RuntimeStore::generations(dataset_dir.path()).unwrap(),
+            generations
+        );
+        assert_eq!(
+            fs::metadata(runtime.store_path()).unwrap().len(),
+            store_length
+        );
+        assert_eq!(
+            runtime.docstore.load_document_ids().unwrap(),
+            ["doc-001", "doc-002"]
+        );
+    }
+
+    #[test]
+    fn transaction_commit_fails_once_another_publish_moves_the_store() {
+        let (dataset_dir, mut runtime) = transaction_dataset();
+        let embedding = runtime.embedding_identity();
+        let mut other = RuntimeStore::open(dataset_dir.path()).unwrap();
+
+        let mut transaction = runtime.writer().unwrap().begin().unwrap();
+        transaction.upsert_vectors(
+            embedding,
+            vec![NewDocumentVector::new(
+                "doc-001",
+                embed_text("alpha refreshed", 384),
+            )],
+        );
+        other
+            .writer()
+            .unwrap()
+            .publish_raw_documents(vec![NewDocument::new("doc-003", "gamma")])
+            .unwrap();
+        let error = transaction.commit().unwrap_err();
+
+        assert!(matches!(
+            error,
+            crate::RuntimeError::InvalidRequest(message) if message.contains("transaction began")
+        ));
+        let mut transaction = runtime.writer().unwrap().begin().unwrap();
+        transaction.delete_documents(["doc-003"]);
+        transaction.commit().unwrap();
+        assert_eq!(
+            runtime.docstore.load_document_ids().unwrap(),
+            ["doc-001", "doc-002"]
+        );
+
+        let mut stale = runtime
+            .writer()
+            .unwrap()
+            .begin()
+            .unwrap()
+            .with_expected_generation(0);
+        stale.delete_documents(["doc-002"]);
+        assert!(matches!(
+            stale.commit().unwrap_err(),
+            crate::RuntimeError::InvalidRequest(message) if message.contains("transaction began at store generation 0")
+        ));
+    }
+
+    #[test]
+    fn vector_only_transaction_commit_rechecks_the_generation_under_the_writer_lock() {
+        let (dataset_dir, mut runtime) = transaction_dataset();
+        let embedding = runtime.embedding_identity();
+        let base = runtime
+            .writer()
+            .unwrap()
+            .begin()
+            .unwrap()
+            .expected_generation();
+        // A named-space publish leaves the doc segment and the default space alone, so only the
+        // generation precondition can notice it.
+        RuntimeStore::open(dataset_dir.path())
+            .unwrap()
+            .writer()
+            .unwrap()
+            .publish_raw_vectors_to_space(
+                RuntimeVectorSpace::new("images", 2, RuntimeVectorMetric::L2)
+                    .with_embedding(test_embedding("images")),
+                vec![NewDocumentVector::new("doc-001", vec![1.0, 0.0])],
+            )
+            .unwrap();
+        let generations = RuntimeStore::generations(dataset_dir.path()).unwrap();
+
+        // The publish step a vector-only commit ends in, reached past the unlocked fail-fast
+        // check as if the other publish had landed in between.
+        let writer = runtime.writer().unwrap();
+        let space = writer.store.default_vector_space(embedding).unwrap();
+        let error = writer
+            .publish_raw_vectors_for_space(
+                space,
+                vec![NewDocumentVector::new(
+                    "doc-001",
+                    embed_text("alpha refreshed", 384),
+                )],
+                Some(base),
+            )
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            crate::RuntimeError::InvalidRequest(message)
+                if message.contains(&format!("transaction began at store generation {base}"))
+        ));
+        assert_eq!(
+            RuntimeStore::generations(dataset_dir.path()).unwrap(),
+            generations
+        );
+    }
+
+    #[test]
+    fn transaction_commit_keeps_named_spaces_and_sparse_rows_for_untouched_documents() {
+        let (_dataset_dir, mut runtime) = transaction_dataset();
+        let images = RuntimeVectorSpace::new("images", 2, RuntimeVectorMetric::L2)
+            .with_embedding(test_embedding("images"));
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_vectors_to_space(
+                images.clone(),
+                vec![
+                    NewDocumentVector::new("doc-001", vec![0.0, 0.0]),
+                    NewDocumentVector::new("doc-002", vec![1.0, 1.0]),
+                ],
+            )
+            .unwrap();
+        runtime
+            .writer()
+            .unwrap()
+            .publish_raw_sparse_vectors(vec![
+                NewDocumentSparseVector::new("doc-001", vec![(7, 1.0)]),
+                NewDocumentSparseVector::new("doc-002", vec![(7, 2.0)]),
+            ])
+            .unwrap();
+
+        let mut transaction = runtime.writer().unwrap().begin().unwrap();
+        transaction
+            .upsert_documents(vec![NewDocument::new("doc-003", "gamma")])
+            .delete_documents(["doc-002"]);
+        transaction.commit().unwrap();
+
+        assert_eq!(
+            runtime.docstore.load_document_ids().unwrap(),
+            ["doc-001", "doc-003"]
+        );
+        assert_eq!(runtime.vector_spaces().unwrap()[1], images);
+        let hit_ids = |runtime: &mut RuntimeStore, request: RuntimeSearchRequest| {
+            runtime
+                .search(request)
+                .unwrap()
+                .hits
+                .into_iter()
+                .map(|hit| hit.doc_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            hit_ids(
+                &mut runtime,
+                RuntimeSearchRequest {
+                    mode: RuntimeSearchMode::Vector,
+                    top_k: 3,
+                    vector_space_queries: vec![RuntimeVectorSpaceQuery::new(
+                        "images",
+                        vec![1.0, 1.0]
+                    )
+                    .with_embedding(test_embedding("images"))],
+                    ..Default::default()
+                }
+            ),
+            ["doc-001"]
+        );
+        assert_eq!(
+            hit_ids(
+                &mut runtime,
+                RuntimeSearchRequest {
+                    mode: RuntimeSearchMode::Sparse,
+                    top_k: 3,
+                    sparse_query: Some(vec![(7, 1.0)]),
+                    ..Default::default()
+                }
+            ),
+            ["doc-001"]
+        );
+    }
+}
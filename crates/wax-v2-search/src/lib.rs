@@ -11,6 +11,7 @@ pub struct HybridHitDiagnostic {
     pub doc_id: String,
     pub text_rank: Option<usize>,
     pub vector_rank: Option<usize>,
+    pub sparse_rank: Option<usize>,
     pub rrf_score: f64,
 }
 
@@ -58,6 +59,26 @@ pub trait MetadataSource {
     fn field_value(&self, doc_id: &str, field: &str) -> Option<&str>;
 }
 
+/// One metadata group kept by [`collapse_hits_by_metadata`]. `key` is `None` for a hit without
+/// the field, which always forms a group of its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollapsedGroup {
+    pub key: Option<String>,
+    pub doc_ids: Vec<String>,
+    pub hit_count: usize,
+}
+
+/// Stored document vectors consulted by [`maximal_marginal_relevance`].
+pub trait DocumentVectorSource {
+    fn document_vector(&self, doc_id: &str) -> Option<&[f32]>;
+}
+
+impl DocumentVectorSource for HashMap<String, Vec<f32>> {
+    fn document_vector(&self, doc_id: &str) -> Option<&[f32]> {
+        self.get(doc_id).map(Vec::as_slice)
+    }
+}
+
 pub fn search_first_hybrid_query(
     text_lane: &TextLane,
     vector_lane: &mut VectorLane,
@@ -123,20 +144,32 @@ pub fn hybrid_search_report(
     text_hits: &[String],
     vector_hits: &[String],
     limit: usize,
+) -> HybridSearchReport {
+    hybrid_search_report_with_sparse(text_hits, vector_hits, &[], limit)
+}
+
+/// Three-lane variant of [`hybrid_search_report`] that also fuses learned sparse hits.
+pub fn hybrid_search_report_with_sparse(
+    text_hits: &[String],
+    vector_hits: &[String],
+    sparse_hits: &[String],
+    limit: usize,
 ) -> HybridSearchReport {
     let mut scores = HashMap::<String, f64>::new();
     let mut text_ranks = HashMap::<String, usize>::new();
     let mut vector_ranks = HashMap::<String, usize>::new();
+    let mut sparse_ranks = HashMap::<String, usize>::new();
 
-    for (rank, doc_id) in text_hits.iter().enumerate() {
-        let rank = rank + 1;
-        text_ranks.insert(doc_id.clone(), rank);
-        *scores.entry(doc_id.clone()).or_insert(0.0) += 1.0 / (RRF_K + rank as f64);
-    }
-    for (rank, doc_id) in vector_hits.iter().enumerate() {
-        let rank = rank + 1;
-        vector_ranks.insert(doc_id.clone(), rank);
-        *scores.entry(doc_id.clone()).or_insert(0.0) += 1.0 / (RRF_K + rank as f64);
+    for (hits, ranks) in [
+        (text_hits, &mut text_ranks),
+        (vector_hits, &mut vector_ranks),
+        (sparse_hits, &mut sparse_ranks),
+    ] {
+        for (rank, doc_id) in hits.iter().enumerate() {
+            let rank = rank + 1;
+            ranks.insert(doc_id.clone(), rank);
+            *scores.entry(doc_id.clone()).or_insert(0.0) += 1.0 / (RRF_K + rank as f64);
+        }
     }
 
     let mut fused = scores.into_iter().collect::<Vec<_>>();
@@ -154,6 +187,7 @@ pub fn hybrid_search_report(
             doc_id: doc_id.clone(),
             text_rank: text_ranks.get(doc_id).copied(),
             vector_rank: vector_ranks.get(doc_id).copied(),
+            sparse_rank: sparse_ranks.get(doc_id).copied(),
             rrf_score: *score,
         })
         .collect::<Vec<_>>();
@@ -176,6 +210,133 @@ pub fn reciprocal_rank_fusion(
     hybrid_search_report(text_hits, vector_hits, limit).fused_hits
 }
 
+/// Fuses any number of ranked hit lists with equal-weight reciprocal rank fusion, using the same
+/// score and doc_id tie-break as [`hybrid_search_report`].
+pub fn reciprocal_rank_fusion_lists(hit_lists: &[Vec<String>], limit: usize) -> Vec<String> {
+    reciprocal_rank_fusion_scored(hit_lists, limit)
+        .into_iter()
+        .map(|(doc_id, _)| doc_id)
+        .collect()
+}
+
+/// [`reciprocal_rank_fusion_lists`] with each hit's fused score.
+pub fn reciprocal_rank_fusion_scored(
+    hit_lists: &[Vec<String>],
+    limit: usize,
+) -> Vec<(String, f64)> {
+    let mut scores = HashMap::<&str, f64>::new();
+    for hits in hit_lists {
+        for (rank, doc_id) in hits.iter().enumerate() {
+            *scores.entry(doc_id.as_str()).or_insert(0.0) += 1.0 / (RRF_K + (rank + 1) as f64);
+        }
+    }
+
+    let mut fused = scores.into_iter().collect::<Vec<_>>();
+    fused.sort_by(|left, right| right.1.total_cmp(&left.1).then_with(|| left.0.cmp(right.0)));
+    fused
+        .into_iter()
+        .take(limit)
+        .map(|(doc_id, score)| (doc_id.to_owned(), score))
+        .collect()
+}
+
+/// Re-ranks fused hits with maximal marginal relevance. Relevance falls linearly with the fused
+/// rank (1 for the first hit); each pick maximises `lambda * relevance - (1 - lambda) * max
+/// cosine similarity` to the hits already picked, with earlier ranks winning ties. `lambda = 1`
+/// keeps the fused order. Hits without a stored vector are never penalised as duplicates.
+pub fn maximal_marginal_relevance(
+    hits: &[String],
+    vectors: &impl DocumentVectorSource,
+    lambda: f32,
+    limit: usize,
+) -> Vec<String> {
+    let candidate_vectors = hits
+        .iter()
+        .map(|doc_id| vectors.document_vector(doc_id))
+        .collect::<Vec<_>>();
+    let mut remaining = (0..hits.len()).collect::<Vec<_>>();
+    let mut max_similarity = vec![None::<f32>; hits.len()];
+    let mut selected = Vec::with_capacity(limit.min(hits.len()));
+    while selected.len() < limit && !remaining.is_empty() {
+        let mut best_slot = 0;
+        let mut best_score = f32::NEG_INFINITY;
+        for (slot, &rank) in remaining.iter().enumerate() {
+            let relevance = 1.0 - rank as f32 / hits.len() as f32;
+            let redundancy = max_similarity[rank].unwrap_or(0.0);
+            let score = lambda * relevance - (1.0 - lambda) * redundancy;
+            if score > best_score {
+                best_slot = slot;
+                best_score = score;
+            }
+        }
+
+        let picked = remaining.remove(best_slot);
+        if let Some(picked_vector) = candidate_vectors[picked] {
+            for &rank in &remaining {
+                if let Some(vector) = candidate_vectors[rank] {
+                    let similarity = cosine_similarity(picked_vector, vector);
+                    max_similarity[rank] = Some(
+                        max_similarity[rank].map_or(similarity, |current| current.max(similarity)),
+                    );
+                }
+            }
+        }
+        selected.push(hits[picked].clone());
+    }
+    selected
+}
+
+fn cosine_similarity(left: &[f32], right: &[f32]) -> f32 {
+    let (mut dot, mut left_norm, mut right_norm) = (0.0f32, 0.0f32, 0.0f32);
+    for (lhs, rhs) in left.iter().zip(right) {
+        dot += lhs * rhs;
+        left_norm += lhs * lhs;
+        right_norm += rhs * rhs;
+    }
+    if left_norm == 0.0 || right_norm == 0.0 {
+        return 0.0;
+    }
+    dot / (left_norm.sqrt() * right_norm.sqrt())
+}
+
+/// Groups ranked hits by a metadata field, keeping the best `max_hits_per_group` hits of each
+/// group and the `group_limit` groups with the best leading hits. `hit_count` counts every ranked
+/// hit of the group, including the ones past `max_hits_per_group`.
+pub fn collapse_hits_by_metadata(
+    hits: &[String],
+    metadata_source: &impl MetadataSource,
+    field: &str,
+    max_hits_per_group: usize,
+    group_limit: usize,
+) -> Vec<CollapsedGroup> {
+    let mut groups = Vec::<CollapsedGroup>::new();
+    let mut group_positions = HashMap::<&str, usize>::new();
+    for doc_id in hits {
+        let key = metadata_source.field_value(doc_id, field);
+        let position = match key.and_then(|key| group_positions.get(key)) {
+            Some(&position) => position,
+            None => {
+                if let Some(key) = key {
+                    group_positions.insert(key, groups.len());
+                }
+                groups.push(CollapsedGroup {
+                    key: key.map(ToOwned::to_owned),
+                    doc_ids: Vec::new(),
+                    hit_count: 0,
+                });
+                groups.len() - 1
+            }
+        };
+        let group = &mut groups[position];
+        group.hit_count += 1;
+        if group.doc_ids.len() < max_hits_per_group {
+            group.doc_ids.push(doc_id.clone());
+        }
+    }
+    groups.truncate(group_limit);
+    groups
+}
+
 pub fn filter_hits_by_metadata(
     hits: &[String],
     metadata_source: &impl MetadataSource,
@@ -202,8 +363,9 @@ mod tests {
     use std::collections::HashMap;
 
     use crate::{
-        filter_hits_by_metadata, hybrid_search_report, reciprocal_rank_fusion, MetadataFilter,
-        MetadataSource,
+        collapse_hits_by_metadata, filter_hits_by_metadata, hybrid_search_report,
+        hybrid_search_report_with_sparse, maximal_marginal_relevance, reciprocal_rank_fusion,
+        reciprocal_rank_fusion_lists, CollapsedGroup, MetadataFilter, MetadataSource,
     };
 
     struct TestMetadataSource {
@@ -230,6 +392,22 @@ mod tests {
         assert_eq!(hits, vec!["doc-1", "doc-2", "doc-3"]);
     }
 
+    #[test]
+    fn reciprocal_rank_fusion_lists_matches_two_lane_fusion_and_accepts_more_lanes() {
+        let text_hits = vec!["doc-2".to_owned(), "doc-1".to_owned()];
+        let vector_hits = vec!["doc-1".to_owned(), "doc-3".to_owned()];
+        assert_eq!(
+            reciprocal_rank_fusion_lists(&[text_hits.clone(), vector_hits.clone()], 3),
+            reciprocal_rank_fusion(&text_hits, &vector_hits, 3)
+        );
+
+        let image_hits = vec!["doc-3".to_owned(), "doc-4".to_owned()];
+        assert_eq!(
+            reciprocal_rank_fusion_lists(&[text_hits, vector_hits, image_hits], 4),
+            vec!["doc-1", "doc-3", "doc-2", "doc-4"]
+        );
+    }
+
     #[test]
     fn hybrid_search_report_captures_lane_ranks_and_scores() {
         let report = hybrid_search_report(
@@ -252,6 +430,97 @@ mod tests {
         assert_eq!(report.diagnostics[2].vector_rank, Some(2));
     }
 
+    #[test]
+    fn hybrid_search_report_with_sparse_fuses_a_third_lane() {
+        let text_hits = vec!["doc-2".to_owned(), "doc-1".to_owned()];
+        let vector_hits = vec!["doc-1".to_owned(), "doc-3".to_owned()];
+        assert_eq!(
+            hybrid_search_report_with_sparse(&text_hits, &vector_hits, &[], 3),
+            hybrid_search_report(&text_hits, &vector_hits, 3)
+        );
+
+        let sparse_hits = vec!["doc-3".to_owned()];
+        let report = hybrid_search_report_with_sparse(&text_hits, &vector_hits, &sparse_hits, 3);
+        assert_eq!(report.fused_hits, vec!["doc-1", "doc-3", "doc-2"]);
+        assert_eq!(report.diagnostics[1].sparse_rank, Some(1));
+        assert_eq!(report.diagnostics[1].vector_rank, Some(2));
+        assert_eq!(report.diagnostics[0].sparse_rank, None);
+        assert_eq!(
+            report.fused_hits,
+            reciprocal_rank_fusion_lists(&[text_hits, vector_hits, sparse_hits], 3)
+        );
+    }
+
+    #[test]
+    fn maximal_marginal_relevance_demotes_near_duplicates_by_lambda() {
+        let hits = ["doc-a1", "doc-a2", "doc-b", "doc-c"]
+            .map(str::to_owned)
+            .to_vec();
+        let vectors = HashMap::from([
+            ("doc-a1".to_owned(), vec![1.0, 0.0]),
+            ("doc-a2".to_owned(), vec![0.99, 0.05]),
+            ("doc-b".to_owned(), vec![0.0, 1.0]),
+        ]);
+
+        assert_eq!(maximal_marginal_relevance(&hits, &vectors, 1.0, 4), hits);
+        assert_eq!(
+            maximal_marginal_relevance(&hits, &vectors, 0.5, 4),
+            vec!["doc-a1", "doc-b", "doc-c", "doc-a2"]
+        );
+        assert_eq!(
+            maximal_marginal_relevance(&hits, &vectors, 0.5, 2),
+            vec!["doc-a1", "doc-b"]
+        );
+        assert!(maximal_marginal_relevance(&hits, &vectors, 0.5, 0).is_empty());
+    }
+
+    #[test]
+    fn collapse_hits_by_metadata_keeps_best_hits_per_group_with_counts() {
+        let source = |doc_id: &str, source_id: Option<&str>| {
+            let mut fields = HashMap::from([("text".to_owned(), doc_id.to_owned())]);
+            if let Some(source_id) = source_id {
+                fields.insert("source_id".to_owned(), source_id.to_owned());
+            }
+            (doc_id.to_owned(), fields)
+        };
+        let docs = TestMetadataSource {
+            docs: HashMap::from([
+                source("doc-1", Some("file-a")),
+                source("doc-2", Some("file-a")),
+                source("doc-3", None),
+                source("doc-4", Some("file-b")),
+                source("doc-5", Some("file-a")),
+                source("doc-6", Some("file-c")),
+            ]),
+        };
+        let hits = ["doc-1", "doc-2", "doc-3", "doc-4", "doc-5", "doc-6"]
+            .map(str::to_owned)
+            .to_vec();
+        let group = |key: Option<&str>, doc_ids: &[&str], hit_count| CollapsedGroup {
+            key: key.map(ToOwned::to_owned),
+            doc_ids: doc_ids.iter().map(|doc_id| (*doc_id).to_owned()).collect(),
+            hit_count,
+        };
+
+        assert_eq!(
+            collapse_hits_by_metadata(&hits, &docs, "source_id", 1, 3),
+            vec![
+                group(Some("file-a"), &["doc-1"], 3),
+                group(None, &["doc-3"], 1),
+                group(Some("file-b"), &["doc-4"], 1),
+            ]
+        );
+        assert_eq!(
+            collapse_hits_by_metadata(&hits, &docs, "source_id", 2, 10),
+            vec![
+                group(Some("file-a"), &["doc-1", "doc-2"], 3),
+                group(None, &["doc-3"], 1),
+                group(Some("file-b"), &["doc-4"], 1),
+                group(Some("file-c"), &["doc-6"], 1),
+            ]
+        );
+    }
+
     #[test]
     fn filter_hits_by_metadata_keeps_docs_matching_top_level_string_clauses() {
         let filter = MetadataFilter::from_pairs([("workspace_id", "w1")]);
@@ -0,0 +1,820 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
+use std::path::Path;
+
+use wax_v2_core::{PendingSegmentDescriptor, PendingSegmentWrite, SegmentDescriptor, SegmentKind};
+
+const SPARSE_SEGMENT_MAGIC: &[u8; 4] = b"WXSP";
+const SPARSE_SEGMENT_MAJOR: u16 = 1;
+const SPARSE_SEGMENT_MINOR: u16 = 0;
+const SPARSE_SEGMENT_HEADER_LENGTH: usize = 32;
+const SPARSE_TERM_ENTRY_LENGTH: usize = 24;
+const SPARSE_POSTING_LENGTH: usize = 8;
+/// Relative slack applied to score upper bounds, so float rounding never prunes a document whose
+/// exact score reaches the current top-k threshold.
+const UPPER_BOUND_SLACK: f64 = 1e-9;
+
+/// One `(term_id, weight)` entry of a learned sparse vector.
+pub type SparseTerm = (u32, f32);
+
+/// A document's doc_id with its sparse vector.
+pub type SparseRow = (String, Vec<SparseTerm>);
+
+/// Dynamic-pruning strategy used to walk the sparse inverted index. Both return the exact top-k.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SparseSearchAlgorithm {
+    #[default]
+    Wand,
+    MaxScore,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseLane {
+    doc_ids: Vec<String>,
+    postings: HashMap<u32, SparsePostingList>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct SparsePostingList {
+    max_weight: f32,
+    doc_indices: Vec<u32>,
+    weights: Vec<f32>,
+}
+
+impl SparseLane {
+    /// Loads the latest manifest-visible sparse segment of `mount_root/store.wax`.
+    pub fn load(mount_root: &Path) -> Result<Self, String> {
+        Self::load_at_generation(mount_root, None)
+    }
+
+    /// Loads the sparse segment visible at a store manifest generation; `None` is the active one.
+    pub fn load_at_generation(mount_root: &Path, generation: Option<u64>) -> Result<Self, String> {
+        let store_path = mount_root.join("store.wax");
+        if !store_path.exists() {
+            return Err(
+                "store.wax is missing; publish sparse vectors before runtime sparse search"
+                    .to_owned(),
+            );
+        }
+        let Some(current) = latest_store_sparse_segment(&store_path, generation)? else {
+            return Err(
+                "current store generation has no sparse segment; publish sparse vectors before runtime sparse search"
+                    .to_owned(),
+            );
+        };
+        if current.stale {
+            return Err(
+                "latest sparse segment is stale relative to the current document generation; republish sparse vectors before runtime sparse search"
+                    .to_owned(),
+            );
+        }
+        let bytes = wax_v2_core::map_segment_object(&store_path, &current.descriptor)
+            .map_err(|error| error.to_string())?;
+        let segment = BinarySparseSegment::decode(&bytes)?;
+        Ok(Self {
+            doc_ids: segment.doc_ids,
+            postings: segment.terms.into_iter().collect(),
+        })
+    }
+
+    pub fn doc_count(&self) -> usize {
+        self.doc_ids.len()
+    }
+
+    pub fn search(&self, query: &[SparseTerm], limit: usize) -> Result<Vec<String>, String> {
+        self.search_with_algorithm(query, limit, SparseSearchAlgorithm::default())
+    }
+
+    /// [`Self::search`] with each hit's dot-product score.
+    pub fn search_scored(
+        &self,
+        query: &[SparseTerm],
+        limit: usize,
+    ) -> Result<Vec<(String, f64)>, String> {
+        self.search_scored_with_algorithm(query, limit, SparseSearchAlgorithm::default())
+    }
+
+    /// Scores documents by the dot product of their sparse vector with `query` and returns the
+    /// top `limit` doc ids, ordered by score descending and then doc_id ascending.
+    pub fn search_with_algorithm(
+        &self,
+        query: &[SparseTerm],
+        limit: usize,
+        algorithm: SparseSearchAlgorithm,
+    ) -> Result<Vec<String>, String> {
+        Ok(self
+            .search_scored_with_algorithm(query, limit, algorithm)?
+            .into_iter()
+            .map(|(doc_id, _)| doc_id)
+            .collect())
+    }
+
+    fn search_scored_with_algorithm(
+        &self,
+        query: &[SparseTerm],
+        limit: usize,
+        algorithm: SparseSearchAlgorithm,
+    ) -> Result<Vec<(String, f64)>, String> {
+        let mut query = query.to_vec();
+        validate_sparse_terms(&mut query, "sparse query")?;
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut cursors = query
+            .iter()
+            .filter_map(|(term_id, weight)| {
+                self.postings
+                    .get(term_id)
+                    .map(|postings| PostingCursor::new(postings, f64::from(*weight)))
+            })
+            .collect::<Vec<_>>();
+        let mut top = TopDocs::new(limit);
+        match algorithm {
+            SparseSearchAlgorithm::Wand => self.search_wand(&mut cursors, &mut top),
+            SparseSearchAlgorithm::MaxScore => self.search_max_score(&mut cursors, &mut top),
+        }
+        Ok(top.into_scored_doc_ids())
+    }
+
+    fn search_wand<'a>(&'a self, cursors: &mut [PostingCursor<'_>], top: &mut TopDocs<'a>) {
+        let mut order = (0..cursors.len()).collect::<Vec<_>>();
+        loop {
+            order.retain(|index| cursors[*index].doc().is_some());
+            order.sort_by_key(|index| (cursors[*index].doc(), *index));
+
+            let mut upper_bound = 0.0;
+            let mut pivot = None;
+            for (position, index) in order.iter().enumerate() {
+                upper_bound += cursors[*index].upper_bound;
+                if top.may_enter(upper_bound) {
+                    pivot = Some(position);
+                    break;
+                }
+            }
+            let Some(pivot) = pivot else {
+                break;
+            };
+            let pivot_doc = cursors[order[pivot]]
+                .doc()
+                .expect("exhausted cursors removed");
+
+            if cursors[order[0]].doc() == Some(pivot_doc) {
+                top.offer(self.candidate(cursors, pivot_doc));
+                for index in &order {
+                    if cursors[*index].doc() == Some(pivot_doc) {
+                        cursors[*index].advance();
+                    }
+                }
+            } else {
+                for index in &order[..pivot] {
+                    cursors[*index].seek(pivot_doc);
+                }
+            }
+        }
+    }
+
+    fn search_max_score<'a>(&'a self, cursors: &mut [PostingCursor<'_>], top: &mut TopDocs<'a>) {
+        let mut by_bound = (0..cursors.len()).collect::<Vec<_>>();
+        by_bound.sort_by(|left, right| {
+            cursors[*left]
+                .upper_bound
+                .total_cmp(&cursors[*right].upper_bound)
+                .then_with(|| left.cmp(right))
+        });
+        let mut prefix_bounds = Vec::with_capacity(by_bound.len() + 1);
+        prefix_bounds.push(0.0);
+        for index in &by_bound {
+            let previous = *prefix_bounds.last().expect("seeded with zero");
+            prefix_bounds.push(previous + cursors[*index].upper_bound);
+        }
+
+        loop {
+            // Terms whose combined bound cannot reach the threshold are only probed for documents
+            // surfaced by the remaining essential terms.
+            let non_essential = (0..=by_bound.len())
+                .take_while(|count| !top.may_enter(prefix_bounds[*count]))
+                .last()
+                .unwrap_or(0);
+            let essential = &by_bound[non_essential..];
+            let Some(doc) = essential
+                .iter()
+                .filter_map(|index| cursors[*index].doc())
+                .min()
+            else {
+                break;
+            };
+
+            let mut partial = essential
+                .iter()
+                .filter(|index| cursors[**index].doc() == Some(doc))
+                .map(|index| cursors[*index].contribution())
+                .sum::<f64>();
+            let mut pruned = false;
+            for position in (0..non_essential).rev() {
+                if !top.may_enter(partial + prefix_bounds[position + 1]) {
+                    pruned = true;
+                    break;
+                }
+                let cursor = &mut cursors[by_bound[position]];
+                cursor.seek(doc);
+                if cursor.doc() == Some(doc) {
+                    partial += cursor.contribution();
+                }
+            }
+            if !pruned {
+                top.offer(self.candidate(cursors, doc));
+            }
+
+            for index in essential {
+                if cursors[*index].doc() == Some(doc) {
+                    cursors[*index].advance();
+                }
+            }
+        }
+    }
+
+    /// Sums contributions in query-term order so both algorithms produce bit-identical scores.
+    fn candidate<'a>(&'a self, cursors: &[PostingCursor<'_>], doc: u32) -> Candidate<'a> {
+        let score = cursors
+            .iter()
+            .filter(|cursor| cursor.doc() == Some(doc))
+            .map(PostingCursor::contribution)
+            .sum();
+        Candidate {
+            score,
+            doc_id: &self.doc_ids[doc as usize],
+        }
+    }
+}
+
+struct PostingCursor<'a> {
+    query_weight: f64,
+    upper_bound: f64,
+    doc_indices: &'a [u32],
+    weights: &'a [f32],
+    position: usize,
+}
+
+impl<'a> PostingCursor<'a> {
+    fn new(postings: &'a SparsePostingList, query_weight: f64) -> Self {
+        Self {
+            query_weight,
+            upper_bound: query_weight * f64::from(postings.max_weight),
+            doc_indices: &postings.doc_indices,
+            weights: &postings.weights,
+            position: 0,
+        }
+    }
+
+    fn doc(&self) -> Option<u32> {
+        self.doc_indices.get(self.position).copied()
+    }
+
+    fn contribution(&self) -> f64 {
+        self.query_weight * f64::from(self.weights[self.position])
+    }
+
+    fn advance(&mut self) {
+        self.position += 1;
+    }
+
+    fn seek(&mut self, target: u32) {
+        self.position += self.doc_indices[self.position..].partition_point(|doc| *doc < target);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Candidate<'a> {
+    score: f64,
+    doc_id: &'a str,
+}
+
+impl PartialEq for Candidate<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate<'_> {}
+
+impl PartialOrd for Candidate<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate<'_> {
+    // Better hits order first: higher score, then smaller doc_id.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .score
+            .total_cmp(&self.score)
+            .then_with(|| self.doc_id.cmp(other.doc_id))
+    }
+}
+
+struct TopDocs<'a> {
+    limit: usize,
+    heap: BinaryHeap<Candidate<'a>>,
+}
+
+impl<'a> TopDocs<'a> {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            heap: BinaryHeap::with_capacity(limit),
+        }
+    }
+
+    fn may_enter(&self, upper_bound: f64) -> bool {
+        if self.heap.len() < self.limit {
+            return true;
+        }
+        self.heap
+            .peek()
+            .is_none_or(|worst| upper_bound + upper_bound.abs() * UPPER_BOUND_SLACK >= worst.score)
+    }
+
+    fn offer(&mut self, candidate: Candidate<'a>) {
+        if self.heap.len() < self.limit {
+            self.heap.push(candidate);
+        } else if self.heap.peek().is_some_and(|worst| candidate < *worst) {
+            self.heap.pop();
+            self.heap.push(candidate);
+        }
+    }
+
+    fn into_scored_doc_ids(self) -> Vec<(String, f64)> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|candidate| (candidate.doc_id.to_owned(), candidate.score))
+            .collect()
+    }
+}
+
+/// Prepares a sparse segment from `(doc_id, terms)` rows given in wax doc id order.
+pub fn prepare_raw_sparse_segment(rows: &[SparseRow]) -> Result<PendingSegmentWrite, String> {
+    let segment = BinarySparseSegment::from_rows(rows)?;
+    let object_bytes = segment.encode()?;
+    Ok(PendingSegmentWrite {
+        descriptor: PendingSegmentDescriptor {
+            family: SegmentKind::Spr,
+            family_version: 1,
+            flags: 0,
+            doc_id_start: 0,
+            doc_id_end_exclusive: rows.len() as u64,
+            min_timestamp_ms: 0,
+            max_timestamp_ms: 0,
+            live_items: rows.len() as u64,
+            tombstoned_items: 0,
+            backend_id: 0,
+            backend_aux: segment.terms.len() as u64,
+        },
+        object_bytes,
+    })
+}
+
+/// Loads the rows of the current sparse segment so callers can upsert into it. Returns `None`
+/// when the store has no sparse segment or it predates the latest document generation.
+pub fn load_current_store_sparse_rows(store_path: &Path) -> Result<Option<Vec<SparseRow>>, String> {
+    if !store_path.exists() {
+        return Ok(None);
+    }
+    let Some(current) = latest_store_sparse_segment(store_path, None)? else {
+        return Ok(None);
+    };
+    if current.stale {
+        return Ok(None);
+    }
+    let bytes = wax_v2_core::map_segment_object(store_path, &current.descriptor)
+        .map_err(|error| error.to_string())?;
+    Ok(Some(BinarySparseSegment::decode(&bytes)?.into_rows()))
+}
+
+/// Doc ids of a sparse segment payload, in row order.
+pub fn decode_sparse_segment_doc_ids(bytes: &[u8]) -> Result<Vec<String>, String> {
+    Ok(BinarySparseSegment::decode(bytes)?.doc_ids)
+}
+
+/// Sorts `terms` by term id and rejects repeated ids or weights that are not finite and positive;
+/// dynamic pruning relies on every contribution being positive.
+pub fn validate_sparse_terms(terms: &mut [SparseTerm], context: &str) -> Result<(), String> {
+    if let Some((term_id, weight)) = terms
+        .iter()
+        .find(|(_, weight)| !weight.is_finite() || *weight <= 0.0)
+    {
+        return Err(format!(
+            "{context} weight for term {term_id} must be finite and positive, got {weight}"
+        ));
+    }
+    terms.sort_by_key(|(term_id, _)| *term_id);
+    if let Some(pair) = terms.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(format!("{context} repeats term {}", pair[0].0));
+    }
+    Ok(())
+}
+
+struct StoreSparseSegment {
+    descriptor: SegmentDescriptor,
+    stale: bool,
+}
+
+fn latest_store_sparse_segment(
+    store_path: &Path,
+    generation: Option<u64>,
+) -> Result<Option<StoreSparseSegment>, String> {
+    let opened = wax_v2_core::open_store_at_generation(store_path, generation)
+        .map_err(|error| error.to_string())?;
+    let latest_doc_generation = opened
+        .manifest
+        .segments
+        .iter()
+        .filter(|segment| segment.family == SegmentKind::Doc)
+        .map(|segment| segment.segment_generation)
+        .max();
+    Ok(opened
+        .manifest
+        .segments
+        .iter()
+        .filter(|segment| segment.family == SegmentKind::Spr)
+        .max_by_key(|segment| (segment.segment_generation, segment.object_offset))
+        .map(|descriptor| StoreSparseSegment {
+            stale: latest_doc_generation
+                .is_some_and(|generation| descriptor.segment_generation < generation),
+            descriptor: descriptor.clone(),
+        }))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct BinarySparseSegment {
+    doc_ids: Vec<String>,
+    terms: Vec<(u32, SparsePostingList)>,
+}
+
+impl BinarySparseSegment {
+    fn from_rows(rows: &[SparseRow]) -> Result<Self, String> {
+        if rows.is_empty() {
+            return Err("sparse segment requires at least one document".to_owned());
+        }
+        let doc_count = u32::try_from(rows.len())
+            .map_err(|_| "sparse segment has too many documents".to_owned())?;
+        let mut seen_doc_ids = HashSet::with_capacity(rows.len());
+        let mut terms = BTreeMap::<u32, SparsePostingList>::new();
+        for (doc_index, (doc_id, row_terms)) in (0..doc_count).zip(rows) {
+            if !seen_doc_ids.insert(doc_id.as_str()) {
+                return Err(format!("sparse segment repeats doc_id {doc_id}"));
+            }
+            if row_terms.is_empty() {
+                return Err(format!("sparse vector for {doc_id} has no terms"));
+            }
+            let mut row_terms = row_terms.clone();
+            validate_sparse_terms(&mut row_terms, &format!("sparse vector for {doc_id}"))?;
+            for (term_id, weight) in row_terms {
+                let postings = terms.entry(term_id).or_insert_with(|| SparsePostingList {
+                    max_weight: 0.0,
+                    doc_indices: Vec::new(),
+                    weights: Vec::new(),
+                });
+                postings.max_weight = postings.max_weight.max(weight);
+                postings.doc_indices.push(doc_index);
+                postings.weights.push(weight);
+            }
+        }
+
+        Ok(Self {
+            doc_ids: rows.iter().map(|(doc_id, _)| doc_id.clone()).collect(),
+            terms: terms.into_iter().collect(),
+        })
+    }
+
+    fn into_rows(self) -> Vec<SparseRow> {
+        let mut rows = self
+            .doc_ids
+            .into_iter()
+            .map(|doc_id| (doc_id, Vec::new()))
+            .collect::<Vec<_>>();
+        for (term_id, postings) in self.terms {
+            for (doc_index, weight) in postings.doc_indices.into_iter().zip(postings.weights) {
+                rows[doc_index as usize].1.push((term_id, weight));
+            }
+        }
+        rows
+    }
+
+    fn encode(&self) -> Result<Vec<u8>, String> {
+        let mut bytes = vec![0; SPARSE_SEGMENT_HEADER_LENGTH];
+        bytes[..4].copy_from_slice(SPARSE_SEGMENT_MAGIC);
+        bytes[4..6].copy_from_slice(&SPARSE_SEGMENT_MAJOR.to_le_bytes());
+        bytes[6..8].copy_from_slice(&SPARSE_SEGMENT_MINOR.to_le_bytes());
+        bytes[8..16].copy_from_slice(&(self.doc_ids.len() as u64).to_le_bytes());
+        bytes[16..24].copy_from_slice(&(self.terms.len() as u64).to_le_bytes());
+        for doc_id in &self.doc_ids {
+            let length = u32::try_from(doc_id.len())
+                .map_err(|_| format!("sparse segment doc_id too long: {doc_id}"))?;
+            bytes.extend_from_slice(&length.to_le_bytes());
+            bytes.extend_from_slice(doc_id.as_bytes());
+        }
+        bytes.resize(bytes.len().next_multiple_of(8), 0);
+        let terms_offset = bytes.len() as u64;
+        bytes[24..32].copy_from_slice(&terms_offset.to_le_bytes());
+
+        let mut posting_start = 0u64;
+        for (term_id, postings) in &self.terms {
+            bytes.extend_from_slice(&term_id.to_le_bytes());
+            bytes.extend_from_slice(&postings.max_weight.to_le_bytes());
+            bytes.extend_from_slice(&posting_start.to_le_bytes());
+            bytes.extend_from_slice(&(postings.doc_indices.len() as u64).to_le_bytes());
+            posting_start += postings.doc_indices.len() as u64;
+        }
+        for (_, postings) in &self.terms {
+            for (doc_index, weight) in postings.doc_indices.iter().zip(&postings.weights) {
+                bytes.extend_from_slice(&doc_index.to_le_bytes());
+                bytes.extend_from_slice(&weight.to_le_bytes());
+            }
+        }
+        Ok(bytes)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < SPARSE_SEGMENT_HEADER_LENGTH {
+            return Err(format!(
+                "sparse segment too short: expected at least {SPARSE_SEGMENT_HEADER_LENGTH} bytes"
+            ));
+        }
+        if &bytes[..4] != SPARSE_SEGMENT_MAGIC {
+            return Err("sparse segment magic mismatch".to_owned());
+        }
+        if read_u16(bytes, 4) != SPARSE_SEGMENT_MAJOR || read_u16(bytes, 6) > SPARSE_SEGMENT_MINOR {
+            return Err("unsupported sparse segment version".to_owned());
+        }
+        let doc_count = read_u64(bytes, 8) as usize;
+        let term_count = read_u64(bytes, 16) as usize;
+        let terms_offset = usize::try_from(read_u64(bytes, 24))
+            .map_err(|_| "sparse segment terms_offset overflow".to_owned())?;
+        if doc_count > (bytes.len() - SPARSE_SEGMENT_HEADER_LENGTH) / 4 {
+            return Err("sparse segment doc_count exceeds possible records in slice".to_owned());
+        }
+
+        let mut cursor = SPARSE_SEGMENT_HEADER_LENGTH;
+        let mut doc_ids = Vec::with_capacity(doc_count);
+        for _ in 0..doc_count {
+            let length = read_u32_at(bytes, &mut cursor)? as usize;
+            let end = cursor
+                .checked_add(length)
+                .filter(|end| *end <= bytes.len())
+                .ok_or_else(|| "sparse segment truncated while reading doc_id".to_owned())?;
+            let doc_id =
+                std::str::from_utf8(&bytes[cursor..end]).map_err(|error| error.to_string())?;
+            doc_ids.push(doc_id.to_owned());
+            cursor = end;
+        }
+        if terms_offset != cursor.next_multiple_of(8) {
+            return Err("sparse segment terms_offset mismatch".to_owned());
+        }
+        let postings_offset = term_count
+            .checked_mul(SPARSE_TERM_ENTRY_LENGTH)
+            .and_then(|length| length.checked_add(terms_offset))
+            .filter(|offset| *offset <= bytes.len())
+            .ok_or_else(|| "sparse segment term_count exceeds possible records".to_owned())?;
+        let posting_count = (bytes.len() - postings_offset) / SPARSE_POSTING_LENGTH;
+        if postings_offset + posting_count * SPARSE_POSTING_LENGTH != bytes.len() {
+            return Err("sparse segment trailing bytes mismatch".to_owned());
+        }
+
+        let mut terms = Vec::with_capacity(term_count);
+        let mut expected_start = 0usize;
+        for entry in 0..term_count {
+            let entry_offset = terms_offset + entry * SPARSE_TERM_ENTRY_LENGTH;
+            let term_id = read_u32(bytes, entry_offset);
+            let max_weight = f32::from_le_bytes(
+                bytes[entry_offset + 4..entry_offset + 8]
+                    .try_into()
+                    .expect("f32 slice"),
+            );
+            let start = read_u64(bytes, entry_offset + 8) as usize;
+            let count = read_u64(bytes, entry_offset + 16) as usize;
+            if start != expected_start || count == 0 || count > posting_count - start {
+                return Err(format!(
+                    "sparse segment posting range for term {term_id} is invalid"
+                ));
+            }
+            expected_start = start + count;
+            if terms
+                .last()
+                .is_some_and(|(previous, _): &(u32, SparsePostingList)| *previous >= term_id)
+            {
+                return Err("sparse segment term ids must be sorted and unique".to_owned());
+            }
+
+            let mut postings = SparsePostingList {
+                max_weight,
+                doc_indices: Vec::with_capacity(count),
+                weights: Vec::with_capacity(count),
+            };
+            let mut observed_max = 0.0f32;
+            for posting in start..start + count {
+                let offset = postings_offset + posting * SPARSE_POSTING_LENGTH;
+                let doc_index = read_u32(bytes, offset);
+                let weight =
+                    f32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().expect("f32"));
+                if doc_index as usize >= doc_count
+                    || postings
+                        .doc_indices
+                        .last()
+                        .is_some_and(|previous| *previous >= doc_index)
+                {
+                    return Err(format!(
+                        "sparse segment postings for term {term_id} must reference sorted documents"
+                    ));
+                }
+                if !weight.is_finite() || weight <= 0.0 {
+                    return Err(format!(
+                        "sparse segment weight for term {term_id} must be finite and positive"
+                    ));
+                }
+                observed_max = observed_max.max(weight);
+                postings.doc_indices.push(doc_index);
+                postings.weights.push(weight);
+            }
+            if observed_max != max_weight {
+                return Err(format!(
+                    "sparse segment max weight for term {term_id} does not match its postings"
+                ));
+            }
+            terms.push((term_id, postings));
+        }
+        if expected_start != posting_count {
+            return Err("sparse segment has unreferenced postings".to_owned());
+        }
+
+        Ok(Self { doc_ids, terms })
+    }
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(bytes[offset..offset + 2].try_into().expect("u16 slice"))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().expect("u32 slice"))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().expect("u64 slice"))
+}
+
+fn read_u32_at(bytes: &[u8], cursor: &mut usize) -> Result<u32, String> {
+    let end = cursor
+        .checked_add(4)
+        .ok_or_else(|| "sparse segment cursor overflow".to_owned())?;
+    if end > bytes.len() {
+        return Err("sparse segment truncated while reading u32".to_owned());
+    }
+    let value = read_u32(bytes, *cursor);
+    *cursor = end;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tempfile::tempdir;
+    use wax_v2_core::{create_empty_store, publish_segments};
+
+    use crate::{
+        load_current_store_sparse_rows, prepare_raw_sparse_segment, BinarySparseSegment,
+        SparseLane, SparseRow, SparseSearchAlgorithm, SparseTerm,
+    };
+
+    fn exhaustive_search(rows: &[SparseRow], query: &[SparseTerm], limit: usize) -> Vec<String> {
+        let mut query = query.to_vec();
+        query.sort_by_key(|(term_id, _)| *term_id);
+        let mut scored = rows
+            .iter()
+            .filter_map(|(doc_id, terms)| {
+                let terms = terms.iter().copied().collect::<HashMap<_, _>>();
+                let mut matched = false;
+                let score = query
+                    .iter()
+                    .filter_map(|(term_id, weight)| {
+                        terms.get(term_id).map(|doc_weight| {
+                            matched = true;
+                            f64::from(*weight) * f64::from(*doc_weight)
+                        })
+                    })
+                    .sum::<f64>();
+                matched.then(|| (doc_id.clone(), score))
+            })
+            .collect::<Vec<_>>();
+        scored.sort_by(|left, right| {
+            right
+                .1
+                .total_cmp(&left.1)
+                .then_with(|| left.0.cmp(&right.0))
+        });
+        scored
+            .into_iter()
+            .take(limit)
+            .map(|(doc_id, _)| doc_id)
+            .collect()
+    }
+
+    fn synthetic_rows(doc_count: usize) -> Vec<SparseRow> {
+        let mut state = 0x2545_f491_u32;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+        (0..doc_count)
+            .map(|index| {
+                let mut terms = (0..1 + next() % 6)
+                    .map(|_| (next() % 40, 0.25 * (1 + next() % 8) as f32))
+                    .collect::<Vec<_>>();
+                terms.sort_by_key(|(term_id, _)| *term_id);
+                terms.dedup_by_key(|(term_id, _)| *term_id);
+                (format!("doc-{index:03}"), terms)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sparse_segment_round_trips_rows_and_rejects_invalid_weights() {
+        let rows = vec![
+            ("doc-1".to_owned(), vec![(7, 0.5), (3, 1.5)]),
+            ("doc-2".to_owned(), vec![(3, 0.25)]),
+        ];
+        let segment = BinarySparseSegment::from_rows(&rows).unwrap();
+        let encoded = segment.encode().unwrap();
+        assert_eq!(&encoded[..4], b"WXSP");
+        let decoded = BinarySparseSegment::decode(&encoded).unwrap();
+        assert_eq!(decoded, segment);
+        assert_eq!(
+            decoded.into_rows(),
+            vec![
+                ("doc-1".to_owned(), vec![(3, 1.5), (7, 0.5)]),
+                ("doc-2".to_owned(), vec![(3, 0.25)]),
+            ]
+        );
+
+        let error =
+            BinarySparseSegment::from_rows(&[("doc-1".to_owned(), vec![(1, -0.5)])]).unwrap_err();
+        assert!(error.contains("finite and positive"));
+        let error =
+            BinarySparseSegment::from_rows(&[("doc-1".to_owned(), vec![(1, 0.5), (1, 0.7)])])
+                .unwrap_err();
+        assert!(error.contains("repeats term 1"));
+
+        let mut truncated = encoded.clone();
+        truncated.pop();
+        assert!(BinarySparseSegment::decode(&truncated).is_err());
+    }
+
+    #[test]
+    fn wand_and_max_score_match_exhaustive_scoring_including_ties() {
+        let temp_dir = tempdir().unwrap();
+        let store_path = temp_dir.path().join("store.wax");
+        create_empty_store(&store_path).unwrap();
+        let rows = synthetic_rows(300);
+        publish_segments(
+            &store_path,
+            vec![prepare_raw_sparse_segment(&rows).unwrap()],
+        )
+        .unwrap();
+
+        let lane = SparseLane::load(temp_dir.path()).unwrap();
+        assert_eq!(lane.doc_count(), 300);
+        let queries: Vec<Vec<SparseTerm>> = vec![
+            vec![(3, 1.0)],
+            vec![(1, 0.5), (2, 0.5), (39, 2.0)],
+            vec![(5, 1.0), (11, 1.0), (17, 1.0), (23, 1.0)],
+            vec![(38, 0.75), (0, 1.25), (20, 0.5), (9, 3.0), (14, 1.0)],
+            vec![(1000, 1.0)],
+        ];
+        for query in &queries {
+            for limit in [1, 5, 17, 400] {
+                let expected = exhaustive_search(&rows, query, limit);
+                for algorithm in [SparseSearchAlgorithm::Wand, SparseSearchAlgorithm::MaxScore] {
+                    assert_eq!(
+                        lane.search_with_algorithm(query, limit, algorithm).unwrap(),
+                        expected,
+                        "{algorithm:?} limit {limit} query {query:?}"
+                    );
+                }
+            }
+        }
+
+        let error = lane.search(&[(1, 1.0), (1, 2.0)], 3).unwrap_err();
+        assert!(error.contains("repeats term 1"));
+        assert_eq!(
+            load_current_store_sparse_rows(&store_path)
+                .unwrap()
+                .unwrap()[7],
+            rows[7]
+        );
+    }
+}
@@ -7,6 +7,10 @@ use serde::Deserialize;
 use wax_bench_model::{tokenize, DatasetPackManifest};
 use wax_v2_core::{PendingSegmentDescriptor, PendingSegmentWrite, SegmentDescriptor, SegmentKind};
 
+mod stream;
+
+pub use stream::{StreamingTextSegmentBuilder, DEFAULT_TEXT_SPILL_RUN_BYTES};
+
 const TEXT_SEGMENT_MAGIC: &[u8; 4] = b"WXTG";
 const TEXT_SEGMENT_MAJOR: u16 = 1;
 const TEXT_SEGMENT_MINOR: u16 = 0;
@@ -113,10 +117,15 @@ enum TextLaneSource {
 }
 
 impl TextLaneMetadata {
-    fn resolve(mount_root: &Path, manifest: &DatasetPackManifest) -> Result<Self, String> {
+    fn resolve(
+        mount_root: &Path,
+        manifest: &DatasetPackManifest,
+        generation: Option<u64>,
+    ) -> Result<Self, String> {
         let store_path = mount_root.join("store.wax");
         if store_path.exists() {
-            let opened = wax_v2_core::open_store(&store_path).map_err(|error| error.to_string())?;
+            let opened = wax_v2_core::open_store_at_generation(&store_path, generation)
+                .map_err(|error| error.to_string())?;
             let latest_doc_generation = opened
                 .manifest
                 .segments
@@ -184,7 +193,16 @@ impl TextQueryInputs {
 
 impl TextLane {
     pub fn load(mount_root: &Path, manifest: &DatasetPackManifest) -> Result<Self, String> {
-        let metadata = TextLaneMetadata::resolve(mount_root, manifest)?;
+        Self::load_at_generation(mount_root, manifest, None)
+    }
+
+    /// Loads the text segment visible at a store manifest generation; `None` is the active one.
+    pub fn load_at_generation(
+        mount_root: &Path,
+        manifest: &DatasetPackManifest,
+        generation: Option<u64>,
+    ) -> Result<Self, String> {
+        let metadata = TextLaneMetadata::resolve(mount_root, manifest, generation)?;
         let query_inputs = TextQueryInputs::resolve(mount_root, manifest)?;
         let (first_text_query, first_text_top_k) =
             load_first_text_query(&query_inputs.query_paths)?;
@@ -229,6 +247,14 @@ impl TextLane {
     }
 
     pub fn search_with_limit(&self, query: &str, limit: usize) -> Vec<String> {
+        self.search_scored_with_limit(query, limit)
+            .into_iter()
+            .map(|(doc_id, _)| doc_id)
+            .collect()
+    }
+
+    /// [`Self::search_with_limit`] with each hit's score: the number of query tokens it matches.
+    pub fn search_scored_with_limit(&self, query: &str, limit: usize) -> Vec<(String, u32)> {
         let mut scores: HashMap<String, u32> = HashMap::new();
         for token in tokenize(query) {
             if let Some(doc_ids) = self.inverted.get(&token) {
@@ -240,10 +266,8 @@ impl TextLane {
 
         let mut hits: Vec<(String, u32)> = scores.into_iter().collect();
         hits.sort_by(|left, right| right.1.cmp(&left.1).then_with(|| left.0.cmp(&right.0)));
-        hits.into_iter()
-            .take(limit)
-            .map(|(doc_id, _)| doc_id)
-            .collect()
+        hits.truncate(limit);
+        hits
     }
 }
 
@@ -308,6 +332,19 @@ where
     })
 }
 
+/// Distinct doc ids referenced by a text segment payload, sorted. Documents without tokens do not
+/// appear in any posting list.
+pub fn decode_text_segment_doc_ids(bytes: &[u8]) -> Result<Vec<String>, String> {
+    let mut doc_ids = BinaryTextSegment::decode(bytes)?
+        .postings
+        .into_iter()
+        .flat_map(|posting| posting.doc_ids)
+        .collect::<Vec<_>>();
+    doc_ids.sort();
+    doc_ids.dedup();
+    Ok(doc_ids)
+}
+
 pub fn validate_store_segment_against_dataset_pack(
     mount_root: &Path,
     manifest: &DatasetPackManifest,
@@ -683,9 +720,7 @@ mod tests {
         .unwrap();
         fs::write(
             temp_dir.path().join("queries.jsonl"),
-            concat!(
-                "{\"query_id\":\"q-001\",\"query_class\":\"keyword\",\"difficulty\":\"easy\",\"query_text\":\"alpha beta\",\"top_k\":2,\"filter_spec\":{},\"preview_expected\":true,\"embedding_available\":false,\"lane_eligibility\":{\"text\":true,\"vector\":false,\"hybrid\":true}}\n",
-            ),
+            "{\"query_id\":\"q-001\",\"query_class\":\"keyword\",\"difficulty\":\"easy\",\"query_text\":\"alpha beta\",\"top_k\":2,\"filter_spec\":{},\"preview_expected\":true,\"embedding_available\":false,\"lane_eligibility\":{\"text\":true,\"vector\":false,\"hybrid\":true}}\n",
         )
         .unwrap();
 
@@ -700,7 +735,7 @@ mod tests {
     #[test]
     fn text_lane_metadata_resolves_persisted_inputs_without_query_sidecars() {
         let mount_root = PathBuf::from("/tmp/wax-text");
-        let metadata = TextLaneMetadata::resolve(&mount_root, &test_manifest()).unwrap();
+        let metadata = TextLaneMetadata::resolve(&mount_root, &test_manifest(), None).unwrap();
         let query_inputs = TextQueryInputs::resolve(&mount_root, &test_manifest()).unwrap();
 
         assert_eq!(metadata.indexed_doc_count, 2);
@@ -804,9 +839,7 @@ mod tests {
         let temp_dir = tempdir().unwrap();
         fs::write(
             temp_dir.path().join("queries.jsonl"),
-            concat!(
-                "{\"query_id\":\"q-filtered\",\"query_class\":\"keyword\",\"difficulty\":\"easy\",\"query_text\":\"alpha\",\"top_k\":2,\"filter_spec\":{\"workspace_id\":\"w1\",\"ignored\":1},\"preview_expected\":true,\"embedding_available\":false,\"lane_eligibility\":{\"text\":true,\"vector\":false,\"hybrid\":false}}\n",
-            ),
+            "{\"query_id\":\"q-filtered\",\"query_class\":\"keyword\",\"difficulty\":\"easy\",\"query_text\":\"alpha\",\"top_k\":2,\"filter_spec\":{\"workspace_id\":\"w1\",\"ignored\":1},\"preview_expected\":true,\"embedding_available\":false,\"lane_eligibility\":{\"text\":true,\"vector\":false,\"hybrid\":false}}\n",
         )
         .unwrap();
 
@@ -840,9 +873,7 @@ mod tests {
         .unwrap();
         fs::write(
             temp_dir.path().join("queries.jsonl"),
-            concat!(
-                "{\"query_id\":\"q-001\",\"query_class\":\"keyword\",\"difficulty\":\"easy\",\"query_text\":\"alpha beta\",\"top_k\":2,\"filter_spec\":{},\"preview_expected\":true,\"embedding_available\":false,\"lane_eligibility\":{\"text\":true,\"vector\":false,\"hybrid\":true}}\n",
-            ),
+            "{\"query_id\":\"q-001\",\"query_class\":\"keyword\",\"difficulty\":\"easy\",\"query_text\":\"alpha beta\",\"top_k\":2,\"filter_spec\":{},\"preview_expected\":true,\"embedding_available\":false,\"lane_eligibility\":{\"text\":true,\"vector\":false,\"hybrid\":true}}\n",
         )
         .unwrap();
         create_empty_store(&store_path).unwrap();
@@ -971,9 +1002,7 @@ mod tests {
         .unwrap();
         fs::write(
             temp_dir.path().join("queries.jsonl"),
-            concat!(
-                "{\"query_id\":\"q-001\",\"query_class\":\"keyword\",\"difficulty\":\"easy\",\"query_text\":\"alpha beta\",\"top_k\":2,\"filter_spec\":{},\"preview_expected\":true,\"embedding_available\":false,\"lane_eligibility\":{\"text\":true,\"vector\":false,\"hybrid\":true}}\n",
-            ),
+            "{\"query_id\":\"q-001\",\"query_class\":\"keyword\",\"difficulty\":\"easy\",\"query_text\":\"alpha beta\",\"top_k\":2,\"filter_spec\":{},\"preview_expected\":true,\"embedding_available\":false,\"lane_eligibility\":{\"text\":true,\"vector\":false,\"hybrid\":true}}\n",
         )
         .unwrap();
         create_empty_store(&store_path).unwrap();
@@ -0,0 +1,250 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use wax_bench_model::tokenize;
+use wax_v2_core::{PendingSegmentDescriptor, SegmentKind, SpilledSegmentWrite};
+
+use crate::{TEXT_SEGMENT_MAGIC, TEXT_SEGMENT_MAJOR, TEXT_SEGMENT_MINOR};
+
+/// Default in-memory budget for `(token, doc_id)` pairs before a sorted run is spilled.
+pub const DEFAULT_TEXT_SPILL_RUN_BYTES: usize = 64 * 1024 * 1024;
+
+/// Builds a text segment from a document stream with bounded memory.
+///
+/// Token postings are buffered as `(token, doc_id)` pairs and spilled as sorted runs once they
+/// exceed the run budget; `finish` merges the runs into posting lists. The object is
+/// byte-identical to [`crate::prepare_text_segment_from_document_refs`] for the same documents.
+pub struct StreamingTextSegmentBuilder {
+    spill_dir: PathBuf,
+    run_bytes: usize,
+    pending: Vec<(String, String)>,
+    pending_bytes: usize,
+    runs: Vec<File>,
+    doc_count: usize,
+}
+
+impl StreamingTextSegmentBuilder {
+    pub fn new(spill_dir: &Path) -> Self {
+        Self {
+            spill_dir: spill_dir.to_path_buf(),
+            run_bytes: DEFAULT_TEXT_SPILL_RUN_BYTES,
+            pending: Vec::new(),
+            pending_bytes: 0,
+            runs: Vec::new(),
+            doc_count: 0,
+        }
+    }
+
+    pub fn with_run_bytes(mut self, run_bytes: usize) -> Self {
+        self.run_bytes = run_bytes.max(1);
+        self
+    }
+
+    pub fn push_document(&mut self, doc_id: &str, text: &str) -> Result<(), String> {
+        self.doc_count += 1;
+        let mut seen_tokens = HashSet::new();
+        for token in tokenize(text) {
+            if seen_tokens.insert(token.clone()) {
+                self.pending_bytes +=
+                    token.len() + doc_id.len() + 2 * std::mem::size_of::<String>();
+                self.pending.push((token, doc_id.to_owned()));
+            }
+        }
+        if self.pending_bytes >= self.run_bytes {
+            self.spill_run()?;
+        }
+        Ok(())
+    }
+
+    /// Number of sorted runs spilled so far.
+    pub fn spilled_runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    pub fn finish(mut self) -> Result<SpilledSegmentWrite, String> {
+        self.spill_run()?;
+        let mut runs = self
+            .runs
+            .into_iter()
+            .map(|run| RunReader {
+                reader: BufReader::new(run),
+            })
+            .collect::<Vec<_>>();
+        let mut heap = BinaryHeap::new();
+        for (index, run) in runs.iter_mut().enumerate() {
+            if let Some((token, doc_id)) = run.next_pair()? {
+                heap.push(Reverse((token, doc_id, index)));
+            }
+        }
+
+        let mut object = BufWriter::new(
+            tempfile::tempfile_in(&self.spill_dir).map_err(|error| error.to_string())?,
+        );
+        write_all(&mut object, TEXT_SEGMENT_MAGIC)?;
+        write_all(&mut object, &TEXT_SEGMENT_MAJOR.to_le_bytes())?;
+        write_all(&mut object, &TEXT_SEGMENT_MINOR.to_le_bytes())?;
+        write_all(&mut object, &0u64.to_le_bytes())?;
+        let mut posting_count = 0u64;
+        let mut current: Option<(String, Vec<String>)> = None;
+        while let Some(Reverse((token, doc_id, index))) = heap.pop() {
+            if let Some((next_token, next_doc_id)) = runs[index].next_pair()? {
+                heap.push(Reverse((next_token, next_doc_id, index)));
+            }
+            match current.as_mut() {
+                Some((current_token, doc_ids)) if *current_token == token => doc_ids.push(doc_id),
+                _ => {
+                    if let Some((token, doc_ids)) = current.take() {
+                        write_posting(&mut object, &token, &doc_ids)?;
+                        posting_count += 1;
+                    }
+                    current = Some((token, vec![doc_id]));
+                }
+            }
+        }
+        if let Some((token, doc_ids)) = current.take() {
+            write_posting(&mut object, &token, &doc_ids)?;
+            posting_count += 1;
+        }
+        let mut object = object
+            .into_inner()
+            .map_err(|error| error.error().to_string())?;
+        object
+            .seek(SeekFrom::Start(8))
+            .and_then(|_| object.write_all(&posting_count.to_le_bytes()))
+            .map_err(|error| error.to_string())?;
+
+        Ok(SpilledSegmentWrite {
+            descriptor: PendingSegmentDescriptor {
+                family: SegmentKind::Txt,
+                family_version: 1,
+                flags: 0,
+                doc_id_start: 0,
+                doc_id_end_exclusive: self.doc_count as u64,
+                min_timestamp_ms: 0,
+                max_timestamp_ms: 0,
+                live_items: self.doc_count as u64,
+                tombstoned_items: 0,
+                backend_id: 0,
+                backend_aux: posting_count,
+            },
+            object,
+        })
+    }
+
+    fn spill_run(&mut self) -> Result<(), String> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        self.pending.sort_unstable();
+        let mut run = BufWriter::new(
+            tempfile::tempfile_in(&self.spill_dir).map_err(|error| error.to_string())?,
+        );
+        for (token, doc_id) in self.pending.drain(..) {
+            write_length_prefixed(&mut run, &token)?;
+            write_length_prefixed(&mut run, &doc_id)?;
+        }
+        let mut run = run
+            .into_inner()
+            .map_err(|error| error.error().to_string())?;
+        run.seek(SeekFrom::Start(0))
+            .map_err(|error| error.to_string())?;
+        self.runs.push(run);
+        self.pending_bytes = 0;
+        Ok(())
+    }
+}
+
+struct RunReader {
+    reader: BufReader<File>,
+}
+
+impl RunReader {
+    fn next_pair(&mut self) -> Result<Option<(String, String)>, String> {
+        let Some(token) = self.read_string()? else {
+            return Ok(None);
+        };
+        let doc_id = self
+            .read_string()?
+            .ok_or_else(|| "text spill run ended inside a posting pair".to_owned())?;
+        Ok(Some((token, doc_id)))
+    }
+
+    fn read_string(&mut self) -> Result<Option<String>, String> {
+        let mut length = [0u8; 4];
+        match self.reader.read_exact(&mut length) {
+            Ok(()) => {}
+            Err(error) if error.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(error) => return Err(error.to_string()),
+        }
+        let mut bytes = vec![0u8; u32::from_le_bytes(length) as usize];
+        self.reader
+            .read_exact(&mut bytes)
+            .map_err(|error| error.to_string())?;
+        String::from_utf8(bytes)
+            .map_err(|error| error.to_string())
+            .map(Some)
+    }
+}
+
+fn write_posting(out: &mut impl Write, token: &str, doc_ids: &[String]) -> Result<(), String> {
+    write_all(out, &(token.len() as u32).to_le_bytes())?;
+    write_all(out, &(doc_ids.len() as u32).to_le_bytes())?;
+    write_all(out, token.as_bytes())?;
+    for doc_id in doc_ids {
+        write_length_prefixed(out, doc_id)?;
+    }
+    Ok(())
+}
+
+fn write_length_prefixed(out: &mut impl Write, value: &str) -> Result<(), String> {
+    write_all(out, &(value.len() as u32).to_le_bytes())?;
+    write_all(out, value.as_bytes())
+}
+
+fn write_all(out: &mut impl Write, bytes: &[u8]) -> Result<(), String> {
+    out.write_all(bytes).map_err(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Seek, SeekFrom};
+
+    use tempfile::tempdir;
+
+    use crate::{prepare_text_segment_from_document_refs, StreamingTextSegmentBuilder};
+
+    #[test]
+    fn streaming_builder_merges_spilled_runs_into_the_in_memory_text_segment() {
+        let temp_dir = tempdir().unwrap();
+        let documents = (0..200)
+            .map(|index| {
+                (
+                    format!("doc-{:03}", 199 - index),
+                    format!("shared token{} group{} shared", index, index % 7),
+                )
+            })
+            .collect::<Vec<_>>();
+        let expected = prepare_text_segment_from_document_refs(
+            documents
+                .iter()
+                .map(|(doc_id, text)| (doc_id.as_str(), text.as_str())),
+        )
+        .unwrap();
+
+        let mut builder = StreamingTextSegmentBuilder::new(temp_dir.path()).with_run_bytes(1024);
+        for (doc_id, text) in &documents {
+            builder.push_document(doc_id, text).unwrap();
+        }
+        assert!(builder.spilled_runs() > 1);
+        let mut spilled = builder.finish().unwrap();
+        let mut object_bytes = Vec::new();
+        spilled.object.seek(SeekFrom::Start(0)).unwrap();
+        spilled.object.read_to_end(&mut object_bytes).unwrap();
+
+        assert_eq!(spilled.descriptor, expected.descriptor);
+        assert_eq!(object_bytes, expected.object_bytes);
+    }
+}
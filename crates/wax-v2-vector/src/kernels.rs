@@ -0,0 +1,506 @@
+//! Exact distance kernels with runtime CPU dispatch.
+//!
+//! Every backend accumulates into the same [`KERNEL_LANES`] strided lanes (lane `i` sums the
+//! terms at indices `i mod 16`) using a separate multiply and add, reduces the lanes in one fixed
+//! tree order and adds the remainder sequentially. SIMD results are therefore bit-identical to the
+//! scalar path, and scores do not depend on the CPU a store is queried on.
+
+use std::sync::OnceLock;
+
+const KERNEL_LANES: usize = 16;
+
+/// Instruction set used by the distance kernels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimdLevel {
+    Scalar,
+    Neon,
+    Avx2,
+    Avx512,
+}
+
+impl SimdLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Scalar => "scalar",
+            Self::Neon => "neon",
+            Self::Avx2 => "avx2",
+            Self::Avx512 => "avx512",
+        }
+    }
+}
+
+/// The best instruction set supported by the running CPU, detected once per process.
+pub fn simd_level() -> SimdLevel {
+    static LEVEL: OnceLock<SimdLevel> = OnceLock::new();
+    *LEVEL.get_or_init(|| {
+        supported_simd_levels()
+            .last()
+            .copied()
+            .unwrap_or(SimdLevel::Scalar)
+    })
+}
+
+/// Every kernel level the running CPU can execute, from scalar up to the best one.
+pub fn supported_simd_levels() -> Vec<SimdLevel> {
+    #[allow(unused_mut)]
+    let mut levels = vec![SimdLevel::Scalar];
+    #[cfg(target_arch = "x86_64")]
+    {
+        if std::arch::is_x86_feature_detected!("avx2") {
+            levels.push(SimdLevel::Avx2);
+        }
+        if std::arch::is_x86_feature_detected!("avx512f") {
+            levels.push(SimdLevel::Avx512);
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            levels.push(SimdLevel::Neon);
+        }
+    }
+    levels
+}
+
+pub(crate) fn dot_f32(left: &[f32], right: &[f32]) -> f32 {
+    // SAFETY: `simd_level` only returns levels detected on this CPU.
+    unsafe { dot_f32_with(simd_level(), left, right) }
+}
+
+pub(crate) fn squared_l2_f32(left: &[f32], right: &[f32]) -> f32 {
+    // SAFETY: `simd_level` only returns levels detected on this CPU.
+    unsafe { squared_l2_f32_with(simd_level(), left, right) }
+}
+
+/// Dot product against an int8 preview row stored as raw bytes.
+pub(crate) fn dot_i8(left: &[f32], right: &[u8]) -> f32 {
+    // SAFETY: `simd_level` only returns levels detected on this CPU.
+    unsafe { dot_i8_with(simd_level(), left, right) }
+}
+
+/// # Safety
+///
+/// `level` must be one of [`supported_simd_levels`]; a level the CPU lacks executes
+/// unsupported instructions.
+unsafe fn dot_f32_with(level: SimdLevel, left: &[f32], right: &[f32]) -> f32 {
+    let len = left.len().min(right.len());
+    let (left, right) = (&left[..len], &right[..len]);
+    match level {
+        // SAFETY: the caller guarantees the level is supported by this CPU.
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Avx512 => unsafe { x86::dot_f32_avx512(left, right) },
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Avx2 => unsafe { x86::dot_f32_avx2(left, right) },
+        #[cfg(target_arch = "aarch64")]
+        SimdLevel::Neon => unsafe { neon::dot_f32(left, right) },
+        _ => scalar::dot_f32(left, right),
+    }
+}
+
+/// # Safety
+///
+/// Same contract as [`dot_f32_with`].
+unsafe fn squared_l2_f32_with(level: SimdLevel, left: &[f32], right: &[f32]) -> f32 {
+    let len = left.len().min(right.len());
+    let (left, right) = (&left[..len], &right[..len]);
+    match level {
+        // SAFETY: the caller guarantees the level is supported by this CPU.
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Avx512 => unsafe { x86::squared_l2_f32_avx512(left, right) },
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Avx2 => unsafe { x86::squared_l2_f32_avx2(left, right) },
+        #[cfg(target_arch = "aarch64")]
+        SimdLevel::Neon => unsafe { neon::squared_l2_f32(left, right) },
+        _ => scalar::squared_l2_f32(left, right),
+    }
+}
+
+/// # Safety
+///
+/// Same contract as [`dot_f32_with`].
+unsafe fn dot_i8_with(level: SimdLevel, left: &[f32], right: &[u8]) -> f32 {
+    let len = left.len().min(right.len());
+    let (left, right) = (&left[..len], &right[..len]);
+    match level {
+        // SAFETY: the caller guarantees the level is supported by this CPU.
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Avx512 => unsafe { x86::dot_i8_avx512(left, right) },
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Avx2 => unsafe { x86::dot_i8_avx2(left, right) },
+        #[cfg(target_arch = "aarch64")]
+        SimdLevel::Neon => unsafe { neon::dot_i8(left, right) },
+        _ => scalar::dot_i8(left, right),
+    }
+}
+
+/// Folds the lanes pairwise (8, 4, 2, 1 apart) and adds the sequential remainder.
+fn reduce_lanes(mut lanes: [f32; KERNEL_LANES], remainder: f32) -> f32 {
+    let mut width = KERNEL_LANES / 2;
+    while width > 0 {
+        for index in 0..width {
+            lanes[index] += lanes[index + width];
+        }
+        width /= 2;
+    }
+    lanes[0] + remainder
+}
+
+mod scalar {
+    use super::{reduce_lanes, KERNEL_LANES};
+
+    fn accumulate(len: usize, term: impl Fn(usize) -> f32) -> f32 {
+        let block_len = len - len % KERNEL_LANES;
+        let mut lanes = [0.0f32; KERNEL_LANES];
+        for block in (0..block_len).step_by(KERNEL_LANES) {
+            for (lane, sum) in lanes.iter_mut().enumerate() {
+                *sum += term(block + lane);
+            }
+        }
+        let remainder = (block_len..len).fold(0.0f32, |sum, index| sum + term(index));
+        reduce_lanes(lanes, remainder)
+    }
+
+    pub(super) fn dot_f32(left: &[f32], right: &[f32]) -> f32 {
+        accumulate(left.len(), |index| left[index] * right[index])
+    }
+
+    pub(super) fn squared_l2_f32(left: &[f32], right: &[f32]) -> f32 {
+        accumulate(left.len(), |index| {
+            let delta = left[index] - right[index];
+            delta * delta
+        })
+    }
+
+    pub(super) fn dot_i8(left: &[f32], right: &[u8]) -> f32 {
+        accumulate(left.len(), |index| {
+            left[index] * f32::from(right[index] as i8)
+        })
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    use super::{reduce_lanes, KERNEL_LANES};
+
+    fn remainder(len: usize, term: impl Fn(usize) -> f32) -> f32 {
+        let block_len = len - len % KERNEL_LANES;
+        (block_len..len).fold(0.0f32, |sum, index| sum + term(index))
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn store_avx2(low: __m256, high: __m256) -> [f32; KERNEL_LANES] {
+        let mut lanes = [0.0f32; KERNEL_LANES];
+        _mm256_storeu_ps(lanes.as_mut_ptr(), low);
+        _mm256_storeu_ps(lanes.as_mut_ptr().add(8), high);
+        lanes
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn dot_f32_avx2(left: &[f32], right: &[f32]) -> f32 {
+        let blocks = left.len() / KERNEL_LANES;
+        let (mut low, mut high) = (_mm256_setzero_ps(), _mm256_setzero_ps());
+        for block in 0..blocks {
+            let offset = block * KERNEL_LANES;
+            let (lhs, rhs) = (left.as_ptr().add(offset), right.as_ptr().add(offset));
+            low = _mm256_add_ps(
+                low,
+                _mm256_mul_ps(_mm256_loadu_ps(lhs), _mm256_loadu_ps(rhs)),
+            );
+            high = _mm256_add_ps(
+                high,
+                _mm256_mul_ps(_mm256_loadu_ps(lhs.add(8)), _mm256_loadu_ps(rhs.add(8))),
+            );
+        }
+        reduce_lanes(
+            store_avx2(low, high),
+            remainder(left.len(), |index| left[index] * right[index]),
+        )
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn squared_l2_f32_avx2(left: &[f32], right: &[f32]) -> f32 {
+        let blocks = left.len() / KERNEL_LANES;
+        let (mut low, mut high) = (_mm256_setzero_ps(), _mm256_setzero_ps());
+        for block in 0..blocks {
+            let offset = block * KERNEL_LANES;
+            let (lhs, rhs) = (left.as_ptr().add(offset), right.as_ptr().add(offset));
+            let low_delta = _mm256_sub_ps(_mm256_loadu_ps(lhs), _mm256_loadu_ps(rhs));
+            let high_delta =
+                _mm256_sub_ps(_mm256_loadu_ps(lhs.add(8)), _mm256_loadu_ps(rhs.add(8)));
+            low = _mm256_add_ps(low, _mm256_mul_ps(low_delta, low_delta));
+            high = _mm256_add_ps(high, _mm256_mul_ps(high_delta, high_delta));
+        }
+        reduce_lanes(
+            store_avx2(low, high),
+            remainder(left.len(), |index| {
+                let delta = left[index] - right[index];
+                delta * delta
+            }),
+        )
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn dot_i8_avx2(left: &[f32], right: &[u8]) -> f32 {
+        let blocks = left.len() / KERNEL_LANES;
+        let (mut low, mut high) = (_mm256_setzero_ps(), _mm256_setzero_ps());
+        for block in 0..blocks {
+            let offset = block * KERNEL_LANES;
+            let (lhs, rhs) = (left.as_ptr().add(offset), right.as_ptr().add(offset));
+            let bytes = _mm_loadu_si128(rhs.cast());
+            let low_values = _mm256_cvtepi32_ps(_mm256_cvtepi8_epi32(bytes));
+            let high_values = _mm256_cvtepi32_ps(_mm256_cvtepi8_epi32(_mm_srli_si128(bytes, 8)));
+            low = _mm256_add_ps(low, _mm256_mul_ps(_mm256_loadu_ps(lhs), low_values));
+            high = _mm256_add_ps(
+                high,
+                _mm256_mul_ps(_mm256_loadu_ps(lhs.add(8)), high_values),
+            );
+        }
+        reduce_lanes(
+            store_avx2(low, high),
+            remainder(left.len(), |index| {
+                left[index] * f32::from(right[index] as i8)
+            }),
+        )
+    }
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn store_avx512(sums: __m512) -> [f32; KERNEL_LANES] {
+        let mut lanes = [0.0f32; KERNEL_LANES];
+        _mm512_storeu_ps(lanes.as_mut_ptr(), sums);
+        lanes
+    }
+
+    #[target_feature(enable = "avx512f")]
+    pub(super) unsafe fn dot_f32_avx512(left: &[f32], right: &[f32]) -> f32 {
+        let blocks = left.len() / KERNEL_LANES;
+        let mut sums = _mm512_setzero_ps();
+        for block in 0..blocks {
+            let offset = block * KERNEL_LANES;
+            let product = _mm512_mul_ps(
+                _mm512_loadu_ps(left.as_ptr().add(offset)),
+                _mm512_loadu_ps(right.as_ptr().add(offset)),
+            );
+            sums = _mm512_add_ps(sums, product);
+        }
+        reduce_lanes(
+            store_avx512(sums),
+            remainder(left.len(), |index| left[index] * right[index]),
+        )
+    }
+
+    #[target_feature(enable = "avx512f")]
+    pub(super) unsafe fn squared_l2_f32_avx512(left: &[f32], right: &[f32]) -> f32 {
+        let blocks = left.len() / KERNEL_LANES;
+        let mut sums = _mm512_setzero_ps();
+        for block in 0..blocks {
+            let offset = block * KERNEL_LANES;
+            let delta = _mm512_sub_ps(
+                _mm512_loadu_ps(left.as_ptr().add(offset)),
+                _mm512_loadu_ps(right.as_ptr().add(offset)),
+            );
+            sums = _mm512_add_ps(sums, _mm512_mul_ps(delta, delta));
+        }
+        reduce_lanes(
+            store_avx512(sums),
+            remainder(left.len(), |index| {
+                let delta = left[index] - right[index];
+                delta * delta
+            }),
+        )
+    }
+
+    #[target_feature(enable = "avx512f")]
+    pub(super) unsafe fn dot_i8_avx512(left: &[f32], right: &[u8]) -> f32 {
+        let blocks = left.len() / KERNEL_LANES;
+        let mut sums = _mm512_setzero_ps();
+        for block in 0..blocks {
+            let offset = block * KERNEL_LANES;
+            let bytes = _mm_loadu_si128(right.as_ptr().add(offset).cast());
+            let values = _mm512_cvtepi32_ps(_mm512_cvtepi8_epi32(bytes));
+            sums = _mm512_add_ps(
+                sums,
+                _mm512_mul_ps(_mm512_loadu_ps(left.as_ptr().add(offset)), values),
+            );
+        }
+        reduce_lanes(
+            store_avx512(sums),
+            remainder(left.len(), |index| {
+                left[index] * f32::from(right[index] as i8)
+            }),
+        )
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use std::arch::aarch64::*;
+
+    use super::{reduce_lanes, KERNEL_LANES};
+
+    fn remainder(len: usize, term: impl Fn(usize) -> f32) -> f32 {
+        let block_len = len - len % KERNEL_LANES;
+        (block_len..len).fold(0.0f32, |sum, index| sum + term(index))
+    }
+
+    #[target_feature(enable = "neon")]
+    unsafe fn store(sums: [float32x4_t; 4]) -> [f32; KERNEL_LANES] {
+        let mut lanes = [0.0f32; KERNEL_LANES];
+        for (quarter, sum) in sums.into_iter().enumerate() {
+            vst1q_f32(lanes.as_mut_ptr().add(quarter * 4), sum);
+        }
+        lanes
+    }
+
+    #[target_feature(enable = "neon")]
+    pub(super) unsafe fn dot_f32(left: &[f32], right: &[f32]) -> f32 {
+        let blocks = left.len() / KERNEL_LANES;
+        let mut sums = [vdupq_n_f32(0.0); 4];
+        for block in 0..blocks {
+            let offset = block * KERNEL_LANES;
+            for (quarter, sum) in sums.iter_mut().enumerate() {
+                let index = offset + quarter * 4;
+                let product = vmulq_f32(
+                    vld1q_f32(left.as_ptr().add(index)),
+                    vld1q_f32(right.as_ptr().add(index)),
+                );
+                *sum = vaddq_f32(*sum, product);
+            }
+        }
+        reduce_lanes(
+            store(sums),
+            remainder(left.len(), |index| left[index] * right[index]),
+        )
+    }
+
+    #[target_feature(enable = "neon")]
+    pub(super) unsafe fn squared_l2_f32(left: &[f32], right: &[f32]) -> f32 {
+        let blocks = left.len() / KERNEL_LANES;
+        let mut sums = [vdupq_n_f32(0.0); 4];
+        for block in 0..blocks {
+            let offset = block * KERNEL_LANES;
+            for (quarter, sum) in sums.iter_mut().enumerate() {
+                let index = offset + quarter * 4;
+                let delta = vsubq_f32(
+                    vld1q_f32(left.as_ptr().add(index)),
+                    vld1q_f32(right.as_ptr().add(index)),
+                );
+                *sum = vaddq_f32(*sum, vmulq_f32(delta, delta));
+            }
+        }
+        reduce_lanes(
+            store(sums),
+            remainder(left.len(), |index| {
+                let delta = left[index] - right[index];
+                delta * delta
+            }),
+        )
+    }
+
+    #[target_feature(enable = "neon")]
+    pub(super) unsafe fn dot_i8(left: &[f32], right: &[u8]) -> f32 {
+        let blocks = left.len() / KERNEL_LANES;
+        let mut sums = [vdupq_n_f32(0.0); 4];
+        for block in 0..blocks {
+            let offset = block * KERNEL_LANES;
+            let bytes = vld1q_s8(right.as_ptr().add(offset).cast());
+            let low = vmovl_s8(vget_low_s8(bytes));
+            let high = vmovl_s8(vget_high_s8(bytes));
+            let values = [
+                vcvtq_f32_s32(vmovl_s16(vget_low_s16(low))),
+                vcvtq_f32_s32(vmovl_s16(vget_high_s16(low))),
+                vcvtq_f32_s32(vmovl_s16(vget_low_s16(high))),
+                vcvtq_f32_s32(vmovl_s16(vget_high_s16(high))),
+            ];
+            for ((quarter, sum), values) in sums.iter_mut().enumerate().zip(values) {
+                let product = vmulq_f32(vld1q_f32(left.as_ptr().add(offset + quarter * 4)), values);
+                *sum = vaddq_f32(*sum, product);
+            }
+        }
+        reduce_lanes(
+            store(sums),
+            remainder(left.len(), |index| {
+                left[index] * f32::from(right[index] as i8)
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        dot_f32_with, dot_i8_with, scalar, squared_l2_f32_with, supported_simd_levels, SimdLevel,
+    };
+
+    fn sample(len: usize, seed: u32) -> Vec<f32> {
+        let mut state = seed.wrapping_mul(0x9e37_79b9).wrapping_add(1);
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state % 20_000) as f32 / 7_919.0 - 1.25
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_supported_simd_level_matches_the_scalar_kernels_bit_for_bit() {
+        let levels = supported_simd_levels();
+        assert_eq!(levels[0], SimdLevel::Scalar);
+        for len in [0, 1, 7, 15, 16, 17, 31, 33, 64, 100, 384, 1_027] {
+            let left = sample(len, len as u32);
+            let right = sample(len, len as u32 + 101);
+            let preview = right
+                .iter()
+                .map(|value| (value * 90.0) as i8 as u8)
+                .collect::<Vec<_>>();
+            let expected_dot = scalar::dot_f32(&left, &right);
+            let expected_l2 = scalar::squared_l2_f32(&left, &right);
+            let expected_i8 = scalar::dot_i8(&left, &preview);
+            for level in &levels {
+                // SAFETY: every level comes from `supported_simd_levels`.
+                let (dot, l2, i8) = unsafe {
+                    (
+                        dot_f32_with(*level, &left, &right),
+                        squared_l2_f32_with(*level, &left, &right),
+                        dot_i8_with(*level, &left, &preview),
+                    )
+                };
+                assert_eq!(
+                    dot.to_bits(),
+                    expected_dot.to_bits(),
+                    "dot {} len {len}",
+                    level.as_str()
+                );
+                assert_eq!(
+                    l2.to_bits(),
+                    expected_l2.to_bits(),
+                    "l2 {} len {len}",
+                    level.as_str()
+                );
+                assert_eq!(
+                    i8.to_bits(),
+                    expected_i8.to_bits(),
+                    "i8 {} len {len}",
+                    level.as_str()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn scalar_kernels_stay_close_to_a_naive_f64_reference() {
+        let left = sample(1_000, 3);
+        let right = sample(1_000, 4);
+        let reference = left
+            .iter()
+            .zip(&right)
+            .map(|(lhs, rhs)| f64::from(*lhs) * f64::from(*rhs))
+            .sum::<f64>();
+        let score = f64::from(scalar::dot_f32(&left, &right));
+        assert!((score - reference).abs() <= 1e-3 * reference.abs().max(1.0));
+        assert_eq!(scalar::dot_f32(&[1.0; 5], &[2.0, 3.0, 4.0, 5.0, 6.0]), 20.0);
+    }
+}
@@ -1,13 +1,14 @@
 use std::borrow::Cow;
-use std::cell::UnsafeCell;
+use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
 use std::fs;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::ops::Range;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Instant;
 
 use bytemuck::try_cast_slice;
@@ -22,23 +23,301 @@ use wax_bench_model::{
 use wax_v2_core::{PendingSegmentDescriptor, PendingSegmentWrite, SegmentDescriptor, SegmentKind};
 use wax_v2_docstore::{load_document_ids_from_documents, parse_document_id};
 
+mod kernels;
+mod stream;
+
+pub use kernels::{simd_level, supported_simd_levels, SimdLevel};
+pub use stream::StreamingVectorSegmentBuilder;
+
 type BorrowedHnsw<'a> = Hnsw<'a, f32, DistCosine>;
 const VECTOR_SEGMENT_MAGIC: &[u8; 4] = b"WXVG";
 const VECTOR_SEGMENT_MAJOR: u16 = 1;
-const VECTOR_SEGMENT_MINOR: u16 = 0;
+const VECTOR_SEGMENT_MINOR: u16 = 4;
+const VECTOR_SEGMENT_ENCODING_MINOR: u16 = 1;
+const VECTOR_SEGMENT_SPACE_MINOR: u16 = 2;
+const VECTOR_SEGMENT_MULTI_VECTOR_MINOR: u16 = 3;
+const VECTOR_SEGMENT_EMBEDDING_MINOR: u16 = 4;
 const VECTOR_SEGMENT_HEADER_LENGTH: usize = 48;
 const VECTOR_SEGMENT_FLAG_HAS_PREVIEW: u32 = 1;
+const VECTOR_SEGMENT_FLAG_MULTI_VECTOR: u32 = 1 << 1;
+const VECTOR_SEGMENT_FLAG_EMBEDDING_IDENTITY: u32 = 1 << 2;
+const VECTOR_SEGMENT_ENCODING_SHIFT: u32 = 8;
+const VECTOR_SEGMENT_ENCODING_MASK: u32 = 0xff << VECTOR_SEGMENT_ENCODING_SHIFT;
+const VECTOR_SEGMENT_METRIC_SHIFT: u32 = 16;
+const VECTOR_SEGMENT_METRIC_MASK: u32 = 0xff << VECTOR_SEGMENT_METRIC_SHIFT;
+const VECTOR_SPACE_NAME_MAX_LENGTH: usize = 64;
+const EMBEDDING_IDENTITY_FIELD_MAX_LENGTH: usize = 256;
+
+/// Name of the vector space described by the dataset manifest's `vector_profile`.
+pub const DEFAULT_VECTOR_SPACE: &str = "default";
+const HALF_DECODE_LANES: usize = 8;
+/// Smallest number of documents worth handing to an extra exact-scan thread.
+const EXACT_SCAN_MIN_DOCS_PER_THREAD: usize = 4096;
+/// Rows scored against every query of a batch before moving on.
+const BATCH_SCAN_ROW_BLOCK: usize = 256;
+const F16_EXPONENT_REBIAS: f32 = f32::from_bits(0x7780_0000);
+
+/// Payload encoding of the exact vectors stored in a `Vec` segment.
+///
+/// Queries are always scored in f32; half-precision rows are widened on the fly by the
+/// dot-product kernels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VectorEncoding {
+    #[default]
+    F32,
+    F16,
+    Bf16,
+}
+
+impl VectorEncoding {
+    /// Maps a manifest `embedding_dtype` to a payload encoding. Dtypes without a dedicated
+    /// exact payload encoding (for example `i8`) are stored as f32.
+    pub fn from_embedding_dtype(dtype: &str) -> Self {
+        match dtype {
+            "f16" | "float16" => Self::F16,
+            "bf16" | "bfloat16" => Self::Bf16,
+            _ => Self::F32,
+        }
+    }
+
+    pub fn bytes_per_value(self) -> usize {
+        match self {
+            Self::F32 => 4,
+            Self::F16 | Self::Bf16 => 2,
+        }
+    }
+
+    fn as_code(self) -> u32 {
+        match self {
+            Self::F32 => 0,
+            Self::F16 => 1,
+            Self::Bf16 => 2,
+        }
+    }
+
+    fn from_code(code: u32) -> Result<Self, String> {
+        match code {
+            0 => Ok(Self::F32),
+            1 => Ok(Self::F16),
+            2 => Ok(Self::Bf16),
+            _ => Err(format!("unsupported vector segment encoding: {code}")),
+        }
+    }
+
+    fn encode_value(self, value: f32, bytes: &mut Vec<u8>) {
+        match self {
+            Self::F32 => bytes.extend_from_slice(&value.to_le_bytes()),
+            Self::F16 => bytes.extend_from_slice(&f32_to_f16_bits(value).to_le_bytes()),
+            Self::Bf16 => bytes.extend_from_slice(&f32_to_bf16_bits(value).to_le_bytes()),
+        }
+    }
+}
+
+/// Radius of a range search. Cosine spaces accept either form (distance is `1 - similarity`),
+/// dot-product spaces only a similarity and L2 spaces only a Euclidean distance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VectorRadius {
+    MinSimilarity(f32),
+    MaxDistance(f32),
+}
+
+impl VectorRadius {
+    /// Lowest exact lane score inside the radius for `metric`.
+    fn min_score(self, metric: VectorMetric) -> Result<f32, String> {
+        match (self, metric) {
+            (Self::MinSimilarity(value) | Self::MaxDistance(value), _) if !value.is_finite() => {
+                Err("vector radius must be finite".to_owned())
+            }
+            (Self::MaxDistance(distance), _) if distance < 0.0 => {
+                Err("vector radius distance must not be negative".to_owned())
+            }
+            (Self::MinSimilarity(similarity), VectorMetric::Cosine | VectorMetric::Dot) => {
+                Ok(similarity)
+            }
+            (Self::MaxDistance(distance), VectorMetric::Cosine) => Ok(1.0 - distance),
+            (Self::MaxDistance(distance), VectorMetric::L2) => Ok(-(distance * distance)),
+            (Self::MinSimilarity(_), VectorMetric::L2) => {
+                Err("l2 vector spaces take a max distance radius".to_owned())
+            }
+            (Self::MaxDistance(_), VectorMetric::Dot) => {
+                Err("dot vector spaces take a min similarity radius".to_owned())
+            }
+        }
+    }
+}
+
+/// Similarity metric of a vector space. Cosine spaces expect normalized vectors and score
+/// by dot product, matching the default lane; L2 spaces score by negative squared distance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VectorMetric {
+    #[default]
+    Cosine,
+    Dot,
+    L2,
+}
+
+impl VectorMetric {
+    pub fn from_distance_metric(metric: &str) -> Result<Self, String> {
+        match metric {
+            "cosine" => Ok(Self::Cosine),
+            "dot" => Ok(Self::Dot),
+            "l2" => Ok(Self::L2),
+            _ => Err(format!("unsupported vector distance metric: {metric}")),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Cosine => "cosine",
+            Self::Dot => "dot",
+            Self::L2 => "l2",
+        }
+    }
+
+    fn as_code(self) -> u32 {
+        match self {
+            Self::Cosine => 0,
+            Self::Dot => 1,
+            Self::L2 => 2,
+        }
+    }
+
+    fn from_code(code: u32) -> Result<Self, String> {
+        match code {
+            0 => Ok(Self::Cosine),
+            1 => Ok(Self::Dot),
+            2 => Ok(Self::L2),
+            _ => Err(format!("unsupported vector segment metric: {code}")),
+        }
+    }
+}
+
+/// Embedding model that produced a space's vectors, mirroring the `embedding_*` fields of the
+/// dataset identity.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EmbeddingIdentity {
+    pub spec_id: String,
+    pub model_version: String,
+    pub model_hash: String,
+}
+
+impl EmbeddingIdentity {
+    pub fn new(
+        spec_id: impl Into<String>,
+        model_version: impl Into<String>,
+        model_hash: impl Into<String>,
+    ) -> Self {
+        Self {
+            spec_id: spec_id.into(),
+            model_version: model_version.into(),
+            model_hash: model_hash.into(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (field, value) in [
+            ("spec_id", &self.spec_id),
+            ("model_version", &self.model_version),
+            ("model_hash", &self.model_hash),
+        ] {
+            if value.is_empty() || value.len() > EMBEDDING_IDENTITY_FIELD_MAX_LENGTH {
+                return Err(format!(
+                    "embedding {field} must be 1..={EMBEDDING_IDENTITY_FIELD_MAX_LENGTH} bytes"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for EmbeddingIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}@{} ({})",
+            self.spec_id, self.model_version, self.model_hash
+        )
+    }
+}
+
+/// Shape of one named vector space. Each space is persisted as its own `Vec` segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VectorSpaceSpec {
+    pub name: String,
+    pub dimensions: usize,
+    pub metric: VectorMetric,
+    pub encoding: VectorEncoding,
+    pub embedding: Option<EmbeddingIdentity>,
+}
+
+impl VectorSpaceSpec {
+    pub fn new(name: impl Into<String>, dimensions: usize, metric: VectorMetric) -> Self {
+        Self {
+            name: name.into(),
+            dimensions,
+            metric,
+            encoding: VectorEncoding::F32,
+            embedding: None,
+        }
+    }
+
+    pub fn with_encoding(mut self, encoding: VectorEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn with_embedding(mut self, embedding: EmbeddingIdentity) -> Self {
+        self.embedding = Some(embedding);
+        self
+    }
+}
 
-struct HnswIoOwner(UnsafeCell<HnswIo>);
+/// Latest manifest-visible `Vec` segment of one vector space.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreVectorSpace {
+    pub spec: VectorSpaceSpec,
+    pub multi_vector: bool,
+    pub descriptor: SegmentDescriptor,
+}
+
+pub fn validate_vector_space_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > VECTOR_SPACE_NAME_MAX_LENGTH {
+        return Err(format!(
+            "vector space name must be 1..={VECTOR_SPACE_NAME_MAX_LENGTH} bytes"
+        ));
+    }
+    if !name
+        .bytes()
+        .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'-' | b'.'))
+    {
+        return Err(format!(
+            "vector space name {name} may only contain ASCII letters, digits, '_', '-' or '.'"
+        ));
+    }
+    Ok(())
+}
+
+/// Reload state the loaded HNSW graph borrows from. `HnswIo::load_hnsw` needs `&mut HnswIo`,
+/// so the load holds the lock and never releases it: the graph is the only borrow of the
+/// `HnswIo` for as long as it lives, and the owner stays shareable across reader threads.
+struct HnswIoOwner(Mutex<HnswIo>);
 
 impl HnswIoOwner {
     fn new(mount_root: &Path, basename: &str) -> Self {
-        Self(UnsafeCell::new(HnswIo::new(mount_root, basename)))
+        Self(Mutex::new(HnswIo::new(mount_root, basename)))
     }
 
     fn load<'a>(&'a self) -> Result<BorrowedHnsw<'a>, String> {
+        let mut guard = self
+            .0
+            .lock()
+            .map_err(|_| "hnsw loader lock is poisoned".to_owned())?;
+        let io: *mut HnswIo = &mut *guard;
+        std::mem::forget(guard);
+        // SAFETY: the forgotten guard keeps the lock held for the owner's lifetime, so no other
+        // reference to the `HnswIo` can be created while the loaded graph borrows it.
         unsafe {
-            (&mut *self.0.get())
+            (*io)
                 .load_hnsw::<f32, DistCosine>()
                 .map_err(|error| error.to_string())
         }
@@ -113,11 +392,56 @@ pub struct VectorLane {
     doc_id_offsets: Vec<u64>,
     doc_vectors: ByteStorage,
     hnsw_available: bool,
-    hnsw_index: Option<HnswIndexCell>,
+    /// Loaded on the first HNSW search unless the lane was opened in HNSW mode.
+    hnsw_index: OnceLock<HnswIndexCell>,
     preview_vectors: Option<ByteStorage>,
+    sub_vector_offsets: Option<Vec<u32>>,
+    encoding: VectorEncoding,
+    metric: VectorMetric,
+    exact_scan_threads: usize,
     pub dimensions: usize,
 }
 
+/// Cursor from [`VectorLane::sorted_document_vectors`].
+pub struct SortedDocumentVectors<'a> {
+    lane: &'a VectorLane,
+    rows: Vec<usize>,
+    next: usize,
+}
+
+impl SortedDocumentVectors<'_> {
+    /// Stored vector of `doc_id`, decoded to f32. Lookups must come in ascending doc_id order;
+    /// `None` for documents without a row and for every document of a multi-vector lane.
+    pub fn vector(&mut self, doc_id: &str) -> Option<Vec<f32>> {
+        while let Some(&row) = self.rows.get(self.next) {
+            match self.lane.doc_id_bytes(row).cmp(doc_id.as_bytes()) {
+                Ordering::Less => self.next += 1,
+                Ordering::Equal => {
+                    self.next += 1;
+                    return Some(decode_encoded_row(
+                        self.lane.vector_bytes(row),
+                        self.lane.encoding,
+                    ));
+                }
+                Ordering::Greater => return None,
+            }
+        }
+        None
+    }
+}
+
+/// Borrowed, thread-shareable view of the lane data exact scans read.
+#[derive(Clone, Copy)]
+struct ExactScanView<'a> {
+    doc_vectors: &'a [u8],
+    row_length: usize,
+    encoding: VectorEncoding,
+    metric: VectorMetric,
+    doc_id_blob: &'a [u8],
+    doc_id_offsets: &'a [u64],
+    sub_vector_offsets: Option<&'a [u32]>,
+}
+
 #[derive(Debug)]
 enum ByteStorage {
     Mapped(Mmap),
@@ -155,25 +479,62 @@ struct VectorLaneMetadata {
 struct StoreVectorSegment {
     store_path: PathBuf,
     descriptor: SegmentDescriptor,
+    space: VectorSpaceSpec,
     has_preview: bool,
 }
 
 impl VectorLaneMetadata {
     fn resolve(mount_root: &Path, manifest: &DatasetPackManifest) -> Result<Self, String> {
-        Self::resolve_with_store_preference(mount_root, manifest, true)
+        Self::resolve_at_generation(mount_root, manifest, None)
+    }
+
+    fn resolve_at_generation(
+        mount_root: &Path,
+        manifest: &DatasetPackManifest,
+        generation: Option<u64>,
+    ) -> Result<Self, String> {
+        Self::resolve_with_store_preference(mount_root, manifest, true, generation)
+    }
+
+    fn resolve_space(
+        mount_root: &Path,
+        manifest: &DatasetPackManifest,
+        space: &str,
+        generation: Option<u64>,
+    ) -> Result<Self, String> {
+        if space == DEFAULT_VECTOR_SPACE {
+            return Self::resolve_at_generation(mount_root, manifest, generation);
+        }
+        validate_vector_space_name(space)?;
+        let vector_segment = resolve_store_vector_segment(mount_root, space, generation)?
+            .ok_or_else(|| format!("vector space {space} has no published vectors"))?;
+        let doc_count = usize::try_from(vector_segment.descriptor.live_items)
+            .map_err(|_| "vector segment live_items exceeds addressable memory".to_owned())?;
+        // Named spaces only live in the store; compatibility sidecars describe the default space.
+        let compatibility = Self::resolve_compatibility(mount_root, manifest)?;
+        Ok(Self {
+            dimensions: vector_segment.space.dimensions,
+            doc_count,
+            vector_segment: Some(vector_segment),
+            vector_lane_skeleton_path: None,
+            preview_vectors_path: None,
+            hnsw_graph_basename: None,
+            ..compatibility
+        })
     }
 
     fn resolve_compatibility(
         mount_root: &Path,
         manifest: &DatasetPackManifest,
     ) -> Result<Self, String> {
-        Self::resolve_with_store_preference(mount_root, manifest, false)
+        Self::resolve_with_store_preference(mount_root, manifest, false, None)
     }
 
     fn resolve_with_store_preference(
         mount_root: &Path,
         manifest: &DatasetPackManifest,
         prefer_store_segment: bool,
+        generation: Option<u64>,
     ) -> Result<Self, String> {
         let document_vectors_path = manifest
             .files
@@ -183,13 +544,13 @@ impl VectorLaneMetadata {
             .ok_or_else(|| "document_vectors file missing from manifest".to_owned())?;
 
         let vector_segment = if prefer_store_segment {
-            resolve_store_vector_segment(mount_root)?
+            resolve_store_vector_segment(mount_root, DEFAULT_VECTOR_SPACE, generation)?
         } else {
             None
         };
         if prefer_store_segment
             && vector_segment.is_none()
-            && store_has_manifest_visible_family(mount_root, SegmentKind::Doc)?
+            && store_has_manifest_visible_family(mount_root, SegmentKind::Doc, generation)?
         {
             return Err(
                 "current store generation has manifest-visible documents but no matching vector segment; publish vectors before runtime vector search"
@@ -280,16 +641,17 @@ impl VectorQueryInputs {
 }
 
 trait VectorBackend {
+    /// Best `limit` rows as (row index, score), ordered by score and then doc_id.
     fn search(
         &self,
-        lane: &mut VectorLane,
+        lane: &VectorLane,
         query: &[f32],
         limit: usize,
-    ) -> Result<Vec<String>, String>;
+    ) -> Result<Vec<(usize, f32)>, String>;
 
     fn profile(&self, lane: &VectorLane, query: &[f32], limit: usize) -> SearchPhaseProfile;
 
-    fn warmup(&self, _lane: &mut VectorLane) -> Result<(), String> {
+    fn warmup(&self, _lane: &VectorLane) -> Result<(), String> {
         Ok(())
     }
 }
@@ -305,10 +667,10 @@ const HNSW_BACKEND: HnswBackend = HnswBackend;
 impl VectorBackend for ExactBackend {
     fn search(
         &self,
-        lane: &mut VectorLane,
+        lane: &VectorLane,
         query: &[f32],
         limit: usize,
-    ) -> Result<Vec<String>, String> {
+    ) -> Result<Vec<(usize, f32)>, String> {
         Ok(lane.search_exact(query, limit))
     }
 
@@ -320,10 +682,10 @@ impl VectorBackend for ExactBackend {
 impl VectorBackend for PreviewBackend {
     fn search(
         &self,
-        lane: &mut VectorLane,
+        lane: &VectorLane,
         query: &[f32],
         limit: usize,
-    ) -> Result<Vec<String>, String> {
+    ) -> Result<Vec<(usize, f32)>, String> {
         if lane.preview_vectors.is_some() {
             return Ok(lane.search_with_quantized_preview(query, limit));
         }
@@ -343,10 +705,10 @@ impl VectorBackend for PreviewBackend {
 impl VectorBackend for HnswBackend {
     fn search(
         &self,
-        lane: &mut VectorLane,
+        lane: &VectorLane,
         query: &[f32],
         limit: usize,
-    ) -> Result<Vec<String>, String> {
+    ) -> Result<Vec<(usize, f32)>, String> {
         if lane.ensure_hnsw_sidecar()? {
             return Ok(lane.search_with_hnsw(query, limit));
         }
@@ -355,14 +717,14 @@ impl VectorBackend for HnswBackend {
     }
 
     fn profile(&self, lane: &VectorLane, query: &[f32], limit: usize) -> SearchPhaseProfile {
-        if lane.hnsw_index.is_some() {
+        if lane.hnsw_index.get().is_some() {
             return lane.profile_hnsw_search(query, limit);
         }
 
         lane.profile_exact_search(query, limit)
     }
 
-    fn warmup(&self, lane: &mut VectorLane) -> Result<(), String> {
+    fn warmup(&self, lane: &VectorLane) -> Result<(), String> {
         lane.ensure_hnsw_sidecar()?;
         Ok(())
     }
@@ -385,6 +747,41 @@ impl VectorLane {
         Self::load_runtime_with_report(mount_root, manifest, vector_mode).map(|(lane, _)| lane)
     }
 
+    /// Loads the default-space runtime lane visible at a store manifest generation; `None` is
+    /// the active one.
+    pub fn load_runtime_at_generation(
+        mount_root: &Path,
+        manifest: &DatasetPackManifest,
+        vector_mode: VectorQueryMode,
+        generation: Option<u64>,
+    ) -> Result<Self, String> {
+        let metadata = VectorLaneMetadata::resolve_at_generation(mount_root, manifest, generation)?;
+
+        Self::load_from_parts(mount_root, metadata, None, vector_mode).map(|(lane, _)| lane)
+    }
+
+    /// Loads the store-backed lane of one named vector space for runtime queries.
+    pub fn load_runtime_space(
+        mount_root: &Path,
+        manifest: &DatasetPackManifest,
+        space: &str,
+        vector_mode: VectorQueryMode,
+    ) -> Result<Self, String> {
+        Self::load_runtime_space_at_generation(mount_root, manifest, space, vector_mode, None)
+    }
+
+    pub fn load_runtime_space_at_generation(
+        mount_root: &Path,
+        manifest: &DatasetPackManifest,
+        space: &str,
+        vector_mode: VectorQueryMode,
+        generation: Option<u64>,
+    ) -> Result<Self, String> {
+        let metadata = VectorLaneMetadata::resolve_space(mount_root, manifest, space, generation)?;
+
+        Self::load_from_parts(mount_root, metadata, None, vector_mode).map(|(lane, _)| lane)
+    }
+
     pub fn load_with_report(
         mount_root: &Path,
         manifest: &DatasetPackManifest,
@@ -428,6 +825,9 @@ impl VectorLane {
         let doc_id_offsets = vector_lane_doc_id_offsets(doc_ids.as_slice(), &skeleton_header)?;
         let doc_vectors = loaded_vectors.doc_vectors;
         let preview_vectors = loaded_vectors.preview_vectors;
+        let sub_vector_offsets = loaded_vectors.sub_vector_offsets;
+        let encoding = loaded_vectors.encoding;
+        let metric = loaded_vectors.metric;
         let (first_vector_query, first_hybrid_query) = if let Some(query_inputs) = query_inputs {
             let query_vector_records =
                 load_query_vector_records_from_paths(&query_inputs.query_vector_paths)?;
@@ -475,8 +875,12 @@ impl VectorLane {
                 doc_id_offsets,
                 doc_vectors,
                 hnsw_available,
-                hnsw_index,
+                hnsw_index: hnsw_index.map(OnceLock::from).unwrap_or_default(),
                 preview_vectors,
+                sub_vector_offsets,
+                encoding,
+                metric,
+                exact_scan_threads: 1,
                 dimensions,
             },
             hnsw_sidecar_load_ms,
@@ -484,11 +888,47 @@ impl VectorLane {
     }
 
     pub fn is_hnsw_sidecar_materialized(&self) -> bool {
-        self.hnsw_index.is_some()
+        self.hnsw_index.get().is_some()
+    }
+
+    pub fn encoding(&self) -> VectorEncoding {
+        self.encoding
+    }
+
+    pub fn metric(&self) -> VectorMetric {
+        self.metric
+    }
+
+    pub fn exact_scan_threads(&self) -> usize {
+        self.exact_scan_threads
+    }
+
+    /// Caps the threads exact and max-sim scans split rows across. Results do not depend on the
+    /// budget; small lanes stay single-threaded.
+    pub fn set_exact_scan_threads(&mut self, threads: usize) {
+        self.exact_scan_threads = threads.max(1);
+    }
+
+    pub fn with_exact_scan_threads(mut self, threads: usize) -> Self {
+        self.set_exact_scan_threads(threads);
+        self
+    }
+
+    pub fn is_multi_vector(&self) -> bool {
+        self.sub_vector_offsets.is_some()
+    }
+
+    /// Embedding identity recorded in the lane's store segment; `None` for compatibility lanes
+    /// and segments written before identities were recorded.
+    pub fn embedding_identity(&self) -> Option<&EmbeddingIdentity> {
+        self.metadata
+            .vector_segment
+            .as_ref()
+            .and_then(|segment| segment.space.embedding.as_ref())
     }
 
     pub fn search_first_vector_query(
-        &mut self,
+        &self,
         mode: VectorQueryMode,
         auto_force_exact: bool,
     ) -> Result<Vec<String>, String> {
@@ -526,31 +966,227 @@ impl VectorLane {
     }
 
     pub fn search_with_query(
-        &mut self,
+        &self,
         query: &[f32],
         limit: usize,
         mode: VectorQueryMode,
         auto_force_exact: bool,
     ) -> Result<Vec<String>, String> {
+        self.search_scored_with_query(query, limit, mode, auto_force_exact)
+            .map(without_scores)
+    }
+
+    /// [`Self::search_with_query`] with each hit's score, ordered by score and then doc_id.
+    pub fn search_scored_with_query(
+        &self,
+        query: &[f32],
+        limit: usize,
+        mode: VectorQueryMode,
+        auto_force_exact: bool,
+    ) -> Result<Vec<(String, f32)>, String> {
         if limit == 0 || self.dimensions == 0 {
             return Ok(Vec::new());
         }
         validate_query_dimensions(query, self.dimensions)?;
+        if self.is_multi_vector() {
+            return Err("multi-vector lanes only support max-sim search".to_owned());
+        }
 
         let selected_mode = self.resolve_runtime_query_mode(limit, mode, auto_force_exact);
-        self.backend_for_mode(selected_mode)
-            .search(self, query, limit)
+        let hits = self
+            .backend_for_mode(selected_mode)
+            .search(self, query, limit)?;
+        Ok(self.scored_doc_ids(hits))
+    }
+
+    /// Late-interaction search: each document scores the sum, over query vectors, of the best
+    /// exact score among its sub-vectors. Single-vector lanes treat every row as one document.
+    pub fn search_max_sim(
+        &self,
+        query_vectors: &[Vec<f32>],
+        limit: usize,
+    ) -> Result<Vec<String>, String> {
+        self.search_max_sim_scored(query_vectors, limit)
+            .map(without_scores)
+    }
+
+    /// [`Self::search_max_sim`] with each hit's summed score.
+    pub fn search_max_sim_scored(
+        &self,
+        query_vectors: &[Vec<f32>],
+        limit: usize,
+    ) -> Result<Vec<(String, f32)>, String> {
+        if limit == 0 || self.dimensions == 0 || query_vectors.is_empty() {
+            return Ok(Vec::new());
+        }
+        for query in query_vectors {
+            validate_query_dimensions(query, self.dimensions)?;
+        }
+
+        let hits = self.parallel_top_hits(limit, |view, index| {
+            let rows = view.sub_vector_rows(index);
+            query_vectors
+                .iter()
+                .map(|query| {
+                    rows.clone()
+                        .map(|row| view.exact_score(query, view.vector_bytes(row)))
+                        .fold(f32::NEG_INFINITY, f32::max)
+                })
+                .sum::<f32>()
+        });
+        Ok(self.scored_doc_ids(hits))
+    }
+
+    /// Stored vectors of `doc_ids`, decoded to f32, in request order; `None` for documents without
+    /// a row and for every document of a multi-vector lane.
+    pub fn document_vectors(&self, doc_ids: &[String]) -> Vec<Option<Vec<f32>>> {
+        let mut vectors = vec![None; doc_ids.len()];
+        if self.is_multi_vector() {
+            return vectors;
+        }
+        let positions = doc_ids
+            .iter()
+            .enumerate()
+            .map(|(position, doc_id)| (doc_id.as_bytes(), position))
+            .collect::<HashMap<_, _>>();
+        for index in 0..self.skeleton_header.doc_count as usize {
+            if let Some(&position) = positions.get(self.doc_id_bytes(index)) {
+                vectors[position] =
+                    Some(decode_encoded_row(self.vector_bytes(index), self.encoding));
+            }
+        }
+        vectors
+    }
+
+    /// Merge cursor over the lane's rows in doc_id order, for callers that look up vectors along
+    /// an ascending doc_id list: the rows are walked once however many lookups follow.
+    pub fn sorted_document_vectors(&self) -> SortedDocumentVectors<'_> {
+        let mut rows = if self.is_multi_vector() {
+            Vec::new()
+        } else {
+            (0..self.skeleton_header.doc_count as usize).collect::<Vec<_>>()
+        };
+        if !rows.is_sorted_by_key(|&row| self.doc_id_bytes(row)) {
+            rows.sort_unstable_by_key(|&row| self.doc_id_bytes(row));
+        }
+        SortedDocumentVectors {
+            lane: self,
+            rows,
+            next: 0,
+        }
+    }
+
+    /// Every document whose score falls inside `radius`, best first and cut at `limit` when set.
+    /// HNSW lanes widen the candidate set until it reaches past the radius, so their result is
+    /// approximate like top-k HNSW search; every other mode scans exactly.
+    pub fn search_within(
+        &self,
+        query: &[f32],
+        radius: VectorRadius,
+        limit: Option<usize>,
+        mode: VectorQueryMode,
+    ) -> Result<Vec<String>, String> {
+        self.search_within_scored(query, radius, limit, mode)
+            .map(without_scores)
+    }
+
+    /// [`Self::search_within`] with each hit's score.
+    pub fn search_within_scored(
+        &self,
+        query: &[f32],
+        radius: VectorRadius,
+        limit: Option<usize>,
+        mode: VectorQueryMode,
+    ) -> Result<Vec<(String, f32)>, String> {
+        if limit == Some(0) || self.dimensions == 0 {
+            return Ok(Vec::new());
+        }
+        validate_query_dimensions(query, self.dimensions)?;
+        if self.is_multi_vector() {
+            return Err("multi-vector lanes only support max-sim search".to_owned());
+        }
+        let min_score = radius.min_score(self.metric)?;
+
+        let doc_count = self.skeleton_header.doc_count as usize;
+        let selected_mode =
+            self.resolve_runtime_query_mode(limit.unwrap_or(doc_count).max(1), mode, false);
+        let hits =
+            if matches!(selected_mode, VectorQueryMode::Hnsw) && self.ensure_hnsw_sidecar()? {
+                self.hnsw_hits_within(query, min_score, limit)
+            } else {
+                self.exact_hits_within(query, min_score, limit)
+            };
+        Ok(self.scored_doc_ids(hits))
+    }
+
+    fn scored_doc_ids(&self, hits: Vec<(usize, f32)>) -> Vec<(String, f32)> {
+        hits.into_iter()
+            .map(|(index, score)| (self.doc_id(index).to_owned(), score))
+            .collect()
+    }
+
+    /// Exact top-k for a block of queries in one pass over the rows: each block of rows is scored
+    /// against every query while it is hot in cache. Per-query hits match `ExactFlat` search.
+    pub fn search_batch(
+        &self,
+        queries: &[Vec<f32>],
+        limit: usize,
+    ) -> Result<Vec<Vec<String>>, String> {
+        if limit == 0 || self.dimensions == 0 || queries.is_empty() {
+            return Ok(vec![Vec::new(); queries.len()]);
+        }
+        for query in queries {
+            validate_query_dimensions(query, self.dimensions)?;
+        }
+        if self.is_multi_vector() {
+            return Err("multi-vector lanes only support max-sim search".to_owned());
+        }
+
+        let chunk_heaps = self.scan_chunks(|view, chunk| {
+            let mut heaps = queries
+                .iter()
+                .map(|_| BinaryHeap::with_capacity(limit))
+                .collect::<Vec<_>>();
+            for block_start in chunk.clone().step_by(BATCH_SCAN_ROW_BLOCK) {
+                let block = block_start..(block_start + BATCH_SCAN_ROW_BLOCK).min(chunk.end);
+                for (query, hits) in queries.iter().zip(&mut heaps) {
+                    for index in block.clone() {
+                        let score = view.exact_score(query, view.vector_bytes(index));
+                        view.collect_top_hit(hits, limit, index, score);
+                    }
+                }
+            }
+            heaps
+        });
+        let mut query_heaps = queries
+            .iter()
+            .map(|_| Vec::with_capacity(chunk_heaps.len()))
+            .collect::<Vec<_>>();
+        for heaps in chunk_heaps {
+            for (chunks, heap) in query_heaps.iter_mut().zip(heaps) {
+                chunks.push(heap);
+            }
+        }
+        Ok(query_heaps
+            .into_iter()
+            .map(|heaps| {
+                merge_top_hits(heaps, limit)
+                    .into_iter()
+                    .map(|(index, _)| self.doc_id(index).to_owned())
+                    .collect()
+            })
+            .collect())
     }
 
     pub fn prime_followup_mode_for_first_vector_query(
-        &mut self,
+        &self,
         mode: VectorQueryMode,
     ) -> Result<(), String> {
         self.prime_followup_mode(self.first_vector_top_k.max(1), mode)
     }
 
     pub fn prime_followup_mode_for_first_hybrid_query(
-        &mut self,
+        &self,
         mode: VectorQueryMode,
     ) -> Result<(), String> {
         self.prime_followup_mode(self.first_hybrid_top_k.max(1), mode)
@@ -619,7 +1255,7 @@ impl VectorLane {
 
     fn profile_exact_search(&self, query: &[f32], limit: usize) -> SearchPhaseProfile {
         let exact_start = Instant::now();
-        let hits = self.search_exact(query, limit);
+        let hits = without_scores(self.scored_doc_ids(self.search_exact(query, limit)));
         let exact_scan_ms = elapsed_ms(exact_start.elapsed());
         SearchPhaseProfile {
             selected_mode: VectorQueryMode::ExactFlat,
@@ -653,7 +1289,7 @@ impl VectorLane {
         let rerank_start = Instant::now();
         let mut reranked = Vec::with_capacity(candidates.len());
         for (index, _) in &candidates {
-            let exact_score = dot_product_f32le(query, self.vector_bytes(*index));
+            let exact_score = self.exact_score(query, self.vector_bytes(*index));
             reranked.push((*index, exact_score));
         }
 
@@ -683,7 +1319,7 @@ impl VectorLane {
         let approximate_start = Instant::now();
         let neighbours = self
             .hnsw_index
-            .as_ref()
+            .get()
             .expect("checked by caller")
             .with_dependent(|_, hnsw_index| hnsw_index.search(query, candidate_limit, ef_search));
         let approximate_search_ms = elapsed_ms(approximate_start.elapsed());
@@ -716,8 +1352,8 @@ impl VectorLane {
         }
     }
 
-    fn ensure_hnsw_sidecar(&mut self) -> Result<bool, String> {
-        if self.hnsw_index.is_none() {
+    fn ensure_hnsw_sidecar(&self) -> Result<bool, String> {
+        if self.hnsw_index.get().is_none() {
             if let Some(basename) = self.metadata.hnsw_graph_basename.as_deref() {
                 if !self
                     .mount_root
@@ -730,30 +1366,28 @@ impl VectorLane {
                 {
                     return Ok(false);
                 }
-                self.hnsw_index = Some(load_hnsw_index(&self.mount_root, basename)?);
+                // Readers racing on the first load each build a graph; the first one is kept.
+                let _ = self
+                    .hnsw_index
+                    .set(load_hnsw_index(&self.mount_root, basename)?);
             }
         }
 
-        Ok(self.hnsw_index.is_some())
+        Ok(self.hnsw_index.get().is_some())
     }
 
-    fn prime_followup_mode(&mut self, limit: usize, mode: VectorQueryMode) -> Result<(), String> {
+    fn prime_followup_mode(&self, limit: usize, mode: VectorQueryMode) -> Result<(), String> {
         let selected_mode = self.resolve_query_mode(limit, mode);
         self.backend_for_mode(selected_mode).warmup(self)
     }
 
-    fn search_exact(&self, query: &[f32], limit: usize) -> Vec<String> {
-        self.top_hits_from_scores(
-            limit,
-            (0..self.skeleton_header.doc_count as usize)
-                .map(|index| (index, dot_product_f32le(query, self.vector_bytes(index)))),
-        )
-        .into_iter()
-        .map(|(index, _)| self.doc_id(index).to_owned())
-        .collect()
+    fn search_exact(&self, query: &[f32], limit: usize) -> Vec<(usize, f32)> {
+        self.parallel_top_hits(limit, |view, index| {
+            view.exact_score(query, view.vector_bytes(index))
+        })
     }
 
-    fn search_with_quantized_preview(&self, query: &[f32], limit: usize) -> Vec<String> {
+    fn search_with_quantized_preview(&self, query: &[f32], limit: usize) -> Vec<(usize, f32)> {
         let preview_vectors = self
             .preview_vectors
             .as_ref()
@@ -770,24 +1404,21 @@ impl VectorLane {
 
         let mut reranked = Vec::with_capacity(candidates.len());
         for (index, _) in candidates {
-            let exact_score = dot_product_f32le(query, self.vector_bytes(index));
+            let exact_score = self.exact_score(query, self.vector_bytes(index));
             reranked.push((index, exact_score));
         }
 
         reranked.sort_by(|left, right| self.compare_hits(*left, *right));
+        reranked.truncate(limit);
         reranked
-            .into_iter()
-            .take(limit)
-            .map(|(index, _)| self.doc_id(index).to_owned())
-            .collect()
     }
 
-    fn search_with_hnsw(&self, query: &[f32], limit: usize) -> Vec<String> {
+    fn search_with_hnsw(&self, query: &[f32], limit: usize) -> Vec<(usize, f32)> {
         let candidate_limit = self.hnsw_candidate_limit(limit);
         let ef_search = candidate_limit.max(limit).max(32);
         let neighbours = self
             .hnsw_index
-            .as_ref()
+            .get()
             .expect("checked by caller")
             .with_dependent(|_, hnsw_index| hnsw_index.search(query, candidate_limit, ef_search));
         let mut reranked = Vec::with_capacity(neighbours.len());
@@ -799,33 +1430,59 @@ impl VectorLane {
         }
 
         reranked.sort_by(|left, right| self.compare_hits(*left, *right));
+        reranked.truncate(limit);
         reranked
-            .into_iter()
-            .take(limit)
-            .map(|(index, _)| self.doc_id(index).to_owned())
-            .collect()
     }
 
-    fn collect_top_hit(
+    /// Scores every document on up to `exact_scan_threads` threads, each keeping its own top-k
+    /// heap over a contiguous chunk. Hits order totally by (score, doc_id), so merging the
+    /// per-thread heaps yields exactly the single-threaded result.
+    fn parallel_top_hits(
         &self,
-        hits: &mut BinaryHeap<TopHit>,
         limit: usize,
-        index: usize,
-        score: f32,
-    ) {
-        let candidate = (index, score);
-        if hits.len() < limit {
-            hits.push(self.top_hit(index, score));
-            return;
+        score: impl Fn(&ExactScanView<'_>, usize) -> f32 + Sync,
+    ) -> Vec<(usize, f32)> {
+        if limit == 0 {
+            return Vec::new();
         }
 
-        if hits
-            .peek()
-            .is_some_and(|worst_hit| self.compare_hits(candidate, worst_hit.as_tuple()).is_lt())
-        {
-            hits.pop();
-            hits.push(self.top_hit(index, score));
+        let heaps = self.scan_chunks(|view, chunk| {
+            view.top_hit_heap(limit, chunk.map(|index| (index, score(view, index))))
+        });
+        merge_top_hits(heaps, limit)
+    }
+
+    /// Runs `scan` over contiguous document chunks, one scoped thread per chunk, and returns the
+    /// results in chunk order. Small lanes and a budget of one thread scan inline.
+    fn scan_chunks<T: Send>(
+        &self,
+        scan: impl Fn(&ExactScanView<'_>, Range<usize>) -> T + Sync,
+    ) -> Vec<T> {
+        let doc_count = self.skeleton_header.doc_count as usize;
+        let view = self.scan_view();
+        let threads = self
+            .exact_scan_threads
+            .min(doc_count / EXACT_SCAN_MIN_DOCS_PER_THREAD)
+            .max(1);
+        if threads == 1 {
+            return vec![scan(&view, 0..doc_count)];
         }
+
+        let chunk_len = doc_count.div_ceil(threads);
+        let scan = &scan;
+        std::thread::scope(|scope| {
+            let workers = (0..doc_count)
+                .step_by(chunk_len)
+                .map(|start| {
+                    let chunk = start..(start + chunk_len).min(doc_count);
+                    scope.spawn(move || scan(&view, chunk))
+                })
+                .collect::<Vec<_>>();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("exact scan worker panicked"))
+                .collect()
+        })
     }
 
     fn top_hits_from_scores(
@@ -833,41 +1490,98 @@ impl VectorLane {
         limit: usize,
         scores: impl IntoIterator<Item = (usize, f32)>,
     ) -> Vec<(usize, f32)> {
-        if limit == 0 {
-            return Vec::new();
-        }
-
-        let mut hits = BinaryHeap::with_capacity(limit);
-        for (index, score) in scores {
-            self.collect_top_hit(&mut hits, limit, index, score);
-        }
+        self.scan_view().top_hits_from_scores(limit, scores)
+    }
 
-        let mut hits = hits
+    fn exact_hits_within(
+        &self,
+        query: &[f32],
+        min_score: f32,
+        limit: Option<usize>,
+    ) -> Vec<(usize, f32)> {
+        let mut hits = self
+            .scan_chunks(|view, chunk| {
+                chunk
+                    .map(|index| (index, view.exact_score(query, view.vector_bytes(index))))
+                    .filter(|(_, score)| *score >= min_score)
+                    .collect::<Vec<_>>()
+            })
             .into_iter()
-            .map(|hit| hit.as_tuple())
+            .flatten()
             .collect::<Vec<_>>();
         hits.sort_by(|left, right| self.compare_hits(*left, *right));
+        if let Some(limit) = limit {
+            hits.truncate(limit);
+        }
         hits
     }
 
-    fn top_hit(&self, index: usize, score: f32) -> TopHit {
-        TopHit {
-            index,
-            score,
-            doc_id: self.doc_id_bytes(index).to_vec(),
+    /// Doubles the HNSW candidate count while every candidate still lands inside the radius. A
+    /// radius that still covers every candidate at the full lane size falls back to an exact scan.
+    fn hnsw_hits_within(
+        &self,
+        query: &[f32],
+        min_score: f32,
+        limit: Option<usize>,
+    ) -> Vec<(usize, f32)> {
+        let doc_count = self.skeleton_header.doc_count as usize;
+        let mut candidate_limit = self.hnsw_candidate_limit(limit.unwrap_or(1));
+        loop {
+            let neighbours = self
+                .hnsw_index
+                .get()
+                .expect("checked by caller")
+                .with_dependent(|_, hnsw_index| {
+                    hnsw_index.search(query, candidate_limit, candidate_limit.max(32))
+                });
+            let mut hits = neighbours
+                .iter()
+                .filter_map(|neighbour| self.checked_exact_hit(query, neighbour.d_id))
+                .filter(|(_, score)| *score >= min_score)
+                .collect::<Vec<_>>();
+            let radius_covers_candidates = hits.len() == neighbours.len();
+            let reached_limit = limit.is_some_and(|limit| hits.len() >= limit);
+            if radius_covers_candidates && !reached_limit {
+                if candidate_limit >= doc_count {
+                    return self.exact_hits_within(query, min_score, limit);
+                }
+                candidate_limit = candidate_limit.saturating_mul(2).min(doc_count);
+                continue;
+            }
+
+            hits.sort_by(|left, right| self.compare_hits(*left, *right));
+            if let Some(limit) = limit {
+                hits.truncate(limit);
+            }
+            return hits;
         }
     }
 
     fn compare_hits(&self, left: (usize, f32), right: (usize, f32)) -> std::cmp::Ordering {
-        right
-            .1
-            .total_cmp(&left.1)
-            .then_with(|| self.doc_id_bytes(left.0).cmp(self.doc_id_bytes(right.0)))
+        self.scan_view().compare_hits(left, right)
+    }
+
+    fn scan_view(&self) -> ExactScanView<'_> {
+        let blob_base = self.skeleton_header.doc_id_blob_offset as usize;
+        ExactScanView {
+            doc_vectors: self.doc_vectors.as_slice(),
+            row_length: self.dimensions * self.encoding.bytes_per_value(),
+            encoding: self.encoding,
+            metric: self.metric,
+            doc_id_blob: &self.doc_ids.as_slice()
+                [blob_base..blob_base + self.skeleton_header.doc_id_blob_length as usize],
+            doc_id_offsets: &self.doc_id_offsets,
+            sub_vector_offsets: self.sub_vector_offsets.as_deref(),
+        }
     }
 
     fn checked_exact_hit(&self, query: &[f32], index: usize) -> Option<(usize, f32)> {
         let exact_vector = self.checked_vector_bytes(index)?;
-        Some((index, dot_product_f32le(query, exact_vector)))
+        Some((index, self.exact_score(query, exact_vector)))
+    }
+
+    fn exact_score(&self, query: &[f32], row: &[u8]) -> f32 {
+        self.scan_view().exact_score(query, row)
     }
 
     fn doc_id(&self, index: usize) -> &str {
@@ -875,26 +1589,20 @@ impl VectorLane {
     }
 
     fn doc_id_bytes(&self, index: usize) -> &[u8] {
-        let start = self.doc_id_offsets[index] as usize;
-        let end = self.doc_id_offsets[index + 1] as usize;
-        let blob_base = self.skeleton_header.doc_id_blob_offset as usize;
-        let blob = &self.doc_ids.as_slice()
-            [blob_base..blob_base + self.skeleton_header.doc_id_blob_length as usize];
-        &blob[start..end]
+        self.scan_view().doc_id_bytes(index)
     }
 
     fn vector_bytes(&self, index: usize) -> &[u8] {
-        let row_length = self.dimensions * 4;
-        let start = index * row_length;
-        let end = start + row_length;
-        &self.doc_vectors.as_slice()[start..end]
+        self.scan_view().vector_bytes(index)
     }
 
     fn checked_vector_bytes(&self, index: usize) -> Option<&[u8]> {
         if index >= self.skeleton_header.doc_count as usize {
             return None;
         }
-        let row_length = self.dimensions.checked_mul(4)?;
+        let row_length = self
+            .dimensions
+            .checked_mul(self.encoding.bytes_per_value())?;
         let start = index.checked_mul(row_length)?;
         let end = start.checked_add(row_length)?;
         self.doc_vectors.as_slice().get(start..end)
@@ -911,6 +1619,114 @@ impl VectorLane {
     }
 }
 
+impl<'a> ExactScanView<'a> {
+    fn exact_score(&self, query: &[f32], row: &[u8]) -> f32 {
+        match self.metric {
+            VectorMetric::Cosine | VectorMetric::Dot => {
+                dot_product_encoded(query, row, self.encoding)
+            }
+            VectorMetric::L2 => -squared_l2_encoded(query, row, self.encoding),
+        }
+    }
+
+    fn doc_id_bytes(&self, index: usize) -> &'a [u8] {
+        let start = self.doc_id_offsets[index] as usize;
+        let end = self.doc_id_offsets[index + 1] as usize;
+        &self.doc_id_blob[start..end]
+    }
+
+    fn vector_bytes(&self, index: usize) -> &'a [u8] {
+        let start = index * self.row_length;
+        &self.doc_vectors[start..start + self.row_length]
+    }
+
+    fn sub_vector_rows(&self, index: usize) -> Range<usize> {
+        match self.sub_vector_offsets {
+            Some(offsets) => offsets[index] as usize..offsets[index + 1] as usize,
+            None => index..index + 1,
+        }
+    }
+
+    fn top_hit_heap(
+        &self,
+        limit: usize,
+        scores: impl IntoIterator<Item = (usize, f32)>,
+    ) -> BinaryHeap<TopHit> {
+        let mut hits = BinaryHeap::with_capacity(limit);
+        for (index, score) in scores {
+            self.collect_top_hit(&mut hits, limit, index, score);
+        }
+        hits
+    }
+
+    fn collect_top_hit(
+        &self,
+        hits: &mut BinaryHeap<TopHit>,
+        limit: usize,
+        index: usize,
+        score: f32,
+    ) {
+        if hits.len() < limit {
+            hits.push(self.top_hit(index, score));
+            return;
+        }
+        if hits.peek().is_some_and(|worst_hit| {
+            self.compare_hits((index, score), worst_hit.as_tuple())
+                .is_lt()
+        }) {
+            hits.pop();
+            hits.push(self.top_hit(index, score));
+        }
+    }
+
+    fn top_hits_from_scores(
+        &self,
+        limit: usize,
+        scores: impl IntoIterator<Item = (usize, f32)>,
+    ) -> Vec<(usize, f32)> {
+        if limit == 0 {
+            return Vec::new();
+        }
+
+        let mut hits = self
+            .top_hit_heap(limit, scores)
+            .into_iter()
+            .map(|hit| hit.as_tuple())
+            .collect::<Vec<_>>();
+        hits.sort_by(|left, right| self.compare_hits(*left, *right));
+        hits
+    }
+
+    fn top_hit(&self, index: usize, score: f32) -> TopHit {
+        TopHit {
+            index,
+            score,
+            doc_id: self.doc_id_bytes(index).to_vec(),
+        }
+    }
+
+    fn compare_hits(&self, left: (usize, f32), right: (usize, f32)) -> Ordering {
+        right
+            .1
+            .total_cmp(&left.1)
+            .then_with(|| self.doc_id_bytes(left.0).cmp(self.doc_id_bytes(right.0)))
+    }
+}
+
+fn without_scores(hits: Vec<(String, f32)>) -> Vec<String> {
+    hits.into_iter().map(|(doc_id, _)| doc_id).collect()
+}
+
+fn merge_top_hits(
+    heaps: impl IntoIterator<Item = BinaryHeap<TopHit>>,
+    limit: usize,
+) -> Vec<(usize, f32)> {
+    let mut hits = heaps.into_iter().flatten().collect::<Vec<_>>();
+    hits.sort();
+    hits.truncate(limit);
+    hits.into_iter().map(|hit| hit.as_tuple()).collect()
+}
+
 pub fn resolve_auto_vector_mode(
     doc_count: usize,
     limit: usize,
@@ -936,17 +1752,17 @@ fn auto_exact_fallback_doc_count(_limit: usize) -> usize {
 
 fn load_vector_lane_skeleton(metadata: &VectorLaneMetadata) -> Result<ByteStorage, String> {
     if let Some(path) = metadata.vector_lane_skeleton_path.as_ref() {
-        return map_read_only(&path).map(ByteStorage::Mapped);
+        return map_read_only(path).map(ByteStorage::Mapped);
     }
 
     let doc_ids = if let Some(document_ids_path) = metadata.document_ids_path.as_ref() {
-        load_document_ids(&document_ids_path)?
+        load_document_ids(document_ids_path)?
     } else {
         let documents_path = metadata
             .documents_path
             .as_ref()
             .ok_or_else(|| "documents file missing from manifest".to_owned())?;
-        load_document_ids_from_documents(&documents_path).map_err(docstore_error)?
+        load_document_ids_from_documents(documents_path).map_err(docstore_error)?
     };
     Ok(ByteStorage::Owned(build_vector_lane_skeleton(
         &doc_ids,
@@ -958,29 +1774,34 @@ struct LoadedVectorPayloads {
     doc_ids: ByteStorage,
     doc_vectors: ByteStorage,
     preview_vectors: Option<ByteStorage>,
+    sub_vector_offsets: Option<Vec<u32>>,
+    encoding: VectorEncoding,
+    metric: VectorMetric,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct BinaryVectorSegmentLayout {
-    dimensions: usize,
+    space: VectorSpaceSpec,
     doc_ids: Vec<String>,
+    multi_vector: Option<MultiVectorIndex>,
     exact_vectors_range: Range<usize>,
     preview_vectors_range: Option<Range<usize>>,
 }
 
-fn resolve_store_vector_segment(mount_root: &Path) -> Result<Option<StoreVectorSegment>, String> {
+fn resolve_store_vector_segment(
+    mount_root: &Path,
+    space: &str,
+    generation: Option<u64>,
+) -> Result<Option<StoreVectorSegment>, String> {
     let store_path = mount_root.join("store.wax");
     if !store_path.exists() {
         return Ok(None);
     }
-    let opened = wax_v2_core::open_store(&store_path).map_err(|error| error.to_string())?;
-    let latest_vec = opened
-        .manifest
-        .segments
-        .iter()
-        .filter(|segment| segment.family == SegmentKind::Vec)
-        .max_by_key(|segment| (segment.segment_generation, segment.object_offset))
-        .cloned();
+    let opened = wax_v2_core::open_store_at_generation(&store_path, generation)
+        .map_err(|error| error.to_string())?;
+    let latest_vec = latest_store_vector_spaces(&store_path, &opened.manifest)?
+        .into_iter()
+        .find(|candidate| candidate.spec.name == space);
     let latest_doc_generation = opened
         .manifest
         .segments
@@ -988,34 +1809,210 @@ fn resolve_store_vector_segment(mount_root: &Path) -> Result<Option<StoreVectorS
         .filter(|segment| segment.family == SegmentKind::Doc)
         .max_by_key(|segment| (segment.segment_generation, segment.object_offset))
         .map(|segment| segment.segment_generation);
-    if let (Some(doc_generation), Some(descriptor)) = (latest_doc_generation, latest_vec.as_ref()) {
-        if descriptor.segment_generation < doc_generation {
-            return Err(
+    if let (Some(doc_generation), Some(latest)) = (latest_doc_generation, latest_vec.as_ref()) {
+        if latest.descriptor.segment_generation < doc_generation {
+            return Err(if space == DEFAULT_VECTOR_SPACE {
                 "latest vector segment is stale relative to the current document generation; republish vectors before runtime vector search"
-                    .to_owned(),
-            );
+                    .to_owned()
+            } else {
+                format!(
+                    "vector space {space} is stale relative to the current document generation; republish its vectors before runtime vector search"
+                )
+            });
         }
     }
-    let Some(descriptor) = latest_vec else {
+    let Some(latest) = latest_vec else {
         return Ok(None);
     };
-    let has_preview = descriptor.backend_aux != 0;
+    let has_preview = latest.descriptor.backend_aux != 0;
     Ok(Some(StoreVectorSegment {
         store_path,
-        descriptor,
+        descriptor: latest.descriptor,
+        space: latest.spec,
         has_preview,
     }))
 }
 
+/// Decoded rows of one vector space, widened back to f32.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreVectorRows {
+    pub spec: VectorSpaceSpec,
+    pub rows: Vec<(String, Vec<f32>)>,
+}
+
+/// Decoded multi-vector rows of one vector space, widened back to f32.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreMultiVectorRows {
+    pub spec: VectorSpaceSpec,
+    pub rows: Vec<(String, Vec<Vec<f32>>)>,
+}
+
+/// Loads the rows of a space's current segment so callers can upsert into it. Returns `None`
+/// when the space has no segment or its segment predates the latest document generation.
+pub fn load_current_store_vector_rows(
+    store_path: &Path,
+    space: &str,
+) -> Result<Option<StoreVectorRows>, String> {
+    let Some((bytes, layout)) = load_current_store_vector_layout(store_path, space)? else {
+        return Ok(None);
+    };
+    if layout.multi_vector.is_some() {
+        return Err(format!(
+            "vector space {space} stores multi-vector rows; publish multi-vector rows instead"
+        ));
+    }
+    let row_length = layout.space.dimensions * layout.space.encoding.bytes_per_value();
+    let rows = layout
+        .doc_ids
+        .into_iter()
+        .zip(bytes[layout.exact_vectors_range].chunks_exact(row_length.max(1)))
+        .map(|(doc_id, row)| (doc_id, decode_encoded_row(row, layout.space.encoding)))
+        .collect();
+    Ok(Some(StoreVectorRows {
+        spec: layout.space,
+        rows,
+    }))
+}
+
+/// Multi-vector counterpart of [`load_current_store_vector_rows`].
+pub fn load_current_store_multi_vector_rows(
+    store_path: &Path,
+    space: &str,
+) -> Result<Option<StoreMultiVectorRows>, String> {
+    let Some((bytes, layout)) = load_current_store_vector_layout(store_path, space)? else {
+        return Ok(None);
+    };
+    let Some(index) = layout.multi_vector.as_ref() else {
+        return Err(format!(
+            "vector space {space} stores single-vector rows; publish single vectors instead"
+        ));
+    };
+    let row_length = layout.space.dimensions * layout.space.encoding.bytes_per_value();
+    let exact_vectors = &bytes[layout.exact_vectors_range.clone()];
+    let rows = layout
+        .doc_ids
+        .iter()
+        .zip(index.sub_vector_offsets.windows(2))
+        .map(|(doc_id, range)| {
+            let sub_vectors = (range[0] as usize..range[1] as usize)
+                .map(|row| {
+                    decode_encoded_row(
+                        &exact_vectors[row * row_length..(row + 1) * row_length],
+                        layout.space.encoding,
+                    )
+                })
+                .collect();
+            (doc_id.clone(), sub_vectors)
+        })
+        .collect();
+    Ok(Some(StoreMultiVectorRows {
+        spec: layout.space,
+        rows,
+    }))
+}
+
+fn load_current_store_vector_layout(
+    store_path: &Path,
+    space: &str,
+) -> Result<Option<(wax_v2_core::SegmentObject, BinaryVectorSegmentLayout)>, String> {
+    if !store_path.exists() {
+        return Ok(None);
+    }
+    let opened = wax_v2_core::open_store(store_path).map_err(|error| error.to_string())?;
+    let Some(current) = latest_store_vector_spaces(store_path, &opened.manifest)?
+        .into_iter()
+        .find(|candidate| candidate.spec.name == space)
+    else {
+        return Ok(None);
+    };
+    let latest_doc_generation = opened
+        .manifest
+        .segments
+        .iter()
+        .filter(|segment| segment.family == SegmentKind::Doc)
+        .map(|segment| segment.segment_generation)
+        .max();
+    if latest_doc_generation
+        .is_some_and(|generation| current.descriptor.segment_generation < generation)
+    {
+        return Ok(None);
+    }
+
+    let bytes = wax_v2_core::map_segment_object(store_path, &current.descriptor)
+        .map_err(|error| error.to_string())?;
+    let layout = BinaryVectorSegmentLayout::decode(&bytes)?;
+    Ok(Some((bytes, layout)))
+}
+
+/// Doc ids of a vector segment payload, in row order.
+pub fn decode_vector_segment_doc_ids(bytes: &[u8]) -> Result<Vec<String>, String> {
+    Ok(BinaryVectorSegmentLayout::decode(bytes)?.doc_ids)
+}
+
+/// Lists the vector spaces visible in the current store manifest, ordered by name.
+pub fn store_vector_spaces(store_path: &Path) -> Result<Vec<StoreVectorSpace>, String> {
+    store_vector_spaces_at_generation(store_path, None)
+}
+
+/// Lists the vector spaces visible at a store manifest generation; `None` is the active one.
+pub fn store_vector_spaces_at_generation(
+    store_path: &Path,
+    generation: Option<u64>,
+) -> Result<Vec<StoreVectorSpace>, String> {
+    if !store_path.exists() {
+        return Ok(Vec::new());
+    }
+    let opened = wax_v2_core::open_store_at_generation(store_path, generation)
+        .map_err(|error| error.to_string())?;
+    latest_store_vector_spaces(store_path, &opened.manifest)
+}
+
+fn latest_store_vector_spaces(
+    store_path: &Path,
+    manifest: &wax_v2_core::ActiveManifest,
+) -> Result<Vec<StoreVectorSpace>, String> {
+    let mut spaces = std::collections::BTreeMap::<String, StoreVectorSpace>::new();
+    for descriptor in manifest
+        .segments
+        .iter()
+        .filter(|segment| segment.family == SegmentKind::Vec)
+    {
+        let bytes = wax_v2_core::map_segment_object(store_path, descriptor)
+            .map_err(|error| error.to_string())?;
+        let header = VectorSegmentHeader::decode(&bytes)?;
+        let spec = header.space;
+        let is_newer = spaces.get(&spec.name).is_none_or(|current| {
+            (descriptor.segment_generation, descriptor.object_offset)
+                > (
+                    current.descriptor.segment_generation,
+                    current.descriptor.object_offset,
+                )
+        });
+        if is_newer {
+            spaces.insert(
+                spec.name.clone(),
+                StoreVectorSpace {
+                    spec,
+                    multi_vector: header.multi_vector,
+                    descriptor: descriptor.clone(),
+                },
+            );
+        }
+    }
+    Ok(spaces.into_values().collect())
+}
+
 fn store_has_manifest_visible_family(
     mount_root: &Path,
     family: SegmentKind,
+    generation: Option<u64>,
 ) -> Result<bool, String> {
     let store_path = mount_root.join("store.wax");
     if !store_path.exists() {
         return Ok(false);
     }
-    let opened = wax_v2_core::open_store(&store_path).map_err(|error| error.to_string())?;
+    let opened = wax_v2_core::open_store_at_generation(&store_path, generation)
+        .map_err(|error| error.to_string())?;
     Ok(opened
         .manifest
         .segments
@@ -1032,7 +2029,7 @@ fn load_vector_segment(
             .map_err(|error| error.to_string())?,
     );
     let layout = BinaryVectorSegmentLayout::decode(&bytes)?;
-    if layout.dimensions != metadata.dimensions {
+    if layout.space.dimensions != metadata.dimensions {
         return Err("vector segment dimensions do not match manifest".to_owned());
     }
     if layout.doc_ids.len() != metadata.doc_count {
@@ -1041,7 +2038,7 @@ fn load_vector_segment(
 
     let doc_ids = ByteStorage::Owned(build_vector_lane_skeleton(
         &layout.doc_ids,
-        layout.dimensions as u32,
+        layout.space.dimensions as u32,
     ));
     let doc_vectors = ByteStorage::SegmentSlice {
         object: bytes.clone(),
@@ -1058,6 +2055,11 @@ fn load_vector_segment(
         doc_ids,
         doc_vectors,
         preview_vectors,
+        sub_vector_offsets: layout
+            .multi_vector
+            .map(|multi_vector| multi_vector.sub_vector_offsets),
+        encoding: layout.space.encoding,
+        metric: layout.space.metric,
     })
 }
 
@@ -1070,6 +2072,7 @@ fn load_compatibility_vector_payloads(
         doc_vectors.as_slice(),
         metadata.dimensions,
         metadata.doc_count,
+        VectorEncoding::F32,
     )?;
     let preview_vectors = metadata
         .preview_vectors_path
@@ -1085,6 +2088,9 @@ fn load_compatibility_vector_payloads(
         doc_ids,
         doc_vectors,
         preview_vectors,
+        sub_vector_offsets: None,
+        encoding: VectorEncoding::F32,
+        metric: VectorMetric::Cosine,
     })
 }
 
@@ -1117,8 +2123,9 @@ pub fn prepare_compatibility_vector_segment(
     manifest: &DatasetPackManifest,
 ) -> Result<PendingSegmentWrite, String> {
     let raw_vectors = load_compatibility_raw_vectors(mount_root, manifest)?;
-    prepare_raw_vector_segment(
+    prepare_raw_vector_segment_with_encoding(
         manifest.vector_profile.embedding_dimensions as usize,
+        VectorEncoding::from_embedding_dtype(&manifest.vector_profile.embedding_dtype),
         &raw_vectors,
     )
 }
@@ -1127,7 +2134,34 @@ pub fn prepare_raw_vector_segment(
     expected_dimensions: usize,
     vector_inputs: &[(String, Vec<f32>)],
 ) -> Result<PendingSegmentWrite, String> {
-    let segment = BinaryVectorSegment::from_raw_vectors(expected_dimensions, vector_inputs)?;
+    prepare_raw_vector_segment_with_encoding(
+        expected_dimensions,
+        VectorEncoding::F32,
+        vector_inputs,
+    )
+}
+
+pub fn prepare_raw_vector_segment_with_encoding(
+    expected_dimensions: usize,
+    encoding: VectorEncoding,
+    vector_inputs: &[(String, Vec<f32>)],
+) -> Result<PendingSegmentWrite, String> {
+    prepare_raw_vector_segment_for_space(
+        &VectorSpaceSpec::new(
+            DEFAULT_VECTOR_SPACE,
+            expected_dimensions,
+            VectorMetric::Cosine,
+        )
+        .with_encoding(encoding),
+        vector_inputs,
+    )
+}
+
+pub fn prepare_raw_vector_segment_for_space(
+    space: &VectorSpaceSpec,
+    vector_inputs: &[(String, Vec<f32>)],
+) -> Result<PendingSegmentWrite, String> {
+    let segment = BinaryVectorSegment::from_raw_vectors(space, vector_inputs)?;
     let object_bytes = segment.encode()?;
     Ok(PendingSegmentWrite {
         descriptor: PendingSegmentDescriptor {
@@ -1147,6 +2181,38 @@ pub fn prepare_raw_vector_segment(
     })
 }
 
+/// Prepares a multi-vector segment; inputs are `(wax_doc_id, doc_id, sub_vectors)` sorted by
+/// wax doc id.
+pub fn prepare_raw_multi_vector_segment(
+    space: &VectorSpaceSpec,
+    vector_inputs: &[(u64, String, Vec<Vec<f32>>)],
+) -> Result<PendingSegmentWrite, String> {
+    let segment = BinaryVectorSegment::from_raw_multi_vectors(space, vector_inputs)?;
+    let object_bytes = segment.encode()?;
+    let doc_id_start = vector_inputs
+        .first()
+        .map_or(0, |(wax_doc_id, _, _)| *wax_doc_id);
+    let doc_id_end_exclusive = vector_inputs
+        .last()
+        .map_or(0, |(wax_doc_id, _, _)| wax_doc_id + 1);
+    Ok(PendingSegmentWrite {
+        descriptor: PendingSegmentDescriptor {
+            family: SegmentKind::Vec,
+            family_version: 1,
+            flags: 0,
+            doc_id_start,
+            doc_id_end_exclusive,
+            min_timestamp_ms: 0,
+            max_timestamp_ms: 0,
+            live_items: vector_inputs.len() as u64,
+            tombstoned_items: 0,
+            backend_id: 0,
+            backend_aux: 0,
+        },
+        object_bytes,
+    })
+}
+
 fn load_document_ids(path: &Path) -> Result<Vec<String>, String> {
     let file = File::open(path).map_err(|error| error.to_string())?;
     let reader = BufReader::new(file);
@@ -1179,7 +2245,12 @@ pub fn load_compatibility_raw_vectors(
     };
     let exact_vectors =
         fs::read(&metadata.document_vectors_path).map_err(|error| error.to_string())?;
-    validate_document_vectors(&exact_vectors, metadata.dimensions, metadata.doc_count)?;
+    validate_document_vectors(
+        &exact_vectors,
+        metadata.dimensions,
+        metadata.doc_count,
+        VectorEncoding::F32,
+    )?;
     if doc_ids.len() != metadata.doc_count {
         return Err("document_ids row count does not match manifest vector_count".to_owned());
     }
@@ -1199,7 +2270,8 @@ pub fn validate_store_segment_against_dataset_pack(
     mount_root: &Path,
     manifest: &DatasetPackManifest,
 ) -> Result<(), String> {
-    let Some(store_segment) = resolve_store_vector_segment(mount_root)? else {
+    let Some(store_segment) = resolve_store_vector_segment(mount_root, DEFAULT_VECTOR_SPACE, None)?
+    else {
         return Ok(());
     };
     let metadata = VectorLaneMetadata::resolve_compatibility(mount_root, manifest)?;
@@ -1208,8 +2280,17 @@ pub fn validate_store_segment_against_dataset_pack(
     }
 
     let expected_raw_vectors = load_compatibility_raw_vectors(mount_root, manifest)?;
-    let expected_without_preview =
-        BinaryVectorSegment::from_raw_vectors(metadata.dimensions, &expected_raw_vectors)?;
+    let expected_without_preview = BinaryVectorSegment::from_raw_vectors(
+        &VectorSpaceSpec::new(
+            DEFAULT_VECTOR_SPACE,
+            metadata.dimensions,
+            VectorMetric::Cosine,
+        )
+        .with_encoding(VectorEncoding::from_embedding_dtype(
+            &manifest.vector_profile.embedding_dtype,
+        )),
+        &expected_raw_vectors,
+    )?;
     let mut expected_with_preview = expected_without_preview.clone();
     if let Some(preview_path) = metadata
         .preview_vectors_path
@@ -1238,27 +2319,68 @@ pub fn validate_store_segment_against_dataset_pack(
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct BinaryVectorSegment {
-    dimensions: usize,
+    space: VectorSpaceSpec,
     doc_ids: Vec<String>,
+    multi_vector: Option<MultiVectorIndex>,
     exact_vectors: Vec<u8>,
     preview_vectors: Option<Vec<u8>>,
 }
 
+/// Sub-vector index of a multi-vector segment: document `i` owns the exact rows
+/// `sub_vector_offsets[i]..sub_vector_offsets[i + 1]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MultiVectorIndex {
+    wax_doc_ids: Vec<u64>,
+    sub_vector_offsets: Vec<u32>,
+}
+
+impl MultiVectorIndex {
+    fn row_count(&self) -> usize {
+        self.sub_vector_offsets.last().copied().unwrap_or(0) as usize
+    }
+
+    fn validate(&self, doc_count: usize) -> Result<(), String> {
+        if self.wax_doc_ids.len() != doc_count || self.sub_vector_offsets.len() != doc_count + 1 {
+            return Err("multi-vector index length does not match doc_count".to_owned());
+        }
+        if self.wax_doc_ids.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err("multi-vector wax doc ids must be strictly increasing".to_owned());
+        }
+        if self.sub_vector_offsets.first() != Some(&0)
+            || self
+                .sub_vector_offsets
+                .windows(2)
+                .any(|pair| pair[0] >= pair[1])
+        {
+            return Err(
+                "multi-vector sub-vector offsets must start at zero and give every document at least one vector"
+                    .to_owned(),
+            );
+        }
+        Ok(())
+    }
+}
+
 impl BinaryVectorSegment {
     fn from_raw_vectors(
-        expected_dimensions: usize,
+        space: &VectorSpaceSpec,
         vector_inputs: &[(String, Vec<f32>)],
     ) -> Result<Self, String> {
         if vector_inputs.is_empty() {
             return Err("raw vector segment requires at least one vector".to_owned());
         }
-        if expected_dimensions == 0 {
+        if space.dimensions == 0 {
             return Err("raw vector segment requires non-zero dimensions".to_owned());
         }
+        validate_vector_space_name(&space.name)?;
+        let expected_dimensions = space.dimensions;
+        let encoding = space.encoding;
 
         let mut seen_doc_ids = std::collections::BTreeSet::new();
         let mut doc_ids = Vec::with_capacity(vector_inputs.len());
-        let mut exact_vectors = Vec::with_capacity(vector_inputs.len() * expected_dimensions * 4);
+        let mut exact_vectors = Vec::with_capacity(
+            vector_inputs.len() * expected_dimensions * encoding.bytes_per_value(),
+        );
 
         for (doc_id, values) in vector_inputs {
             if values.len() != expected_dimensions {
@@ -1274,38 +2396,184 @@ impl BinaryVectorSegment {
             }
             doc_ids.push(doc_id.clone());
             for value in values {
-                exact_vectors.extend_from_slice(&value.to_le_bytes());
+                encoding.encode_value(*value, &mut exact_vectors);
+            }
+        }
+
+        Ok(Self {
+            space: space.clone(),
+            doc_ids,
+            multi_vector: None,
+            exact_vectors,
+            preview_vectors: None,
+        })
+    }
+
+    fn from_raw_multi_vectors(
+        space: &VectorSpaceSpec,
+        vector_inputs: &[(u64, String, Vec<Vec<f32>>)],
+    ) -> Result<Self, String> {
+        if vector_inputs.is_empty() {
+            return Err("raw multi-vector segment requires at least one document".to_owned());
+        }
+        if space.dimensions == 0 {
+            return Err("raw multi-vector segment requires non-zero dimensions".to_owned());
+        }
+        validate_vector_space_name(&space.name)?;
+        if space.name == DEFAULT_VECTOR_SPACE {
+            return Err("the default vector space cannot hold multi-vector rows".to_owned());
+        }
+
+        let mut doc_ids = Vec::with_capacity(vector_inputs.len());
+        let mut wax_doc_ids = Vec::with_capacity(vector_inputs.len());
+        let mut sub_vector_offsets = Vec::with_capacity(vector_inputs.len() + 1);
+        sub_vector_offsets.push(0u32);
+        let mut exact_vectors = Vec::new();
+        for (wax_doc_id, doc_id, sub_vectors) in vector_inputs {
+            if sub_vectors.is_empty() {
+                return Err(format!("raw multi-vector for {doc_id} has no vectors"));
+            }
+            for values in sub_vectors {
+                if values.len() != space.dimensions {
+                    return Err(format!(
+                        "raw multi-vector for {doc_id} has {} values but expected {}",
+                        values.len(),
+                        space.dimensions
+                    ));
+                }
+                for value in values {
+                    space.encoding.encode_value(*value, &mut exact_vectors);
+                }
             }
+            let next_offset =
+                sub_vector_offsets
+                    .last()
+                    .copied()
+                    .unwrap_or(0)
+                    .checked_add(u32::try_from(sub_vectors.len()).map_err(|_| {
+                        format!("raw multi-vector for {doc_id} has too many vectors")
+                    })?)
+                    .ok_or_else(|| "raw multi-vector segment has too many vectors".to_owned())?;
+            sub_vector_offsets.push(next_offset);
+            wax_doc_ids.push(*wax_doc_id);
+            doc_ids.push(doc_id.clone());
         }
+        let multi_vector = MultiVectorIndex {
+            wax_doc_ids,
+            sub_vector_offsets,
+        };
+        multi_vector.validate(doc_ids.len()).map_err(|error| {
+            format!("raw multi-vector inputs must be sorted by unique wax doc id: {error}")
+        })?;
 
         Ok(Self {
-            dimensions: expected_dimensions,
+            space: space.clone(),
             doc_ids,
+            multi_vector: Some(multi_vector),
             exact_vectors,
             preview_vectors: None,
         })
     }
 
+    fn row_count(&self) -> usize {
+        self.multi_vector
+            .as_ref()
+            .map_or(self.doc_ids.len(), MultiVectorIndex::row_count)
+    }
+
     fn encode(&self) -> Result<Vec<u8>, String> {
-        if self.doc_ids.is_empty() && self.dimensions != 0 {
+        let dimensions = self.space.dimensions;
+        if self.doc_ids.is_empty() && dimensions != 0 {
             return Err("vector segment cannot encode dimensions without rows".to_owned());
         }
-        validate_document_vectors(&self.exact_vectors, self.dimensions, self.doc_ids.len())?;
+        if let Some(multi_vector) = self.multi_vector.as_ref() {
+            multi_vector.validate(self.doc_ids.len())?;
+            if self.preview_vectors.is_some() {
+                return Err("multi-vector segments do not support preview vectors".to_owned());
+            }
+        }
+        validate_document_vectors(
+            &self.exact_vectors,
+            dimensions,
+            self.row_count(),
+            self.space.encoding,
+        )?;
         if let Some(preview_vectors) = self.preview_vectors.as_ref() {
-            validate_preview_vectors(preview_vectors, self.dimensions, self.doc_ids.len())?;
+            validate_preview_vectors(preview_vectors, dimensions, self.doc_ids.len())?;
         }
 
-        let flags = if self.preview_vectors.is_some() {
+        let mut bytes = self.encode_prefix(self.exact_vectors.len())?;
+        bytes.extend_from_slice(&self.exact_vectors);
+        if let Some(preview_vectors) = self.preview_vectors.as_ref() {
+            bytes.extend_from_slice(preview_vectors);
+        }
+        Ok(bytes)
+    }
+
+    /// Encodes the header, space section and doc id section; the exact vectors follow directly.
+    fn encode_prefix(&self, exact_vectors_len: usize) -> Result<Vec<u8>, String> {
+        let dimensions = self.space.dimensions;
+        let preview_flag = if self.preview_vectors.is_some() {
             VECTOR_SEGMENT_FLAG_HAS_PREVIEW
         } else {
             0
         };
+        let multi_vector_flag = if self.multi_vector.is_some() {
+            VECTOR_SEGMENT_FLAG_MULTI_VECTOR
+        } else {
+            0
+        };
+        let embedding_flag = if self.space.embedding.is_some() {
+            VECTOR_SEGMENT_FLAG_EMBEDDING_IDENTITY
+        } else {
+            0
+        };
+        let flags = preview_flag
+            | multi_vector_flag
+            | embedding_flag
+            | (self.space.encoding.as_code() << VECTOR_SEGMENT_ENCODING_SHIFT)
+            | (self.space.metric.as_code() << VECTOR_SEGMENT_METRIC_SHIFT);
+        // The default cosine space without an embedding identity keeps the minor 1 layout so
+        // existing segments stay byte-identical; every other space records its name (and its
+        // embedding identity, if any) ahead of the doc ids.
+        let (minor, space_section) = if self.multi_vector.is_none()
+            && self.space.embedding.is_none()
+            && self.space.name == DEFAULT_VECTOR_SPACE
+            && self.space.metric == VectorMetric::Cosine
+        {
+            (VECTOR_SEGMENT_ENCODING_MINOR, Vec::new())
+        } else {
+            let mut section = Vec::with_capacity(4 + self.space.name.len());
+            push_length_prefixed(&mut section, &self.space.name);
+            let minor = if let Some(embedding) = self.space.embedding.as_ref() {
+                embedding.validate()?;
+                push_length_prefixed(&mut section, &embedding.spec_id);
+                push_length_prefixed(&mut section, &embedding.model_version);
+                push_length_prefixed(&mut section, &embedding.model_hash);
+                VECTOR_SEGMENT_EMBEDDING_MINOR
+            } else if self.multi_vector.is_some() {
+                VECTOR_SEGMENT_MULTI_VECTOR_MINOR
+            } else {
+                VECTOR_SEGMENT_SPACE_MINOR
+            };
+            (minor, section)
+        };
         let mut doc_ids_section = Vec::new();
         for doc_id in &self.doc_ids {
             doc_ids_section.extend_from_slice(&(doc_id.len() as u32).to_le_bytes());
             doc_ids_section.extend_from_slice(doc_id.as_bytes());
         }
-        let doc_ids_offset = VECTOR_SEGMENT_HEADER_LENGTH;
+        let doc_ids_offset = VECTOR_SEGMENT_HEADER_LENGTH + space_section.len();
+        if let Some(multi_vector) = self.multi_vector.as_ref() {
+            let index_offset = align_up_usize(doc_ids_offset + doc_ids_section.len(), 8)?;
+            doc_ids_section.resize(index_offset - doc_ids_offset, 0);
+            for wax_doc_id in &multi_vector.wax_doc_ids {
+                doc_ids_section.extend_from_slice(&wax_doc_id.to_le_bytes());
+            }
+            for offset in &multi_vector.sub_vector_offsets {
+                doc_ids_section.extend_from_slice(&offset.to_le_bytes());
+            }
+        }
         let exact_vectors_offset = align_up_usize(
             doc_ids_offset
                 .checked_add(doc_ids_section.len())
@@ -1316,7 +2584,7 @@ impl BinaryVectorSegment {
         let preview_vectors_offset = if self.preview_vectors.is_some() {
             Some(
                 (exact_vectors_offset as u64)
-                    .checked_add(self.exact_vectors.len() as u64)
+                    .checked_add(exact_vectors_len as u64)
                     .ok_or_else(|| "vector segment preview offset overflow".to_owned())?,
             )
         } else {
@@ -1326,18 +2594,15 @@ impl BinaryVectorSegment {
         let mut bytes = Vec::new();
         bytes.extend_from_slice(VECTOR_SEGMENT_MAGIC);
         bytes.extend_from_slice(&VECTOR_SEGMENT_MAJOR.to_le_bytes());
-        bytes.extend_from_slice(&VECTOR_SEGMENT_MINOR.to_le_bytes());
-        bytes.extend_from_slice(&(self.dimensions as u32).to_le_bytes());
+        bytes.extend_from_slice(&minor.to_le_bytes());
+        bytes.extend_from_slice(&(dimensions as u32).to_le_bytes());
         bytes.extend_from_slice(&flags.to_le_bytes());
         bytes.extend_from_slice(&(self.doc_ids.len() as u64).to_le_bytes());
         bytes.extend_from_slice(&(doc_ids_offset as u64).to_le_bytes());
         bytes.extend_from_slice(&(exact_vectors_offset as u64).to_le_bytes());
         bytes.extend_from_slice(&preview_vectors_offset.unwrap_or(0).to_le_bytes());
+        bytes.extend_from_slice(&space_section);
         bytes.extend_from_slice(&doc_ids_section);
-        bytes.extend_from_slice(&self.exact_vectors);
-        if let Some(preview_vectors) = self.preview_vectors.as_ref() {
-            bytes.extend_from_slice(preview_vectors);
-        }
         Ok(bytes)
     }
 
@@ -1350,15 +2615,27 @@ impl BinaryVectorSegment {
             .map(|range| bytes[range].to_vec());
 
         Ok(Self {
-            dimensions: layout.dimensions,
+            space: layout.space,
             doc_ids: layout.doc_ids,
+            multi_vector: layout.multi_vector,
             exact_vectors,
             preview_vectors,
         })
     }
 }
 
-impl BinaryVectorSegmentLayout {
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct VectorSegmentHeader {
+    space: VectorSpaceSpec,
+    multi_vector: bool,
+    flags: u32,
+    doc_count: usize,
+    doc_ids_offset: usize,
+    exact_vectors_offset: usize,
+    preview_vectors_offset: usize,
+}
+
+impl VectorSegmentHeader {
     fn decode(bytes: &[u8]) -> Result<Self, String> {
         if bytes.len() < VECTOR_SEGMENT_HEADER_LENGTH {
             return Err(format!(
@@ -1368,19 +2645,80 @@ impl BinaryVectorSegmentLayout {
         if &bytes[..4] != VECTOR_SEGMENT_MAGIC {
             return Err("vector segment magic mismatch".to_owned());
         }
-        if read_u16(bytes, 4) != VECTOR_SEGMENT_MAJOR || read_u16(bytes, 6) != VECTOR_SEGMENT_MINOR
-        {
+        let minor = read_u16(bytes, 6);
+        if read_u16(bytes, 4) != VECTOR_SEGMENT_MAJOR || minor > VECTOR_SEGMENT_MINOR {
             return Err("unsupported vector segment version".to_owned());
         }
 
         let dimensions = usize::try_from(read_u32(bytes, 8))
             .map_err(|_| "vector segment dimensions exceed addressable memory".to_owned())?;
         let flags = read_u32(bytes, 12);
+        let encoding = VectorEncoding::from_code(
+            (flags & VECTOR_SEGMENT_ENCODING_MASK) >> VECTOR_SEGMENT_ENCODING_SHIFT,
+        )?;
+        if minor < VECTOR_SEGMENT_ENCODING_MINOR && encoding != VectorEncoding::F32 {
+            return Err("vector segment minor version 0 only supports f32 payloads".to_owned());
+        }
+        let metric = VectorMetric::from_code(
+            (flags & VECTOR_SEGMENT_METRIC_MASK) >> VECTOR_SEGMENT_METRIC_SHIFT,
+        )?;
+        if minor < VECTOR_SEGMENT_SPACE_MINOR && metric != VectorMetric::Cosine {
+            return Err(
+                "vector segment metric requires the vector space section of minor version 2"
+                    .to_owned(),
+            );
+        }
+        let multi_vector = flags & VECTOR_SEGMENT_FLAG_MULTI_VECTOR != 0;
+        if multi_vector
+            && (minor < VECTOR_SEGMENT_MULTI_VECTOR_MINOR
+                || flags & VECTOR_SEGMENT_FLAG_HAS_PREVIEW != 0)
+        {
+            return Err(
+                "multi-vector segments require minor version 3 and cannot carry preview vectors"
+                    .to_owned(),
+            );
+        }
+        let has_embedding = flags & VECTOR_SEGMENT_FLAG_EMBEDDING_IDENTITY != 0;
+        if has_embedding && minor < VECTOR_SEGMENT_EMBEDDING_MINOR {
+            return Err("vector segment embedding identity requires minor version 4".to_owned());
+        }
         let doc_count = read_u64_as_usize(bytes, 16, "doc_count")?;
         let doc_ids_offset = read_u64_as_usize(bytes, 24, "doc_ids offset")?;
         let exact_vectors_offset = read_u64_as_usize(bytes, 32, "exact vectors offset")?;
         let preview_vectors_offset = read_u64_as_usize(bytes, 40, "preview vectors offset")?;
-        if doc_ids_offset != VECTOR_SEGMENT_HEADER_LENGTH
+
+        let (name, embedding, space_section_end) = if minor >= VECTOR_SEGMENT_SPACE_MINOR {
+            let mut cursor = VECTOR_SEGMENT_HEADER_LENGTH;
+            let name = read_length_prefixed_at(bytes, &mut cursor, "vector space name")?;
+            validate_vector_space_name(&name)?;
+            let embedding = if has_embedding {
+                let embedding = EmbeddingIdentity {
+                    spec_id: read_length_prefixed_at(bytes, &mut cursor, "embedding spec_id")?,
+                    model_version: read_length_prefixed_at(
+                        bytes,
+                        &mut cursor,
+                        "embedding model_version",
+                    )?,
+                    model_hash: read_length_prefixed_at(
+                        bytes,
+                        &mut cursor,
+                        "embedding model_hash",
+                    )?,
+                };
+                embedding.validate()?;
+                Some(embedding)
+            } else {
+                None
+            };
+            (name, embedding, cursor)
+        } else {
+            (
+                DEFAULT_VECTOR_SPACE.to_owned(),
+                None,
+                VECTOR_SEGMENT_HEADER_LENGTH,
+            )
+        };
+        if doc_ids_offset != space_section_end
             || doc_ids_offset > exact_vectors_offset
             || exact_vectors_offset > bytes.len()
             || exact_vectors_offset % 4 != 0
@@ -1399,6 +2737,37 @@ impl BinaryVectorSegmentLayout {
             );
         }
 
+        Ok(Self {
+            space: VectorSpaceSpec {
+                name,
+                dimensions,
+                metric,
+                encoding,
+                embedding,
+            },
+            multi_vector,
+            flags,
+            doc_count,
+            doc_ids_offset,
+            exact_vectors_offset,
+            preview_vectors_offset,
+        })
+    }
+}
+
+impl BinaryVectorSegmentLayout {
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let header = VectorSegmentHeader::decode(bytes)?;
+        let VectorSegmentHeader {
+            space,
+            multi_vector,
+            flags,
+            doc_count,
+            doc_ids_offset,
+            exact_vectors_offset,
+            preview_vectors_offset,
+        } = header;
+
         let doc_ids_section = &bytes[doc_ids_offset..exact_vectors_offset];
         let exact_vectors_end = if flags & VECTOR_SEGMENT_FLAG_HAS_PREVIEW != 0 {
             preview_vectors_offset
@@ -1414,23 +2783,83 @@ impl BinaryVectorSegmentLayout {
 
         let mut cursor = 0usize;
         let doc_ids = read_length_prefixed_strings(doc_ids_section, doc_count, &mut cursor)?;
+        let multi_vector = if multi_vector {
+            let index =
+                read_multi_vector_index(doc_ids_section, doc_ids_offset, doc_count, &mut cursor)?;
+            index.validate(doc_count)?;
+            Some(index)
+        } else {
+            None
+        };
         if doc_ids_section[cursor..].iter().any(|byte| *byte != 0) {
             return Err("vector segment doc_id section length mismatch".to_owned());
         }
-        validate_document_vectors(&bytes[exact_vectors_range.clone()], dimensions, doc_count)?;
+        validate_document_vectors(
+            &bytes[exact_vectors_range.clone()],
+            space.dimensions,
+            multi_vector
+                .as_ref()
+                .map_or(doc_count, MultiVectorIndex::row_count),
+            space.encoding,
+        )?;
         if let Some(preview_vectors_range) = preview_vectors_range.as_ref() {
-            validate_preview_vectors(&bytes[preview_vectors_range.clone()], dimensions, doc_count)?;
+            validate_preview_vectors(
+                &bytes[preview_vectors_range.clone()],
+                space.dimensions,
+                doc_count,
+            )?;
         }
 
         Ok(Self {
-            dimensions,
+            space,
             doc_ids,
+            multi_vector,
             exact_vectors_range,
             preview_vectors_range,
         })
     }
 }
 
+/// Reads the sub-vector index that follows the doc id strings. `cursor` is relative to the doc id
+/// section, which starts at `section_offset` in the segment; the index is 8-byte aligned there.
+fn read_multi_vector_index(
+    section: &[u8],
+    section_offset: usize,
+    doc_count: usize,
+    cursor: &mut usize,
+) -> Result<MultiVectorIndex, String> {
+    let index_start = align_up_usize(section_offset + *cursor, 8)? - section_offset;
+    let offsets_len = doc_count
+        .checked_add(1)
+        .and_then(|count| count.checked_mul(4))
+        .ok_or_else(|| "multi-vector index length overflow".to_owned())?;
+    let index_end = doc_count
+        .checked_mul(8)
+        .and_then(|length| length.checked_add(offsets_len))
+        .and_then(|length| length.checked_add(index_start))
+        .ok_or_else(|| "multi-vector index length overflow".to_owned())?;
+    if index_end > section.len() {
+        return Err("vector segment truncated while reading multi-vector index".to_owned());
+    }
+    if section[*cursor..index_start].iter().any(|byte| *byte != 0) {
+        return Err("multi-vector index padding must be zero".to_owned());
+    }
+    let wax_doc_ids_end = index_start + doc_count * 8;
+    let wax_doc_ids = section[index_start..wax_doc_ids_end]
+        .chunks_exact(8)
+        .map(|chunk| u64::from_le_bytes(chunk.try_into().expect("u64 chunk")))
+        .collect();
+    let sub_vector_offsets = section[wax_doc_ids_end..index_end]
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().expect("u32 chunk")))
+        .collect();
+    *cursor = index_end;
+    Ok(MultiVectorIndex {
+        wax_doc_ids,
+        sub_vector_offsets,
+    })
+}
+
 fn read_u16(bytes: &[u8], offset: usize) -> u16 {
     u16::from_le_bytes(bytes[offset..offset + 2].try_into().expect("u16 slice"))
 }
@@ -1453,6 +2882,20 @@ fn read_u32_at(bytes: &[u8], cursor: &mut usize, context: &str) -> Result<u32, S
     Ok(value)
 }
 
+fn read_length_prefixed_at(
+    bytes: &[u8],
+    cursor: &mut usize,
+    context: &str,
+) -> Result<String, String> {
+    let length = read_u32_at(bytes, cursor, context)? as usize;
+    read_string_at(bytes, cursor, length, context)
+}
+
+fn push_length_prefixed(bytes: &mut Vec<u8>, value: &str) {
+    bytes.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(value.as_bytes());
+}
+
 fn read_string_at(
     bytes: &[u8],
     cursor: &mut usize,
@@ -1515,12 +2958,13 @@ fn validate_document_vectors(
     bytes: &[u8],
     dimensions: usize,
     doc_count: usize,
+    encoding: VectorEncoding,
 ) -> Result<(), String> {
     if dimensions == 0 {
         return Ok(());
     }
     let bytes_per_row = dimensions
-        .checked_mul(4)
+        .checked_mul(encoding.bytes_per_value())
         .ok_or_else(|| "document vector payload shape overflows addressable memory".to_owned())?;
     let expected_values = doc_count
         .checked_mul(dimensions)
@@ -1528,7 +2972,7 @@ fn validate_document_vectors(
     if !bytes.len().is_multiple_of(bytes_per_row) {
         return Err("document vector payload has invalid length".to_owned());
     }
-    if bytes.len() / 4 != expected_values {
+    if bytes.len() / encoding.bytes_per_value() != expected_values {
         return Err("document vector payload row count does not match manifest".to_owned());
     }
     Ok(())
@@ -1587,6 +3031,20 @@ fn decode_f32le_slice(bytes: &[u8]) -> Vec<f32> {
         .collect()
 }
 
+fn decode_encoded_row(bytes: &[u8], encoding: VectorEncoding) -> Vec<f32> {
+    let decode_half = |decode: fn(u16) -> f32| {
+        bytes
+            .chunks_exact(2)
+            .map(|chunk| decode(u16::from_le_bytes([chunk[0], chunk[1]])))
+            .collect()
+    };
+    match encoding {
+        VectorEncoding::F32 => decode_f32le_slice(bytes),
+        VectorEncoding::F16 => decode_half(f16_bits_to_f32),
+        VectorEncoding::Bf16 => decode_half(bf16_bits_to_f32),
+    }
+}
+
 fn first_vector_query_from_records(
     records: &[QueryVectorRecord],
 ) -> Result<FirstVectorQuery, String> {
@@ -1612,6 +3070,14 @@ fn first_hybrid_vector_query_from_records(
         })
 }
 
+fn dot_product_encoded(left: &[f32], right: &[u8], encoding: VectorEncoding) -> f32 {
+    match encoding {
+        VectorEncoding::F32 => dot_product_f32le(left, right),
+        VectorEncoding::F16 => dot_product_half_le(left, right, f16_bits_to_f32),
+        VectorEncoding::Bf16 => dot_product_half_le(left, right, bf16_bits_to_f32),
+    }
+}
+
 fn dot_product_f32le(left: &[f32], right: &[u8]) -> f32 {
     #[cfg(target_endian = "little")]
     {
@@ -1620,63 +3086,163 @@ fn dot_product_f32le(left: &[f32], right: &[u8]) -> f32 {
         }
     }
 
+    // Rows the mmap leaves unaligned decode into a reused scratch row, so they still run on the
+    // SIMD kernels and score bit-identically to aligned rows.
+    thread_local! {
+        static DECODED_ROW: RefCell<Vec<f32>> = const { RefCell::new(Vec::new()) };
+    }
+    DECODED_ROW.with(|decoded| {
+        let mut decoded = decoded.borrow_mut();
+        decoded.clear();
+        decoded.extend(
+            right
+                .chunks_exact(4)
+                .map(|bytes| f32::from_le_bytes(bytes.try_into().expect("validated vector chunk"))),
+        );
+        dot_product_f32_slice(left, &decoded)
+    })
+}
+
+fn dot_product_f32_slice(left: &[f32], right: &[f32]) -> f32 {
+    kernels::dot_f32(left, right)
+}
+
+/// Scores a half-precision row by widening fixed-size blocks into an f32 scratch buffer first, so
+/// the decode and the multiply-add loops both stay branch-free and vectorizable.
+fn dot_product_half_le(left: &[f32], right: &[u8], decode: fn(u16) -> f32) -> f32 {
+    let len = left.len().min(right.len() / 2);
+    let block_len = len - len % HALF_DECODE_LANES;
+    let mut sums = [0.0f32; HALF_DECODE_LANES];
+    let mut decoded = [0.0f32; HALF_DECODE_LANES];
+
+    for (left_block, right_block) in left[..block_len]
+        .chunks_exact(HALF_DECODE_LANES)
+        .zip(right[..block_len * 2].chunks_exact(HALF_DECODE_LANES * 2))
+    {
+        for (lane, bytes) in decoded.iter_mut().zip(right_block.chunks_exact(2)) {
+            *lane = decode(u16::from_le_bytes([bytes[0], bytes[1]]));
+        }
+        for ((sum, lhs), rhs) in sums.iter_mut().zip(left_block).zip(&decoded) {
+            *sum += lhs * rhs;
+        }
+    }
+
+    let mut tail = 0.0f32;
+    for (lhs, bytes) in left[block_len..len]
+        .iter()
+        .zip(right[block_len * 2..len * 2].chunks_exact(2))
+    {
+        tail += lhs * decode(u16::from_le_bytes([bytes[0], bytes[1]]));
+    }
+
+    sums.iter().sum::<f32>() + tail
+}
+
+fn squared_l2_encoded(left: &[f32], right: &[u8], encoding: VectorEncoding) -> f32 {
+    match encoding {
+        VectorEncoding::F32 => squared_l2_f32le(left, right),
+        VectorEncoding::F16 => squared_l2_half_le(left, right, f16_bits_to_f32),
+        VectorEncoding::Bf16 => squared_l2_half_le(left, right, bf16_bits_to_f32),
+    }
+}
+
+fn squared_l2_f32le(left: &[f32], right: &[u8]) -> f32 {
+    #[cfg(target_endian = "little")]
+    {
+        if let Ok(right_f32) = try_cast_slice::<u8, f32>(right) {
+            return squared_l2_f32_slice(left, right_f32);
+        }
+    }
+
     left.iter()
         .zip(right.chunks_exact(4))
         .map(|(lhs, rhs)| {
-            let rhs = f32::from_le_bytes(rhs.try_into().expect("validated vector chunk"));
-            lhs * rhs
+            let delta = lhs - f32::from_le_bytes(rhs.try_into().expect("validated vector chunk"));
+            delta * delta
         })
         .sum()
 }
 
-fn dot_product_f32_slice(left: &[f32], right: &[f32]) -> f32 {
-    let len = left.len().min(right.len());
-    let mut sum0 = 0.0f32;
-    let mut sum1 = 0.0f32;
-    let mut sum2 = 0.0f32;
-    let mut sum3 = 0.0f32;
-    let mut index = 0usize;
+fn squared_l2_f32_slice(left: &[f32], right: &[f32]) -> f32 {
+    kernels::squared_l2_f32(left, right)
+}
 
-    while index + 4 <= len {
-        sum0 += left[index] * right[index];
-        sum1 += left[index + 1] * right[index + 1];
-        sum2 += left[index + 2] * right[index + 2];
-        sum3 += left[index + 3] * right[index + 3];
-        index += 4;
-    }
+fn squared_l2_half_le(left: &[f32], right: &[u8], decode: fn(u16) -> f32) -> f32 {
+    left.iter()
+        .zip(right.chunks_exact(2))
+        .map(|(lhs, bytes)| {
+            let delta = lhs - decode(u16::from_le_bytes([bytes[0], bytes[1]]));
+            delta * delta
+        })
+        .sum()
+}
 
-    let mut tail = 0.0f32;
-    while index < len {
-        tail += left[index] * right[index];
-        index += 1;
-    }
+/// Widens IEEE 754 binary16 bits without branching on the exponent: the magnitude is rebiased
+/// with a single multiply (which also normalizes subnormals) and inf/NaN are patched by a select.
+fn f16_bits_to_f32(bits: u16) -> f32 {
+    let sign = u32::from(bits & 0x8000) << 16;
+    let magnitude = u32::from(bits & 0x7fff) << 13;
+    let widened = if magnitude >= 0x0f80_0000 {
+        magnitude | 0x7f80_0000
+    } else {
+        (f32::from_bits(magnitude) * F16_EXPONENT_REBIAS).to_bits()
+    };
+    f32::from_bits(widened | sign)
+}
 
-    sum0 + sum1 + sum2 + sum3 + tail
+fn bf16_bits_to_f32(bits: u16) -> f32 {
+    f32::from_bits(u32::from(bits) << 16)
 }
 
-fn dot_product_i8_preview(left: &[f32], right: &[u8]) -> f32 {
-    let len = left.len().min(right.len());
-    let mut sum0 = 0.0f32;
-    let mut sum1 = 0.0f32;
-    let mut sum2 = 0.0f32;
-    let mut sum3 = 0.0f32;
-    let mut index = 0usize;
+/// Narrows to IEEE 754 binary16 with round-to-nearest-even, saturating to infinity on overflow.
+fn f32_to_f16_bits(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+    if exponent == 0xff {
+        let quiet_nan = if mantissa != 0 { 0x0200 } else { 0 };
+        return sign | 0x7c00 | quiet_nan;
+    }
 
-    while index + 4 <= len {
-        sum0 += left[index] * (right[index] as i8 as f32);
-        sum1 += left[index + 1] * (right[index + 1] as i8 as f32);
-        sum2 += left[index + 2] * (right[index + 2] as i8 as f32);
-        sum3 += left[index + 3] * (right[index + 3] as i8 as f32);
-        index += 4;
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1f {
+        return sign | 0x7c00;
     }
+    if half_exponent <= 0 {
+        if half_exponent < -10 {
+            return sign;
+        }
+        let mantissa = mantissa | 0x0080_0000;
+        let shift = (14 - half_exponent) as u32;
+        let round_bit = 1u32 << (shift - 1);
+        let mut half_mantissa = mantissa >> shift;
+        if mantissa & round_bit != 0 && mantissa & (3 * round_bit - 1) != 0 {
+            half_mantissa += 1;
+        }
+        return sign | half_mantissa as u16;
+    }
+
+    let round_bit = 0x0000_1000u32;
+    let mut half_bits = ((half_exponent as u32) << 10) | (mantissa >> 13);
+    if mantissa & round_bit != 0 && mantissa & (3 * round_bit - 1) != 0 {
+        half_bits += 1;
+    }
+    sign | half_bits as u16
+}
 
-    let mut tail = 0.0f32;
-    while index < len {
-        tail += left[index] * (right[index] as i8 as f32);
-        index += 1;
+/// Narrows to bfloat16 with round-to-nearest-even, keeping NaNs quiet.
+fn f32_to_bf16_bits(value: f32) -> u16 {
+    let bits = value.to_bits();
+    if value.is_nan() {
+        return ((bits >> 16) as u16) | 0x0040;
     }
+    let rounding_bias = 0x7fff + ((bits >> 16) & 1);
+    (bits.wrapping_add(rounding_bias) >> 16) as u16
+}
 
-    sum0 + sum1 + sum2 + sum3 + tail
+fn dot_product_i8_preview(left: &[f32], right: &[u8]) -> f32 {
+    kernels::dot_i8(left, right)
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -1721,12 +3287,18 @@ mod tests {
     use wax_v2_docstore::prepare_raw_documents_segment;
 
     use crate::{
-        align_up_usize, dot_product_f32le, load_compatibility_raw_vectors, load_vector_segment,
-        prepare_raw_vector_segment, publish_compatibility_vector_segment,
-        read_length_prefixed_strings, read_u64, resolve_auto_vector_mode,
-        validate_document_vectors, validate_preview_vectors,
-        validate_store_segment_against_dataset_pack, BinaryVectorSegment, ByteStorage,
-        StoreVectorSegment, VectorLane, VectorLaneMetadata, VectorQueryInputs,
+        align_up_usize, bf16_bits_to_f32, dot_product_encoded, dot_product_f32le, f16_bits_to_f32,
+        f32_to_bf16_bits, f32_to_f16_bits, load_compatibility_raw_vectors,
+        load_current_store_multi_vector_rows, load_current_store_vector_rows, load_vector_segment,
+        prepare_raw_multi_vector_segment, prepare_raw_vector_segment,
+        prepare_raw_vector_segment_for_space, prepare_raw_vector_segment_with_encoding,
+        publish_compatibility_vector_segment, read_length_prefixed_strings, read_u32, read_u64,
+        resolve_auto_vector_mode, store_vector_spaces, validate_document_vectors,
+        validate_preview_vectors, validate_store_segment_against_dataset_pack, BinaryVectorSegment,
+        ByteStorage, EmbeddingIdentity, MultiVectorIndex, StoreVectorSegment, VectorEncoding,
+        VectorLane, VectorLaneMetadata, VectorMetric, VectorQueryInputs, VectorRadius,
+        VectorSpaceSpec, DEFAULT_VECTOR_SPACE, EXACT_SCAN_MIN_DOCS_PER_THREAD,
+        VECTOR_SEGMENT_FLAG_EMBEDDING_IDENTITY,
     };
 
     #[test]
@@ -1737,10 +3309,250 @@ mod tests {
         assert_eq!(score, 20.0);
     }
 
+    #[test]
+    fn dot_product_f32le_scores_unaligned_rows_like_aligned_ones() {
+        let left = (0..37)
+            .map(|index| (index as f32 * 0.37).sin())
+            .collect::<Vec<_>>();
+        let right = (0..37)
+            .map(|index| (index as f32 * 1.13).cos() * 3.0)
+            .collect::<Vec<_>>();
+        let right_bytes: &[u8] = bytemuck::cast_slice(&right);
+        let mut buffer = vec![0u8; right_bytes.len() + 4];
+        let offset = (1..4)
+            .find(|offset| !(buffer.as_ptr() as usize + offset).is_multiple_of(4))
+            .expect("unaligned offset");
+        buffer[offset..offset + right_bytes.len()].copy_from_slice(right_bytes);
+        let unaligned = &buffer[offset..offset + right_bytes.len()];
+        assert!(bytemuck::try_cast_slice::<u8, f32>(unaligned).is_err());
+
+        assert_eq!(
+            dot_product_f32le(&left, unaligned).to_bits(),
+            dot_product_f32le(&left, right_bytes).to_bits()
+        );
+    }
+
+    #[test]
+    fn half_precision_conversions_round_trip_representable_values_and_specials() {
+        for value in [
+            0.0f32,
+            -0.0,
+            1.0,
+            -2.5,
+            0.333_251_95,
+            65_504.0,
+            6.103_515_6e-5,
+        ] {
+            assert_eq!(
+                f16_bits_to_f32(f32_to_f16_bits(value)).to_bits(),
+                value.to_bits()
+            );
+        }
+        for value in [0.0f32, -0.0, 1.0, -2.5, 0.332_031_25, 3.389_531_4e38] {
+            assert_eq!(
+                bf16_bits_to_f32(f32_to_bf16_bits(value)).to_bits(),
+                value.to_bits()
+            );
+        }
+
+        assert_eq!(f32_to_f16_bits(f32::INFINITY), 0x7c00);
+        assert_eq!(f32_to_f16_bits(f32::NEG_INFINITY), 0xfc00);
+        assert_eq!(f32_to_f16_bits(1.0e6), 0x7c00);
+        assert!(f16_bits_to_f32(f32_to_f16_bits(f32::NAN)).is_nan());
+        assert_eq!(f16_bits_to_f32(0x7c00), f32::INFINITY);
+        assert!(bf16_bits_to_f32(f32_to_bf16_bits(f32::NAN)).is_nan());
+        assert_eq!(
+            bf16_bits_to_f32(f32_to_bf16_bits(f32::INFINITY)),
+            f32::INFINITY
+        );
+    }
+
+    #[test]
+    fn f32_to_f16_bits_rounds_to_nearest_even_including_subnormals() {
+        let smallest_subnormal = 5.960_464_5e-8f32;
+
+        assert_eq!(f32_to_f16_bits(smallest_subnormal), 0x0001);
+        assert_eq!(f16_bits_to_f32(0x0001), smallest_subnormal);
+        assert_eq!(f16_bits_to_f32(0x03ff), 6.097_555e-5);
+        assert_eq!(f32_to_f16_bits(smallest_subnormal / 2.0), 0x0000);
+        assert_eq!(f32_to_f16_bits(smallest_subnormal * 1.5), 0x0002);
+        assert_eq!(f32_to_f16_bits(1.0 + 2.0f32.powi(-11)), 0x3c00);
+        assert_eq!(f32_to_f16_bits(1.0 + 3.0 * 2.0f32.powi(-11)), 0x3c02);
+        assert_eq!(f32_to_bf16_bits(1.0 + 2.0f32.powi(-8)), 0x3f80);
+        assert_eq!(f32_to_bf16_bits(1.0 + 3.0 * 2.0f32.powi(-8)), 0x3f82);
+    }
+
+    #[test]
+    fn half_dot_product_kernels_match_widened_scalar_reference_with_tail() {
+        let query = (0..19)
+            .map(|index| (index as f32 * 0.37).sin())
+            .collect::<Vec<_>>();
+        let row = (0..19)
+            .map(|index| (index as f32 * 0.11).cos())
+            .collect::<Vec<_>>();
+
+        for (encoding, decode) in [
+            (VectorEncoding::F16, f16_bits_to_f32 as fn(u16) -> f32),
+            (VectorEncoding::Bf16, bf16_bits_to_f32 as fn(u16) -> f32),
+        ] {
+            let mut bytes = Vec::new();
+            for value in &row {
+                encoding.encode_value(*value, &mut bytes);
+            }
+            let reference = query
+                .iter()
+                .zip(bytes.chunks_exact(2))
+                .map(|(lhs, rhs)| {
+                    f64::from(*lhs) * f64::from(decode(u16::from_le_bytes([rhs[0], rhs[1]])))
+                })
+                .sum::<f64>();
+            let full_precision = query
+                .iter()
+                .zip(&row)
+                .map(|(lhs, rhs)| f64::from(*lhs) * f64::from(*rhs))
+                .sum::<f64>();
+
+            let score = f64::from(dot_product_encoded(&query, &bytes, encoding));
+
+            assert!((score - reference).abs() < 1.0e-5, "{encoding:?}");
+            assert!((score - full_precision).abs() < 5.0e-2, "{encoding:?}");
+        }
+    }
+
+    #[test]
+    fn half_precision_segments_halve_payload_and_preserve_exact_search_order() {
+        let raw_vectors = (0..12)
+            .map(|index| {
+                let angle = index as f32 * 0.25;
+                (format!("doc-{index:02}"), vec![angle.cos(), angle.sin()])
+            })
+            .collect::<Vec<_>>();
+        let query = [0.8f32, 0.6f32];
+        let f32_segment =
+            BinaryVectorSegment::from_raw_vectors(&default_space(2), &raw_vectors).unwrap();
+
+        for encoding in [VectorEncoding::F16, VectorEncoding::Bf16] {
+            let temp_dir = tempdir().unwrap();
+            let store_path = temp_dir.path().join("store.wax");
+            create_empty_store(&store_path).unwrap();
+            let pending =
+                prepare_raw_vector_segment_with_encoding(2, encoding, &raw_vectors).unwrap();
+            let decoded = BinaryVectorSegment::decode(&pending.object_bytes).unwrap();
+            assert_eq!(decoded.space.encoding, encoding);
+            assert_eq!(
+                decoded.exact_vectors.len() * 2,
+                f32_segment.exact_vectors.len()
+            );
+            publish_segments(&store_path, vec![pending]).unwrap();
+
+            let lane = VectorLane::load_runtime(
+                temp_dir.path(),
+                &test_manifest_with_count(raw_vectors.len(), false, false),
+                VectorQueryMode::Auto,
+            )
+            .unwrap();
+            assert_eq!(lane.encoding(), encoding);
+
+            let mut expected = raw_vectors
+                .iter()
+                .map(|(doc_id, values)| {
+                    (doc_id.clone(), query[0] * values[0] + query[1] * values[1])
+                })
+                .collect::<Vec<_>>();
+            expected.sort_by(|left, right| right.1.total_cmp(&left.1).then(left.0.cmp(&right.0)));
+            let expected = expected
+                .into_iter()
+                .take(4)
+                .map(|(doc_id, _)| doc_id)
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                lane.search_with_query(&query, 4, VectorQueryMode::ExactFlat, false)
+                    .unwrap(),
+                expected,
+                "{encoding:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parallel_exact_scan_matches_single_thread_hits_including_score_ties() {
+        let doc_count = EXACT_SCAN_MIN_DOCS_PER_THREAD * 5 + 17;
+        // Few distinct rows, so most scores tie and ordering falls back to doc ids across chunks.
+        let raw_vectors = (0..doc_count)
+            .map(|index| {
+                let bucket = (index * 7_919 % 13) as f32;
+                (
+                    format!("doc-{:06}", (index * 104_729) % doc_count),
+                    vec![bucket, 1.0 - bucket * 0.5],
+                )
+            })
+            .collect::<Vec<_>>();
+        let temp_dir = tempdir().unwrap();
+        let store_path = temp_dir.path().join("store.wax");
+        create_empty_store(&store_path).unwrap();
+        publish_segments(
+            &store_path,
+            vec![prepare_raw_vector_segment(2, &raw_vectors).unwrap()],
+        )
+        .unwrap();
+        let mut lane = VectorLane::load_runtime(
+            temp_dir.path(),
+            &test_manifest_with_count(doc_count, false, false),
+            VectorQueryMode::Auto,
+        )
+        .unwrap();
+        let query = [0.6f32, 0.3];
+
+        let single = lane
+            .search_with_query(&query, 25, VectorQueryMode::ExactFlat, false)
+            .unwrap();
+        assert_eq!(single.len(), 25);
+        assert!(single.windows(2).all(|pair| pair[0] < pair[1]));
+        for threads in [2, 4, 7, 64] {
+            lane.set_exact_scan_threads(threads);
+            assert_eq!(
+                lane.search_with_query(&query, 25, VectorQueryMode::ExactFlat, false)
+                    .unwrap(),
+                single,
+                "{threads} threads"
+            );
+            assert_eq!(lane.search_max_sim(&[query.to_vec()], 25).unwrap(), single);
+            let batch = [query.to_vec(), vec![-0.2, 1.0], vec![1.0, 0.0]];
+            let expected = batch
+                .iter()
+                .map(|query| {
+                    lane.search_with_query(query, 25, VectorQueryMode::ExactFlat, false)
+                        .unwrap()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(expected[0], single);
+            assert_eq!(lane.search_batch(&batch, 25).unwrap(), expected);
+        }
+        lane.set_exact_scan_threads(0);
+        assert_eq!(lane.exact_scan_threads(), 1);
+    }
+
+    #[test]
+    fn vector_segment_decode_rejects_unknown_payload_encoding() {
+        let mut bytes = BinaryVectorSegment::from_raw_vectors(
+            &default_space(2),
+            &[("doc-1".to_owned(), vec![1.0f32, 0.0f32])],
+        )
+        .unwrap()
+        .encode()
+        .unwrap();
+        bytes[13] = 0x7f;
+
+        let error = BinaryVectorSegment::decode(&bytes).expect_err("unknown encoding");
+
+        assert!(error.contains("encoding"));
+    }
+
     #[test]
     fn raw_vector_segment_encodes_exact_vectors_as_little_endian_bytes() {
         let segment = BinaryVectorSegment::from_raw_vectors(
-            2,
+            &default_space(2),
             &[("doc-1".to_owned(), vec![1.0f32, -2.5f32])],
         )
         .expect("raw vector segment should build");
@@ -1769,8 +3581,8 @@ mod tests {
 
     #[test]
     fn validate_document_vectors_rejects_overflowing_shape_before_multiplication() {
-        let error =
-            validate_document_vectors(&[], usize::MAX / 2 + 1, 3).expect_err("shape overflows");
+        let error = validate_document_vectors(&[], usize::MAX / 2 + 1, 3, VectorEncoding::F32)
+            .expect_err("shape overflows");
 
         assert!(error.contains("overflows"));
     }
@@ -1813,7 +3625,7 @@ mod tests {
         )
         .unwrap();
 
-        let mut lane = VectorLane::load(
+        let lane = VectorLane::load(
             temp_dir.path(),
             &test_manifest(false, false),
             VectorQueryMode::ExactFlat,
@@ -2034,7 +3846,7 @@ mod tests {
         fs::remove_file(temp_dir.path().join("document_vectors.bin")).unwrap();
         fs::remove_file(temp_dir.path().join("preview.bin")).unwrap();
 
-        let mut lane = VectorLane::load(
+        let lane = VectorLane::load(
             temp_dir.path(),
             &test_manifest(true, false),
             VectorQueryMode::PreviewQ8,
@@ -2070,7 +3882,7 @@ mod tests {
         .unwrap();
         publish_segments(&store_path, vec![pending]).unwrap();
 
-        let mut lane = VectorLane::load_runtime(
+        let lane = VectorLane::load_runtime(
             temp_dir.path(),
             &test_manifest(false, false),
             VectorQueryMode::Auto,
@@ -2084,6 +3896,373 @@ mod tests {
         );
     }
 
+    #[test]
+    fn named_vector_space_segments_record_name_and_metric_while_default_keeps_minor_one() {
+        let rows = [("doc-1".to_owned(), vec![1.0f32, 0.0f32])];
+        let default_bytes = BinaryVectorSegment::from_raw_vectors(&default_space(2), &rows)
+            .unwrap()
+            .encode()
+            .unwrap();
+        assert_eq!(u16::from_le_bytes([default_bytes[6], default_bytes[7]]), 1);
+        assert_eq!(read_u64(&default_bytes, 24), 48);
+
+        let space =
+            VectorSpaceSpec::new("images", 2, VectorMetric::L2).with_encoding(VectorEncoding::F16);
+        let named_bytes = BinaryVectorSegment::from_raw_vectors(&space, &rows)
+            .unwrap()
+            .encode()
+            .unwrap();
+        assert_eq!(u16::from_le_bytes([named_bytes[6], named_bytes[7]]), 2);
+        assert_eq!(read_u64(&named_bytes, 24), 48 + 4 + "images".len() as u64);
+
+        let decoded = BinaryVectorSegment::decode(&named_bytes).unwrap();
+        assert_eq!(decoded.space, space);
+        assert_eq!(decoded.doc_ids, vec!["doc-1"]);
+
+        let error = BinaryVectorSegment::from_raw_vectors(
+            &VectorSpaceSpec::new("bad space", 2, VectorMetric::Dot),
+            &rows,
+        )
+        .expect_err("space names with spaces should be rejected");
+        assert!(error.contains("vector space name"));
+    }
+
+    #[test]
+    fn embedding_identity_round_trips_in_a_minor_four_space_section() {
+        let rows = [("doc-1".to_owned(), vec![1.0f32, 0.0f32])];
+        let space = default_space(2).with_embedding(EmbeddingIdentity::new(
+            "minilm-l6-2-f32-cosine",
+            "2024-01",
+            "sha256:model",
+        ));
+        let bytes = BinaryVectorSegment::from_raw_vectors(&space, &rows)
+            .unwrap()
+            .encode()
+            .unwrap();
+        assert_eq!(u16::from_le_bytes([bytes[6], bytes[7]]), 4);
+        assert_ne!(
+            read_u32(&bytes, 12) & VECTOR_SEGMENT_FLAG_EMBEDDING_IDENTITY,
+            0
+        );
+        assert_eq!(BinaryVectorSegment::decode(&bytes).unwrap().space, space);
+
+        let mut downgraded = bytes.clone();
+        downgraded[6..8].copy_from_slice(&3u16.to_le_bytes());
+        assert!(BinaryVectorSegment::decode(&downgraded)
+            .unwrap_err()
+            .contains("requires minor version 4"));
+
+        let error = BinaryVectorSegment::from_raw_vectors(
+            &default_space(2).with_embedding(EmbeddingIdentity::new("", "v1", "sha256:x")),
+            &rows,
+        )
+        .unwrap()
+        .encode()
+        .expect_err("empty identity fields should be rejected");
+        assert!(error.contains("embedding spec_id"));
+    }
+
+    #[test]
+    fn runtime_space_lanes_search_each_named_space_with_its_own_shape_and_metric() {
+        let temp_dir = tempdir().unwrap();
+        let store_path = temp_dir.path().join("store.wax");
+        create_empty_store(&store_path).unwrap();
+
+        let default_pending = prepare_raw_vector_segment(
+            2,
+            &[
+                ("doc-1".to_owned(), vec![1.0f32, 0.0f32]),
+                ("doc-2".to_owned(), vec![0.0f32, 1.0f32]),
+            ],
+        )
+        .unwrap();
+        let l2_pending = prepare_raw_vector_segment_for_space(
+            &VectorSpaceSpec::new("images", 3, VectorMetric::L2),
+            &[
+                ("doc-1".to_owned(), vec![10.0f32, 10.0, 10.0]),
+                ("doc-2".to_owned(), vec![1.0f32, 1.0, 1.0]),
+            ],
+        )
+        .unwrap();
+        publish_segments(&store_path, vec![default_pending, l2_pending]).unwrap();
+
+        let spaces = store_vector_spaces(&store_path).unwrap();
+        assert_eq!(
+            spaces
+                .iter()
+                .map(|space| (space.spec.name.as_str(), space.spec.dimensions))
+                .collect::<Vec<_>>(),
+            vec![(DEFAULT_VECTOR_SPACE, 2), ("images", 3)]
+        );
+
+        let manifest = test_manifest(false, false);
+        let default_lane =
+            VectorLane::load_runtime(temp_dir.path(), &manifest, VectorQueryMode::Auto).unwrap();
+        assert_eq!(
+            default_lane
+                .search_with_query(&[0.0, 1.0], 1, VectorQueryMode::ExactFlat, false)
+                .unwrap(),
+            vec!["doc-2"]
+        );
+
+        let images_lane = VectorLane::load_runtime_space(
+            temp_dir.path(),
+            &manifest,
+            "images",
+            VectorQueryMode::Auto,
+        )
+        .unwrap();
+        assert_eq!(images_lane.metric(), VectorMetric::L2);
+        // A dot product would prefer the large vector; L2 picks the nearest one.
+        assert_eq!(
+            images_lane
+                .search_with_query(&[1.5, 1.5, 1.5], 2, VectorQueryMode::ExactFlat, false)
+                .unwrap(),
+            vec!["doc-2", "doc-1"]
+        );
+
+        let error = match VectorLane::load_runtime_space(
+            temp_dir.path(),
+            &manifest,
+            "missing",
+            VectorQueryMode::Auto,
+        ) {
+            Ok(_) => panic!("unknown vector space should be rejected"),
+            Err(error) => error,
+        };
+        assert!(error.contains("vector space missing"));
+    }
+
+    #[test]
+    fn multi_vector_segments_index_sub_vectors_by_wax_doc_id_and_search_by_max_sim() {
+        let temp_dir = tempdir().unwrap();
+        let store_path = temp_dir.path().join("store.wax");
+        create_empty_store(&store_path).unwrap();
+
+        let space = VectorSpaceSpec::new("tokens", 2, VectorMetric::Dot);
+        let rows = vec![
+            (
+                0u64,
+                "doc-1".to_owned(),
+                vec![vec![1.0f32, 0.0], vec![0.0f32, 0.2]],
+            ),
+            (
+                1u64,
+                "doc-2".to_owned(),
+                vec![vec![0.7f32, 0.0], vec![0.0f32, 0.7], vec![0.1f32, 0.1]],
+            ),
+        ];
+        let encoded = BinaryVectorSegment::from_raw_multi_vectors(&space, &rows)
+            .unwrap()
+            .encode()
+            .unwrap();
+        assert_eq!(u16::from_le_bytes([encoded[6], encoded[7]]), 3);
+        let decoded = BinaryVectorSegment::decode(&encoded).unwrap();
+        assert_eq!(decoded.doc_ids, vec!["doc-1", "doc-2"]);
+        assert_eq!(
+            decoded.multi_vector,
+            Some(MultiVectorIndex {
+                wax_doc_ids: vec![0, 1],
+                sub_vector_offsets: vec![0, 2, 5],
+            })
+        );
+
+        let unsorted = [rows[1].clone(), rows[0].clone()];
+        let error = BinaryVectorSegment::from_raw_multi_vectors(&space, &unsorted)
+            .expect_err("multi-vector rows must be sorted by wax doc id");
+        assert!(error.contains("sorted by unique wax doc id"));
+
+        let pending = prepare_raw_multi_vector_segment(&space, &rows).unwrap();
+        publish_segments(&store_path, vec![pending]).unwrap();
+        let spaces = store_vector_spaces(&store_path).unwrap();
+        assert!(spaces[0].multi_vector);
+        assert_eq!(
+            load_current_store_multi_vector_rows(&store_path, "tokens")
+                .unwrap()
+                .unwrap()
+                .rows[1],
+            (
+                "doc-2".to_owned(),
+                vec![vec![0.7f32, 0.0], vec![0.0f32, 0.7], vec![0.1f32, 0.1]]
+            )
+        );
+        assert!(load_current_store_vector_rows(&store_path, "tokens").is_err());
+
+        let lane = VectorLane::load_runtime_space(
+            temp_dir.path(),
+            &test_manifest(false, false),
+            "tokens",
+            VectorQueryMode::Auto,
+        )
+        .unwrap();
+        assert!(lane.is_multi_vector());
+        // doc-1 wins the first query token outright but doc-2 covers both tokens better.
+        assert_eq!(
+            lane.search_max_sim(&[vec![1.0, 0.0], vec![0.0, 1.0]], 2)
+                .unwrap(),
+            vec!["doc-2", "doc-1"]
+        );
+        assert_eq!(
+            lane.search_max_sim(&[vec![1.0, 0.0]], 2).unwrap(),
+            vec!["doc-1", "doc-2"]
+        );
+        assert!(lane
+            .search_with_query(&[1.0, 0.0], 1, VectorQueryMode::ExactFlat, false)
+            .is_err());
+    }
+
+    #[test]
+    fn search_batch_scores_each_query_like_exact_search_and_validates_inputs() {
+        let raw_vectors = (0..600)
+            .map(|index| {
+                let angle = index as f32 * 0.013;
+                (format!("doc-{index:03}"), vec![angle.cos(), angle.sin()])
+            })
+            .collect::<Vec<_>>();
+        let temp_dir = tempdir().unwrap();
+        let store_path = temp_dir.path().join("store.wax");
+        create_empty_store(&store_path).unwrap();
+        publish_segments(
+            &store_path,
+            vec![prepare_raw_vector_segment(2, &raw_vectors).unwrap()],
+        )
+        .unwrap();
+        let lane = VectorLane::load_runtime(
+            temp_dir.path(),
+            &test_manifest_with_count(raw_vectors.len(), false, false),
+            VectorQueryMode::Auto,
+        )
+        .unwrap();
+
+        let queries = vec![vec![1.0f32, 0.0], vec![0.0, 1.0], vec![-0.6, 0.8]];
+        let expected = queries
+            .iter()
+            .map(|query| {
+                lane.search_with_query(query, 7, VectorQueryMode::ExactFlat, false)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(lane.search_batch(&queries, 7).unwrap(), expected);
+        assert_eq!(expected[0][0], "doc-000");
+        assert_eq!(
+            lane.search_batch(&queries, 0).unwrap(),
+            vec![Vec::<String>::new(); 3]
+        );
+        assert!(lane.search_batch(&[], 7).unwrap().is_empty());
+        let error = lane
+            .search_batch(&[vec![1.0, 0.0], vec![1.0, 0.0, 0.0]], 7)
+            .unwrap_err();
+        assert!(error.contains("dimensions"), "{error}");
+    }
+
+    #[test]
+    fn search_within_returns_every_exact_hit_inside_the_radius() {
+        let raw_vectors = (0..40)
+            .map(|index| {
+                let angle = index as f32 * 0.05;
+                (format!("doc-{index:02}"), vec![angle.cos(), angle.sin()])
+            })
+            .collect::<Vec<_>>();
+        let temp_dir = tempdir().unwrap();
+        let store_path = temp_dir.path().join("store.wax");
+        create_empty_store(&store_path).unwrap();
+        publish_segments(
+            &store_path,
+            vec![prepare_raw_vector_segment(2, &raw_vectors).unwrap()],
+        )
+        .unwrap();
+        let mut lane = VectorLane::load_runtime(
+            temp_dir.path(),
+            &test_manifest_with_count(raw_vectors.len(), false, false),
+            VectorQueryMode::Auto,
+        )
+        .unwrap();
+        let query = [1.0f32, 0.0];
+        let expected = raw_vectors
+            .iter()
+            .filter(|(_, values)| values[0] >= 0.9)
+            .map(|(doc_id, _)| doc_id.clone())
+            .collect::<Vec<_>>();
+        assert!(expected.len() > 5 && expected.len() < raw_vectors.len());
+
+        let within = lane
+            .search_within(
+                &query,
+                VectorRadius::MinSimilarity(0.9),
+                None,
+                VectorQueryMode::ExactFlat,
+            )
+            .unwrap();
+        assert_eq!(within, expected);
+        lane.set_exact_scan_threads(4);
+        assert_eq!(
+            lane.search_within(
+                &query,
+                VectorRadius::MaxDistance(0.1),
+                Some(3),
+                VectorQueryMode::Auto,
+            )
+            .unwrap(),
+            expected[..3]
+        );
+        assert!(lane
+            .search_within(
+                &query,
+                VectorRadius::MinSimilarity(1.5),
+                None,
+                VectorQueryMode::ExactFlat,
+            )
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(
+            VectorRadius::MaxDistance(2.0).min_score(VectorMetric::L2),
+            Ok(-4.0)
+        );
+        assert!(VectorRadius::MinSimilarity(0.5)
+            .min_score(VectorMetric::L2)
+            .is_err());
+        assert!(VectorRadius::MaxDistance(0.5)
+            .min_score(VectorMetric::Dot)
+            .is_err());
+        assert!(VectorRadius::MaxDistance(-1.0)
+            .min_score(VectorMetric::Cosine)
+            .is_err());
+        assert!(VectorRadius::MinSimilarity(f32::NAN)
+            .min_score(VectorMetric::Cosine)
+            .is_err());
+    }
+
+    #[test]
+    fn sorted_document_vectors_merge_ascending_lookups_with_rows_in_any_order() {
+        let raw_vectors = ["doc-c", "doc-a", "doc-d", "doc-b"]
+            .into_iter()
+            .enumerate()
+            .map(|(index, doc_id)| (doc_id.to_owned(), vec![index as f32, 1.0]))
+            .collect::<Vec<_>>();
+        let temp_dir = tempdir().unwrap();
+        let store_path = temp_dir.path().join("store.wax");
+        create_empty_store(&store_path).unwrap();
+        publish_segments(
+            &store_path,
+            vec![prepare_raw_vector_segment(2, &raw_vectors).unwrap()],
+        )
+        .unwrap();
+        let lane = VectorLane::load_runtime(
+            temp_dir.path(),
+            &test_manifest_with_count(raw_vectors.len(), false, false),
+            VectorQueryMode::Auto,
+        )
+        .unwrap();
+
+        let mut vectors = lane.sorted_document_vectors();
+        assert_eq!(vectors.vector("doc-a"), Some(vec![1.0, 1.0]));
+        assert_eq!(vectors.vector("doc-b"), Some(vec![3.0, 1.0]));
+        assert_eq!(vectors.vector("doc-bb"), None);
+        assert_eq!(vectors.vector("doc-d"), Some(vec![2.0, 1.0]));
+        assert_eq!(vectors.vector("doc-e"), None);
+    }
+
     #[test]
     fn search_with_query_rejects_mismatched_query_dimensions() {
         let temp_dir = tempdir().unwrap();
@@ -2100,7 +4279,7 @@ mod tests {
         .unwrap();
         publish_segments(&store_path, vec![pending]).unwrap();
 
-        let mut lane = VectorLane::load_runtime(
+        let lane = VectorLane::load_runtime(
             temp_dir.path(),
             &test_manifest(false, false),
             VectorQueryMode::ExactFlat,
@@ -2142,6 +4321,7 @@ mod tests {
             vector_segment: Some(StoreVectorSegment {
                 store_path: store_path.clone(),
                 descriptor,
+                space: default_space(2),
                 has_preview: false,
             }),
             vector_lane_skeleton_path: None,
@@ -2182,7 +4362,7 @@ mod tests {
         let pending = prepare_raw_vector_segment(2, &raw_vectors).unwrap();
         publish_segments(&store_path, vec![pending]).unwrap();
 
-        let mut lane = VectorLane::load_runtime(
+        let lane = VectorLane::load_runtime(
             temp_dir.path(),
             &test_manifest_with_count(65, false, true),
             VectorQueryMode::Auto,
@@ -2221,7 +4401,7 @@ mod tests {
         let pending = prepare_raw_vector_segment(2, &raw_vectors).unwrap();
         publish_segments(&store_path, vec![pending]).unwrap();
 
-        let mut lane = VectorLane::load_runtime(
+        let lane = VectorLane::load_runtime(
             temp_dir.path(),
             &test_manifest_with_count(65, false, true),
             VectorQueryMode::Auto,
@@ -2468,7 +4648,7 @@ mod tests {
     #[test]
     fn binary_vector_segment_aligns_exact_vector_payloads_to_four_bytes() {
         let bytes = BinaryVectorSegment::from_raw_vectors(
-            3,
+            &default_space(3),
             &[("doc-1".to_owned(), vec![1.0f32, 0.0f32, 0.5f32])],
         )
         .unwrap()
@@ -2481,6 +4661,10 @@ mod tests {
         assert!(BinaryVectorSegment::decode(&bytes).is_ok());
     }
 
+    fn default_space(dimensions: usize) -> VectorSpaceSpec {
+        VectorSpaceSpec::new(DEFAULT_VECTOR_SPACE, dimensions, VectorMetric::Cosine)
+    }
+
     fn test_manifest(with_preview: bool, with_hnsw: bool) -> DatasetPackManifest {
         test_manifest_with_count(3, with_preview, with_hnsw)
     }
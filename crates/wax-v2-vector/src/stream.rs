@@ -0,0 +1,201 @@
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use wax_v2_core::{PendingSegmentDescriptor, SegmentKind, SpilledSegmentWrite};
+
+use crate::{validate_vector_space_name, BinaryVectorSegment, VectorSpaceSpec};
+
+/// Builds a single-vector segment from a row stream with bounded memory.
+///
+/// Encoded rows are spilled to a temp file as they arrive; only doc ids stay in memory. `finish`
+/// copies the rows into the segment object in the order given by a sort key (the wax doc id for
+/// store publishes), so the object matches [`crate::prepare_raw_vector_segment_for_space`] for
+/// the same rows in that order.
+pub struct StreamingVectorSegmentBuilder {
+    space: VectorSpaceSpec,
+    spill_dir: PathBuf,
+    doc_ids: Vec<String>,
+    rows: BufWriter<File>,
+    row_buffer: Vec<u8>,
+}
+
+impl StreamingVectorSegmentBuilder {
+    pub fn new(space: &VectorSpaceSpec, spill_dir: &Path) -> Result<Self, String> {
+        if space.dimensions == 0 {
+            return Err("raw vector segment requires non-zero dimensions".to_owned());
+        }
+        validate_vector_space_name(&space.name)?;
+        let rows = tempfile::tempfile_in(spill_dir).map_err(|error| error.to_string())?;
+        Ok(Self {
+            space: space.clone(),
+            spill_dir: spill_dir.to_path_buf(),
+            doc_ids: Vec::new(),
+            rows: BufWriter::new(rows),
+            row_buffer: Vec::new(),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.doc_ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.doc_ids.is_empty()
+    }
+
+    pub fn push(&mut self, doc_id: &str, values: &[f32]) -> Result<(), String> {
+        if values.len() != self.space.dimensions {
+            return Err(format!(
+                "raw vector for {doc_id} has {} values but expected {}",
+                values.len(),
+                self.space.dimensions
+            ));
+        }
+        self.row_buffer.clear();
+        for value in values {
+            self.space
+                .encoding
+                .encode_value(*value, &mut self.row_buffer);
+        }
+        self.rows
+            .write_all(&self.row_buffer)
+            .map_err(|error| error.to_string())?;
+        self.doc_ids.push(doc_id.to_owned());
+        Ok(())
+    }
+
+    pub fn finish(
+        self,
+        sort_key: impl Fn(&str) -> Option<u64>,
+    ) -> Result<SpilledSegmentWrite, String> {
+        if self.doc_ids.is_empty() {
+            return Err("raw vector segment requires at least one vector".to_owned());
+        }
+        let mut order = self
+            .doc_ids
+            .iter()
+            .enumerate()
+            .map(|(index, doc_id)| {
+                sort_key(doc_id)
+                    .map(|key| (key, index))
+                    .ok_or_else(|| format!("raw vector doc_id {doc_id} has no sort key"))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        order.sort_unstable();
+        // A repeated doc id shares its sort key, so it sorts next to itself.
+        if let Some(pair) = order.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(format!(
+                "raw vector doc_id {} was provided more than once",
+                self.doc_ids[pair[1].1]
+            ));
+        }
+
+        let row_length = self.space.dimensions * self.space.encoding.bytes_per_value();
+        let mut rows = self
+            .rows
+            .into_inner()
+            .map_err(|error| error.error().to_string())?;
+        let header = BinaryVectorSegment {
+            space: self.space,
+            doc_ids: order
+                .iter()
+                .map(|(_, index)| self.doc_ids[*index].clone())
+                .collect(),
+            multi_vector: None,
+            exact_vectors: Vec::new(),
+            preview_vectors: None,
+        }
+        .encode_prefix(row_length * order.len())?;
+
+        let mut object = BufWriter::new(
+            tempfile::tempfile_in(&self.spill_dir).map_err(|error| error.to_string())?,
+        );
+        object
+            .write_all(&header)
+            .map_err(|error| error.to_string())?;
+        let mut row = vec![0u8; row_length];
+        for (position, (_, index)) in order.iter().enumerate() {
+            // Rows already in key order are read sequentially without a seek.
+            if position == 0 || order[position - 1].1 + 1 != *index {
+                rows.seek(SeekFrom::Start((index * row_length) as u64))
+                    .map_err(|error| error.to_string())?;
+            }
+            rows.read_exact(&mut row)
+                .map_err(|error| error.to_string())?;
+            object.write_all(&row).map_err(|error| error.to_string())?;
+        }
+        let object = object
+            .into_inner()
+            .map_err(|error| error.error().to_string())?;
+
+        Ok(SpilledSegmentWrite {
+            descriptor: PendingSegmentDescriptor {
+                family: SegmentKind::Vec,
+                family_version: 1,
+                flags: 0,
+                doc_id_start: 0,
+                doc_id_end_exclusive: order.len() as u64,
+                min_timestamp_ms: 0,
+                max_timestamp_ms: 0,
+                live_items: order.len() as u64,
+                tombstoned_items: 0,
+                backend_id: 0,
+                backend_aux: 0,
+            },
+            object,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Seek, SeekFrom};
+
+    use tempfile::tempdir;
+
+    use crate::{
+        prepare_raw_vector_segment_for_space, StreamingVectorSegmentBuilder, VectorEncoding,
+        VectorMetric, VectorSpaceSpec,
+    };
+
+    #[test]
+    fn streaming_builder_reorders_spilled_rows_into_the_in_memory_vector_segment() {
+        let temp_dir = tempdir().unwrap();
+        let space =
+            VectorSpaceSpec::new("notes", 3, VectorMetric::Dot).with_encoding(VectorEncoding::F16);
+        let rows = (0..40)
+            .map(|index| {
+                (
+                    format!("doc-{index:02}"),
+                    vec![index as f32, 1.0 - index as f32, 0.5],
+                )
+            })
+            .collect::<Vec<_>>();
+        let expected = prepare_raw_vector_segment_for_space(&space, &rows).unwrap();
+
+        let mut builder = StreamingVectorSegmentBuilder::new(&space, temp_dir.path()).unwrap();
+        for (doc_id, values) in rows.iter().rev() {
+            builder.push(doc_id, values).unwrap();
+        }
+        assert!(builder.push("doc-00", &[0.0, 0.0]).is_err());
+        let mut spilled = builder
+            .finish(|doc_id| doc_id.trim_start_matches("doc-").parse().ok())
+            .unwrap();
+        let mut object_bytes = Vec::new();
+        spilled.object.seek(SeekFrom::Start(0)).unwrap();
+        spilled.object.read_to_end(&mut object_bytes).unwrap();
+
+        assert_eq!(spilled.descriptor, expected.descriptor);
+        assert_eq!(object_bytes, expected.object_bytes);
+
+        let mut repeated = StreamingVectorSegmentBuilder::new(&space, temp_dir.path()).unwrap();
+        repeated.push("doc-00", &[0.0, 0.0, 0.0]).unwrap();
+        repeated.push("doc-01", &[1.0, 0.0, 0.0]).unwrap();
+        repeated.push("doc-00", &[0.0, 1.0, 0.0]).unwrap();
+        let error = repeated
+            .finish(|doc_id| doc_id.trim_start_matches("doc-").parse().ok())
+            .unwrap_err();
+        assert!(error.contains("doc-00 was provided more than once"));
+    }
+}
@@ -327,7 +327,7 @@ fn dataset_manifest_validation_rejects_invalid_dataset_tier() {
 #[test]
 fn dataset_manifest_validation_rejects_invalid_embedding_dtype() {
     let (mut manifest, _, manifest_root) = load_manifest();
-    manifest.vector_profile.embedding_dtype = "f16".to_owned();
+    manifest.vector_profile.embedding_dtype = "f64".to_owned();
     assert_eq!(
         validate_manifest(&manifest, &manifest_root)
             .unwrap_err()
@@ -93,6 +93,11 @@ fn mcp_surface_ingests_documents_and_vectors_through_explicit_raw_requests() {
     let vector_ingest = mcp
         .handle(McpRequest::IngestVectors {
             session_id,
+            embedding: wax_v2_mcp::McpEmbeddingIdentity {
+                spec_id: manifest.identity.embedding_spec_id.clone(),
+                model_version: manifest.identity.embedding_model_version.clone(),
+                model_hash: manifest.identity.embedding_model_hash.clone(),
+            },
             vectors: vec![
                 wax_v2_mcp::McpNewDocumentVector {
                     doc_id: "doc-001".to_owned(),
@@ -6,6 +6,10 @@ use tempfile::tempdir;
 use wax_bench_model::embed_text;
 use wax_bench_packer::{pack_dataset, PackRequest};
 use wax_v2_docstore::Docstore;
+use wax_v2_runtime::{
+    RuntimeEmbeddingIdentity, RuntimeSearchMode, RuntimeSearchRequest, RuntimeStore,
+    RuntimeVectorMetric, RuntimeVectorSpaceQuery,
+};
 
 #[test]
 fn product_cli_ingests_documents_and_vectors_through_explicit_raw_commands() {
@@ -60,6 +64,25 @@ fn product_cli_ingests_documents_and_vectors_through_explicit_raw_commands() {
         "--input",
         docs_jsonl.to_str().unwrap(),
     ]);
+    let unnamed_vectors = Command::new("cargo")
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args([
+            "run",
+            "-p",
+            "wax-cli",
+            "--",
+            "ingest",
+            "vectors",
+            "--root",
+            dataset_dir.path().to_str().unwrap(),
+            "--input",
+            vectors_jsonl.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(!unnamed_vectors.status.success());
+    assert!(String::from_utf8_lossy(&unnamed_vectors.stderr)
+        .contains("vector space default requires --embedding-spec-id"));
     run_wax(&[
         "ingest",
         "vectors",
@@ -67,6 +90,12 @@ fn product_cli_ingests_documents_and_vectors_through_explicit_raw_commands() {
         dataset_dir.path().to_str().unwrap(),
         "--input",
         vectors_jsonl.to_str().unwrap(),
+        "--embedding-spec-id",
+        &manifest.identity.embedding_spec_id,
+        "--embedding-model-version",
+        &manifest.identity.embedding_model_version,
+        "--embedding-model-hash",
+        &manifest.identity.embedding_model_hash,
     ]);
 
     for kind in [
@@ -159,6 +188,350 @@ fn product_cli_docs_ingest_preserves_unknown_top_level_document_fields() {
     );
 }
 
+#[test]
+fn product_cli_docs_ingest_writes_block_compressed_doc_segments() {
+    let dataset_dir = tempdir().unwrap();
+    let fixture_root =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/bench/source/minimal");
+    let manifest = pack_dataset(&PackRequest::new(
+        &fixture_root,
+        dataset_dir.path(),
+        "small",
+        "clean",
+    ))
+    .unwrap();
+
+    let docs_jsonl = dataset_dir.path().join("raw-docs-compressed.jsonl");
+    fs::write(
+        &docs_jsonl,
+        concat!(
+            "{\"doc_id\":\"doc-001\",\"text\":\"rust benchmark guide\",\"metadata\":{}}\n",
+            "{\"doc_id\":\"doc-002\",\"text\":\"swift release notes\",\"metadata\":{}}\n",
+        ),
+    )
+    .unwrap();
+
+    run_wax(&["create", "--root", dataset_dir.path().to_str().unwrap()]);
+    run_wax(&[
+        "ingest",
+        "docs",
+        "--root",
+        dataset_dir.path().to_str().unwrap(),
+        "--input",
+        docs_jsonl.to_str().unwrap(),
+        "--compression",
+        "zstd",
+    ]);
+
+    let store_path = dataset_dir.path().join("store.wax");
+    let opened = wax_v2_core::open_store(&store_path).unwrap();
+    let descriptor = opened
+        .manifest
+        .segments
+        .iter()
+        .find(|segment| segment.family == wax_v2_core::SegmentKind::Doc)
+        .unwrap();
+    let object = wax_v2_core::read_segment_object(&store_path, descriptor).unwrap();
+    assert_eq!(u16::from_le_bytes([object[6], object[7]]), 3);
+    assert_eq!(u16::from_le_bytes([object[88], object[89]]), 2);
+
+    let docstore = Docstore::open(dataset_dir.path(), &manifest).unwrap();
+    let documents = docstore
+        .load_documents_by_id(&["doc-002".to_owned()])
+        .unwrap();
+    assert_eq!(
+        documents
+            .get("doc-002")
+            .and_then(|document| document.get("text")),
+        Some(&serde_json::json!("swift release notes"))
+    );
+}
+
+#[test]
+fn product_cli_ingests_vectors_into_a_named_space_alongside_the_default_space() {
+    let dataset_dir = tempdir().unwrap();
+    let fixture_root =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/bench/source/minimal");
+    let manifest = pack_dataset(&PackRequest::new(
+        &fixture_root,
+        dataset_dir.path(),
+        "small",
+        "clean",
+    ))
+    .unwrap();
+
+    let docs_jsonl = dataset_dir.path().join("raw-docs.jsonl");
+    fs::write(
+        &docs_jsonl,
+        concat!(
+            "{\"doc_id\":\"doc-001\",\"text\":\"rust benchmark guide\"}\n",
+            "{\"doc_id\":\"doc-002\",\"text\":\"semantic latency checklist\"}\n",
+        ),
+    )
+    .unwrap();
+    let vectors_jsonl = dataset_dir.path().join("raw-vectors.jsonl");
+    fs::write(
+        &vectors_jsonl,
+        format!(
+            "{}\n{}\n",
+            serde_json::json!({
+                "doc_id": "doc-001",
+                "values": embed_text("rust benchmark guide", 384),
+            }),
+            serde_json::json!({
+                "doc_id": "doc-002",
+                "values": embed_text("semantic latency checklist", 384),
+            }),
+        ),
+    )
+    .unwrap();
+    let image_vectors_jsonl = dataset_dir.path().join("image-vectors.jsonl");
+    fs::write(
+        &image_vectors_jsonl,
+        concat!(
+            "{\"doc_id\":\"doc-001\",\"values\":[4.0,4.0,4.0]}\n",
+            "{\"doc_id\":\"doc-002\",\"values\":[0.0,0.0,1.0]}\n",
+        ),
+    )
+    .unwrap();
+
+    let root = dataset_dir.path().to_str().unwrap();
+    run_wax(&["create", "--root", root]);
+    run_wax(&[
+        "ingest",
+        "docs",
+        "--root",
+        root,
+        "--input",
+        docs_jsonl.to_str().unwrap(),
+    ]);
+    run_wax(&[
+        "ingest",
+        "vectors",
+        "--root",
+        root,
+        "--input",
+        vectors_jsonl.to_str().unwrap(),
+        "--embedding-spec-id",
+        &manifest.identity.embedding_spec_id,
+        "--embedding-model-version",
+        &manifest.identity.embedding_model_version,
+        "--embedding-model-hash",
+        &manifest.identity.embedding_model_hash,
+    ]);
+    run_wax(&[
+        "ingest",
+        "vectors",
+        "--root",
+        root,
+        "--input",
+        image_vectors_jsonl.to_str().unwrap(),
+        "--space",
+        "images",
+        "--metric",
+        "l2",
+        "--embedding-spec-id",
+        "image-encoder-3",
+        "--embedding-model-version",
+        "v1",
+        "--embedding-model-hash",
+        "sha256:images",
+    ]);
+
+    let mut runtime = RuntimeStore::open(dataset_dir.path()).unwrap();
+    let spaces = runtime.vector_spaces().unwrap();
+    assert_eq!(
+        spaces
+            .iter()
+            .map(|space| (space.name.as_str(), space.dimensions, space.metric))
+            .collect::<Vec<_>>(),
+        vec![
+            ("default", 384, RuntimeVectorMetric::Cosine),
+            ("images", 3, RuntimeVectorMetric::L2),
+        ]
+    );
+    let response = runtime
+        .search(RuntimeSearchRequest {
+            mode: RuntimeSearchMode::Hybrid,
+            text_query: Some("latency".to_owned()),
+            vector_query: Some(embed_text("semantic latency checklist", 384)),
+            top_k: 2,
+            vector_embedding: Some(runtime.embedding_identity()),
+            vector_space_queries: vec![RuntimeVectorSpaceQuery::new("images", vec![0.0, 0.0, 0.5])
+                .with_embedding(RuntimeEmbeddingIdentity::new(
+                    "image-encoder-3",
+                    "v1",
+                    "sha256:images",
+                ))],
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(response.hits[0].doc_id, "doc-002");
+}
+
+#[test]
+fn product_cli_ingests_multi_vector_rows_and_searches_them_by_max_sim() {
+    let dataset_dir = tempdir().unwrap();
+    let fixture_root =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/bench/source/minimal");
+    pack_dataset(&PackRequest::new(
+        &fixture_root,
+        dataset_dir.path(),
+        "small",
+        "clean",
+    ))
+    .unwrap();
+
+    let docs_jsonl = dataset_dir.path().join("raw-docs.jsonl");
+    fs::write(
+        &docs_jsonl,
+        concat!(
+            "{\"doc_id\":\"doc-001\",\"text\":\"rust benchmark guide\"}\n",
+            "{\"doc_id\":\"doc-002\",\"text\":\"semantic latency checklist\"}\n",
+        ),
+    )
+    .unwrap();
+    let token_vectors_jsonl = dataset_dir.path().join("token-vectors.jsonl");
+    fs::write(
+        &token_vectors_jsonl,
+        concat!(
+            "{\"doc_id\":\"doc-001\",\"vectors\":[[1.0,0.0],[0.0,0.2]]}\n",
+            "{\"doc_id\":\"doc-002\",\"vectors\":[[0.7,0.0],[0.0,0.7]]}\n",
+        ),
+    )
+    .unwrap();
+
+    let root = dataset_dir.path().to_str().unwrap();
+    run_wax(&["create", "--root", root]);
+    run_wax(&[
+        "ingest",
+        "docs",
+        "--root",
+        root,
+        "--input",
+        docs_jsonl.to_str().unwrap(),
+    ]);
+    run_wax(&[
+        "ingest",
+        "vectors",
+        "--root",
+        root,
+        "--input",
+        token_vectors_jsonl.to_str().unwrap(),
+        "--space",
+        "tokens",
+        "--metric",
+        "dot",
+        "--multi-vector",
+        "--embedding-spec-id",
+        "token-encoder-2",
+        "--embedding-model-version",
+        "v1",
+        "--embedding-model-hash",
+        "sha256:tokens",
+    ]);
+
+    let mut runtime = RuntimeStore::open(dataset_dir.path()).unwrap();
+    let spaces = runtime.vector_spaces().unwrap();
+    assert_eq!(spaces.len(), 1);
+    assert!(spaces[0].multi_vector);
+    assert_eq!(spaces[0].dimensions, 2);
+    let response = runtime
+        .search(RuntimeSearchRequest {
+            mode: RuntimeSearchMode::Vector,
+            top_k: 2,
+            vector_space_queries: vec![RuntimeVectorSpaceQuery::multi(
+                "tokens",
+                vec![vec![1.0, 0.0], vec![0.0, 1.0]],
+            )
+            .with_embedding(RuntimeEmbeddingIdentity::new(
+                "token-encoder-2",
+                "v1",
+                "sha256:tokens",
+            ))],
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(
+        response
+            .hits
+            .iter()
+            .map(|hit| hit.doc_id.as_str())
+            .collect::<Vec<_>>(),
+        vec!["doc-002", "doc-001"]
+    );
+}
+
+#[test]
+fn product_cli_ingests_sparse_vectors_and_searches_the_sparse_lane() {
+    let dataset_dir = tempdir().unwrap();
+    let fixture_root =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/bench/source/minimal");
+    pack_dataset(&PackRequest::new(
+        &fixture_root,
+        dataset_dir.path(),
+        "small",
+        "clean",
+    ))
+    .unwrap();
+
+    let docs_jsonl = dataset_dir.path().join("raw-docs.jsonl");
+    fs::write(
+        &docs_jsonl,
+        concat!(
+            "{\"doc_id\":\"doc-001\",\"text\":\"rust benchmark guide\"}\n",
+            "{\"doc_id\":\"doc-002\",\"text\":\"semantic latency checklist\"}\n",
+        ),
+    )
+    .unwrap();
+    let sparse_jsonl = dataset_dir.path().join("sparse-vectors.jsonl");
+    fs::write(
+        &sparse_jsonl,
+        concat!(
+            "{\"doc_id\":\"doc-001\",\"terms\":[[101,0.4],[2048,1.2]]}\n",
+            "{\"doc_id\":\"doc-002\",\"terms\":[[101,1.5]]}\n",
+        ),
+    )
+    .unwrap();
+
+    let root = dataset_dir.path().to_str().unwrap();
+    run_wax(&["create", "--root", root]);
+    run_wax(&[
+        "ingest",
+        "docs",
+        "--root",
+        root,
+        "--input",
+        docs_jsonl.to_str().unwrap(),
+    ]);
+    run_wax(&[
+        "ingest",
+        "sparse",
+        "--root",
+        root,
+        "--input",
+        sparse_jsonl.to_str().unwrap(),
+    ]);
+
+    let mut runtime = RuntimeStore::open(dataset_dir.path()).unwrap();
+    let response = runtime
+        .search(RuntimeSearchRequest {
+            mode: RuntimeSearchMode::Sparse,
+            top_k: 2,
+            sparse_query: Some(vec![(101, 1.0)]),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(
+        response
+            .hits
+            .iter()
+            .map(|hit| hit.doc_id.as_str())
+            .collect::<Vec<_>>(),
+        vec!["doc-002", "doc-001"]
+    );
+}
+
 fn run_wax(args: &[&str]) {
     let output = Command::new("cargo")
         .current_dir(env!("CARGO_MANIFEST_DIR"))
@@ -6,7 +6,8 @@ use wax_bench_model::embed_text;
 use wax_bench_packer::{pack_adhoc_dataset, AdhocPackRequest};
 use wax_v2_docstore::Docstore;
 use wax_v2_runtime::{
-    NewDocument, NewDocumentVector, RuntimeSearchMode, RuntimeSearchRequest, RuntimeStore,
+    NewDocument, NewDocumentVector, RuntimeSearchMode, RuntimeSearchRequest, RuntimeSearchResponse,
+    RuntimeStore,
 };
 
 #[test]
@@ -15,7 +16,7 @@ fn raw_and_compatibility_ingest_produce_equivalent_runtime_results_for_the_same_
     let compat_root = tempdir().unwrap();
     let raw_root = tempdir().unwrap();
     let docs_path = source_dir.path().join("docs.ndjson");
-    let docs = vec![
+    let docs = [
         (
             "doc-001",
             "rust benchmark guide",
@@ -79,14 +80,18 @@ fn raw_and_compatibility_ingest_produce_equivalent_runtime_results_for_the_same_
                 .with_metadata(json!({"kind":"notes","workspace":"prod"})),
         ])
         .unwrap();
+    let embedding = raw_runtime.embedding_identity();
     raw_runtime
         .writer()
         .unwrap()
-        .publish_raw_vectors(vec![
-            NewDocumentVector::new("doc-001", embed_text("rust benchmark guide", 384)),
-            NewDocumentVector::new("doc-002", embed_text("semantic latency checklist", 384)),
-            NewDocumentVector::new("doc-003", embed_text("hybrid search tuning notes", 384)),
-        ])
+        .publish_raw_vectors(
+            embedding,
+            vec![
+                NewDocumentVector::new("doc-001", embed_text("rust benchmark guide", 384)),
+                NewDocumentVector::new("doc-002", embed_text("semantic latency checklist", 384)),
+                NewDocumentVector::new("doc-003", embed_text("hybrid search tuning notes", 384)),
+            ],
+        )
         .unwrap();
     raw_runtime.close().unwrap();
 
@@ -114,16 +119,17 @@ fn raw_and_compatibility_ingest_produce_equivalent_runtime_results_for_the_same_
     let text_request = RuntimeSearchRequest {
         mode: RuntimeSearchMode::Text,
         text_query: Some("rust benchmark".to_owned()),
-        vector_query: None,
         top_k: 3,
         include_preview: true,
+        ..Default::default()
     };
     let vector_request = RuntimeSearchRequest {
         mode: RuntimeSearchMode::Vector,
-        text_query: None,
         vector_query: Some(embed_text("semantic latency checklist", 384)),
         top_k: 3,
         include_preview: true,
+        vector_embedding: Some(raw_runtime.embedding_identity()),
+        ..Default::default()
     };
     let hybrid_request = RuntimeSearchRequest {
         mode: RuntimeSearchMode::Hybrid,
@@ -131,20 +137,51 @@ fn raw_and_compatibility_ingest_produce_equivalent_runtime_results_for_the_same_
         vector_query: Some(embed_text("hybrid search tuning notes", 384)),
         top_k: 3,
         include_preview: true,
+        vector_embedding: Some(raw_runtime.embedding_identity()),
+        ..Default::default()
     };
 
+    assert_equivalent_responses(&mut compat_runtime, &mut raw_runtime, text_request);
+    assert_equivalent_responses(&mut compat_runtime, &mut raw_runtime, vector_request);
+    assert_equivalent_responses(&mut compat_runtime, &mut raw_runtime, hybrid_request);
+}
+
+/// Compares both stores' responses in full except `next_cursor`, which encodes each store's own
+/// generation; the cursors are followed instead and must lead to equal next pages.
+fn assert_equivalent_responses(
+    compat_runtime: &mut RuntimeStore,
+    raw_runtime: &mut RuntimeStore,
+    request: RuntimeSearchRequest,
+) {
+    let compat = compat_runtime.search(request.clone()).unwrap();
+    let raw = raw_runtime.search(request.clone()).unwrap();
     assert_eq!(
-        compat_runtime.search(text_request.clone()).unwrap(),
-        raw_runtime.search(text_request).unwrap()
-    );
-    assert_eq!(
-        compat_runtime.search(vector_request.clone()).unwrap(),
-        raw_runtime.search(vector_request).unwrap()
-    );
-    assert_eq!(
-        compat_runtime.search(hybrid_request.clone()).unwrap(),
-        raw_runtime.search(hybrid_request).unwrap()
+        RuntimeSearchResponse {
+            next_cursor: None,
+            ..compat.clone()
+        },
+        RuntimeSearchResponse {
+            next_cursor: None,
+            ..raw.clone()
+        }
     );
+    assert_eq!(compat.next_cursor.is_some(), raw.next_cursor.is_some());
+    if let (Some(compat_cursor), Some(raw_cursor)) = (compat.next_cursor, raw.next_cursor) {
+        assert_eq!(
+            compat_runtime
+                .search(RuntimeSearchRequest {
+                    cursor: Some(compat_cursor),
+                    ..request.clone()
+                })
+                .unwrap(),
+            raw_runtime
+                .search(RuntimeSearchRequest {
+                    cursor: Some(raw_cursor),
+                    ..request
+                })
+                .unwrap()
+        );
+    }
 }
 
 fn remove_sidecars(root: &std::path::Path, manifest: &wax_bench_model::DatasetPackManifest) {
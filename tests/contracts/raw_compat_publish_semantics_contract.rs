@@ -13,7 +13,7 @@ fn raw_full_snapshot_publish_matches_compatibility_import_semantics_for_equivale
     let compat_root = tempdir().unwrap();
     let raw_root = tempdir().unwrap();
     let docs_path = source_dir.path().join("docs.ndjson");
-    let docs = vec![
+    let docs = [
         (
             "doc-001",
             "rust benchmark guide",
@@ -64,6 +64,7 @@ fn raw_full_snapshot_publish_matches_compatibility_import_semantics_for_equivale
     compat_runtime.close().unwrap();
 
     let mut raw_runtime = RuntimeStore::create(raw_root.path()).unwrap();
+    let embedding = raw_runtime.embedding_identity();
     let raw_report = raw_runtime
         .writer()
         .unwrap()
@@ -76,11 +77,20 @@ fn raw_full_snapshot_publish_matches_compatibility_import_semantics_for_equivale
                 NewDocument::new("doc-003", "hybrid search tuning notes")
                     .with_metadata(json!({"kind":"notes","workspace":"prod"})),
             ],
-            Some(vec![
-                NewDocumentVector::new("doc-001", embed_text("rust benchmark guide", 384)),
-                NewDocumentVector::new("doc-002", embed_text("semantic latency checklist", 384)),
-                NewDocumentVector::new("doc-003", embed_text("hybrid search tuning notes", 384)),
-            ]),
+            Some((
+                embedding,
+                vec![
+                    NewDocumentVector::new("doc-001", embed_text("rust benchmark guide", 384)),
+                    NewDocumentVector::new(
+                        "doc-002",
+                        embed_text("semantic latency checklist", 384),
+                    ),
+                    NewDocumentVector::new(
+                        "doc-003",
+                        embed_text("hybrid search tuning notes", 384),
+                    ),
+                ],
+            )),
         )
         .unwrap();
     raw_runtime.close().unwrap();
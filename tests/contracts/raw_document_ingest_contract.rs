@@ -51,9 +51,9 @@ fn raw_document_publish_supports_reopen_search_without_dataset_sidecars() {
         .search(RuntimeSearchRequest {
             mode: RuntimeSearchMode::Text,
             text_query: Some("rust benchmark".to_owned()),
-            vector_query: None,
             top_k: 2,
             include_preview: true,
+            ..Default::default()
         })
         .unwrap();
     assert_eq!(response.hits[0].doc_id, "doc-001");
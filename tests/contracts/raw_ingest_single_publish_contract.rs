@@ -75,9 +75,9 @@ fn staged_compatibility_publish_creates_one_visible_generation_for_multiple_fami
         .search(RuntimeSearchRequest {
             mode: RuntimeSearchMode::Text,
             text_query: Some("rust benchmark".to_owned()),
-            vector_query: None,
             top_k: 2,
             include_preview: true,
+            ..Default::default()
         })
         .unwrap();
     assert_eq!(response.hits[0].doc_id, "doc-001");
@@ -5,7 +5,8 @@ use tempfile::tempdir;
 use wax_bench_model::embed_text;
 use wax_bench_packer::{pack_adhoc_dataset, AdhocPackRequest};
 use wax_v2_runtime::{
-    NewDocument, RuntimePublishFamily, RuntimeSearchMode, RuntimeSearchRequest, RuntimeStore,
+    NewDocument, NewDocumentVector, RuntimePublishFamily, RuntimeSearchMode, RuntimeSearchRequest,
+    RuntimeStore,
 };
 
 #[test]
@@ -39,19 +40,23 @@ fn runtime_store_preserves_text_and_serves_vector_search_when_raw_vectors_are_pu
         vec![RuntimePublishFamily::Doc, RuntimePublishFamily::Text]
     );
 
+    let embedding = runtime.embedding_identity();
     let raw_vector_report = runtime
         .writer()
         .unwrap()
-        .publish_raw_vectors(vec![
-            wax_v2_runtime::NewDocumentVector::new(
-                "doc-001",
-                embed_text("rust benchmark guide", 384),
-            ),
-            wax_v2_runtime::NewDocumentVector::new(
-                "doc-002",
-                embed_text("semantic latency checklist", 384),
-            ),
-        ])
+        .publish_raw_vectors(
+            embedding,
+            vec![
+                wax_v2_runtime::NewDocumentVector::new(
+                    "doc-001",
+                    embed_text("rust benchmark guide", 384),
+                ),
+                wax_v2_runtime::NewDocumentVector::new(
+                    "doc-002",
+                    embed_text("semantic latency checklist", 384),
+                ),
+            ],
+        )
         .unwrap();
     assert_eq!(raw_vector_report.generation, 2);
     assert_eq!(
@@ -82,9 +87,9 @@ fn runtime_store_preserves_text_and_serves_vector_search_when_raw_vectors_are_pu
         .search(RuntimeSearchRequest {
             mode: RuntimeSearchMode::Text,
             text_query: Some("rust benchmark".to_owned()),
-            vector_query: None,
             top_k: 2,
             include_preview: true,
+            ..Default::default()
         })
         .unwrap();
     assert_eq!(text_response.hits[0].doc_id, "doc-001");
@@ -96,11 +101,129 @@ fn runtime_store_preserves_text_and_serves_vector_search_when_raw_vectors_are_pu
     let vector_response = reopened
         .search(RuntimeSearchRequest {
             mode: RuntimeSearchMode::Vector,
-            text_query: None,
             vector_query: Some(embed_text("semantic latency checklist", 384)),
             top_k: 2,
-            include_preview: false,
+            vector_embedding: Some(reopened.embedding_identity()),
+            ..Default::default()
         })
         .unwrap();
     assert_eq!(vector_response.hits[0].doc_id, "doc-002");
 }
+
+#[test]
+fn runtime_store_upserts_vector_subsets_and_excludes_documents_without_vectors() {
+    let source_dir = tempdir().unwrap();
+    let dataset_dir = tempdir().unwrap();
+    let docs_path = source_dir.path().join("docs.ndjson");
+    fs::write(&docs_path, "{\"doc_id\":\"seed-001\",\"text\":\"seed\"}\n").unwrap();
+    pack_adhoc_dataset(&AdhocPackRequest::new(
+        &docs_path,
+        dataset_dir.path(),
+        "small",
+    ))
+    .unwrap();
+
+    let mut runtime = RuntimeStore::create(dataset_dir.path()).unwrap();
+    runtime
+        .writer()
+        .unwrap()
+        .publish_raw_documents(vec![
+            NewDocument::new("doc-001", "rust benchmark guide"),
+            NewDocument::new("doc-002", "semantic latency checklist"),
+            NewDocument::new("doc-003", "hybrid search tuning notes"),
+        ])
+        .unwrap();
+    let embedding = runtime.embedding_identity();
+    runtime
+        .writer()
+        .unwrap()
+        .publish_raw_vectors(
+            embedding,
+            vec![NewDocumentVector::new(
+                "doc-001",
+                embed_text("rust benchmark guide", 384),
+            )],
+        )
+        .unwrap();
+
+    let vector_hits = |runtime: &mut RuntimeStore, query: &str| {
+        runtime
+            .search(RuntimeSearchRequest {
+                mode: RuntimeSearchMode::Vector,
+                vector_query: Some(embed_text(query, 384)),
+                top_k: 3,
+                vector_embedding: Some(runtime.embedding_identity()),
+                ..Default::default()
+            })
+            .unwrap()
+            .hits
+            .into_iter()
+            .map(|hit| hit.doc_id)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        vector_hits(&mut runtime, "semantic latency checklist"),
+        vec!["doc-001"]
+    );
+
+    let hybrid = runtime
+        .search(RuntimeSearchRequest {
+            mode: RuntimeSearchMode::Hybrid,
+            text_query: Some("hybrid tuning".to_owned()),
+            vector_query: Some(embed_text("hybrid search tuning notes", 384)),
+            top_k: 3,
+            vector_embedding: Some(runtime.embedding_identity()),
+            ..Default::default()
+        })
+        .unwrap();
+    assert!(hybrid.hits.iter().any(|hit| hit.doc_id == "doc-003"));
+
+    let embedding = runtime.embedding_identity();
+    runtime
+        .writer()
+        .unwrap()
+        .publish_raw_vectors(
+            embedding,
+            vec![NewDocumentVector::new(
+                "doc-002",
+                embed_text("semantic latency checklist", 384),
+            )],
+        )
+        .unwrap();
+    assert_eq!(
+        vector_hits(&mut runtime, "semantic latency checklist"),
+        vec!["doc-002", "doc-001"]
+    );
+
+    let embedding = runtime.embedding_identity();
+    runtime
+        .writer()
+        .unwrap()
+        .publish_raw_vectors(
+            embedding,
+            vec![NewDocumentVector::new(
+                "doc-001",
+                embed_text("semantic latency checklist", 384),
+            )],
+        )
+        .unwrap();
+    // doc-001 now carries the same embedding as doc-002, so the doc_id tie-break decides.
+    assert_eq!(
+        vector_hits(&mut runtime, "semantic latency checklist"),
+        vec!["doc-001", "doc-002"]
+    );
+
+    let embedding = runtime.embedding_identity();
+    let error = runtime
+        .writer()
+        .unwrap()
+        .publish_raw_vectors(
+            embedding,
+            vec![NewDocumentVector::new(
+                "doc-404",
+                embed_text("missing", 384),
+            )],
+        )
+        .expect_err("vectors for unknown documents are rejected");
+    assert!(error.to_string().contains("doc-404"));
+}
@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use tempfile::tempdir;
+use wax_bench_packer::{pack_dataset, PackRequest};
+
+#[test]
+fn wax_snapshot_and_rollback_move_the_store_between_generations() {
+    let dataset_dir = tempdir().unwrap();
+    let fixture_root =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/bench/source/minimal");
+    pack_dataset(&PackRequest::new(
+        &fixture_root,
+        dataset_dir.path(),
+        "small",
+        "clean",
+    ))
+    .unwrap();
+    let root = dataset_dir.path().to_str().unwrap();
+
+    let first_docs = dataset_dir.path().join("first-docs.jsonl");
+    fs::write(
+        &first_docs,
+        "{\"doc_id\":\"doc-001\",\"text\":\"lantern harbor\"}\n",
+    )
+    .unwrap();
+    let second_docs = dataset_dir.path().join("second-docs.jsonl");
+    fs::write(
+        &second_docs,
+        "{\"doc_id\":\"doc-002\",\"text\":\"lantern market\"}\n",
+    )
+    .unwrap();
+    assert_success(&run_wax(&["create", "--root", root]));
+    assert_success(&run_wax(&[
+        "ingest",
+        "docs",
+        "--root",
+        root,
+        "--input",
+        first_docs.to_str().unwrap(),
+    ]));
+
+    let output = run_wax(&["snapshot", "create", "--root", root, "--name", "v1"]);
+    assert_success(&output);
+    let created: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(created[0]["name"], "v1");
+    let v1_generation = created[0]["generation"].as_u64().unwrap();
+    let duplicate = run_wax(&["snapshot", "create", "--root", root, "--name", "v1"]);
+    assert!(!duplicate.status.success());
+
+    let output = run_wax(&[
+        "ingest",
+        "docs",
+        "--root",
+        root,
+        "--input",
+        second_docs.to_str().unwrap(),
+    ]);
+    assert_success(&output);
+    let second_report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let second_generation = second_report["generation"].as_u64().unwrap().to_string();
+    assert_eq!(search_doc_ids(root), ["doc-001", "doc-002"]);
+
+    let output = run_wax(&["snapshot", "list", "--root", root]);
+    assert_success(&output);
+    let listed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert_eq!(listed[0]["generation"], v1_generation);
+
+    let output = run_wax(&["snapshot", "restore", "--root", root, "--name", "v1"]);
+    assert_success(&output);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(report["generation"].as_u64().unwrap() > v1_generation);
+    assert_eq!(report["published_families"], serde_json::json!([]));
+    assert_eq!(search_doc_ids(root), ["doc-001"]);
+    assert_success(&run_wax(&["verify", "--root", root]));
+
+    assert_success(&run_wax(&[
+        "snapshot", "delete", "--root", root, "--name", "v1",
+    ]));
+    let output = run_wax(&["snapshot", "list", "--root", root]);
+    assert_success(&output);
+    let listed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(listed, serde_json::json!([]));
+    let missing = run_wax(&["snapshot", "restore", "--root", root, "--name", "v1"]);
+    assert!(!missing.status.success());
+
+    let output = run_wax(&[
+        "rollback",
+        "--root",
+        root,
+        "--generation",
+        &second_generation,
+    ]);
+    assert_success(&output);
+    assert_eq!(search_doc_ids(root), ["doc-001", "doc-002"]);
+    let future = run_wax(&["rollback", "--root", root, "--generation", "999"]);
+    assert!(!future.status.success());
+}
+
+fn search_doc_ids(root: &str) -> Vec<String> {
+    let output = run_wax(&["search", "--root", root, "--text", "lantern"]);
+    assert_success(&output);
+    let hits: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    hits.as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["doc_id"].as_str().unwrap().to_owned())
+        .collect()
+}
+
+fn run_wax(args: &[&str]) -> Output {
+    Command::new("cargo")
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["run", "-q", "-p", "wax-cli", "--"])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "stdout:\n{}\nstderr:\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
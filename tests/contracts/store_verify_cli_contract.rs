@@ -0,0 +1,192 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use tempfile::tempdir;
+use wax_bench_model::embed_text;
+use wax_bench_packer::{pack_dataset, PackRequest};
+use wax_v2_core::{open_store, SegmentKind};
+
+#[test]
+fn wax_verify_reports_a_clean_store_and_fails_on_a_corrupted_segment() {
+    let dataset_dir = tempdir().unwrap();
+    let fixture_root =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/bench/source/minimal");
+    let manifest = pack_dataset(&PackRequest::new(
+        &fixture_root,
+        dataset_dir.path(),
+        "small",
+        "clean",
+    ))
+    .unwrap();
+    let root = dataset_dir.path().to_str().unwrap();
+
+    let docs_jsonl = dataset_dir.path().join("raw-docs.jsonl");
+    fs::write(
+        &docs_jsonl,
+        concat!(
+            "{\"doc_id\":\"doc-001\",\"text\":\"rust benchmark guide\"}\n",
+            "{\"doc_id\":\"doc-002\",\"text\":\"semantic latency checklist\"}\n",
+        ),
+    )
+    .unwrap();
+    let vectors_jsonl = dataset_dir.path().join("raw-vectors.jsonl");
+    fs::write(
+        &vectors_jsonl,
+        format!(
+            "{}\n{}\n",
+            serde_json::json!({
+                "doc_id": "doc-001",
+                "values": embed_text("rust benchmark guide", 384),
+            }),
+            serde_json::json!({
+                "doc_id": "doc-002",
+                "values": embed_text("semantic latency checklist", 384),
+            }),
+        ),
+    )
+    .unwrap();
+    assert_success(&run_wax(&["create", "--root", root]));
+    assert_success(&run_wax(&[
+        "ingest",
+        "docs",
+        "--root",
+        root,
+        "--input",
+        docs_jsonl.to_str().unwrap(),
+    ]));
+    assert_success(&run_wax(&[
+        "ingest",
+        "vectors",
+        "--root",
+        root,
+        "--input",
+        vectors_jsonl.to_str().unwrap(),
+        "--embedding-spec-id",
+        &manifest.identity.embedding_spec_id,
+        "--embedding-model-version",
+        &manifest.identity.embedding_model_version,
+        "--embedding-model-hash",
+        &manifest.identity.embedding_model_hash,
+    ]));
+
+    let output = run_wax(&["verify", "--root", root]);
+    assert_success(&output);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["ok"], true);
+    assert_eq!(report["issues"], serde_json::json!([]));
+    assert_eq!(report["superblocks"].as_array().unwrap().len(), 2);
+    let families = report["segments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|segment| {
+            assert_eq!(segment["checksum_valid"], true);
+            segment["family"].as_str().unwrap().to_owned()
+        })
+        .collect::<Vec<_>>();
+    for family in ["doc", "txt", "vec"] {
+        assert!(families.iter().any(|candidate| candidate == family));
+    }
+
+    let store_path = dataset_dir.path().join("store.wax");
+    let doc_segment = open_store(&store_path)
+        .unwrap()
+        .manifest
+        .segments
+        .into_iter()
+        .filter(|segment| segment.family == SegmentKind::Doc)
+        .max_by_key(|segment| segment.segment_generation)
+        .unwrap();
+    let mut bytes = fs::read(&store_path).unwrap();
+    let last_payload_byte = (doc_segment.object_offset + doc_segment.object_length - 1) as usize;
+    bytes[last_payload_byte] ^= 0xff;
+    fs::write(&store_path, bytes).unwrap();
+
+    let output = run_wax(&["verify", "--root", root]);
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["ok"], false);
+    assert!(report["issues"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|issue| issue["kind"] == "object_checksum"));
+}
+
+#[test]
+fn wax_verify_repair_truncates_a_torn_tail() {
+    let store_dir = tempdir().unwrap();
+    let fixture_root =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/bench/source/minimal");
+    pack_dataset(&PackRequest::new(
+        &fixture_root,
+        store_dir.path(),
+        "small",
+        "clean",
+    ))
+    .unwrap();
+    let root = store_dir.path().to_str().unwrap();
+    let docs_jsonl = store_dir.path().join("docs.jsonl");
+    fs::write(
+        &docs_jsonl,
+        "{\"doc_id\":\"doc-001\",\"text\":\"rust benchmark guide\"}\n",
+    )
+    .unwrap();
+    assert_success(&run_wax(&["create", "--root", root]));
+    assert_success(&run_wax(&[
+        "ingest",
+        "docs",
+        "--root",
+        root,
+        "--input",
+        docs_jsonl.to_str().unwrap(),
+    ]));
+    let store_path = store_dir.path().join("store.wax");
+    let committed_length = fs::metadata(&store_path).unwrap().len();
+    let mut file = fs::OpenOptions::new()
+        .append(true)
+        .open(&store_path)
+        .unwrap();
+    file.write_all(&[0xab; 300]).unwrap();
+    drop(file);
+
+    let output = run_wax(&["verify", "--root", root]);
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["torn_tail_bytes"], 300);
+    assert_eq!(report["repaired_tail_bytes"], serde_json::Value::Null);
+    let issue = &report["issues"][0];
+    assert_eq!(issue["kind"], "torn_tail");
+    assert!(issue["message"]
+        .as_str()
+        .unwrap()
+        .contains("wax verify --repair"));
+
+    let output = run_wax(&["verify", "--root", root, "--repair"]);
+    assert_success(&output);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["ok"], true);
+    assert_eq!(report["torn_tail_bytes"], 0);
+    assert_eq!(report["repaired_tail_bytes"], 300);
+    assert_eq!(fs::metadata(&store_path).unwrap().len(), committed_length);
+}
+
+fn run_wax(args: &[&str]) -> Output {
+    Command::new("cargo")
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["run", "-q", "-p", "wax-cli", "--"])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "stdout:\n{}\nstderr:\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
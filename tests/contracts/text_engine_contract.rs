@@ -3,7 +3,10 @@ use std::fs;
 use tempfile::tempdir;
 use wax_bench_model::{MountRequest, OpenRequest, SearchRequest, WaxEngine};
 use wax_bench_packer::{pack_dataset, PackRequest};
-use wax_bench_text_engine::{query_batch_ranked_results, query_text_preview, PackedTextEngine};
+use wax_bench_text_engine::{
+    profile_doc_hydrate, query_batch_ranked_results, query_text_preview, DocCompression,
+    PackedTextEngine,
+};
 use wax_v2_core::create_empty_store;
 use wax_v2_docstore::Docstore;
 use wax_v2_runtime::{NewDocument, RuntimeStore};
@@ -523,3 +526,29 @@ fn query_batch_returns_empty_hits_when_query_has_no_eligible_lanes() {
     assert_eq!(results[0].query_id, "q-no-lane");
     assert!(results[0].hits.is_empty());
 }
+
+#[test]
+fn profile_doc_hydrate_reports_open_and_hydrate_cost_per_codec() {
+    let dataset_dir = tempdir().unwrap();
+    let manifest = pack_dataset(&PackRequest::new(
+        "fixtures/bench/source/minimal",
+        dataset_dir.path(),
+        "small",
+        "clean",
+    ))
+    .unwrap();
+
+    for compression in [
+        DocCompression::None,
+        DocCompression::Lz4,
+        DocCompression::Zstd,
+    ] {
+        let profile = profile_doc_hydrate(dataset_dir.path(), compression).unwrap();
+
+        assert_eq!(profile.compression, compression.as_str());
+        assert_eq!(profile.doc_count as u64, manifest.corpus.doc_count);
+        assert!(profile.segment_bytes > 0);
+        assert!(profile.open_ms >= 0.0 && profile.hydrate_all_ms >= 0.0);
+    }
+    assert!(!dataset_dir.path().join("store.wax").exists());
+}
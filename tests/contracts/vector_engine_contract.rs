@@ -8,7 +8,7 @@ use wax_bench_model::{
 use wax_bench_packer::{pack_dataset, PackRequest};
 use wax_bench_text_engine::{profile_first_vector_query, PackedTextEngine};
 use wax_v2_core::create_empty_store;
-use wax_v2_vector::publish_compatibility_vector_segment;
+use wax_v2_vector::{publish_compatibility_vector_segment, VectorLane, VectorRadius};
 
 fn write_large_auto_source(source_dir: &std::path::Path, doc_count: usize) {
     fs::write(
@@ -920,3 +920,51 @@ fn packed_engine_auto_warmup_hybrid_primes_hnsw_sidecar_for_measured_query() {
     assert_eq!(warmup.hits.first().map(String::as_str), Some("doc-000"));
     assert!(engine.is_vector_hnsw_sidecar_materialized());
 }
+
+#[test]
+fn hnsw_radius_search_widens_candidates_until_the_radius_is_covered() {
+    let source_dir = tempdir().unwrap();
+    let dataset_dir = tempdir().unwrap();
+    write_large_auto_source(source_dir.path(), 150);
+    pack_dataset(&PackRequest::new(
+        source_dir.path(),
+        dataset_dir.path(),
+        "small",
+        "clean",
+    ))
+    .unwrap();
+    let manifest: DatasetPackManifest = serde_json::from_str(
+        &fs::read_to_string(dataset_dir.path().join("manifest.json")).unwrap(),
+    )
+    .unwrap();
+    let exact =
+        VectorLane::load(dataset_dir.path(), &manifest, VectorQueryMode::ExactFlat).unwrap();
+    let hnsw = VectorLane::load(dataset_dir.path(), &manifest, VectorQueryMode::Hnsw).unwrap();
+    assert!(hnsw.is_hnsw_sidecar_materialized());
+    let query = hnsw.first_hybrid_query.clone().unwrap();
+
+    let everything = VectorRadius::MinSimilarity(-1.0);
+    let exact_hits = exact
+        .search_within(&query, everything, None, VectorQueryMode::ExactFlat)
+        .unwrap();
+    assert_eq!(exact_hits.len(), 150);
+    assert_eq!(
+        hnsw.search_within(&query, everything, None, VectorQueryMode::Hnsw)
+            .unwrap(),
+        exact_hits
+    );
+    let capped = hnsw
+        .search_within(&query, everything, Some(5), VectorQueryMode::Hnsw)
+        .unwrap();
+    assert_eq!(capped.len(), 5);
+    assert_eq!(capped[0], "doc-000");
+    assert!(hnsw
+        .search_within(
+            &query,
+            VectorRadius::MinSimilarity(1.5),
+            None,
+            VectorQueryMode::Hnsw
+        )
+        .unwrap()
+        .is_empty());
+}